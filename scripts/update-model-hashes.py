@@ -0,0 +1,54 @@
+#!/usr/bin/env python3
+# Usage: ./scripts/update-model-hashes.py
+#
+# Fills in the `sha256` of every entry in src-tauri/src/transcription/models.json
+# from the Hugging Face LFS metadata of the file its `url` points at. Run it
+# whenever a catalog entry is added or its URL changes.
+
+import json
+import re
+import sys
+import urllib.request
+from pathlib import Path
+
+CATALOG = Path(__file__).resolve().parent.parent / "src-tauri/src/transcription/models.json"
+URL_PATTERN = re.compile(r"^https://huggingface\.co/([^/]+/[^/]+)/resolve/([^/]+)/(.+)$")
+
+
+def lfs_hashes(repo, revision):
+    """sha256 of every LFS file in a Hugging Face repo, by path"""
+    url = f"https://huggingface.co/api/models/{repo}/tree/{revision}?recursive=true"
+    with urllib.request.urlopen(url) as response:
+        files = json.load(response)
+    return {f["path"]: f["lfs"]["oid"] for f in files if f.get("lfs")}
+
+
+def main():
+    catalog = json.loads(CATALOG.read_text())
+    repos = {}
+    missing = []
+
+    for entry in catalog["models"]:
+        match = URL_PATTERN.match(entry["url"])
+        if not match:
+            missing.append(entry["id"])
+            continue
+        repo, revision, path = match.groups()
+        if (repo, revision) not in repos:
+            repos[(repo, revision)] = lfs_hashes(repo, revision)
+        sha256 = repos[(repo, revision)].get(path)
+        if sha256:
+            entry["sha256"] = sha256
+        else:
+            missing.append(entry["id"])
+
+    CATALOG.write_text(json.dumps(catalog, indent=2, ensure_ascii=False) + "\n")
+
+    if missing:
+        print("No LFS hash found for: " + ", ".join(missing), file=sys.stderr)
+        sys.exit(1)
+    print(f"Updated {len(catalog['models'])} entries in {CATALOG}")
+
+
+if __name__ == "__main__":
+    main()
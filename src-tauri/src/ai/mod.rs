@@ -1,5 +1,22 @@
 pub mod ollama;
 pub mod prompts;
+pub mod summarizer;
+
+use std::future::Future;
 
 pub use ollama::{OllamaClient, OllamaModel};
 pub use prompts::SummaryPrompts;
+
+/// A text generation backend the summarizer can run prompts through
+pub trait LlmBackend: Send + Sync {
+    type Error: std::fmt::Display;
+
+    /// Generate a complete (non-streamed) response to `prompt`
+    fn generate(
+        &self,
+        model: &str,
+        prompt: &str,
+        temperature: f32,
+        context_length: Option<u32>,
+    ) -> impl Future<Output = Result<String, Self::Error>> + Send;
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::mpsc;
 
+use crate::ai::LlmBackend;
+
 const OLLAMA_BASE_URL: &str = "http://localhost:11434";
 
 #[derive(Error, Debug)]
@@ -73,6 +75,14 @@ impl OllamaClient {
         }
     }
 
+    /// Client for an Ollama server at another address (e.g. a remote host or a test server)
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
     /// Check if Ollama is running
     pub async fn is_running(&self) -> bool {
         match self.client.get(&self.base_url).send().await {
@@ -293,6 +303,20 @@ impl Default for OllamaClient {
     }
 }
 
+impl LlmBackend for OllamaClient {
+    type Error = OllamaError;
+
+    async fn generate(
+        &self,
+        model: &str,
+        prompt: &str,
+        temperature: f32,
+        context_length: Option<u32>,
+    ) -> Result<String, OllamaError> {
+        OllamaClient::generate(self, model, prompt, temperature, context_length).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,240 @@
+use crate::ai::prompts::MAX_CONTENT_LENGTH;
+use crate::ai::{LlmBackend, SummaryPrompts};
+use crate::db::models::{Summary, SummaryType};
+use crate::db::Database;
+
+/// Summarize a note's transcript and notes with `model` and save the summary
+pub async fn summarize_note<B: LlmBackend>(
+    client: &B,
+    model: &str,
+    db: &Database,
+    note_id: &str,
+    stype: SummaryType,
+    custom_prompt: Option<String>,
+) -> Result<Summary, String> {
+    // Get transcript from database
+    let segments = db
+        .get_transcript_segments(note_id)
+        .map_err(|e| e.to_string())?;
+
+    // Get user notes (description) from database
+    let notes = db
+        .get_note_description(note_id)
+        .map_err(|e| e.to_string())?;
+
+    // Combine segments into full transcript, filtering out blank audio markers
+    let transcript = segments
+        .iter()
+        .map(|s| s.text.clone())
+        .filter(|text| !text.contains("[BLANK_AUDIO]"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let has_transcript = !transcript.trim().is_empty();
+    let has_notes = notes.as_ref().is_some_and(|n| !n.trim().is_empty());
+
+    if !has_transcript && !has_notes {
+        return Err("No content to summarize. Please add notes or record audio first.".to_string());
+    }
+
+    let user_prompt_str = custom_prompt.unwrap_or_else(|| "Summarize this note.".to_string());
+
+    // Check if we need to use chunked summarization
+    let response = if has_transcript && transcript.len() > MAX_CONTENT_LENGTH {
+        // Split transcript into chunks
+        let chunks = split_into_chunks(&transcript, MAX_CONTENT_LENGTH);
+        let total_chunks = chunks.len();
+
+        // Summarize each chunk
+        let mut chunk_summaries = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_prompt = match stype {
+                SummaryType::Overview => {
+                    SummaryPrompts::chunk_overview(chunk, i + 1, total_chunks)
+                }
+                SummaryType::ActionItems => {
+                    SummaryPrompts::chunk_action_items(chunk, i + 1, total_chunks)
+                }
+                SummaryType::KeyDecisions => {
+                    SummaryPrompts::chunk_key_decisions(chunk, i + 1, total_chunks)
+                }
+                SummaryType::Custom => {
+                    SummaryPrompts::chunk_custom(chunk, &user_prompt_str, i + 1, total_chunks)
+                }
+            };
+
+            let chunk_response = client
+                .generate(model, &chunk_prompt, 0.7, Some(4096))
+                .await
+                .map_err(|e| e.to_string())?;
+
+            chunk_summaries.push(strip_thinking_tags(&chunk_response));
+        }
+
+        // Merge chunk summaries
+        let merge_prompt = match stype {
+            SummaryType::Overview => {
+                SummaryPrompts::merge_overview(&chunk_summaries, notes.as_deref())
+            }
+            SummaryType::ActionItems => {
+                SummaryPrompts::merge_action_items(&chunk_summaries, notes.as_deref())
+            }
+            SummaryType::KeyDecisions => {
+                SummaryPrompts::merge_key_decisions(&chunk_summaries, notes.as_deref())
+            }
+            SummaryType::Custom => {
+                SummaryPrompts::merge_custom(&chunk_summaries, &user_prompt_str, notes.as_deref())
+            }
+        };
+
+        client
+            .generate(model, &merge_prompt, 0.7, Some(4096))
+            .await
+            .map_err(|e| e.to_string())?
+    } else if has_transcript {
+        // Build prompt based on summary type (single pass with transcript)
+        let prompt = match stype {
+            SummaryType::Overview => SummaryPrompts::overview(&transcript, notes.as_deref()),
+            SummaryType::ActionItems => {
+                SummaryPrompts::action_items(&transcript, notes.as_deref())
+            }
+            SummaryType::KeyDecisions => {
+                SummaryPrompts::key_decisions(&transcript, notes.as_deref())
+            }
+            SummaryType::Custom => {
+                SummaryPrompts::custom(&transcript, &user_prompt_str, notes.as_deref())
+            }
+        };
+
+        client
+            .generate(model, &prompt, 0.7, Some(4096))
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        // Notes only (no transcript)
+        let notes_content = notes.as_ref().unwrap();
+        let prompt = match stype {
+            SummaryType::Overview => SummaryPrompts::overview_notes_only(notes_content),
+            SummaryType::ActionItems => SummaryPrompts::action_items_notes_only(notes_content),
+            SummaryType::KeyDecisions => SummaryPrompts::key_decisions_notes_only(notes_content),
+            SummaryType::Custom => {
+                SummaryPrompts::custom_notes_only(notes_content, &user_prompt_str)
+            }
+        };
+
+        client
+            .generate(model, &prompt, 0.7, Some(4096))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    // Strip thinking tags from response
+    let clean_response = strip_thinking_tags(&response);
+
+    // Save to database
+    let summary_id = db
+        .add_summary(note_id, &stype, &clean_response)
+        .map_err(|e| e.to_string())?;
+
+    // Fetch the saved summary
+    let summary = db
+        .get_summary(summary_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Failed to retrieve saved summary")?;
+
+    Ok(summary)
+}
+
+/// Split text into chunks of approximately max_size characters
+/// Tries to split on sentence boundaries when possible
+pub(crate) fn split_into_chunks(text: &str, max_size: usize) -> Vec<String> {
+    if text.len() <= max_size {
+        return vec![text.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut current_chunk = String::new();
+
+    // Split by sentences (rough approximation)
+    for sentence in text.split_inclusive(|c| c == '.' || c == '!' || c == '?') {
+        if current_chunk.len() + sentence.len() > max_size && !current_chunk.is_empty() {
+            chunks.push(current_chunk.trim().to_string());
+            current_chunk = String::new();
+        }
+        current_chunk.push_str(sentence);
+    }
+
+    if !current_chunk.trim().is_empty() {
+        chunks.push(current_chunk.trim().to_string());
+    }
+
+    // If we still have chunks that are too large, force split them
+    let mut final_chunks = Vec::new();
+    for chunk in chunks {
+        if chunk.len() <= max_size {
+            final_chunks.push(chunk);
+        } else {
+            // Force split on word boundaries
+            let words: Vec<&str> = chunk.split_whitespace().collect();
+            let mut sub_chunk = String::new();
+            for word in words {
+                if sub_chunk.len() + word.len() + 1 > max_size && !sub_chunk.is_empty() {
+                    final_chunks.push(sub_chunk.trim().to_string());
+                    sub_chunk = String::new();
+                }
+                if !sub_chunk.is_empty() {
+                    sub_chunk.push(' ');
+                }
+                sub_chunk.push_str(word);
+            }
+            if !sub_chunk.trim().is_empty() {
+                final_chunks.push(sub_chunk.trim().to_string());
+            }
+        }
+    }
+
+    final_chunks
+}
+
+/// Strip thinking tags from LLM responses (used by reasoning models like DeepSeek)
+/// Handles: <think>, <thinking>, and variations with different casing
+/// Also handles cases where opening tag is missing but closing tag exists
+pub(crate) fn strip_thinking_tags(text: &str) -> String {
+    let mut result = text.to_string();
+
+    // List of tag patterns to remove (open tag, close tag)
+    let tag_patterns = [
+        ("<think>", "</think>"),
+        ("<thinking>", "</thinking>"),
+    ];
+
+    for (open_tag, close_tag) in tag_patterns {
+        loop {
+            let lower = result.to_lowercase();
+
+            // Check if we have a closing tag
+            if let Some(end_pos) = lower.find(close_tag) {
+                // Look for matching opening tag
+                if let Some(start) = lower.find(open_tag) {
+                    // Both tags found - remove everything between them (inclusive)
+                    let end = end_pos + close_tag.len();
+                    result = format!("{}{}", &result[..start], &result[end..]);
+                } else {
+                    // Only closing tag found - remove everything before and including it
+                    // This handles cases where the model starts with thinking content
+                    let end = end_pos + close_tag.len();
+                    result = result[end..].to_string();
+                }
+            } else if let Some(start) = lower.find(open_tag) {
+                // Only opening tag found - remove everything from it onwards
+                result = result[..start].to_string();
+                break;
+            } else {
+                // No tags found
+                break;
+            }
+        }
+    }
+
+    result.trim().to_string()
+}
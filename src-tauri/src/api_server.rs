@@ -0,0 +1,568 @@
+//! Opt-in HTTP API on localhost for scripts and integrations (Stream Deck
+//! buttons, pulling summaries into other tools)
+//!
+//! Every request needs `Authorization: Bearer <token>`. Reads go through the
+//! same commands and privacy checks as the webview. Recording control is
+//! handed to the webview, which runs the recording and live transcription
+//! exactly as its own buttons do; the server then waits for `AudioState` to
+//! follow.
+//!
+//! ```text
+//! GET  /api/notes                     notes, newest first
+//! GET  /api/notes/{id}                one note
+//! GET  /api/notes/{id}/transcript     transcript segments
+//! GET  /api/notes/{id}/summaries      summaries, newest first
+//! GET  /api/search?q=...              full-text search
+//! GET  /api/recording                 recording status
+//! POST /api/recording/{start,stop,pause,resume}
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::audio::RecordingPhase;
+use crate::commands::ai::get_note_summaries;
+use crate::commands::audio::AudioState;
+use crate::commands::encryption::percent_decode;
+use crate::commands::notes::{list_notes, search_notes};
+use crate::commands::privacy::{
+    ensure_app_unlocked, ensure_note_accessible, is_note_hidden, PrivacyState,
+};
+use crate::commands::transcription::get_transcript;
+use crate::db::models::Note;
+use crate::db::Database;
+
+/// Settings key holding the API server settings
+const API_SERVER_KEY: &str = "api_server";
+
+/// Event asking the webview to start, stop, pause or resume recording
+pub const API_RECORDING_EVENT: &str = "api-recording";
+
+/// How long recording control waits for the webview to act
+const RECORDING_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiServerSettings {
+    pub enabled: bool,
+    /// Port on 127.0.0.1
+    pub port: u16,
+    /// Bearer token; created the first time the server is enabled
+    pub token: String,
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7393,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    #[serde(flatten)]
+    pub settings: ApiServerSettings,
+    pub running: bool,
+    /// Why the server is not running although enabled (e.g. port in use)
+    pub error: Option<String>,
+}
+
+struct RunningServer {
+    server: Arc<Server>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct ApiServerState {
+    token: Mutex<String>,
+    server: Mutex<Option<RunningServer>>,
+    error: Mutex<Option<String>>,
+}
+
+impl ApiServerState {
+    fn is_running(&self) -> bool {
+        self.server.lock().map(|s| s.is_some()).unwrap_or(false)
+    }
+
+    fn stop(&self) {
+        let running = self.server.lock().ok().and_then(|mut s| s.take());
+        if let Some(running) = running {
+            running.server.unblock();
+            // Wait for the listener to close so the port can be bound again
+            let _ = running.thread.join();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingAction {
+    Start,
+    Stop,
+    Pause,
+    Resume,
+}
+
+impl RecordingAction {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "start" => Some(RecordingAction::Start),
+            "stop" => Some(RecordingAction::Stop),
+            "pause" => Some(RecordingAction::Pause),
+            "resume" => Some(RecordingAction::Resume),
+            _ => None,
+        }
+    }
+
+    /// The phase the recorder must be in for the action to make sense
+    fn requires(&self, phase: RecordingPhase) -> Result<(), &'static str> {
+        match (self, phase) {
+            (RecordingAction::Start, RecordingPhase::Idle)
+            | (RecordingAction::Stop, RecordingPhase::Recording | RecordingPhase::Paused)
+            | (RecordingAction::Pause, RecordingPhase::Recording)
+            | (RecordingAction::Resume, RecordingPhase::Paused) => Ok(()),
+            (RecordingAction::Start, _) => Err("Already recording"),
+            (RecordingAction::Resume, RecordingPhase::Recording) => Err("Not paused"),
+            _ => Err("Not recording"),
+        }
+    }
+
+    /// The phase the recorder is in once the action is done
+    fn result(&self) -> RecordingPhase {
+        match self {
+            RecordingAction::Start | RecordingAction::Resume => RecordingPhase::Recording,
+            RecordingAction::Stop => RecordingPhase::Idle,
+            RecordingAction::Pause => RecordingPhase::Paused,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Route<'a> {
+    Notes,
+    Note(&'a str),
+    Transcript(&'a str),
+    Summaries(&'a str),
+    Search,
+    Recording,
+    RecordingControl(RecordingAction),
+}
+
+impl Route<'_> {
+    /// Routes that return note content (recording control keeps working while the app is locked)
+    fn reads_notes(&self) -> bool {
+        !matches!(self, Route::Recording | Route::RecordingControl(_))
+    }
+}
+
+fn route<'a>(method: &Method, path: &'a str) -> Option<Route<'a>> {
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').skip(1).collect();
+    match (method, segments.as_slice()) {
+        (Method::Get, ["api", "notes"]) => Some(Route::Notes),
+        (Method::Get, ["api", "notes", id]) => Some(Route::Note(id)),
+        (Method::Get, ["api", "notes", id, "transcript"]) => Some(Route::Transcript(id)),
+        (Method::Get, ["api", "notes", id, "summaries"]) => Some(Route::Summaries(id)),
+        (Method::Get, ["api", "search"]) => Some(Route::Search),
+        (Method::Get, ["api", "recording"]) => Some(Route::Recording),
+        (Method::Post, ["api", "recording", action]) => {
+            RecordingAction::from_str(action).map(Route::RecordingControl)
+        }
+        _ => None,
+    }
+}
+
+/// Decode one query string parameter (`+` and `%XX` escapes)
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key == name).then(|| percent_decode(&value.replace('+', " ")))
+    })
+}
+
+/// Compare tokens without leaking how much of a guess matched
+fn token_matches(given: &str, token: &str) -> bool {
+    !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn new_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn not_found() -> Self {
+        Self::new(404, "Not found")
+    }
+}
+
+/// Errors of the commands the handlers call
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::new(500, message)
+    }
+}
+
+type ApiResult = Result<String, ApiError>;
+
+fn to_json<T: Serialize>(value: &T) -> ApiResult {
+    serde_json::to_string(value).map_err(|e| e.to_string().into())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordingStatus {
+    phase: &'static str,
+    note_id: Option<String>,
+}
+
+fn recording_status(app: &AppHandle) -> RecordingStatus {
+    let recording = &app.state::<AudioState>().recording;
+    let phase = recording.get_phase();
+    RecordingStatus {
+        phase: match phase {
+            RecordingPhase::Idle => "idle",
+            RecordingPhase::Recording => "recording",
+            RecordingPhase::Paused => "paused",
+        },
+        note_id: match phase {
+            RecordingPhase::Idle => None,
+            _ => recording
+                .current_note_id
+                .lock()
+                .ok()
+                .and_then(|id| id.clone()),
+        },
+    }
+}
+
+/// Have the webview run `action`, then wait for the recorder to follow
+fn control_recording(app: &AppHandle, action: RecordingAction) -> ApiResult {
+    let recording = &app.state::<AudioState>().recording;
+    action
+        .requires(recording.get_phase())
+        .map_err(|e| ApiError::new(409, e))?;
+
+    app.emit(API_RECORDING_EVENT, action)
+        .map_err(|e| e.to_string())?;
+
+    let started = Instant::now();
+    while recording.get_phase() != action.result() {
+        if started.elapsed() > RECORDING_TIMEOUT {
+            return Err(ApiError::new(
+                504,
+                "The app did not respond; check its window",
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    to_json(&recording_status(app))
+}
+
+/// A note the caller may read: it exists and is not a locked confidential note
+fn accessible_note(
+    app: &AppHandle,
+    privacy: &PrivacyState,
+    note_id: &str,
+) -> Result<Note, ApiError> {
+    let note = app
+        .state::<Database>()
+        .find_note(note_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(ApiError::not_found)?;
+    ensure_note_accessible(privacy, &app.state::<Database>(), note_id)
+        .map_err(|e| ApiError::new(403, e))?;
+    Ok(note)
+}
+
+fn handle(app: &AppHandle, route: Route, query: &str) -> ApiResult {
+    let db = app.state::<Database>();
+    if db.is_locked() {
+        return Err(ApiError::new(503, "The library is locked"));
+    }
+    let privacy = app.state::<PrivacyState>();
+    if route.reads_notes() {
+        ensure_app_unlocked(&privacy).map_err(|e| ApiError::new(403, e))?;
+    }
+
+    match route {
+        Route::Notes => {
+            let notes: Vec<Note> = list_notes(app.state(), app.state(), None)?
+                .into_iter()
+                .filter(|note| !is_note_hidden(&privacy, &note.id, note.confidential))
+                .collect();
+            to_json(&notes)
+        }
+        Route::Note(id) => to_json(&accessible_note(app, &privacy, id)?),
+        Route::Transcript(id) => {
+            accessible_note(app, &privacy, id)?;
+            to_json(&get_transcript(id.to_string(), app.state(), app.state())?)
+        }
+        Route::Summaries(id) => {
+            accessible_note(app, &privacy, id)?;
+            to_json(&get_note_summaries(
+                id.to_string(),
+                app.state(),
+                app.state(),
+            )?)
+        }
+        Route::Search => {
+            let q = query_param(query, "q")
+                .filter(|q| !q.trim().is_empty())
+                .ok_or_else(|| ApiError::new(400, "Missing search query `q`"))?;
+            to_json(&search_notes(app.state(), app.state(), q)?)
+        }
+        Route::Recording => to_json(&recording_status(app)),
+        Route::RecordingControl(action) => control_recording(app, action),
+    }
+}
+
+fn respond(app: &AppHandle, request: Request) {
+    let token = app
+        .state::<ApiServerState>()
+        .token
+        .lock()
+        .map(|t| t.clone())
+        .unwrap_or_default();
+    let authorized = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|given| token_matches(given.trim(), &token));
+
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let result = if !authorized {
+        Err(ApiError::new(401, "Missing or invalid API token"))
+    } else {
+        match route(request.method(), path) {
+            Some(route) => handle(app, route, query),
+            None => Err(ApiError::not_found()),
+        }
+    };
+
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err(e) => (
+            e.status,
+            serde_json::json!({ "error": e.message }).to_string(),
+        ),
+    };
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                .expect("valid header"),
+        );
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to answer API request: {}", e);
+    }
+}
+
+fn load_settings(db: &Database) -> ApiServerSettings {
+    db.get_setting(API_SERVER_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Start or stop the server to match the saved settings
+pub fn apply_api_server_settings(app: &AppHandle) {
+    let db = app.state::<Database>();
+    let state = app.state::<ApiServerState>();
+    state.stop();
+    if db.is_locked() {
+        return;
+    }
+
+    let settings = load_settings(&db);
+    if let Ok(mut token) = state.token.lock() {
+        *token = settings.token.clone();
+    }
+    let error = if settings.enabled {
+        match Server::http(("127.0.0.1", settings.port)) {
+            Ok(server) => {
+                let server = Arc::new(server);
+                let handle = app.clone();
+                let listener = server.clone();
+                let thread = std::thread::spawn(move || {
+                    for request in listener.incoming_requests() {
+                        let app = handle.clone();
+                        // Recording control waits on the webview; keep reads flowing
+                        std::thread::spawn(move || respond(&app, request));
+                    }
+                });
+                if let Ok(mut running) = state.server.lock() {
+                    *running = Some(RunningServer { server, thread });
+                }
+                None
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to start the API server on port {}: {}",
+                    settings.port, e
+                );
+                Some(format!("Could not listen on port {}: {}", settings.port, e))
+            }
+        }
+    } else {
+        None
+    };
+    if let Ok(mut last_error) = state.error.lock() {
+        *last_error = error;
+    }
+}
+
+fn status(db: &Database, state: &ApiServerState) -> ApiServerStatus {
+    ApiServerStatus {
+        settings: load_settings(db),
+        running: state.is_running(),
+        error: state.error.lock().ok().and_then(|e| e.clone()),
+    }
+}
+
+fn save_settings(db: &Database, settings: &ApiServerSettings) -> Result<(), String> {
+    let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+    db.set_setting(API_SERVER_KEY, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the API server settings, token included, and whether it is running
+#[tauri::command]
+pub fn get_api_server_status(db: State<Database>, state: State<ApiServerState>) -> ApiServerStatus {
+    status(&db, &state)
+}
+
+/// Enable or disable the API server, or move it to another port
+#[tauri::command]
+pub fn set_api_server_settings(
+    app: AppHandle,
+    db: State<Database>,
+    state: State<ApiServerState>,
+    enabled: bool,
+    port: u16,
+) -> Result<ApiServerStatus, String> {
+    if port < 1024 {
+        return Err("Choose a port from 1024 up".to_string());
+    }
+    let mut settings = load_settings(&db);
+    settings.enabled = enabled;
+    settings.port = port;
+    if settings.token.is_empty() {
+        settings.token = new_token();
+    }
+    save_settings(&db, &settings)?;
+    apply_api_server_settings(&app);
+    Ok(status(&db, &state))
+}
+
+/// Replace the API token; scripts using the old one stop working
+#[tauri::command]
+pub fn regenerate_api_token(
+    db: State<Database>,
+    state: State<ApiServerState>,
+) -> Result<ApiServerStatus, String> {
+    let mut settings = load_settings(&db);
+    settings.token = new_token();
+    save_settings(&db, &settings)?;
+    if let Ok(mut token) = state.token.lock() {
+        *token = settings.token.clone();
+    }
+    Ok(status(&db, &state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route(&Method::Get, "/api/notes"), Some(Route::Notes));
+        assert_eq!(
+            route(&Method::Get, "/api/notes/abc/"),
+            Some(Route::Note("abc"))
+        );
+        assert_eq!(
+            route(&Method::Get, "/api/notes/abc/summaries"),
+            Some(Route::Summaries("abc"))
+        );
+        assert_eq!(
+            route(&Method::Post, "/api/recording/pause"),
+            Some(Route::RecordingControl(RecordingAction::Pause))
+        );
+        assert_eq!(route(&Method::Get, "/api/recording/pause"), None);
+        assert_eq!(route(&Method::Post, "/api/recording/rewind"), None);
+        assert_eq!(route(&Method::Delete, "/api/notes/abc"), None);
+    }
+
+    #[test]
+    fn test_only_note_routes_need_unlocked_app() {
+        assert!(Route::Notes.reads_notes());
+        assert!(Route::Transcript("abc").reads_notes());
+        assert!(Route::Search.reads_notes());
+        assert!(!Route::Recording.reads_notes());
+        assert!(!Route::RecordingControl(RecordingAction::Start).reads_notes());
+    }
+
+    #[test]
+    fn test_query_param() {
+        let query = "limit=5&q=budget+review%20Q3&empty";
+        assert_eq!(query_param(query, "q").as_deref(), Some("budget review Q3"));
+        assert_eq!(query_param(query, "empty").as_deref(), Some(""));
+        assert_eq!(query_param(query, "missing"), None);
+        assert_eq!(query_param("q=c%2B%2B", "q").as_deref(), Some("c++"));
+        assert_eq!(
+            query_param("q=caf%C3%A9+100%", "q").as_deref(),
+            Some("café 100%")
+        );
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("abc123", "abc123"));
+        assert!(!token_matches("abc124", "abc123"));
+        assert!(!token_matches("abc", "abc123"));
+        assert!(!token_matches("", ""));
+        assert_eq!(new_token().len(), 64);
+    }
+
+    #[test]
+    fn test_recording_action() {
+        use RecordingAction::*;
+        assert!(Start.requires(RecordingPhase::Idle).is_ok());
+        assert!(Start.requires(RecordingPhase::Paused).is_err());
+        assert!(Stop.requires(RecordingPhase::Paused).is_ok());
+        assert!(Pause.requires(RecordingPhase::Paused).is_err());
+        assert_eq!(Resume.result(), RecordingPhase::Recording);
+    }
+}
@@ -1,12 +1,230 @@
 //! Acoustic Echo Cancellation (AEC) module
 //!
-//! Currently disabled - using post-processing deduplication instead.
-//! The complex NLMS filter was too slow and not effective enough.
+//! Removes the far-end signal (system audio) from the mic signal when the user
+//! is on speakers instead of headphones. The system-audio stream is used as the
+//! reference: a bulk delay is estimated by cross-correlation, then an NLMS
+//! adaptive filter models the room/speaker echo path and subtracts it.
+//!
+//! Both streams are expected as 16 kHz mono, which is what live transcription
+//! and the batch loader already produce.
 
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 /// Global flag to enable/disable AEC
-static AEC_ENABLED: AtomicBool = AtomicBool::new(false); // Disabled by default now
+static AEC_ENABLED: AtomicBool = AtomicBool::new(false); // Off by default (headphones are common)
+
+/// Shared processor for the live transcription path
+static LIVE_AEC: OnceLock<Mutex<Option<EchoCanceller>>> = OnceLock::new();
+
+fn get_live_aec() -> &'static Mutex<Option<EchoCanceller>> {
+    LIVE_AEC.get_or_init(|| Mutex::new(None))
+}
+
+/// Adaptive filter length: 32 ms at 16 kHz, enough for the residual echo tail
+/// once the bulk delay has been removed
+const FILTER_LEN: usize = 512;
+
+/// NLMS step size
+const STEP_SIZE: f32 = 0.4;
+
+/// Regularization to avoid dividing by ~0 when the reference is silent
+const REGULARIZATION: f32 = 1e-3;
+
+/// Largest speaker-to-mic delay we search for
+const MAX_DELAY_MS: u32 = 500;
+
+/// Decimation factor used for the coarse delay search
+const DELAY_DECIMATION: usize = 4;
+
+/// Minimum normalized correlation for a delay estimate to be trusted
+const MIN_DELAY_CORRELATION: f32 = 0.25;
+
+/// Geigel-style double-talk detector: adaptation freezes while the mic is this
+/// many times louder than the recent reference peak (the near-end user is talking)
+const DOUBLE_TALK_RATIO: f32 = 2.0;
+
+/// How often the bulk delay is re-estimated once one has been found (seconds)
+const DELAY_REESTIMATE_SECS: u32 = 10;
+
+/// Streaming echo canceller (NLMS with bulk delay compensation)
+pub struct EchoCanceller {
+    sample_rate: u32,
+    weights: Vec<f32>,
+    /// Reference history, `reference[0]` is global sample `reference_base`
+    reference: Vec<f32>,
+    reference_base: usize,
+    /// Number of mic samples processed so far
+    mic_pos: usize,
+    /// Estimated bulk delay (reference -> mic) in samples
+    delay: Option<usize>,
+    /// Mic samples processed since the last delay search
+    since_estimate: usize,
+}
+
+impl EchoCanceller {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            weights: vec![0.0; FILTER_LEN],
+            reference: Vec::new(),
+            reference_base: 0,
+            mic_pos: 0,
+            delay: None,
+            since_estimate: 0,
+        }
+    }
+
+    /// Current bulk delay estimate in milliseconds
+    #[allow(dead_code)]
+    pub fn delay_ms(&self) -> Option<f32> {
+        self.delay.map(|d| d as f32 * 1000.0 / self.sample_rate as f32)
+    }
+
+    /// Process a block of mic samples together with the reference samples
+    /// captured over the same period. Returns the echo-cancelled mic signal.
+    pub fn process(&mut self, mic: &[f32], reference: &[f32]) -> Vec<f32> {
+        self.reference.extend_from_slice(reference);
+
+        // (Re-)estimate the bulk delay; only switch when the new estimate is
+        // confident and clearly different from the current one
+        let max_delay = (MAX_DELAY_MS * self.sample_rate / 1000) as usize;
+        let mic_start = self.mic_pos;
+        let reestimate_every = (DELAY_REESTIMATE_SECS * self.sample_rate) as usize;
+        if self.delay.is_none() || self.since_estimate >= reestimate_every {
+            let ref_window = self.reference_window(mic_start, mic.len() + max_delay, max_delay);
+            if let Some((delay, _)) = estimate_delay(mic, &ref_window, max_delay) {
+                self.since_estimate = 0;
+                let changed = match self.delay {
+                    Some(current) => current.abs_diff(delay) > FILTER_LEN / 4,
+                    None => true,
+                };
+                if changed {
+                    self.delay = Some(delay);
+                    self.weights.iter_mut().for_each(|w| *w = 0.0);
+                }
+            }
+        }
+        self.since_estimate += mic.len();
+
+        let Some(delay) = self.delay else {
+            // No echo detected yet: pass the mic through untouched
+            self.mic_pos += mic.len();
+            self.trim_reference(max_delay);
+            return mic.to_vec();
+        };
+
+        // Filter taps are centred slightly before the bulk delay so that small
+        // estimation errors in either direction stay inside the filter
+        let lead = FILTER_LEN / 8;
+        let mut output = Vec::with_capacity(mic.len());
+        let mut x = vec![0.0f32; FILTER_LEN];
+
+        for (i, &d) in mic.iter().enumerate() {
+            let n = mic_start + i;
+
+            // x[k] = reference[n - delay + lead - k]
+            let newest = (n + lead) as isize - delay as isize;
+            let mut energy = 0.0f32;
+            let mut peak = 0.0f32;
+            for (k, xk) in x.iter_mut().enumerate() {
+                *xk = self.reference_at(newest - k as isize);
+                energy += *xk * *xk;
+                peak = peak.max(xk.abs());
+            }
+
+            let estimate: f32 = self.weights.iter().zip(&x).map(|(w, xk)| w * xk).sum();
+            let error = d - estimate;
+            output.push(error);
+
+            // Skip adaptation during double talk or when there is nothing to learn from
+            let double_talk = d.abs() > DOUBLE_TALK_RATIO * peak;
+            if energy > 1e-6 && !double_talk {
+                let g = STEP_SIZE * error / (energy + REGULARIZATION);
+                for (w, xk) in self.weights.iter_mut().zip(&x) {
+                    *w += g * xk;
+                }
+            }
+        }
+
+        self.mic_pos += mic.len();
+        self.trim_reference(max_delay);
+        output
+    }
+
+    fn reference_at(&self, index: isize) -> f32 {
+        if index < self.reference_base as isize {
+            return 0.0;
+        }
+        self.reference
+            .get(index as usize - self.reference_base)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Reference samples aligned so that index 0 is `max_delay` samples before `start`
+    fn reference_window(&self, start: usize, len: usize, max_delay: usize) -> Vec<f32> {
+        let first = start as isize - max_delay as isize;
+        (0..len)
+            .map(|i| self.reference_at(first + i as isize))
+            .collect()
+    }
+
+    /// Drop reference samples that can no longer be reached by the filter
+    fn trim_reference(&mut self, max_delay: usize) {
+        let keep_from = self.mic_pos.saturating_sub(max_delay + FILTER_LEN);
+        if keep_from > self.reference_base {
+            let drop = (keep_from - self.reference_base).min(self.reference.len());
+            self.reference.drain(..drop);
+            self.reference_base += drop;
+        }
+    }
+}
+
+/// Estimate how many samples the echo in `mic` lags behind the reference.
+///
+/// `reference` must start `max_delay` samples before `mic` does (so that
+/// `reference[max_delay + i - delay]` lines up with `mic[i]`). The search runs
+/// on a decimated copy of both signals to keep it cheap.
+/// Returns the delay and the normalized correlation at that delay.
+pub fn estimate_delay(mic: &[f32], reference: &[f32], max_delay: usize) -> Option<(usize, f32)> {
+    let decimate = |s: &[f32]| -> Vec<f32> {
+        s.chunks(DELAY_DECIMATION)
+            .map(|c| c.iter().sum::<f32>() / c.len() as f32)
+            .collect()
+    };
+    let mic_d = decimate(mic);
+    let ref_d = decimate(reference);
+    let max_lag = max_delay / DELAY_DECIMATION;
+
+    let mic_energy: f32 = mic_d.iter().map(|s| s * s).sum();
+    if mic_d.len() < 64 || mic_energy < 1e-4 {
+        return None;
+    }
+
+    let mut best: Option<(usize, f32)> = None;
+    for lag in 0..=max_lag {
+        // mic[i] against reference[max_lag + i - lag]
+        let offset = max_lag - lag;
+        let mut dot = 0.0f32;
+        let mut ref_energy = 0.0f32;
+        for (i, &m) in mic_d.iter().enumerate() {
+            let r = ref_d.get(offset + i).copied().unwrap_or(0.0);
+            dot += m * r;
+            ref_energy += r * r;
+        }
+        if ref_energy < 1e-4 {
+            continue;
+        }
+        let corr = dot.abs() / (mic_energy * ref_energy).sqrt();
+        if best.is_none_or(|(_, c)| corr > c) {
+            best = Some((lag, corr));
+        }
+    }
+
+    best.filter(|(_, corr)| *corr >= MIN_DELAY_CORRELATION)
+        .map(|(lag, corr)| (lag * DELAY_DECIMATION, corr))
+}
 
 /// Check if AEC is enabled
 pub fn is_aec_enabled() -> bool {
@@ -18,22 +236,109 @@ pub fn set_aec_enabled(enabled: bool) {
     AEC_ENABLED.store(enabled, Ordering::SeqCst);
 }
 
-/// Initialize the global AEC processor (no-op now)
-#[allow(dead_code)]
-pub fn init_aec(_sample_rate: u32) {
-    // No-op - AEC disabled
+/// Initialize the live AEC processor for a new session
+pub fn init_aec(sample_rate: u32) {
+    if let Ok(mut guard) = get_live_aec().lock() {
+        *guard = Some(EchoCanceller::new(sample_rate));
+    }
 }
 
-/// Apply AEC to mic samples - now just returns original samples
-/// Echo removal is handled by post-processing deduplication in live.rs
-#[allow(dead_code)]
-pub fn apply_aec(mic_samples: &[f32], _reference_samples: &[f32]) -> Vec<f32> {
-    // Just return original samples - deduplication handles echo removal
-    mic_samples.to_vec()
+/// Apply live AEC to a block of 16 kHz mono mic samples.
+/// Returns the input unchanged when AEC is disabled or not initialized.
+pub fn apply_aec(mic_samples: &[f32], reference_samples: &[f32]) -> Vec<f32> {
+    if !is_aec_enabled() {
+        return mic_samples.to_vec();
+    }
+    match get_live_aec().lock() {
+        Ok(mut guard) => match guard.as_mut() {
+            Some(aec) => aec.process(mic_samples, reference_samples),
+            None => mic_samples.to_vec(),
+        },
+        Err(_) => mic_samples.to_vec(),
+    }
 }
 
-/// Reset the AEC processor (no-op now)
-#[allow(dead_code)]
+/// Reset the live AEC processor
 pub fn reset_aec() {
-    // No-op
+    if let Ok(mut guard) = get_live_aec().lock() {
+        *guard = None;
+    }
+}
+
+/// Run echo cancellation over a whole recording (batch path)
+pub fn cancel_echo(mic_samples: &[f32], reference_samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    // Feed in one-second blocks so the delay estimate can settle like it does live
+    let block = sample_rate as usize;
+    let mut aec = EchoCanceller::new(sample_rate);
+    let mut output = Vec::with_capacity(mic_samples.len());
+
+    for (i, mic_block) in mic_samples.chunks(block.max(1)).enumerate() {
+        let start = (i * block).min(reference_samples.len());
+        let end = (start + mic_block.len()).min(reference_samples.len());
+        output.extend(aec.process(mic_block, &reference_samples[start..end]));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random noise (speech-like broadband signal)
+    fn noise(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+
+    fn energy(s: &[f32]) -> f32 {
+        s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32
+    }
+
+    #[test]
+    fn test_estimate_delay_finds_shift() {
+        let reference = noise(16000 + 8000, 1);
+        let delay = 1200;
+        // mic[i] = reference[max_delay + i - delay]
+        let mic: Vec<f32> = (0..16000).map(|i| 0.5 * reference[8000 + i - delay]).collect();
+        let (found, corr) = estimate_delay(&mic, &reference, 8000).unwrap();
+        assert!(found.abs_diff(delay) <= DELAY_DECIMATION, "found {}", found);
+        assert!(corr > 0.5);
+    }
+
+    #[test]
+    fn test_cancel_echo_reduces_echo_energy() {
+        let sample_rate = 16000;
+        let reference = noise(sample_rate * 6, 7);
+        let delay = 1600; // 100 ms
+        let mic: Vec<f32> = (0..reference.len())
+            .map(|i| {
+                // Direct path plus one short reflection
+                let direct = i.checked_sub(delay).map_or(0.0, |j| reference[j]);
+                let reflection = i.checked_sub(delay + 3).map_or(0.0, |j| reference[j]);
+                0.6 * direct + 0.2 * reflection
+            })
+            .collect();
+
+        let cleaned = cancel_echo(&mic, &reference, sample_rate as u32);
+        assert_eq!(cleaned.len(), mic.len());
+
+        // After convergence the residual echo should be far below the input
+        let tail = sample_rate * 4..;
+        let before = energy(&mic[tail.clone()]);
+        let after = energy(&cleaned[tail]);
+        assert!(after < before * 0.05, "before {} after {}", before, after);
+    }
+
+    #[test]
+    fn test_no_reference_passes_through() {
+        let mic = noise(16000, 3);
+        let cleaned = cancel_echo(&mic, &[], 16000);
+        assert_eq!(cleaned, mic);
+    }
 }
@@ -0,0 +1,278 @@
+//! Cutting a time range out of a WAV file and saving it as WAV, FLAC or Opus.
+
+use std::path::Path;
+
+use hound::{SampleFormat, WavSpec, WavWriter};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus::{Application, Channels, Encoder as OpusEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::mixer::resample;
+use crate::audio::{flac, open_wav, AudioError};
+
+/// Output format of an extracted clip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipFormat {
+    Wav,
+    Flac,
+    Opus,
+}
+
+impl ClipFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ClipFormat::Wav => "wav",
+            ClipFormat::Flac => "flac",
+            ClipFormat::Opus => "opus",
+        }
+    }
+}
+
+/// Interleaved 16-bit audio cut from a longer recording
+#[derive(Debug, Clone)]
+pub struct Clip {
+    pub samples: Vec<i16>,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+/// Read the `[start_ms, end_ms)` range of a WAV file as 16-bit samples
+pub fn read_clip(path: &Path, start_ms: i64, end_ms: i64) -> Result<Clip, AudioError> {
+    let mut reader = open_wav(path)?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1);
+    let rate = spec.sample_rate as i64;
+
+    let total = reader.duration() as i64;
+    let start = (start_ms.max(0) * rate / 1000).min(total);
+    let end = (end_ms.max(0) * rate / 1000).clamp(start, total);
+    reader.seek(start as u32)?;
+    let count = ((end - start) as usize) * channels as usize;
+
+    let samples = if spec.sample_format == SampleFormat::Float {
+        reader
+            .samples::<f32>()
+            .take(count)
+            .filter_map(|s| s.ok())
+            .map(|s| (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+            .collect()
+    } else {
+        let shift = spec.bits_per_sample as i32 - 16;
+        reader
+            .samples::<i32>()
+            .take(count)
+            .filter_map(|s| s.ok())
+            .map(|s| if shift >= 0 { s >> shift } else { s << -shift } as i16)
+            .collect()
+    };
+
+    Ok(Clip {
+        samples,
+        channels,
+        sample_rate: spec.sample_rate,
+    })
+}
+
+/// Write a clip in the requested format
+pub fn write_clip(clip: &Clip, format: ClipFormat, output: &Path) -> Result<(), AudioError> {
+    match format {
+        ClipFormat::Wav => {
+            let spec = WavSpec {
+                channels: clip.channels,
+                sample_rate: clip.sample_rate,
+                bits_per_sample: 16,
+                sample_format: SampleFormat::Int,
+            };
+            let mut writer = WavWriter::create(output, spec)?;
+            for &s in &clip.samples {
+                writer.write_sample(s)?;
+            }
+            writer.finalize()?;
+            Ok(())
+        }
+        ClipFormat::Flac => {
+            flac::write_flac(output, &clip.samples, clip.channels, clip.sample_rate)
+        }
+        ClipFormat::Opus => write_ogg_opus(clip, output),
+    }
+}
+
+/// Input rates libopus accepts without resampling
+const OPUS_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// Encode a clip as Ogg Opus (RFC 7845), resampling when Opus can't take the rate directly
+fn write_ogg_opus(clip: &Clip, output: &Path) -> Result<(), AudioError> {
+    let opus_err = |e: opus::Error| AudioError::EncodeError(e.to_string());
+
+    let channels = clip.channels.clamp(1, 2) as usize;
+    let (samples, rate) = if OPUS_SAMPLE_RATES.contains(&clip.sample_rate) {
+        (downmix_to(clip, channels), clip.sample_rate)
+    } else {
+        (
+            resample_interleaved(
+                &downmix_to(clip, channels),
+                channels,
+                clip.sample_rate,
+                48000,
+            ),
+            48000,
+        )
+    };
+    let opus_channels = if channels == 2 {
+        Channels::Stereo
+    } else {
+        Channels::Mono
+    };
+    let mut encoder = OpusEncoder::new(rate, opus_channels, Application::Voip).map_err(opus_err)?;
+    let pre_skip = encoder.get_lookahead().map_err(opus_err)? as u64 * 48000 / rate as u64;
+
+    let file = std::fs::File::create(output)?;
+    let mut writer = PacketWriter::new(file);
+    let serial = uuid::Uuid::new_v4().as_u128() as u32;
+
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1);
+    head.push(channels as u8);
+    head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+    head.extend_from_slice(&clip.sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes());
+    head.push(0);
+    writer.write_packet(
+        head.into_boxed_slice(),
+        serial,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+
+    let vendor = concat!("netnote ", env!("CARGO_PKG_VERSION"));
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    writer.write_packet(
+        tags.into_boxed_slice(),
+        serial,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+
+    // 20ms frames; the last one is zero-padded and trimmed again by the end granule
+    let frame_len = rate as usize / 50 * channels;
+    let frames: Vec<&[i16]> = samples.chunks(frame_len).collect();
+    let total_granule = pre_skip + (samples.len() / channels) as u64 * 48000 / rate as u64;
+    let mut buffer = [0u8; 4000];
+    let mut padded = vec![0i16; frame_len];
+
+    for (i, frame) in frames.iter().enumerate() {
+        let input = if frame.len() == frame_len {
+            *frame
+        } else {
+            padded[..frame.len()].copy_from_slice(frame);
+            padded[frame.len()..].fill(0);
+            &padded[..]
+        };
+        let len = encoder.encode(input, &mut buffer).map_err(opus_err)?;
+
+        let last = i + 1 == frames.len();
+        let granule = if last {
+            total_granule
+        } else {
+            pre_skip + ((i + 1) * 960) as u64
+        };
+        let end_info = if last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(
+            buffer[..len].to_vec().into_boxed_slice(),
+            serial,
+            end_info,
+            granule,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Reduce a clip to averaged mono or to its first two channels
+fn downmix_to(clip: &Clip, channels: usize) -> Vec<i16> {
+    let source = clip.channels.max(1) as usize;
+    if source == channels {
+        return clip.samples.clone();
+    }
+    clip.samples
+        .chunks(source)
+        .flat_map(|frame| {
+            let mono = frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32;
+            let first = frame.first().copied().unwrap_or(0);
+            let second = frame.get(1).copied().unwrap_or(first);
+            match channels {
+                1 => vec![mono as i16],
+                _ => vec![first, second],
+            }
+        })
+        .collect()
+}
+
+fn resample_interleaved(samples: &[i16], channels: usize, from: u32, to: u32) -> Vec<i16> {
+    let per_channel: Vec<Vec<f32>> = (0..channels)
+        .map(|ch| {
+            let channel: Vec<f32> = samples
+                .iter()
+                .skip(ch)
+                .step_by(channels)
+                .map(|&s| s as f32 / i16::MAX as f32)
+                .collect();
+            resample(&channel, from, to)
+        })
+        .collect();
+
+    let len = per_channel.iter().map(|c| c.len()).min().unwrap_or(0);
+    (0..len)
+        .flat_map(|i| {
+            per_channel
+                .iter()
+                .map(move |c| (c[i] * i16::MAX as f32) as i16)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_clip_cuts_requested_range() {
+        let dir = std::env::temp_dir().join(format!("netnote-clip-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("source.wav");
+
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for i in 0..3000i16 {
+            writer.write_sample(i).unwrap();
+            writer.write_sample(-i).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let clip = read_clip(&path, 1000, 1500).unwrap();
+        assert_eq!(clip.channels, 2);
+        assert_eq!(clip.samples.len(), 500 * 2);
+        assert_eq!(&clip.samples[..2], &[1000, -1000]);
+
+        // Ranges past the end are clamped to the recording
+        let tail = read_clip(&path, 2900, 9000).unwrap();
+        assert_eq!(tail.samples.len(), 100 * 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,361 @@
+//! Noise suppression for microphone audio
+//!
+//! A streaming spectral gate: short-time FFT with 50% overlapping sqrt-Hann
+//! frames, a per-bin noise floor that follows the quietest recent energy, and
+//! a Wiener-style gain (fast attack, slow release) bounded by a maximum
+//! reduction. It runs in the recorder callback and offline over existing
+//! recordings before re-transcription.
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use serde::{Deserialize, Serialize};
+
+/// Target frame length in seconds (rounded up to a power of two in samples)
+const FRAME_SECONDS: f32 = 0.02;
+
+/// Noise floor smoothing when the frame is quieter than the estimate (tracks down fast)
+const NOISE_FALL: f32 = 0.9;
+
+/// Noise floor smoothing when the frame is louder than the estimate (tracks up slowly,
+/// so speech doesn't get absorbed into the floor)
+const NOISE_RISE: f32 = 0.998;
+
+/// Smoothing of the per-bin power over time before it is compared to the floor
+const POWER_SMOOTHING: f32 = 0.7;
+
+/// Over-subtraction factor: bins need this much SNR before they pass untouched
+const OVER_SUBTRACTION: f32 = 2.0;
+
+/// Per-frame gain release (smooths gain drops to avoid "musical noise")
+const GAIN_RELEASE: f32 = 0.6;
+
+/// Where the noise-suppression stage is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseSuppressionTarget {
+    /// Disabled
+    #[default]
+    Off,
+    /// Only the mic WAV written to disk
+    StoredFile,
+    /// Only the audio fed to Whisper (live buffer and re-transcription)
+    Transcription,
+    /// Both the stored file and the transcription feed
+    Both,
+}
+
+impl NoiseSuppressionTarget {
+    pub fn applies_to_file(self) -> bool {
+        matches!(self, Self::StoredFile | Self::Both)
+    }
+
+    pub fn applies_to_transcription(self) -> bool {
+        matches!(self, Self::Transcription | Self::Both)
+    }
+}
+
+/// User-configurable noise suppression settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoiseSuppressionOptions {
+    pub target: NoiseSuppressionTarget,
+    /// Maximum attenuation applied to noise-only bins (dB)
+    pub reduction_db: f32,
+}
+
+impl Default for NoiseSuppressionOptions {
+    fn default() -> Self {
+        Self {
+            target: NoiseSuppressionTarget::Off,
+            reduction_db: 15.0,
+        }
+    }
+}
+
+/// Current options, shared by the recorder and the transcription commands
+static OPTIONS: OnceLock<Mutex<NoiseSuppressionOptions>> = OnceLock::new();
+
+fn options_cell() -> &'static Mutex<NoiseSuppressionOptions> {
+    OPTIONS.get_or_init(|| Mutex::new(NoiseSuppressionOptions::default()))
+}
+
+/// Get the current noise suppression options
+pub fn noise_suppression_options() -> NoiseSuppressionOptions {
+    options_cell().lock().map(|o| *o).unwrap_or_default()
+}
+
+/// Replace the noise suppression options (takes effect on the next recording)
+pub fn set_noise_suppression_options(options: NoiseSuppressionOptions) {
+    if let Ok(mut current) = options_cell().lock() {
+        *current = options;
+    }
+}
+
+/// Streaming single-channel spectral gate.
+///
+/// Output lags input by `hop` samples and is produced in whole hops.
+pub struct SpectralGate {
+    frame_len: usize,
+    hop: usize,
+    window: Vec<f32>,
+    fft: Arc<dyn RealToComplex<f32>>,
+    ifft: Arc<dyn ComplexToReal<f32>>,
+    /// Last `frame_len` input samples
+    history: Vec<f32>,
+    /// Input samples not yet consumed (< hop)
+    pending: Vec<f32>,
+    /// Overlap-add accumulator
+    overlap: Vec<f32>,
+    /// Time-smoothed power per bin
+    power: Vec<f32>,
+    noise: Vec<f32>,
+    gains: Vec<f32>,
+    floor: f32,
+    frames_seen: usize,
+    time_buf: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+}
+
+impl SpectralGate {
+    pub fn new(sample_rate: u32, reduction_db: f32) -> Self {
+        let frame_len = ((sample_rate as f32 * FRAME_SECONDS) as usize)
+            .max(64)
+            .next_power_of_two();
+        let hop = frame_len / 2;
+
+        // sqrt-Hann for analysis and synthesis: the squared window sums to 1 at 50% overlap
+        let window: Vec<f32> = (0..frame_len)
+            .map(|n| (std::f32::consts::PI * n as f32 / frame_len as f32).sin())
+            .collect();
+
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(frame_len);
+        let ifft = planner.plan_fft_inverse(frame_len);
+        let bins = frame_len / 2 + 1;
+
+        Self {
+            frame_len,
+            hop,
+            window,
+            time_buf: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            fft,
+            ifft,
+            history: vec![0.0; frame_len],
+            pending: Vec::with_capacity(hop),
+            overlap: vec![0.0; frame_len],
+            power: vec![0.0; bins],
+            noise: vec![0.0; bins],
+            gains: vec![1.0; bins],
+            floor: 10f32.powf(-reduction_db.max(0.0) / 20.0),
+            frames_seen: 0,
+        }
+    }
+
+    /// Processing delay in samples
+    pub fn latency(&self) -> usize {
+        self.hop
+    }
+
+    /// Feed samples and return whatever denoised output is ready
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::with_capacity(input.len() + self.hop);
+        let mut remaining = input;
+
+        while !remaining.is_empty() {
+            let take = (self.hop - self.pending.len()).min(remaining.len());
+            self.pending.extend_from_slice(&remaining[..take]);
+            remaining = &remaining[take..];
+
+            if self.pending.len() == self.hop {
+                self.history.copy_within(self.hop.., 0);
+                let start = self.frame_len - self.hop;
+                self.history[start..].copy_from_slice(&self.pending);
+                self.pending.clear();
+                self.process_frame(&mut output);
+            }
+        }
+
+        output
+    }
+
+    fn process_frame(&mut self, output: &mut Vec<f32>) {
+        for ((t, &x), &w) in self.time_buf.iter_mut().zip(&self.history).zip(&self.window) {
+            *t = x * w;
+        }
+        if self.fft.process(&mut self.time_buf, &mut self.spectrum).is_err() {
+            return;
+        }
+
+        for (k, bin) in self.spectrum.iter_mut().enumerate() {
+            let power = &mut self.power[k];
+            let noise = &mut self.noise[k];
+            if self.frames_seen == 0 {
+                // Seed both estimates with the first frame
+                *power = bin.norm_sqr();
+                *noise = *power;
+            } else {
+                *power = POWER_SMOOTHING * *power + (1.0 - POWER_SMOOTHING) * bin.norm_sqr();
+                let smoothing = if *power < *noise { NOISE_FALL } else { NOISE_RISE };
+                *noise = smoothing * *noise + (1.0 - smoothing) * *power;
+            }
+
+            let target = if *power > 0.0 {
+                (1.0 - OVER_SUBTRACTION * *noise / *power).max(self.floor)
+            } else {
+                self.floor
+            };
+            let gain = &mut self.gains[k];
+            *gain = if target > *gain {
+                target
+            } else {
+                GAIN_RELEASE * *gain + (1.0 - GAIN_RELEASE) * target
+            };
+            *bin *= *gain;
+        }
+        self.frames_seen += 1;
+
+        // DC and Nyquist bins must be purely real for the inverse transform
+        if let Some(first) = self.spectrum.first_mut() {
+            first.im = 0.0;
+        }
+        if let Some(last) = self.spectrum.last_mut() {
+            last.im = 0.0;
+        }
+        if self.ifft.process(&mut self.spectrum, &mut self.time_buf).is_err() {
+            return;
+        }
+
+        let scale = 1.0 / self.frame_len as f32;
+        for ((acc, &y), &w) in self.overlap.iter_mut().zip(&self.time_buf).zip(&self.window) {
+            *acc += y * scale * w;
+        }
+
+        output.extend_from_slice(&self.overlap[..self.hop]);
+        self.overlap.copy_within(self.hop.., 0);
+        let start = self.frame_len - self.hop;
+        self.overlap[start..].fill(0.0);
+    }
+}
+
+/// Spectral gate for interleaved multi-channel audio (one gate per channel)
+pub struct NoiseSuppressor {
+    gates: Vec<SpectralGate>,
+    channels: usize,
+}
+
+impl NoiseSuppressor {
+    pub fn new(sample_rate: u32, channels: u16, reduction_db: f32) -> Self {
+        let channels = channels.max(1) as usize;
+        Self {
+            gates: (0..channels)
+                .map(|_| SpectralGate::new(sample_rate, reduction_db))
+                .collect(),
+            channels,
+        }
+    }
+
+    /// Denoise an interleaved buffer; output is interleaved and delayed by one hop
+    pub fn process_interleaved(&mut self, data: &[f32]) -> Vec<f32> {
+        if self.channels == 1 {
+            return self.gates[0].process(data);
+        }
+
+        let outputs: Vec<Vec<f32>> = self
+            .gates
+            .iter_mut()
+            .enumerate()
+            .map(|(ch, gate)| {
+                let channel: Vec<f32> = data.iter().skip(ch).step_by(self.channels).copied().collect();
+                gate.process(&channel)
+            })
+            .collect();
+
+        let frames = outputs.iter().map(|o| o.len()).min().unwrap_or(0);
+        let mut interleaved = Vec::with_capacity(frames * self.channels);
+        for i in 0..frames {
+            for output in &outputs {
+                interleaved.push(output[i]);
+            }
+        }
+        interleaved
+    }
+}
+
+/// Denoise a whole mono signal offline; output is time-aligned with the input
+pub fn denoise(samples: &[f32], sample_rate: u32, reduction_db: f32) -> Vec<f32> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let mut gate = SpectralGate::new(sample_rate, reduction_db);
+    let latency = gate.latency();
+    let mut output = gate.process(samples);
+    // Flush the tail through the pipeline
+    output.extend(gate.process(&vec![0.0; latency * 2]));
+
+    output.into_iter().skip(latency).take(samples.len()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic white-ish noise (xorshift)
+    fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state: u32 = 0x1234_5678;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_denoise_attenuates_steady_noise_and_keeps_tone() {
+        let rate = 16000;
+        let hiss = noise(rate as usize * 3, 0.05);
+        let cleaned = denoise(&hiss, rate, 15.0);
+        assert_eq!(cleaned.len(), hiss.len());
+        // After the floor settles, noise should drop by well over 6 dB
+        let tail = rate as usize;
+        let ratio = rms(&cleaned[tail..]) / rms(&hiss[tail..]);
+        assert!(ratio < 0.5, "noise ratio {}", ratio);
+
+        // Speech-like tone bursts (400 ms on/off) over the same hiss pass mostly untouched
+        let burst = (rate as f32 * 0.4) as usize;
+        let mixed: Vec<f32> = hiss
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                let on = (i / burst).is_multiple_of(2);
+                let tone = (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin();
+                n + if on { 0.5 * tone } else { 0.0 }
+            })
+            .collect();
+        let cleaned = denoise(&mixed, rate, 15.0);
+        // Fifth burst, skipping its edges
+        let start = burst * 4 + burst / 4;
+        let end = burst * 5 - burst / 4;
+        let ratio = rms(&cleaned[start..end]) / rms(&mixed[start..end]);
+        assert!(ratio > 0.9, "tone ratio {}", ratio);
+    }
+
+    #[test]
+    fn test_noise_suppressor_keeps_channels_interleaved() {
+        let mut suppressor = NoiseSuppressor::new(48000, 2, 15.0);
+        let input = vec![0.0f32; 4800 * 2];
+        let output = suppressor.process_interleaved(&input);
+        assert_eq!(output.len() % 2, 0);
+        assert!(!output.is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! Input device enumeration and per-device stream configuration
+//!
+//! cpal 0.15 doesn't expose hardware identifiers, so ids are built from the
+//! host, the device name and the device's ordinal among devices with the same
+//! name (`CoreAudio:USB Headset#1`). The ordinal only tells identical devices
+//! apart while the same set is connected: unplugging one of two identical
+//! headsets renumbers the other, and a saved id may then select a different
+//! headset of that model. Plain names (ids saved by older versions) still
+//! resolve to the first match.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::{Device, SampleFormat, SupportedStreamConfig};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioError;
+
+/// How the device's channels are turned into the recorded signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "mode")]
+pub enum ChannelSelection {
+    /// Keep every channel the device delivers
+    #[default]
+    All,
+    /// Average all channels into mono
+    Downmix,
+    /// Record a single channel (0-based) as mono, e.g. one input of an audio interface
+    Single { channel: u16 },
+}
+
+impl ChannelSelection {
+    /// Number of channels written for a device delivering `device_channels`
+    pub fn output_channels(self, device_channels: u16) -> u16 {
+        match self {
+            Self::All => device_channels,
+            Self::Downmix | Self::Single { .. } => 1,
+        }
+    }
+
+    /// Convert interleaved device samples into the selected layout
+    pub fn apply(self, data: &[f32], device_channels: u16) -> Vec<f32> {
+        let channels = device_channels.max(1) as usize;
+        match self {
+            Self::All => data.to_vec(),
+            Self::Downmix => data
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+                .collect(),
+            Self::Single { channel } => {
+                let channel = (channel as usize).min(channels - 1);
+                data.chunks(channels)
+                    .map(|frame| frame.get(channel).copied().unwrap_or(0.0))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Saved input settings for one device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DevicePreferences {
+    /// Requested sample rate (None = device default)
+    pub sample_rate: Option<u32>,
+    pub channels: ChannelSelection,
+}
+
+/// A stream configuration range supported by an input device
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedInputConfig {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+}
+
+/// An input device with its id and supported configurations
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub default_sample_rate: Option<u32>,
+    pub default_channels: Option<u16>,
+    pub configs: Vec<SupportedInputConfig>,
+}
+
+/// Enumerate input devices paired with their ids (host, name and ordinal)
+fn input_devices_with_ids() -> Result<Vec<(String, Device)>, AudioError> {
+    let host = cpal::default_host();
+    let host_name = host.id().name();
+    let mut seen: Vec<String> = Vec::new();
+    let mut result = Vec::new();
+
+    for device in host.input_devices()? {
+        let Ok(name) = device.name() else {
+            continue;
+        };
+        let ordinal = seen.iter().filter(|n| **n == name).count();
+        seen.push(name.clone());
+        result.push((format!("{}:{}#{}", host_name, name, ordinal), device));
+    }
+
+    Ok(result)
+}
+
+/// List input devices with their supported configurations
+pub fn list_input_devices() -> Result<Vec<InputDevice>, AudioError> {
+    let default_name = cpal::default_host()
+        .default_input_device()
+        .and_then(|d| d.name().ok());
+    let mut default_claimed = false;
+    let mut result = Vec::new();
+
+    for (id, device) in input_devices_with_ids()? {
+        let name = device.name().unwrap_or_default();
+        // Only the first device with the default's name is marked as default
+        let is_default = !default_claimed && Some(&name) == default_name.as_ref();
+        default_claimed |= is_default;
+
+        let default_config = device.default_input_config().ok();
+        let configs = device
+            .supported_input_configs()
+            .map(|configs| {
+                configs
+                    .map(|c| SupportedInputConfig {
+                        channels: c.channels(),
+                        min_sample_rate: c.min_sample_rate().0,
+                        max_sample_rate: c.max_sample_rate().0,
+                        sample_format: c.sample_format().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        result.push(InputDevice {
+            id,
+            name,
+            is_default,
+            default_sample_rate: default_config.as_ref().map(|c| c.sample_rate().0),
+            default_channels: default_config.as_ref().map(|c| c.channels()),
+            configs,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Resolve a device id (or legacy plain name) to a device, falling back to the default input.
+/// Returns the device together with its id.
+pub fn find_input_device(device_id: Option<&str>) -> Result<(Device, String), AudioError> {
+    let devices = input_devices_with_ids()?;
+
+    if let Some(wanted) = device_id {
+        let by_id = devices.iter().position(|(id, _)| id == wanted);
+        let by_name = || {
+            devices
+                .iter()
+                .position(|(_, d)| d.name().is_ok_and(|name| name == wanted))
+        };
+        if let Some(index) = by_id.or_else(by_name) {
+            let (id, device) = devices.into_iter().nth(index).ok_or(AudioError::NoInputDevice)?;
+            return Ok((device, id));
+        }
+    }
+
+    let default = cpal::default_host()
+        .default_input_device()
+        .ok_or(AudioError::NoInputDevice)?;
+    let default_name = default.name().ok();
+    let id = devices
+        .into_iter()
+        .find(|(_, d)| d.name().ok() == default_name)
+        .map(|(id, _)| id)
+        .unwrap_or_default();
+    Ok((default, id))
+}
+
+/// Pick the stream configuration for `device`, honouring a preferred sample rate
+/// when the device supports it (otherwise the device default is used)
+pub fn stream_config_for(
+    device: &Device,
+    preferences: &DevicePreferences,
+) -> Result<SupportedStreamConfig, AudioError> {
+    let default = device.default_input_config()?;
+    let Some(rate) = preferences.sample_rate else {
+        return Ok(default);
+    };
+    if default.sample_rate().0 == rate {
+        return Ok(default);
+    }
+
+    let Ok(configs) = device.supported_input_configs() else {
+        return Ok(default);
+    };
+    let best = configs
+        .filter(|c| c.min_sample_rate().0 <= rate && rate <= c.max_sample_rate().0)
+        .filter(|c| {
+            matches!(
+                c.sample_format(),
+                SampleFormat::F32 | SampleFormat::I16 | SampleFormat::U16
+            )
+        })
+        // Prefer the default channel count, then float samples
+        .max_by_key(|c| {
+            (
+                c.channels() == default.channels(),
+                c.sample_format() == SampleFormat::F32,
+            )
+        });
+
+    Ok(best
+        .map(|c| c.with_sample_rate(cpal::SampleRate(rate)))
+        .unwrap_or(default))
+}
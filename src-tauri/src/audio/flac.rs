@@ -0,0 +1,299 @@
+//! Minimal 16-bit FLAC encoder (fixed predictors, Rice-coded residuals).
+//!
+//! Clips are short and always 16-bit PCM, so fixed predictors are enough and
+//! we avoid linking libFLAC or a full encoder crate for one export format. The
+//! output is checked by decoding it with claxon in the tests below.
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::audio::AudioError;
+
+/// Samples per channel in each FLAC frame
+const BLOCK_SIZE: usize = 4096;
+
+/// Highest Rice parameter expressible with the 4-bit parameter encoding
+const MAX_RICE_PARAM: u32 = 14;
+
+/// Bit-level writer, most significant bit first
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            acc: 0,
+            bits: 0,
+        }
+    }
+
+    fn write(&mut self, value: u64, count: u32) {
+        for i in (0..count).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1);
+            self.bits += 1;
+            if self.bits == 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc = 0;
+                self.bits = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, count: u32) {
+        self.write(value as u64 & ((1u64 << count) - 1), count);
+    }
+
+    fn write_unary(&mut self, zeros: u32) {
+        for _ in 0..zeros {
+            self.write(0, 1);
+        }
+        self.write(1, 1);
+    }
+
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Residual of the fixed polynomial predictor of the given order (0-4)
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i64> {
+    samples
+        .windows(order + 1)
+        .map(|w| {
+            let s = |i: usize| w[order - i] as i64;
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+fn zigzag(r: i64) -> u64 {
+    ((r << 1) ^ (r >> 63)) as u64
+}
+
+/// Pick the Rice parameter for a residual and return it with the coded size in bits
+fn rice_cost(residual: &[i64]) -> (u32, u64) {
+    let n = residual.len().max(1) as u64;
+    let sum: u64 = residual.iter().map(|&r| zigzag(r)).sum();
+    let mean = sum / n;
+    let estimate = if mean == 0 {
+        0
+    } else {
+        (63 - mean.leading_zeros()).min(MAX_RICE_PARAM)
+    };
+
+    (estimate.saturating_sub(1)..=(estimate + 1).min(MAX_RICE_PARAM))
+        .map(|k| {
+            let bits: u64 = residual
+                .iter()
+                .map(|&r| (zigzag(r) >> k) + 1 + k as u64)
+                .sum();
+            (k, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+fn write_subframe(w: &mut BitWriter, samples: &[i32]) {
+    const BPS: u32 = 16;
+
+    if samples.iter().all(|&s| s == samples[0]) {
+        w.write(0b0000_0000, 8);
+        w.write_signed(samples[0] as i64, BPS);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BPS as u64;
+    let best = (0..=4usize)
+        .filter(|&order| samples.len() > order)
+        .map(|order| {
+            let residual = fixed_residual(samples, order);
+            let (k, bits) = rice_cost(&residual);
+            let total = bits + order as u64 * BPS as u64 + 10;
+            (order, residual, k, total)
+        })
+        .min_by_key(|(_, _, _, total)| *total);
+
+    match best {
+        Some((order, residual, k, total)) if total < verbatim_bits => {
+            w.write(0b0001_0000 | (order as u64) << 1, 8);
+            for &s in &samples[..order] {
+                w.write_signed(s as i64, BPS);
+            }
+            // Rice coding with 4-bit parameters, one partition
+            w.write(0, 2);
+            w.write(0, 4);
+            w.write(k as u64, 4);
+            for r in residual {
+                let u = zigzag(r);
+                w.write_unary((u >> k) as u32);
+                w.write(u & ((1u64 << k) - 1), k);
+            }
+        }
+        _ => {
+            w.write(0b0000_0010, 8);
+            for &s in samples {
+                w.write_signed(s as i64, BPS);
+            }
+        }
+    }
+}
+
+/// Encode a frame number as FLAC's UTF-8-like variable-length integer
+fn write_frame_number(w: &mut BitWriter, n: u64) {
+    if n < 0x80 {
+        w.write(n, 8);
+        return;
+    }
+    let extra = match n {
+        0..0x800 => 1,
+        0x800..0x10000 => 2,
+        0x10000..0x20_0000 => 3,
+        0x20_0000..0x400_0000 => 4,
+        _ => 5,
+    };
+    let lead_mask = (0xFF00u64 >> (extra + 1)) & 0xFF;
+    w.write(lead_mask | (n >> (6 * extra)), 8);
+    for i in (0..extra).rev() {
+        w.write(0x80 | ((n >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+/// Write interleaved 16-bit samples to a FLAC file
+pub fn write_flac(
+    path: &Path,
+    samples: &[i16],
+    channels: u16,
+    sample_rate: u32,
+) -> Result<(), AudioError> {
+    let channels = channels.clamp(1, 8) as usize;
+    let frames = samples.len() / channels;
+
+    let mut out = BitWriter::new();
+    out.bytes.extend_from_slice(b"fLaC");
+
+    // STREAMINFO (the only, and therefore last, metadata block)
+    out.write(1, 1);
+    out.write(0, 7);
+    out.write(34, 24);
+    out.write(BLOCK_SIZE as u64, 16);
+    out.write(BLOCK_SIZE as u64, 16);
+    out.write(0, 24);
+    out.write(0, 24);
+    out.write(sample_rate as u64, 20);
+    out.write(channels as u64 - 1, 3);
+    out.write(15, 5);
+    out.write(frames as u64, 36);
+    // MD5 left unset (allowed by the spec)
+    out.write(0, 64);
+    out.write(0, 64);
+
+    for (frame_number, block) in samples[..frames * channels]
+        .chunks(BLOCK_SIZE * channels)
+        .enumerate()
+    {
+        let block_len = block.len() / channels;
+        let mut w = BitWriter::new();
+
+        w.write(0b1111_1111_1111_1000, 16);
+        w.write(0b0111, 4);
+        w.write(0b0000, 4);
+        w.write(channels as u64 - 1, 4);
+        w.write(0b100, 3);
+        w.write(0, 1);
+        write_frame_number(&mut w, frame_number as u64);
+        w.write(block_len as u64 - 1, 16);
+        let header_crc = crc8(&w.bytes);
+        w.write(header_crc as u64, 8);
+
+        for ch in 0..channels {
+            let channel: Vec<i32> = block
+                .iter()
+                .skip(ch)
+                .step_by(channels)
+                .map(|&s| s as i32)
+                .collect();
+            write_subframe(&mut w, &channel);
+        }
+
+        w.align();
+        let frame_crc = crc16(&w.bytes);
+        w.write(frame_crc as u64, 16);
+        out.bytes.extend_from_slice(&w.bytes);
+    }
+
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(&out.bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_flac_round_trips() {
+        let dir = std::env::temp_dir().join(format!("netnote-flac-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("clip.flac");
+
+        // Two blocks plus a remainder: a tone on the left, silence on the right
+        let frames = BLOCK_SIZE * 2 + 123;
+        let mut samples = Vec::with_capacity(frames * 2);
+        for i in 0..frames {
+            let t = i as f32 / 16000.0;
+            samples.push((8000.0 * (2.0 * std::f32::consts::PI * 440.0 * t).sin()) as i16);
+            samples.push(0);
+        }
+        write_flac(&path, &samples, 2, 16000).unwrap();
+
+        let mut reader = claxon::FlacReader::open(&path).unwrap();
+        assert_eq!(reader.streaminfo().sample_rate, 16000);
+        assert_eq!(reader.streaminfo().channels, 2);
+        let decoded: Vec<i16> = reader.samples().map(|s| s.unwrap() as i16).collect();
+        assert_eq!(decoded, samples);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+//! EBU R128 loudness measurement and limiting helpers.
+//!
+//! Used by the mixer to bring the mic and system tracks to a common
+//! loudness before they are combined into the playback file.
+
+/// Absolute gate from ITU-R BS.1770-4 (LUFS)
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Relative gate below the ungated loudness (LU)
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Gating block length in seconds (400 ms, 75% overlap)
+const BLOCK_SECONDS: f64 = 0.4;
+const BLOCK_STEP_SECONDS: f64 = 0.1;
+
+/// Second-order IIR section (transposed direct form II)
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn new(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0,
+            b1,
+            b2,
+            a1,
+            a2,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+/// Build the two K-weighting stages (pre-filter shelf + RLB high-pass)
+/// for an arbitrary sample rate, using the same analog prototypes as libebur128.
+fn k_weighting(sample_rate: u32) -> (Biquad, Biquad) {
+    let fs = sample_rate as f64;
+
+    // Stage 1: high shelf (+4 dB above ~1.7 kHz)
+    let f0 = 1_681.974_450_955_533;
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    // Stage 2: high-pass at ~38 Hz
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        1.0,
+        -2.0,
+        1.0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    (shelf, high_pass)
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Measure the integrated loudness (LUFS) of interleaved samples.
+///
+/// Returns `None` when the signal is shorter than one gating block or
+/// every block falls below the absolute gate (i.e. silence).
+pub fn integrated_loudness(samples: &[f32], channels: u16, sample_rate: u32) -> Option<f64> {
+    let channels = channels.max(1) as usize;
+    if sample_rate == 0 {
+        return None;
+    }
+
+    let frames = samples.len() / channels;
+    let block_len = (BLOCK_SECONDS * sample_rate as f64) as usize;
+    let step = (BLOCK_STEP_SECONDS * sample_rate as f64) as usize;
+    if block_len == 0 || step == 0 || frames < block_len {
+        return None;
+    }
+
+    // K-weight each channel and keep the squared output per frame
+    let mut weighted_sq = vec![0.0f64; frames];
+    for ch in 0..channels {
+        let (mut shelf, mut high_pass) = k_weighting(sample_rate);
+        for (frame, acc) in weighted_sq.iter_mut().enumerate() {
+            let x = samples[frame * channels + ch] as f64;
+            let y = high_pass.process(shelf.process(x));
+            // All channels we handle (mono/stereo) have a weighting of 1.0
+            *acc += y * y;
+        }
+    }
+
+    // Prefix sums make each overlapping block O(1)
+    let mut prefix = Vec::with_capacity(frames + 1);
+    prefix.push(0.0f64);
+    for sq in &weighted_sq {
+        let last = *prefix.last().unwrap_or(&0.0);
+        prefix.push(last + sq);
+    }
+
+    let block_powers: Vec<f64> = (0..=frames - block_len)
+        .step_by(step)
+        .map(|start| (prefix[start + block_len] - prefix[start]) / block_len as f64)
+        .collect();
+
+    // Absolute gate
+    let above_absolute: Vec<f64> = block_powers
+        .into_iter()
+        .filter(|&p| p > 0.0 && power_to_lufs(p) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if above_absolute.is_empty() {
+        return None;
+    }
+
+    // Relative gate
+    let ungated = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+    let relative_threshold = power_to_lufs(ungated) + RELATIVE_GATE_LU;
+    let gated: Vec<f64> = above_absolute
+        .into_iter()
+        .filter(|&p| power_to_lufs(p) > relative_threshold)
+        .collect();
+    if gated.is_empty() {
+        return None;
+    }
+
+    let mean = gated.iter().sum::<f64>() / gated.len() as f64;
+    Some(power_to_lufs(mean))
+}
+
+/// Linear gain that brings `measured` loudness to `target`, with the boost
+/// capped at `max_boost_db` so near-silent tracks don't turn into pure noise.
+pub fn gain_to_target(measured: Option<f64>, target: f64, max_boost_db: f64) -> f32 {
+    match measured {
+        Some(lufs) => {
+            let gain_db = (target - lufs).min(max_boost_db);
+            10f64.powf(gain_db / 20.0) as f32
+        }
+        None => 1.0,
+    }
+}
+
+/// Soft limiter: linear below the knee, then a tanh curve that approaches
+/// (but never exceeds) `ceiling`.
+pub fn soft_limit(sample: f32, ceiling: f32) -> f32 {
+    let knee = ceiling * 0.75;
+    let magnitude = sample.abs();
+    if magnitude <= knee {
+        return sample;
+    }
+
+    let headroom = ceiling - knee;
+    let limited = knee + headroom * ((magnitude - knee) / headroom).tanh();
+    limited.copysign(sample)
+}
+
+/// Constant-power pan law: -1.0 is hard left, 0.0 centre, 1.0 hard right.
+/// Returns the (left, right) gains.
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, seconds: f32) -> Vec<f32> {
+        let n = (sample_rate as f32 * seconds) as usize;
+        (0..n)
+            .map(|i| {
+                amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_integrated_loudness_reference_tone() {
+        // A 1 kHz sine at -20 dBFS (peak) in mono measures about -23 LUFS
+        let amplitude = 10f32.powf(-20.0 / 20.0);
+        let tone = sine(1000.0, amplitude, 48000, 5.0);
+        let lufs = integrated_loudness(&tone, 1, 48000).unwrap();
+        assert!((lufs - -23.0).abs() < 0.5, "measured {}", lufs);
+    }
+
+    #[test]
+    fn test_integrated_loudness_silence_is_gated() {
+        let silence = vec![0.0f32; 48000 * 2];
+        assert!(integrated_loudness(&silence, 1, 48000).is_none());
+    }
+
+    #[test]
+    fn test_soft_limit_never_exceeds_ceiling() {
+        for x in [-4.0f32, -1.0, -0.8, 0.0, 0.5, 0.9, 1.2, 10.0] {
+            let y = soft_limit(x, 0.95);
+            assert!(y.abs() <= 0.95);
+            assert_eq!(y.signum(), x.signum());
+        }
+        assert_eq!(soft_limit(0.3, 0.95), 0.3);
+    }
+
+    #[test]
+    fn test_pan_gains() {
+        let (l, r) = pan_gains(-1.0);
+        assert!((l - 1.0).abs() < 1e-6 && r.abs() < 1e-6);
+        let (l, r) = pan_gains(0.0);
+        assert!((l - r).abs() < 1e-6);
+    }
+}
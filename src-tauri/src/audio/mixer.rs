@@ -1,13 +1,15 @@
 //! Audio mixing utilities for combining multiple WAV files.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use serde::{Deserialize, Serialize};
 
-use crate::audio::AudioError;
+use crate::audio::loudness::{gain_to_target, integrated_loudness, pan_gains, soft_limit};
+use crate::audio::{open_wav, AudioError, AudioSource};
 
 /// Simple linear interpolation resampling
-fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+pub(crate) fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     if from_rate == to_rate {
         return samples.to_vec();
     }
@@ -35,185 +37,211 @@ fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     resampled
 }
 
+/// How the mic and system tracks are placed in the stereo output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixMode {
+    /// Both tracks loudness-matched and panned by `mic_pan` / `system_pan`
+    Balanced,
+    /// Mic hard left, system audio hard right (for manual review)
+    StereoSplit,
+}
+
+/// Options for rendering the merged playback file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MixOptions {
+    pub mode: MixMode,
+    /// Integrated loudness each track is normalized to (LUFS)
+    pub target_lufs: f64,
+    /// Maximum boost applied to a quiet track (dB)
+    pub max_boost_db: f64,
+    /// Pan position for the mic track (-1.0 = left, 1.0 = right)
+    pub mic_pan: f32,
+    /// Pan position for the system audio track
+    pub system_pan: f32,
+    /// Peak ceiling of the soft limiter (linear, 0.0-1.0)
+    pub limiter_ceiling: f32,
+}
+
+impl Default for MixOptions {
+    fn default() -> Self {
+        Self {
+            mode: MixMode::Balanced,
+            target_lufs: -18.0,
+            max_boost_db: 20.0,
+            mic_pan: -0.3,
+            system_pan: 0.3,
+            limiter_ceiling: 0.95,
+        }
+    }
+}
+
+impl MixOptions {
+    /// Pan position for a given source, taking the mix mode into account
+    fn pan_for(&self, source: AudioSource) -> f32 {
+        match (self.mode, source) {
+            (MixMode::StereoSplit, AudioSource::Microphone) => -1.0,
+            (MixMode::StereoSplit, AudioSource::SystemAudio) => 1.0,
+            (MixMode::Balanced, AudioSource::Microphone) => self.mic_pan,
+            (MixMode::Balanced, AudioSource::SystemAudio) => self.system_pan,
+        }
+    }
+}
+
+/// A single input file placed on the note timeline
+#[derive(Debug, Clone)]
+pub struct MixTrack {
+    pub path: PathBuf,
+    pub source: AudioSource,
+    /// Position of the file's first sample relative to the note start
+    pub offset_ms: i64,
+}
+
 /// Mix two WAV files into a single output file.
 ///
-/// Both input files should have the same sample rate and channel count.
-/// If they differ, the function will use the first file's format and resample
-/// or remix the second file as needed.
-///
-/// The mixing is done by averaging samples from both sources to prevent clipping.
+/// `file_a` is treated as the mic track and `file_b` as the system audio track;
+/// both start at the same time. See [`mix_tracks`] for the processing applied.
 pub fn mix_wav_files(
     file_a: &Path,
     file_b: &Path,
     output: &Path,
+    options: &MixOptions,
+) -> Result<(), AudioError> {
+    let tracks = [
+        MixTrack {
+            path: file_a.to_path_buf(),
+            source: AudioSource::Microphone,
+            offset_ms: 0,
+        },
+        MixTrack {
+            path: file_b.to_path_buf(),
+            source: AudioSource::SystemAudio,
+            offset_ms: 0,
+        },
+    ];
+    mix_tracks(&tracks, output, options)
+}
+
+/// Mix any number of mic/system tracks into a stereo 16-bit WAV file.
+///
+/// Tracks of the same source are laid out on a shared timeline using their
+/// `offset_ms`, so paused/continued segments land where they were recorded.
+/// Each source is then normalized to `target_lufs` (EBU R128 integrated
+/// loudness), panned, summed and passed through a soft limiter.
+pub fn mix_tracks(
+    tracks: &[MixTrack],
+    output: &Path,
+    options: &MixOptions,
 ) -> Result<(), AudioError> {
-    // Open both input files
-    let mut reader_a = WavReader::open(file_a)?;
-    let mut reader_b = WavReader::open(file_b)?;
+    // Decode every track up front so we know the output sample rate
+    let mut decoded = Vec::with_capacity(tracks.len());
+    for track in tracks {
+        if !track.path.exists() {
+            continue;
+        }
+        let (samples, sample_rate) = read_mono_samples(&track.path)?;
+        decoded.push((track, samples, sample_rate));
+    }
+
+    // The mic track's rate wins; fall back to whatever was decoded first
+    let output_rate = decoded
+        .iter()
+        .find(|(t, _, _)| t.source == AudioSource::Microphone)
+        .or_else(|| decoded.first())
+        .map(|(_, _, rate)| *rate)
+        .ok_or(AudioError::NoTracks)?;
+
+    // Build one timeline per source
+    let mut mic_timeline: Vec<f32> = Vec::new();
+    let mut system_timeline: Vec<f32> = Vec::new();
+
+    for (track, samples, sample_rate) in decoded {
+        let samples = resample(&samples, sample_rate, output_rate);
+        let start = (track.offset_ms.max(0) as u64 * output_rate as u64 / 1000) as usize;
+        let timeline = match track.source {
+            AudioSource::Microphone => &mut mic_timeline,
+            AudioSource::SystemAudio => &mut system_timeline,
+        };
+        if timeline.len() < start + samples.len() {
+            timeline.resize(start + samples.len(), 0.0);
+        }
+        for (dst, src) in timeline[start..].iter_mut().zip(samples) {
+            *dst += src;
+        }
+    }
 
-    let spec_a = reader_a.spec();
-    let spec_b = reader_b.spec();
+    let mic_gain = gain_to_target(
+        integrated_loudness(&mic_timeline, 1, output_rate),
+        options.target_lufs,
+        options.max_boost_db,
+    );
+    let system_gain = gain_to_target(
+        integrated_loudness(&system_timeline, 1, output_rate),
+        options.target_lufs,
+        options.max_boost_db,
+    );
+
+    let (mic_l, mic_r) = pan_gains(options.pan_for(AudioSource::Microphone));
+    let (sys_l, sys_r) = pan_gains(options.pan_for(AudioSource::SystemAudio));
 
-    // Use file A's spec for output, but ensure we handle format differences
     let output_spec = WavSpec {
-        channels: spec_a.channels,
-        sample_rate: spec_a.sample_rate,
+        channels: 2,
+        sample_rate: output_rate,
         bits_per_sample: 16,
         sample_format: SampleFormat::Int,
     };
-
     let mut writer = WavWriter::create(output, output_spec)?;
 
-    // Read samples based on the format
-    match (spec_a.sample_format, spec_b.sample_format) {
-        (SampleFormat::Int, SampleFormat::Int) => {
-            mix_int_samples(&mut reader_a, &mut reader_b, &mut writer, spec_a, spec_b)?;
-        }
-        (SampleFormat::Float, SampleFormat::Float) => {
-            mix_float_samples(&mut reader_a, &mut reader_b, &mut writer, spec_a, spec_b)?;
-        }
-        _ => {
-            // Mixed formats - convert to float, mix, convert back
-            mix_mixed_samples(&mut reader_a, &mut reader_b, &mut writer, spec_a, spec_b)?;
-        }
-    }
+    let ceiling = options.limiter_ceiling.clamp(0.1, 1.0);
+    let len = mic_timeline.len().max(system_timeline.len());
+    for i in 0..len {
+        let mic = mic_timeline.get(i).copied().unwrap_or(0.0) * mic_gain;
+        let sys = system_timeline.get(i).copied().unwrap_or(0.0) * system_gain;
 
-    writer.finalize()?;
-    Ok(())
-}
+        let left = soft_limit(mic * mic_l + sys * sys_l, ceiling);
+        let right = soft_limit(mic * mic_r + sys * sys_r, ceiling);
 
-fn mix_int_samples<R1: std::io::Read, R2: std::io::Read, W: std::io::Write + std::io::Seek>(
-    reader_a: &mut WavReader<R1>,
-    reader_b: &mut WavReader<R2>,
-    writer: &mut WavWriter<W>,
-    spec_a: WavSpec,
-    spec_b: WavSpec,
-) -> Result<(), AudioError> {
-    // Calculate scale factor based on bit depth
-    let scale_a = (1 << (spec_a.bits_per_sample - 1)) as f32;
-    let scale_b = (1 << (spec_b.bits_per_sample - 1)) as f32;
-
-    // Convert to float for processing (normalized to -1.0 to 1.0)
-    let samples_a: Vec<f32> = reader_a
-        .samples::<i32>()
-        .filter_map(|s| s.ok())
-        .map(|s| s as f32 / scale_a)
-        .collect();
-    let samples_b: Vec<f32> = reader_b
-        .samples::<i32>()
-        .filter_map(|s| s.ok())
-        .map(|s| s as f32 / scale_b)
-        .collect();
-
-    // Handle different channel counts
-    let samples_a = normalize_channels_f32(&samples_a, spec_a.channels, spec_a.channels);
-    let samples_b = normalize_channels_f32(&samples_b, spec_b.channels, spec_a.channels);
-
-    // Resample if needed to match sample rates
-    let samples_b = resample(&samples_b, spec_b.sample_rate, spec_a.sample_rate);
-
-    let max_len = samples_a.len().max(samples_b.len());
-
-    for i in 0..max_len {
-        let a = samples_a.get(i).copied().unwrap_or(0.0);
-        let b = samples_b.get(i).copied().unwrap_or(0.0);
-
-        // Mix by averaging to prevent clipping
-        let mixed = (a + b) / 2.0;
-
-        // Convert to i16
-        let sample = (mixed * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-        writer.write_sample(sample)?;
+        writer.write_sample(to_i16(left))?;
+        writer.write_sample(to_i16(right))?;
     }
 
+    writer.finalize()?;
+    crate::crypto::seal_if_enabled(output)?;
     Ok(())
 }
 
-fn mix_float_samples<R1: std::io::Read, R2: std::io::Read, W: std::io::Write + std::io::Seek>(
-    reader_a: &mut WavReader<R1>,
-    reader_b: &mut WavReader<R2>,
-    writer: &mut WavWriter<W>,
-    spec_a: WavSpec,
-    spec_b: WavSpec,
-) -> Result<(), AudioError> {
-    let samples_a: Vec<f32> = reader_a.samples::<f32>().filter_map(|s| s.ok()).collect();
-    let samples_b: Vec<f32> = reader_b.samples::<f32>().filter_map(|s| s.ok()).collect();
-
-    // Handle different channel counts
-    let samples_a = normalize_channels_f32(&samples_a, spec_a.channels, spec_a.channels);
-    let samples_b = normalize_channels_f32(&samples_b, spec_b.channels, spec_a.channels);
-
-    // Resample if needed to match sample rates
-    let samples_b = resample(&samples_b, spec_b.sample_rate, spec_a.sample_rate);
-
-    let max_len = samples_a.len().max(samples_b.len());
-
-    for i in 0..max_len {
-        let a = samples_a.get(i).copied().unwrap_or(0.0);
-        let b = samples_b.get(i).copied().unwrap_or(0.0);
-
-        // Mix by averaging
-        let mixed = (a + b) / 2.0;
-
-        // Convert to i16
-        let sample = (mixed * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-        writer.write_sample(sample)?;
-    }
-
-    Ok(())
+fn to_i16(sample: f32) -> i16 {
+    (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16
 }
 
-fn mix_mixed_samples<R1: std::io::Read, R2: std::io::Read, W: std::io::Write + std::io::Seek>(
-    reader_a: &mut WavReader<R1>,
-    reader_b: &mut WavReader<R2>,
-    writer: &mut WavWriter<W>,
-    spec_a: WavSpec,
-    spec_b: WavSpec,
-) -> Result<(), AudioError> {
-    // Calculate scale factors based on bit depth
-    let scale_a = (1 << (spec_a.bits_per_sample - 1)) as f32;
-    let scale_b = (1 << (spec_b.bits_per_sample - 1)) as f32;
+/// Read a WAV file as mono f32 samples, returning them with the file's sample rate
+fn read_mono_samples(path: &Path) -> Result<(Vec<f32>, u32), AudioError> {
+    let mut reader = open_wav(path)?;
+    let spec = reader.spec();
 
-    // Convert both to float for mixing
-    let samples_a: Vec<f32> = if spec_a.sample_format == SampleFormat::Float {
-        reader_a.samples::<f32>().filter_map(|s| s.ok()).collect()
+    let samples: Vec<f32> = if spec.sample_format == SampleFormat::Float {
+        reader.samples::<f32>().filter_map(|s| s.ok()).collect()
     } else {
-        reader_a
+        let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+        reader
             .samples::<i32>()
             .filter_map(|s| s.ok())
-            .map(|s| s as f32 / scale_a)
+            .map(|s| s as f32 / scale)
             .collect()
     };
 
-    let samples_b: Vec<f32> = if spec_b.sample_format == SampleFormat::Float {
-        reader_b.samples::<f32>().filter_map(|s| s.ok()).collect()
-    } else {
-        reader_b
-            .samples::<i32>()
-            .filter_map(|s| s.ok())
-            .map(|s| s as f32 / scale_b)
+    let channels = spec.channels.max(1) as usize;
+    let mono = if channels > 1 {
+        samples
+            .chunks(channels)
+            .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
             .collect()
+    } else {
+        samples
     };
 
-    // Handle different channel counts
-    let samples_a = normalize_channels_f32(&samples_a, spec_a.channels, spec_a.channels);
-    let samples_b = normalize_channels_f32(&samples_b, spec_b.channels, spec_a.channels);
-
-    // Resample if needed to match sample rates
-    let samples_b = resample(&samples_b, spec_b.sample_rate, spec_a.sample_rate);
-
-    let max_len = samples_a.len().max(samples_b.len());
-
-    for i in 0..max_len {
-        let a = samples_a.get(i).copied().unwrap_or(0.0);
-        let b = samples_b.get(i).copied().unwrap_or(0.0);
-
-        let mixed = (a + b) / 2.0;
-        let sample = (mixed * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-        writer.write_sample(sample)?;
-    }
-
-    Ok(())
+    Ok((mono, spec.sample_rate))
 }
 
 /// Normalize channel count - convert between mono/stereo as needed (i32 version)
@@ -248,37 +276,6 @@ fn normalize_channels(samples: &[i32], from_channels: u16, to_channels: u16) ->
     }
 }
 
-/// Normalize channel count - convert between mono/stereo as needed (f32 version)
-fn normalize_channels_f32(samples: &[f32], from_channels: u16, to_channels: u16) -> Vec<f32> {
-    if from_channels == to_channels {
-        return samples.to_vec();
-    }
-
-    match (from_channels, to_channels) {
-        (1, 2) => {
-            // Mono to stereo - duplicate each sample
-            samples.iter().flat_map(|&s| [s, s]).collect()
-        }
-        (2, 1) => {
-            // Stereo to mono - average pairs
-            samples
-                .chunks(2)
-                .map(|chunk| {
-                    if chunk.len() == 2 {
-                        (chunk[0] + chunk[1]) / 2.0
-                    } else {
-                        chunk[0]
-                    }
-                })
-                .collect()
-        }
-        _ => {
-            // For other channel counts, just take what we have
-            samples.to_vec()
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,4 +294,82 @@ mod tests {
         let mono = normalize_channels(&stereo, 2, 1);
         assert_eq!(mono, vec![150, 350]);
     }
+
+    fn write_tone(path: &Path, sample_rate: u32, seconds: f32, amplitude: f32) {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(path, spec).unwrap();
+        let n = (sample_rate as f32 * seconds) as usize;
+        for i in 0..n {
+            let t = i as f32 / sample_rate as f32;
+            let s = amplitude * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            writer.write_sample(to_i16(s)).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_mix_tracks_respects_offsets_and_split() {
+        let dir = std::env::temp_dir().join(format!("netnote-mix-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mic = dir.join("mic.wav");
+        let system = dir.join("system.wav");
+        let out = dir.join("out.wav");
+
+        write_tone(&mic, 16000, 1.0, 0.05);
+        write_tone(&system, 48000, 1.0, 0.8);
+
+        let tracks = [
+            MixTrack {
+                path: mic.clone(),
+                source: AudioSource::Microphone,
+                offset_ms: 0,
+            },
+            MixTrack {
+                path: system.clone(),
+                source: AudioSource::SystemAudio,
+                offset_ms: 2000,
+            },
+        ];
+        let options = MixOptions {
+            mode: MixMode::StereoSplit,
+            ..MixOptions::default()
+        };
+        mix_tracks(&tracks, &out, &options).unwrap();
+
+        let mut reader = hound::WavReader::open(&out).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 16000);
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        // 2s offset + 1s of system audio, stereo
+        assert_eq!(samples.len(), 3 * 16000 * 2);
+
+        // First second: mic only, on the left channel
+        let first: Vec<&[i16]> = samples[..16000 * 2].chunks(2).collect();
+        assert!(first.iter().any(|f| f[0] != 0));
+        assert!(first.iter().all(|f| f[1].abs() <= 1));
+
+        // Last second: system only, on the right channel
+        let last: Vec<&[i16]> = samples[2 * 16000 * 2..].chunks(2).collect();
+        assert!(last.iter().all(|f| f[0].abs() <= 1));
+        assert!(last.iter().any(|f| f[1] != 0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_mix_tracks_without_audio_is_no_tracks() {
+        let dir = std::env::temp_dir().join(format!("netnote-mix-{}", uuid::Uuid::new_v4()));
+        let tracks = [MixTrack {
+            path: dir.join("missing.wav"),
+            source: AudioSource::Microphone,
+            offset_ms: 0,
+        }];
+        let result = mix_tracks(&tracks, &dir.join("out.wav"), &MixOptions::default());
+        assert!(matches!(result, Err(AudioError::NoTracks)));
+    }
 }
@@ -1,7 +1,13 @@
 pub mod aec;
+pub mod clip;
+pub mod denoise;
+pub mod devices;
+pub mod flac;
+pub mod loudness;
 pub mod mixer;
 pub mod recorder;
 pub mod system_audio;
+pub mod waveform;
 
 #[cfg(target_os = "macos")]
 pub mod macos;
@@ -9,12 +15,16 @@ pub mod macos;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
-pub use mixer::mix_wav_files;
+pub use clip::ClipFormat;
+pub use denoise::NoiseSuppressionOptions;
+pub use devices::{DevicePreferences, InputDevice};
+pub use mixer::{mix_tracks, mix_wav_files, MixMode, MixOptions, MixTrack};
 pub use recorder::{
-    pause_recording, resume_recording, start_recording, stop_recording, RecordingPhase,
-    RecordingState,
+    pause_recording, resume_recording, start_recording, stop_recording, switch_input_device,
+    RecordingPhase, RecordingState,
 };
 pub use system_audio::{create_system_audio_capture, is_system_audio_available, SystemAudioCapture};
+pub use waveform::WaveformPeaks;
 
 // Re-export system audio buffer functions for live transcription
 #[cfg(target_os = "macos")]
@@ -28,11 +38,15 @@ pub fn take_system_audio_samples() -> Vec<f32> {
     Vec::new()
 }
 
+use std::path::Path;
+
+use hound::WavReader;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::crypto::LibraryReader;
+
 /// Audio source type for recording
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AudioSource {
     /// User's microphone input
@@ -84,4 +98,18 @@ pub enum AudioError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Encoding error: {0}")]
+    EncodeError(String),
+
+    #[error("No audio tracks to mix")]
+    NoTracks,
+
+    #[error("Encryption error: {0}")]
+    CryptoError(#[from] crate::crypto::CryptoError),
+}
+
+/// Open a recorded WAV file, decrypting it when the library is encrypted
+pub fn open_wav(path: &Path) -> Result<WavReader<LibraryReader>, AudioError> {
+    Ok(WavReader::new(LibraryReader::open(path)?)?)
 }
@@ -1,16 +1,31 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::{Sample, SampleFormat};
 use hound::{WavSpec, WavWriter};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
 use serde::{Deserialize, Serialize};
 
+use crate::audio::denoise::{self, NoiseSuppressionTarget, NoiseSuppressor};
+use crate::audio::devices::{self, ChannelSelection, DevicePreferences};
 use crate::audio::AudioError;
 
+/// How long an input stream may go without delivering audio before the device
+/// is treated as lost (some backends stall silently instead of reporting an error)
+const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often the capture thread processes the audio queued by the stream callback
+const DRAIN_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Capacity of the callback-to-capture-thread queue, in seconds of audio
+const RING_SECONDS: usize = 2;
+
 /// Recording phase for pause/resume functionality
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -55,6 +70,22 @@ pub struct RecordingState {
     pub current_note_id: std::sync::Mutex<Option<String>>,
     /// Current segment ID in database (for updating duration)
     pub current_segment_db_id: AtomicI64,
+
+    // === Device monitoring ===
+    /// Device requested for the current recording (None = system default)
+    pub device_id: std::sync::Mutex<Option<String>>,
+    /// Set when the input stream errors out or stops delivering audio (e.g. unplugged)
+    pub device_lost: AtomicBool,
+    /// Reason the device was lost
+    pub device_error: std::sync::Mutex<Option<String>>,
+    /// Bumped whenever a new capture thread takes over; older threads exit
+    pub stream_generation: AtomicU64,
+    /// Number of callbacks delivered by the current stream (stall watchdog)
+    pub callback_count: AtomicU64,
+
+    // === Device configuration ===
+    /// Saved sample rate / channel selection per device id
+    pub device_preferences: std::sync::Mutex<HashMap<String, DevicePreferences>>,
 }
 
 impl RecordingState {
@@ -73,7 +104,49 @@ impl RecordingState {
             segment_start_time: std::sync::Mutex::new(None),
             current_note_id: std::sync::Mutex::new(None),
             current_segment_db_id: AtomicI64::new(0),
+            // Device monitoring
+            device_id: std::sync::Mutex::new(None),
+            device_lost: AtomicBool::new(false),
+            device_error: std::sync::Mutex::new(None),
+            stream_generation: AtomicU64::new(0),
+            callback_count: AtomicU64::new(0),
+            // Device configuration
+            device_preferences: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the saved preferences for a device (defaults if none were saved)
+    pub fn preferences_for(&self, device_id: &str) -> DevicePreferences {
+        self.device_preferences
+            .lock()
+            .ok()
+            .and_then(|prefs| prefs.get(device_id).copied())
+            .unwrap_or_default()
+    }
+
+    /// Flag the device of capture thread `generation` as lost (ignored for stale threads)
+    fn mark_device_lost(&self, generation: u64, reason: String) {
+        if self.stream_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Ok(mut error) = self.device_error.lock() {
+            *error = Some(reason);
+        }
+        self.device_lost.store(true, Ordering::SeqCst);
+    }
+
+    /// Take the device-lost flag and its reason, if the device was lost
+    pub fn take_device_lost(&self) -> Option<String> {
+        if !self.device_lost.swap(false, Ordering::SeqCst) {
+            return None;
         }
+        let reason = self.device_error.lock().ok().and_then(|mut e| e.take());
+        Some(reason.unwrap_or_else(|| "Input device lost".to_string()))
+    }
+
+    /// Get the device requested for the current recording
+    pub fn current_device_id(&self) -> Option<String> {
+        self.device_id.lock().ok().and_then(|d| d.clone())
     }
 
     /// Get the current recording phase
@@ -162,12 +235,56 @@ pub fn start_recording(
     state.is_recording.store(true, Ordering::SeqCst);
     state.set_phase(RecordingPhase::Recording);
 
+    spawn_capture_thread(state, output_path, device_id)
+}
+
+/// Move an active recording to another input device (None = system default).
+/// The current file is finalized and capture continues into `output_path`
+/// without leaving the Recording phase, so live transcription keeps running.
+pub fn switch_input_device(
+    state: Arc<RecordingState>,
+    output_path: PathBuf,
+    device_id: Option<String>,
+) -> Result<(), AudioError> {
+    if state.get_phase() != RecordingPhase::Recording {
+        return Err(AudioError::NotRecording);
+    }
+
+    {
+        let mut path = state.output_path.lock().map_err(|_| AudioError::LockError)?;
+        *path = Some(output_path.clone());
+    }
+    {
+        let mut start_time = state.segment_start_time.lock().map_err(|_| AudioError::LockError)?;
+        *start_time = Some(Instant::now());
+    }
+
+    spawn_capture_thread(state, output_path, device_id)
+}
+
+/// Start a capture thread that supersedes any previous one
+fn spawn_capture_thread(
+    state: Arc<RecordingState>,
+    output_path: PathBuf,
+    device_id: Option<String>,
+) -> Result<(), AudioError> {
+    {
+        let mut current = state.device_id.lock().map_err(|_| AudioError::LockError)?;
+        *current = device_id.clone();
+    }
+    state.device_lost.store(false, Ordering::SeqCst);
+    if let Ok(mut error) = state.device_error.lock() {
+        *error = None;
+    }
+    let generation = state.stream_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
     let state_clone = state.clone();
 
     // Spawn recording thread
     thread::spawn(move || {
-        if let Err(e) = run_recording(state_clone, output_path, device_id) {
+        if let Err(e) = run_recording(state_clone.clone(), output_path, device_id, generation) {
             eprintln!("Recording error: {}", e);
+            state_clone.mark_device_lost(generation, e.to_string());
         }
     });
 
@@ -203,10 +320,9 @@ pub fn resume_recording(state: Arc<RecordingState>, output_path: PathBuf) -> Res
     let new_index = state.current_segment_index.fetch_add(1, Ordering::SeqCst) + 1;
     state.current_segment_index.store(new_index, Ordering::SeqCst);
 
-    // Start recording with the new path
-    // Start recording with the new path - NOTE: resumed recording currently uses default device
-    // Todo: persist device ID in state to reuse it here
-    start_recording(state, output_path, None)
+    // Start recording with the new path on the same device as before
+    let device_id = state.current_device_id();
+    start_recording(state, output_path, device_id)
 }
 
 /// Stop recording completely - resets all state
@@ -240,31 +356,14 @@ fn run_recording(
     state: Arc<RecordingState>,
     output_path: PathBuf,
     device_id: Option<String>,
+    generation: u64,
 ) -> Result<(), AudioError> {
-    let host = cpal::default_host();
-    
-    let device = if let Some(id) = device_id {
-        // Try to find device by name (using id as name for now)
-        let mut found_device = None;
-        if let Ok(devices) = host.input_devices() {
-            for d in devices {
-                if let Ok(name) = d.name() {
-                    if name == id {
-                        found_device = Some(d);
-                        break;
-                    }
-                }
-            }
-        }
-        // Fallback to default if not found
-        found_device.or_else(|| host.default_input_device()).ok_or(AudioError::NoInputDevice)?
-    } else {
-        host.default_input_device().ok_or(AudioError::NoInputDevice)?
-    };
-
-    let config = device.default_input_config()?;
+    let (device, resolved_id) = devices::find_input_device(device_id.as_deref())?;
+    let preferences = state.preferences_for(&resolved_id);
+    let config = devices::stream_config_for(&device, &preferences)?;
     let sample_rate = config.sample_rate().0;
-    let channels = config.channels();
+    let device_channels = config.channels();
+    let channels = preferences.channels.output_channels(device_channels);
 
     // Store sample rate and channels for live transcription
     state.sample_rate.store(sample_rate, Ordering::SeqCst);
@@ -282,96 +381,187 @@ fn run_recording(
         sample_format: hound::SampleFormat::Int,
     };
 
-    let writer = WavWriter::create(&output_path, spec)?;
-    let writer = Arc::new(std::sync::Mutex::new(Some(writer)));
-
-    let state_for_callback = state.clone();
-    let writer_clone = writer.clone();
+    let mut writer = WavWriter::create(&output_path, spec)?;
+
+    // The realtime callback only queues raw samples; channel selection, noise
+    // suppression and file writes happen on this thread
+    let ring_len = sample_rate as usize * device_channels as usize * RING_SECONDS;
+    let (mut producer, mut consumer) = HeapRb::<f32>::new(ring_len).split();
+
+    // Optional noise suppression (options are read once per recording)
+    let ns_options = denoise::noise_suppression_options();
+    let ns_target = ns_options.target;
+    let mut capture = CaptureContext {
+        selection: preferences.channels,
+        device_channels,
+        ns_target,
+        suppressor: (ns_target != NoiseSuppressionTarget::Off)
+            .then(|| NoiseSuppressor::new(sample_rate, channels, ns_options.reduction_db)),
+    };
 
-    let err_fn = |err| eprintln!("Audio stream error: {}", err);
+    let state_for_errors = state.clone();
+    let err_fn = move |err: cpal::StreamError| {
+        eprintln!("Audio stream error: {}", err);
+        if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+            state_for_errors.mark_device_lost(generation, err.to_string());
+        }
+    };
 
+    let state_for_callback = state.clone();
     let stream = match config.sample_format() {
         SampleFormat::F32 => device.build_input_stream(
             &config.into(),
             move |data: &[f32], _| {
-                process_audio(data, &state_for_callback, &writer_clone);
+                queue_samples(&state_for_callback, generation, &mut producer, data, |&s| s);
+            },
+            err_fn,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[i16], _| {
+                queue_samples(&state_for_callback, generation, &mut producer, data, |&s| {
+                    s.to_float_sample()
+                });
+            },
+            err_fn,
+            None,
+        )?,
+        SampleFormat::U16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[u16], _| {
+                queue_samples(&state_for_callback, generation, &mut producer, data, |&s| {
+                    s.to_float_sample()
+                });
             },
             err_fn,
             None,
         )?,
-        SampleFormat::I16 => {
-            let state_for_callback = state.clone();
-            let writer_clone = writer.clone();
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[i16], _| {
-                    let float_data: Vec<f32> = data.iter().map(|&s| s.to_float_sample()).collect();
-                    process_audio(&float_data, &state_for_callback, &writer_clone);
-                },
-                err_fn,
-                None,
-            )?
-        }
-        SampleFormat::U16 => {
-            let state_for_callback = state.clone();
-            let writer_clone = writer.clone();
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[u16], _| {
-                    let float_data: Vec<f32> = data.iter().map(|&s| s.to_float_sample()).collect();
-                    process_audio(&float_data, &state_for_callback, &writer_clone);
-                },
-                err_fn,
-                None,
-            )?
-        }
         _ => return Err(AudioError::UnsupportedFormat),
     };
 
+    state.callback_count.store(0, Ordering::SeqCst);
     stream.play()?;
 
-    // Keep thread alive while recording
-    while state.is_recording.load(Ordering::SeqCst) {
-        thread::sleep(std::time::Duration::from_millis(100));
-    }
+    // Process queued audio while recording, until superseded or the device goes away
+    let mut chunk = vec![0.0f32; ring_len];
+    let mut last_count = 0;
+    let mut last_progress = Instant::now();
+    while state.is_recording.load(Ordering::SeqCst)
+        && state.stream_generation.load(Ordering::SeqCst) == generation
+    {
+        thread::sleep(DRAIN_INTERVAL);
+        drain_samples(&mut consumer, &mut chunk, &state, &mut writer, &mut capture);
 
-    // Finalize the WAV file
-    drop(stream);
-    if let Ok(mut guard) = writer.lock() {
-        if let Some(w) = guard.take() {
-            let _ = w.finalize();
+        if state.device_lost.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let count = state.callback_count.load(Ordering::SeqCst);
+        if count != last_count {
+            last_count = count;
+            last_progress = Instant::now();
+        } else if last_progress.elapsed() > STALL_TIMEOUT {
+            state.mark_device_lost(generation, "Input device stopped delivering audio".to_string());
+            break;
         }
     }
 
+    // Write what the stream queued before it stopped, then finalize the WAV file
+    drop(stream);
+    drain_samples(&mut consumer, &mut chunk, &state, &mut writer, &mut capture);
+    let _ = writer.finalize();
+
     Ok(())
 }
 
-fn process_audio(
-    data: &[f32],
-    state: &Arc<RecordingState>,
-    writer: &Arc<std::sync::Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
+/// Per-stream processing settings owned by the capture thread
+struct CaptureContext {
+    selection: ChannelSelection,
+    device_channels: u16,
+    ns_target: NoiseSuppressionTarget,
+    suppressor: Option<NoiseSuppressor>,
+}
+
+/// Realtime side of the capture: count the callback and queue its samples without
+/// blocking or allocating. A full ring drops the whole callback so frames stay aligned.
+fn queue_samples<T>(
+    state: &RecordingState,
+    generation: u64,
+    producer: &mut HeapProd<f32>,
+    data: &[T],
+    to_f32: impl Fn(&T) -> f32,
 ) {
-    if !state.is_recording.load(Ordering::SeqCst) {
+    if !state.is_recording.load(Ordering::SeqCst)
+        || state.stream_generation.load(Ordering::SeqCst) != generation
+    {
         return;
     }
+    state.callback_count.fetch_add(1, Ordering::SeqCst);
+
+    if producer.vacant_len() >= data.len() {
+        producer.push_iter(data.iter().map(to_f32));
+    }
+}
+
+/// Process everything queued by the callback, in whole frames
+fn drain_samples(
+    consumer: &mut HeapCons<f32>,
+    chunk: &mut [f32],
+    state: &RecordingState,
+    writer: &mut WavWriter<std::io::BufWriter<std::fs::File>>,
+    capture: &mut CaptureContext,
+) {
+    let frame = capture.device_channels.max(1) as usize;
+    let chunk_len = chunk.len() / frame * frame;
+    loop {
+        let read = consumer.pop_slice(&mut chunk[..chunk_len]);
+        if read == 0 {
+            break;
+        }
+        process_audio(&chunk[..read], state, writer, capture);
+    }
+}
+
+fn process_audio(
+    data: &[f32],
+    state: &RecordingState,
+    writer: &mut WavWriter<std::io::BufWriter<std::fs::File>>,
+    capture: &mut CaptureContext,
+) {
+    // Channel selection / downmix
+    let selected;
+    let data = if capture.selection == ChannelSelection::All {
+        data
+    } else {
+        selected = capture.selection.apply(data, capture.device_channels);
+        &selected[..]
+    };
+    let ns_target = capture.ns_target;
 
     // Calculate RMS audio level
     let sum: f32 = data.iter().map(|s| s * s).sum();
     let rms = (sum / data.len() as f32).sqrt();
     state.audio_level.store(rms.to_bits(), Ordering::SeqCst);
 
+    // Denoised copy (delayed by one FFT hop) for whichever outputs want it
+    let denoised = capture.suppressor.as_mut().map(|s| s.process_interleaved(data));
+    let (buffer_data, file_data) = match denoised.as_deref() {
+        Some(clean) => (
+            if ns_target.applies_to_transcription() { clean } else { data },
+            if ns_target.applies_to_file() { clean } else { data },
+        ),
+        None => (data, data),
+    };
+
     // Copy samples to buffer for live transcription
     if let Ok(mut buffer) = state.audio_buffer.lock() {
-        buffer.extend_from_slice(data);
+        buffer.extend_from_slice(buffer_data);
     }
 
     // Write to WAV file
-    if let Ok(mut guard) = writer.lock() {
-        if let Some(ref mut w) = *guard {
-            for &sample in data {
-                let sample_i16 = (sample * i16::MAX as f32) as i16;
-                let _ = w.write_sample(sample_i16);
-            }
-        }
+    for &sample in file_data {
+        let sample_i16 = (sample * i16::MAX as f32) as i16;
+        let _ = writer.write_sample(sample_i16);
     }
 }
@@ -0,0 +1,144 @@
+//! Downsampled waveform peaks for drawing a note's playback timeline.
+
+use std::path::Path;
+
+use hound::SampleFormat;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{open_wav, AudioError};
+
+/// Default peak resolution (buckets per second of audio)
+pub const DEFAULT_PEAKS_PER_SECOND: u32 = 50;
+
+/// Min/max sample values per time bucket of a WAV file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformPeaks {
+    pub sample_rate: u32,
+    pub duration_ms: i64,
+    pub peaks_per_second: u32,
+    /// Lowest sample of each bucket across all channels (-1..1)
+    pub min: Vec<f32>,
+    /// Highest sample of each bucket across all channels (-1..1)
+    pub max: Vec<f32>,
+}
+
+/// Compute min/max peaks of a WAV file, streaming so long meetings stay cheap
+pub fn compute_peaks(path: &Path, peaks_per_second: u32) -> Result<WaveformPeaks, AudioError> {
+    let mut reader = open_wav(path)?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    let peaks_per_second = peaks_per_second.clamp(1, spec.sample_rate.max(1));
+    let bucket_samples = (spec.sample_rate / peaks_per_second).max(1) as usize * channels;
+
+    let samples: Box<dyn Iterator<Item = f32>> = if spec.sample_format == SampleFormat::Float {
+        Box::new(reader.samples::<f32>().filter_map(|s| s.ok()))
+    } else {
+        let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+        Box::new(
+            reader
+                .samples::<i32>()
+                .filter_map(|s| s.ok())
+                .map(move |s| s as f32 / scale),
+        )
+    };
+
+    let mut min = Vec::new();
+    let mut max = Vec::new();
+    let (mut lo, mut hi, mut count, mut total) = (0.0f32, 0.0f32, 0usize, 0usize);
+    for sample in samples {
+        lo = lo.min(sample);
+        hi = hi.max(sample);
+        count += 1;
+        total += 1;
+        if count == bucket_samples {
+            min.push(lo);
+            max.push(hi);
+            (lo, hi, count) = (0.0, 0.0, 0);
+        }
+    }
+    if count > 0 {
+        min.push(lo);
+        max.push(hi);
+    }
+
+    let frames = (total / channels) as i64;
+    Ok(WaveformPeaks {
+        sample_rate: spec.sample_rate,
+        duration_ms: frames * 1000 / spec.sample_rate.max(1) as i64,
+        peaks_per_second,
+        min,
+        max,
+    })
+}
+
+/// Load peaks from `cache_path` if it is newer than the audio, otherwise compute and cache them
+pub fn load_or_compute_peaks(
+    audio_path: &Path,
+    cache_path: &Path,
+    peaks_per_second: u32,
+) -> Result<WaveformPeaks, AudioError> {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+
+    if let (Some(audio_time), Some(cache_time)) = (modified(audio_path), modified(cache_path))
+        && cache_time >= audio_time
+        && let Ok(json) = std::fs::read_to_string(cache_path)
+        && let Ok(peaks) = serde_json::from_str::<WaveformPeaks>(&json)
+        && peaks.peaks_per_second == peaks_per_second
+    {
+        return Ok(peaks);
+    }
+
+    let peaks = compute_peaks(audio_path, peaks_per_second)?;
+    match serde_json::to_string(&peaks) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(cache_path, json) {
+                eprintln!("Failed to cache waveform peaks: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize waveform peaks: {}", e),
+    }
+    Ok(peaks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hound::{WavSpec, WavWriter};
+
+    #[test]
+    fn test_compute_peaks_buckets_stereo_frames() {
+        let dir = std::env::temp_dir().join(format!("netnote-peaks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tone.wav");
+
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        // 1.5s: silence on the left, a loud first half-second on the right
+        for i in 0..1500 {
+            writer.write_sample(0i16).unwrap();
+            writer
+                .write_sample(if i < 500 { i16::MAX / 2 } else { 0 })
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let peaks = compute_peaks(&path, 10).unwrap();
+        assert_eq!(peaks.duration_ms, 1500);
+        assert_eq!(peaks.max.len(), 15);
+        assert!(peaks.max[0] > 0.4 && peaks.max[0] < 0.6);
+        assert_eq!(peaks.max[5], 0.0);
+
+        let cache = dir.join("tone.peaks.json");
+        let cached = load_or_compute_peaks(&path, &cache, 10).unwrap();
+        assert!(cache.exists());
+        assert_eq!(load_or_compute_peaks(&path, &cache, 10).unwrap(), cached);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+//! Headless command-line access to the NetNote library; see `netnote_lib::cli`
+
+fn main() -> std::process::ExitCode {
+    netnote_lib::cli::main()
+}
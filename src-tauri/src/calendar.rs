@@ -0,0 +1,1003 @@
+//! Calendar import from iCalendar (`.ics`) files
+//!
+//! Only what meeting notes need is read from `VEVENT`s: title, description,
+//! location, time window and attendees. Recurring events are expanded inside
+//! the import window for `DAILY`, `WEEKLY` (with `BYDAY`) and `MONTHLY` rules,
+//! honoring `INTERVAL`, `COUNT`, `UNTIL`, `EXDATE` and `RECURRENCE-ID`
+//! overrides. Times with a known `TZID` are read in that time zone, floating
+//! times and unknown zones as local time; all-day and cancelled events are
+//! skipped since nobody records them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// `COUNT` rules are expanded from their first occurrence, so at most this
+/// many of them are generated. Other rules start at the import window.
+const MAX_OCCURRENCES: usize = 1000;
+
+/// A single (occurrence of an) event
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsEvent {
+    /// `UID`, suffixed with the occurrence start for recurring events
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Organizer and attendees as a participants string (`Name <email>, ...`)
+    pub participants: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not an iCalendar file: {0}")]
+    NotICalendar(String),
+}
+
+/// A content line: `NAME;PARAM=value:VALUE`
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Every `.ics` file at `path` (a file, or a directory such as a synced CalDAV folder)
+pub fn ics_files(path: &Path) -> Result<Vec<PathBuf>, CalendarError> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Read the events of an `.ics` file that overlap `[from, to)`
+pub fn read_ics_file(
+    path: &Path,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<IcsEvent>, CalendarError> {
+    let text = std::fs::read_to_string(path)?;
+    if !text.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err(CalendarError::NotICalendar(path.display().to_string()));
+    }
+    Ok(parse_ics(&text, from, to))
+}
+
+/// Parse iCalendar text into the events overlapping `[from, to)`, by start time
+pub fn parse_ics(text: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<IcsEvent> {
+    let mut events: HashMap<String, IcsEvent> = HashMap::new();
+    let mut overrides = Vec::new();
+
+    for component in vevents(text) {
+        if component
+            .iter()
+            .any(|p| p.name == "STATUS" && p.value.eq_ignore_ascii_case("CANCELLED"))
+        {
+            // A cancelled occurrence removes it from the series
+            let recurrence_id = component
+                .iter()
+                .find(|p| p.name == "RECURRENCE-ID")
+                .and_then(parse_time);
+            if let (Some(uid), Some(start)) = (uid(&component), recurrence_id) {
+                overrides.push((occurrence_id(&uid, start), None));
+            }
+            continue;
+        }
+        let Some(event) = parse_event(&component) else {
+            continue;
+        };
+
+        match component.iter().find(|p| p.name == "RECURRENCE-ID") {
+            Some(recurrence_id) => {
+                if let Some(original) = parse_time(recurrence_id) {
+                    let id = occurrence_id(&event.id, original);
+                    overrides.push((id.clone(), Some(IcsEvent { id, ..event })));
+                }
+            }
+            None => match component.iter().find(|p| p.name == "RRULE") {
+                Some(rrule) => {
+                    let exdates: Vec<DateTime<Utc>> = component
+                        .iter()
+                        .filter(|p| p.name == "EXDATE")
+                        .flat_map(|p| {
+                            p.value.split(',').filter_map(|value| {
+                                parse_time(&Property {
+                                    name: p.name.clone(),
+                                    params: p.params.clone(),
+                                    value: value.to_string(),
+                                })
+                            })
+                        })
+                        .collect();
+                    let length = event.ends_at - event.starts_at;
+                    // A UTC start repeats in UTC, not in local time
+                    let zone = component
+                        .iter()
+                        .find(|p| p.name == "DTSTART")
+                        .and_then(|p| {
+                            if p.value.trim().ends_with(['Z', 'z']) {
+                                Some(Tz::UTC)
+                            } else {
+                                time_zone(p)
+                            }
+                        });
+                    for start in
+                        expand_rrule(&rrule.value, event.starts_at, zone, from - length, to)
+                    {
+                        if exdates.contains(&start) {
+                            continue;
+                        }
+                        let id = occurrence_id(&event.id, start);
+                        events.insert(
+                            id.clone(),
+                            IcsEvent {
+                                id,
+                                starts_at: start,
+                                ends_at: start + length,
+                                ..event.clone()
+                            },
+                        );
+                    }
+                }
+                None => {
+                    events.insert(event.id.clone(), event);
+                }
+            },
+        }
+    }
+
+    for (id, event) in overrides {
+        match event {
+            Some(event) => events.insert(id, event),
+            None => events.remove(&id),
+        };
+    }
+
+    let mut events: Vec<IcsEvent> = events
+        .into_values()
+        .filter(|e| e.ends_at > from && e.starts_at < to)
+        .collect();
+    events.sort_by(|a, b| a.starts_at.cmp(&b.starts_at).then(a.id.cmp(&b.id)));
+    events
+}
+
+fn occurrence_id(uid: &str, start: DateTime<Utc>) -> String {
+    format!("{}#{}", uid, start.format("%Y%m%dT%H%M%SZ"))
+}
+
+fn uid(component: &[Property]) -> Option<String> {
+    component
+        .iter()
+        .find(|p| p.name == "UID")
+        .map(|p| p.value.clone())
+}
+
+/// The properties of each `VEVENT` (nested components such as `VALARM` are dropped)
+fn vevents(text: &str) -> Vec<Vec<Property>> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    let mut depth = 0;
+
+    for line in unfold(text) {
+        let Some(property) = parse_line(&line) else {
+            continue;
+        };
+        match (
+            property.name.as_str(),
+            property.value.to_ascii_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VEVENT") if current.is_none() => current = Some(Vec::new()),
+            ("END", "VEVENT") if depth == 0 => events.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => depth += 1,
+            ("END", _) if current.is_some() => depth -= 1,
+            _ => {
+                if let Some(properties) = current.as_mut().filter(|_| depth == 0) {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+    events
+}
+
+/// Join folded content lines (continuations start with a space or tab)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    // The value starts at the first colon outside a quoted parameter value
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = split_unquoted(head, ';').into_iter();
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((
+                key.trim().to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn split_unquoted(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Undo TEXT escaping (`\n`, `\,`, `\;`, `\\`)
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn parse_event(component: &[Property]) -> Option<IcsEvent> {
+    let get = |name: &str| component.iter().find(|p| p.name == name);
+    let text = |name: &str| {
+        get(name)
+            .map(|p| unescape(&p.value).trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let start_property = get("DTSTART")?;
+    // All-day events are not meetings anyone records
+    if start_property
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+        || start_property.value.len() == 8
+    {
+        return None;
+    }
+    let starts_at = parse_time(start_property)?;
+    let ends_at = get("DTEND")
+        .and_then(parse_time)
+        .or_else(|| {
+            get("DURATION")
+                .and_then(|p| parse_duration(&p.value))
+                .map(|d| starts_at + d)
+        })
+        .filter(|end| *end >= starts_at)
+        .unwrap_or(starts_at);
+
+    let mut participants: Vec<String> = Vec::new();
+    for property in component
+        .iter()
+        .filter(|p| p.name == "ORGANIZER" || p.name == "ATTENDEE")
+    {
+        if let Some(participant) = participant(property).filter(|p| !participants.contains(p)) {
+            participants.push(participant);
+        }
+    }
+
+    Some(IcsEvent {
+        id: uid(component).unwrap_or_else(|| {
+            format!(
+                "{}@{}",
+                text("SUMMARY").unwrap_or_default(),
+                starts_at.timestamp()
+            )
+        }),
+        title: text("SUMMARY").unwrap_or_else(|| "Untitled event".to_string()),
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        starts_at,
+        ends_at,
+        participants: (!participants.is_empty()).then(|| participants.join(", ")),
+    })
+}
+
+/// `Name <email>` for an organizer or attendee, skipping rooms and resources
+fn participant(property: &Property) -> Option<String> {
+    if property
+        .param("CUTYPE")
+        .is_some_and(|t| t.eq_ignore_ascii_case("ROOM") || t.eq_ignore_ascii_case("RESOURCE"))
+    {
+        return None;
+    }
+    let email = property
+        .value
+        .strip_prefix("mailto:")
+        .or_else(|| property.value.strip_prefix("MAILTO:"))
+        .map(str::trim)
+        .filter(|e| !e.is_empty());
+    // Participant strings are comma-separated, so names cannot contain commas
+    let name = property
+        .param("CN")
+        .map(|cn| {
+            cn.replace([',', ';'], " ")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|cn| !cn.is_empty() && Some(cn.as_str()) != email);
+
+    match (name, email) {
+        (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+        (Some(name), None) => Some(name),
+        (None, Some(email)) => Some(email.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// A DATE-TIME value: UTC (`Z`), in the time zone of its `TZID`, or local
+/// time when floating or the zone is unknown
+fn parse_time(property: &Property) -> Option<DateTime<Utc>> {
+    let value = property.value.trim();
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default())
+        })
+        .ok()?;
+    from_wall_clock(naive, time_zone(property))
+}
+
+/// The IANA zone named by a `TZID` parameter (`Europe/Rome`, `/Europe/Rome`)
+fn time_zone(property: &Property) -> Option<Tz> {
+    property
+        .param("TZID")
+        .map(|id| id.trim_matches('"').trim_start_matches('/'))
+        .and_then(|id| id.parse().ok())
+}
+
+/// A wall-clock time in `zone` (local time without one), or `None` when it
+/// falls in a DST gap
+fn from_wall_clock(naive: NaiveDateTime, zone: Option<Tz>) -> Option<DateTime<Utc>> {
+    match zone {
+        Some(zone) => zone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc)),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc)),
+    }
+}
+
+fn to_wall_clock(at: DateTime<Utc>, zone: Option<Tz>) -> NaiveDateTime {
+    match zone {
+        Some(zone) => at.with_timezone(&zone).naive_local(),
+        None => at.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// An iCalendar DURATION (`PT1H30M`, `P1D`, `P1W`)
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            'T' => {}
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Start times of a recurring event in `[from, until]`. Wall-clock times and
+/// weekdays are those of the start in `zone`, or in local time without one.
+fn expand_rrule(
+    rule: &str,
+    start: DateTime<Utc>,
+    zone: Option<Tz>,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let parts: HashMap<String, String> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim().to_string()))
+        .collect();
+
+    let interval = parts
+        .get("INTERVAL")
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|i| *i > 0)
+        .unwrap_or(1);
+    let count = parts.get("COUNT").and_then(|v| v.parse::<usize>().ok());
+    let rule_until = parts.get("UNTIL").and_then(|v| {
+        parse_time(&Property {
+            name: "UNTIL".to_string(),
+            params: Vec::new(),
+            value: v.clone(),
+        })
+    });
+    let until = rule_until.map_or(until, |u| u.min(until));
+    let limit = count.map(|c| c.min(MAX_OCCURRENCES));
+
+    let first = to_wall_clock(start, zone);
+    // COUNT needs every occurrence from the first; anything else can skip to the window
+    let skip_to = match limit {
+        Some(_) => first,
+        None => to_wall_clock(from, zone).max(first),
+    };
+
+    let mut occurrences = Vec::new();
+    let mut generated = 0;
+    // Takes the next occurrence of the series, false once the series is over
+    let mut next = |at: DateTime<Utc>| {
+        if at > until || limit.is_some_and(|limit| generated >= limit) {
+            return false;
+        }
+        generated += 1;
+        if at >= from {
+            occurrences.push(at);
+        }
+        true
+    };
+
+    match parts.get("FREQ").map(String::as_str) {
+        Some("DAILY") => {
+            let step = Duration::days(interval as i64);
+            let skipped = (skip_to - first).num_days() / interval as i64;
+            let mut day = first + Duration::days(skipped * interval as i64);
+            loop {
+                // A time in a DST gap doesn't exist that day, the next one may
+                if let Some(at) = from_wall_clock(day, zone)
+                    && !next(at)
+                {
+                    break;
+                }
+                day += step;
+            }
+        }
+        Some("WEEKLY") => {
+            let mut weekdays: Vec<Weekday> = parts
+                .get("BYDAY")
+                .map(|days| days.split(',').filter_map(parse_weekday).collect())
+                .unwrap_or_default();
+            if weekdays.is_empty() {
+                weekdays.push(first.weekday());
+            }
+            weekdays.sort_by_key(|d| d.num_days_from_monday());
+
+            let step = Duration::weeks(interval as i64);
+            let first_week = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+            let skipped = (skip_to - first_week).num_weeks() / interval as i64;
+            let mut week_start = first_week + Duration::weeks(skipped * interval as i64);
+            'weeks: loop {
+                for weekday in &weekdays {
+                    let day = week_start + Duration::days(weekday.num_days_from_monday() as i64);
+                    if day < first {
+                        continue;
+                    }
+                    let Some(at) = from_wall_clock(day, zone) else {
+                        continue;
+                    };
+                    if !next(at) {
+                        break 'weeks;
+                    }
+                }
+                week_start += step;
+            }
+        }
+        Some("MONTHLY") => {
+            let list = |key: &str| -> Vec<String> {
+                parts
+                    .get(key)
+                    .map(|v| v.split(',').map(|item| item.trim().to_string()).collect())
+                    .unwrap_or_default()
+            };
+            let by_day: Vec<(Option<i32>, Weekday)> = list("BYDAY")
+                .iter()
+                .filter_map(|d| parse_monthly_weekday(d))
+                .collect();
+            let by_month_day: Vec<i32> = list("BYMONTHDAY")
+                .iter()
+                .filter_map(|d| d.parse().ok())
+                .collect();
+            let by_set_pos: Vec<i32> = list("BYSETPOS")
+                .iter()
+                .filter_map(|p| p.parse().ok())
+                .collect();
+
+            let first_month = first.date() - Duration::days(first.day0() as i64);
+            let last_day = to_wall_clock(until, zone).date();
+            let months_behind = (skip_to.year() - first.year()) * 12 + skip_to.month() as i32
+                - first.month() as i32;
+            'months: for n in (months_behind.max(0) as u32 / interval).. {
+                let Some(month) = first_month.checked_add_months(Months::new(n * interval)) else {
+                    break;
+                };
+                if month > last_day {
+                    break;
+                }
+                for day in month_days(month, first.day(), &by_day, &by_month_day, &by_set_pos) {
+                    let day = day.and_time(first.time());
+                    if day < first {
+                        continue;
+                    }
+                    let Some(at) = from_wall_clock(day, zone) else {
+                        continue;
+                    };
+                    if !next(at) {
+                        break 'months;
+                    }
+                }
+            }
+        }
+        // Unsupported rules keep the first occurrence only
+        _ => {
+            next(start);
+        }
+    }
+    occurrences
+}
+
+/// Days of `month` (its first day) a MONTHLY rule falls on, in order. Without
+/// BYDAY or BYMONTHDAY that is the start's day of month, when the month has it.
+fn month_days(
+    month: NaiveDate,
+    start_day: u32,
+    by_day: &[(Option<i32>, Weekday)],
+    by_month_day: &[i32],
+    by_set_pos: &[i32],
+) -> Vec<NaiveDate> {
+    let all: Vec<NaiveDate> = month
+        .iter_days()
+        .take_while(|d| d.month() == month.month())
+        .collect();
+
+    let mut days: Vec<NaiveDate> = if !by_day.is_empty() {
+        let mut days = Vec::new();
+        for &(ordinal, weekday) in by_day {
+            let matching: Vec<NaiveDate> = all
+                .iter()
+                .copied()
+                .filter(|d| d.weekday() == weekday)
+                .collect();
+            match ordinal {
+                Some(ordinal) => days.extend(at_position(&matching, ordinal)),
+                None => days.extend(matching),
+            }
+        }
+        // BYMONTHDAY narrows BYDAY down when both are given
+        if !by_month_day.is_empty() {
+            days.retain(|d| {
+                by_month_day
+                    .iter()
+                    .any(|&n| at_position(&all, n) == Some(*d))
+            });
+        }
+        days
+    } else if !by_month_day.is_empty() {
+        by_month_day
+            .iter()
+            .filter_map(|&n| at_position(&all, n))
+            .collect()
+    } else {
+        month.with_day(start_day).into_iter().collect()
+    };
+    days.sort();
+    days.dedup();
+
+    if !by_set_pos.is_empty() {
+        days = by_set_pos
+            .iter()
+            .filter_map(|&p| at_position(&days, p))
+            .collect();
+        days.sort();
+        days.dedup();
+    }
+    days
+}
+
+/// The item at a 1-based rule position; negative positions count from the end
+fn at_position<T: Copy>(items: &[T], position: i32) -> Option<T> {
+    let index = if position > 0 {
+        position - 1
+    } else {
+        items.len() as i32 + position
+    };
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| items.get(i).copied())
+}
+
+/// A monthly BYDAY entry: "MO" is every Monday, "1MO" the first, "-1FR" the last Friday
+fn parse_monthly_weekday(day: &str) -> Option<(Option<i32>, Weekday)> {
+    let split = day.find(|c: char| c.is_ascii_alphabetic())?;
+    let (ordinal, weekday) = day.split_at(split);
+    let ordinal = match ordinal.trim_start_matches('+') {
+        "" => None,
+        ordinal => Some(ordinal.parse().ok().filter(|n| *n != 0)?),
+    };
+    Some((ordinal, parse_weekday(weekday)?))
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    // Ordinal prefixes ("1MO", "-1FR") only make sense for monthly rules
+    let day = day
+        .trim()
+        .trim_start_matches(|c: char| c == '-' || c == '+' || c.is_ascii_digit());
+    match day.to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:planning@example.com\r\n\
+SUMMARY:Sprint planning\\, Q3\r\n\
+DESCRIPTION:Agenda:\\nscope\r\n\
+\x20and estimates\r\n\
+DTSTART:20250602T090000Z\r\n\
+DTEND:20250602T100000Z\r\n\
+ORGANIZER;CN=Alice Smith:mailto:alice@example.com\r\n\
+ATTENDEE;CN=\"Rossi, Marco\";ROLE=REQ-PARTICIPANT:mailto:marco@example.com\r\n\
+ATTENDEE;CUTYPE=ROOM;CN=Room 1:mailto:room1@example.com\r\n\
+ATTENDEE:mailto:bob@example.com\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:holiday@example.com\r\n\
+SUMMARY:Holiday\r\n\
+DTSTART;VALUE=DATE:20250603\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+SUMMARY:Standup\r\n\
+DTSTART:20250602T080000Z\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=DAILY;COUNT=4\r\n\
+EXDATE:20250603T080000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+RECURRENCE-ID:20250604T080000Z\r\n\
+SUMMARY:Standup (moved)\r\n\
+DTSTART:20250604T083000Z\r\n\
+DTEND:20250604T084500Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_event_fields() {
+        let events = parse_ics(
+            CALENDAR,
+            utc("2025-06-01T00:00:00Z"),
+            utc("2025-07-01T00:00:00Z"),
+        );
+        let planning = events
+            .iter()
+            .find(|e| e.id == "planning@example.com")
+            .unwrap();
+
+        assert_eq!(planning.title, "Sprint planning, Q3");
+        assert_eq!(
+            planning.description.as_deref(),
+            Some("Agenda:\nscopeand estimates")
+        );
+        assert_eq!(planning.starts_at, utc("2025-06-02T09:00:00Z"));
+        assert_eq!(planning.ends_at, utc("2025-06-02T10:00:00Z"));
+        assert_eq!(
+            planning.participants.as_deref(),
+            Some(
+                "Alice Smith <alice@example.com>, Rossi Marco <marco@example.com>, bob@example.com"
+            )
+        );
+        assert!(!events.iter().any(|e| e.title == "Holiday"));
+    }
+
+    #[test]
+    fn test_recurrence_with_exdate_and_override() {
+        let events = parse_ics(
+            CALENDAR,
+            utc("2025-06-01T00:00:00Z"),
+            utc("2025-07-01T00:00:00Z"),
+        );
+        let standups: Vec<(&str, DateTime<Utc>)> = events
+            .iter()
+            .filter(|e| e.title.starts_with("Standup"))
+            .map(|e| (e.title.as_str(), e.starts_at))
+            .collect();
+
+        assert_eq!(
+            standups,
+            vec![
+                ("Standup", utc("2025-06-02T08:00:00Z")),
+                ("Standup (moved)", utc("2025-06-04T08:30:00Z")),
+                ("Standup", utc("2025-06-05T08:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_window_filters_events() {
+        let events = parse_ics(
+            CALENDAR,
+            utc("2025-06-05T00:00:00Z"),
+            utc("2025-07-01T00:00:00Z"),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].starts_at, utc("2025-06-05T08:00:00Z"));
+    }
+
+    fn vcalendar(events: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+            events
+        )
+    }
+
+    fn starts(text: &str, from: &str, to: &str) -> Vec<DateTime<Utc>> {
+        parse_ics(text, utc(from), utc(to))
+            .iter()
+            .map(|e| e.starts_at)
+            .collect()
+    }
+
+    #[test]
+    fn test_old_daily_series_reaches_the_window() {
+        let text = vcalendar(
+            "BEGIN:VEVENT\r\n\
+UID:daily@example.com\r\n\
+SUMMARY:Daily\r\n\
+DTSTART;TZID=Europe/Rome:20180101T090000\r\n\
+DURATION:PT30M\r\n\
+RRULE:FREQ=DAILY;INTERVAL=2\r\n\
+END:VEVENT\r\n",
+        );
+        // Over MAX_OCCURRENCES days in, starting inside the first occurrence
+        assert_eq!(
+            starts(&text, "2025-06-01T07:15:00Z", "2025-06-06T00:00:00Z"),
+            vec![
+                utc("2025-06-01T07:00:00Z"),
+                utc("2025-06-03T07:00:00Z"),
+                utc("2025-06-05T07:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tzid_times_use_their_zone() {
+        let text = vcalendar(
+            "BEGIN:VEVENT\r\n\
+UID:review@example.com\r\n\
+SUMMARY:Review\r\n\
+DTSTART;TZID=America/New_York:20250115T100000\r\n\
+DTEND;TZID=America/New_York:20250115T110000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:sync@example.com\r\n\
+SUMMARY:Sync\r\n\
+DTSTART;TZID=\"Europe/Rome\":20250320T090000\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=WEEKLY;COUNT=3\r\n\
+END:VEVENT\r\n",
+        );
+        let events = parse_ics(
+            &text,
+            utc("2025-01-01T00:00:00Z"),
+            utc("2025-05-01T00:00:00Z"),
+        );
+
+        let review = events.iter().find(|e| e.title == "Review").unwrap();
+        assert_eq!(review.starts_at, utc("2025-01-15T15:00:00Z"));
+        assert_eq!(review.ends_at, utc("2025-01-15T16:00:00Z"));
+        // 09:00 in Rome stays 09:00 across the switch to summer time
+        let syncs: Vec<_> = events
+            .iter()
+            .filter(|e| e.title == "Sync")
+            .map(|e| e.starts_at)
+            .collect();
+        assert_eq!(
+            syncs,
+            vec![
+                utc("2025-03-20T08:00:00Z"),
+                utc("2025-03-27T08:00:00Z"),
+                utc("2025-04-03T07:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_daily_series_continues_after_a_dst_gap() {
+        let text = vcalendar(
+            "BEGIN:VEVENT\r\n\
+UID:night@example.com\r\n\
+SUMMARY:Night shift handover\r\n\
+DTSTART;TZID=Europe/Rome:20250329T023000\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=DAILY\r\n\
+END:VEVENT\r\n",
+        );
+        // 02:30 doesn't exist in Rome on 30 March 2025
+        assert_eq!(
+            starts(&text, "2025-03-28T00:00:00Z", "2025-04-01T00:00:00Z"),
+            vec![utc("2025-03-29T01:30:00Z"), utc("2025-03-31T00:30:00Z"),]
+        );
+    }
+
+    #[test]
+    fn test_monthly_by_day_and_set_position() {
+        let text = vcalendar(
+            "BEGIN:VEVENT\r\n\
+UID:board@example.com\r\n\
+SUMMARY:Board\r\n\
+DTSTART:20250106T100000Z\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=MONTHLY;BYDAY=1MO\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:report@example.com\r\n\
+SUMMARY:Report\r\n\
+DTSTART:20250131T160000Z\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:close@example.com\r\n\
+SUMMARY:Close\r\n\
+DTSTART:20250115T080000Z\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=MONTHLY;BYMONTHDAY=-1\r\n\
+END:VEVENT\r\n",
+        );
+        let events = parse_ics(
+            &text,
+            utc("2025-01-01T00:00:00Z"),
+            utc("2025-04-10T00:00:00Z"),
+        );
+        let series = |title: &str| -> Vec<DateTime<Utc>> {
+            events
+                .iter()
+                .filter(|e| e.title == title)
+                .map(|e| e.starts_at)
+                .collect()
+        };
+
+        // First Monday of each month, not the 6th
+        assert_eq!(
+            series("Board"),
+            vec![
+                utc("2025-01-06T10:00:00Z"),
+                utc("2025-02-03T10:00:00Z"),
+                utc("2025-03-03T10:00:00Z"),
+                utc("2025-04-07T10:00:00Z"),
+            ]
+        );
+        // Last weekday of the month
+        assert_eq!(
+            series("Report"),
+            vec![
+                utc("2025-01-31T16:00:00Z"),
+                utc("2025-02-28T16:00:00Z"),
+                utc("2025-03-31T16:00:00Z"),
+            ]
+        );
+        // Last day of the month
+        assert_eq!(
+            series("Close"),
+            vec![
+                utc("2025-01-31T08:00:00Z"),
+                utc("2025-02-28T08:00:00Z"),
+                utc("2025-03-31T08:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("1H"), None);
+    }
+}
@@ -0,0 +1,541 @@
+//! `netnote-cli`: the library from a terminal, for scheduled exports and batch
+//! jobs over SSH
+//!
+//! Opens the same `netnote.db` as the app, found in the app data directory or
+//! in `--data-dir` / `NETNOTE_DATA_DIR`. A passphrase-protected library is
+//! unlocked with `NETNOTE_PASSPHRASE`. Confidential notes stay hidden, as in
+//! the app before they are unlocked.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::ai::summarizer::summarize_note;
+use crate::ai::OllamaClient;
+use crate::commands::export::{format_srt_timestamp, note_markdown, transcript_srt};
+use crate::commands::notes::{filtered_notes, matching_notes};
+use crate::crypto::{self, EncryptionConfig};
+use crate::db::models::{Note, NoteFilter, SummaryType};
+use crate::db::Database;
+use crate::transcription::{ModelManager, Transcriber, TranscriptionResult};
+
+const USAGE: &str = "\
+Usage: netnote-cli [--data-dir DIR] <command>
+
+Commands:
+  list [--limit N]                       Notes, newest first
+  show <note>                            A note's details and summaries
+  search <query>                         Full-text search over notes and transcripts
+  export <note> [--format md|srt|json] [--output FILE]
+  transcribe <file> [--model ID] [--language CODE] [--format text|srt|json]
+  summarize <note> [--type overview|action_items|key_decisions|custom]
+                   [--prompt TEXT] [--model NAME]
+
+<note> is a note id or a unique prefix of one.
+
+Environment:
+  NETNOTE_DATA_DIR     Library directory (default: the app's data directory)
+  NETNOTE_PASSPHRASE   Passphrase of a passphrase-protected library";
+
+/// Same as the app's bundle identifier, which names its data directory
+const APP_IDENTIFIER: &str = "com.netnote.app";
+
+/// Settings the app saves for its selected models
+const WHISPER_MODEL_KEY: &str = "whisper_model";
+const WHISPER_LANGUAGE_KEY: &str = "whisper_language";
+const OLLAMA_MODEL_KEY: &str = "ollama_model";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Markdown,
+    Srt,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TranscriptFormat {
+    Text,
+    Srt,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    List {
+        limit: Option<usize>,
+    },
+    Show {
+        note: String,
+    },
+    Search {
+        query: String,
+    },
+    Export {
+        note: String,
+        format: ExportFormat,
+        output: Option<PathBuf>,
+    },
+    Transcribe {
+        file: PathBuf,
+        model: Option<String>,
+        language: Option<String>,
+        format: TranscriptFormat,
+    },
+    Summarize {
+        note: String,
+        summary_type: SummaryType,
+        prompt: Option<String>,
+        model: Option<String>,
+    },
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    data_dir: Option<PathBuf>,
+    command: Command,
+}
+
+fn required_arg(
+    positional: &mut impl Iterator<Item = String>,
+    command: &str,
+    what: &str,
+) -> Result<String, String> {
+    positional
+        .next()
+        .ok_or_else(|| format!("{} needs a {}", command, what))
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(Args {
+                data_dir: None,
+                command: Command::Help,
+            });
+        }
+        if !arg.starts_with("--") {
+            positional.push(arg);
+            continue;
+        }
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} needs a value", arg))?;
+                (arg, value)
+            }
+        };
+        options.insert(name, value);
+    }
+
+    let data_dir = options.remove("--data-dir").map(PathBuf::from);
+    let mut positional = positional.into_iter();
+    let name = positional.next().ok_or("Missing command")?;
+
+    let command = match name.as_str() {
+        "list" => Command::List {
+            limit: options
+                .remove("--limit")
+                .map(|n| n.parse().map_err(|_| format!("Invalid limit: {}", n)))
+                .transpose()?,
+        },
+        "show" => Command::Show {
+            note: required_arg(&mut positional, &name, "note")?,
+        },
+        "search" => {
+            let query = (&mut positional).collect::<Vec<_>>().join(" ");
+            if query.trim().is_empty() {
+                return Err("search needs a query".to_string());
+            }
+            Command::Search { query }
+        }
+        "export" => Command::Export {
+            note: required_arg(&mut positional, &name, "note")?,
+            format: match options.remove("--format").as_deref() {
+                None | Some("md") | Some("markdown") => ExportFormat::Markdown,
+                Some("srt") => ExportFormat::Srt,
+                Some("json") => ExportFormat::Json,
+                Some(other) => return Err(format!("Unknown export format: {}", other)),
+            },
+            output: options.remove("--output").map(PathBuf::from),
+        },
+        "transcribe" => Command::Transcribe {
+            file: required_arg(&mut positional, &name, "file")?.into(),
+            model: options.remove("--model"),
+            language: options.remove("--language"),
+            format: match options.remove("--format").as_deref() {
+                None | Some("text") => TranscriptFormat::Text,
+                Some("srt") => TranscriptFormat::Srt,
+                Some("json") => TranscriptFormat::Json,
+                Some(other) => return Err(format!("Unknown transcript format: {}", other)),
+            },
+        },
+        "summarize" => Command::Summarize {
+            note: required_arg(&mut positional, &name, "note")?,
+            summary_type: match options.remove("--type") {
+                None => SummaryType::Overview,
+                Some(t) => serde_json::from_value(t.clone().into())
+                    .map_err(|_| format!("Unknown summary type: {}", t))?,
+            },
+            prompt: options.remove("--prompt"),
+            model: options.remove("--model"),
+        },
+        "help" => Command::Help,
+        other => return Err(format!("Unknown command: {}", other)),
+    };
+
+    if let Some(option) = options.keys().next() {
+        return Err(format!("Unknown option for {}: {}", name, option));
+    }
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument: {}", extra));
+    }
+    Ok(Args { data_dir, command })
+}
+
+/// Where the app keeps its data: the platform data directory plus the bundle identifier
+fn default_data_dir() -> Option<PathBuf> {
+    let env = |name: &str| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let base = if cfg!(target_os = "macos") {
+        env("HOME").map(|home| home.join("Library/Application Support"))
+    } else if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else {
+        env("XDG_DATA_HOME").or_else(|| env("HOME").map(|home| home.join(".local/share")))
+    };
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+fn open_library(data_dir: &Path) -> Result<Database, String> {
+    let db_path = data_dir.join("netnote.db");
+    if !db_path.exists() {
+        return Err(format!("No NetNote library in {}", data_dir.display()));
+    }
+    let db = Database::open(db_path).map_err(|e| e.to_string())?;
+    if !db.is_locked() {
+        return Ok(db);
+    }
+
+    let passphrase = std::env::var("NETNOTE_PASSPHRASE")
+        .map_err(|_| "The library is passphrase-protected; set NETNOTE_PASSPHRASE")?;
+    let key = EncryptionConfig::load(data_dir)
+        .map_err(|e| e.to_string())?
+        .ok_or("The library is not encrypted")?
+        .unlock(Some(&passphrase))
+        .map_err(|e| e.to_string())?;
+    db.unlock(&key).map_err(|e| e.to_string())?;
+    crypto::set_library_key(Some(key));
+    Ok(db)
+}
+
+/// Find a note by id or unique id prefix, refusing confidential notes
+fn resolve_note(db: &Database, id: &str) -> Result<Note, String> {
+    let note = match db.find_note(id).map_err(|e| e.to_string())? {
+        Some(note) => note,
+        None => {
+            let mut matches = filtered_notes(db, &NoteFilter::default())?
+                .into_iter()
+                .filter(|note| note.id.starts_with(id));
+            match (matches.next(), matches.next()) {
+                (Some(note), None) => note,
+                (None, _) => return Err(format!("No note {}", id)),
+                (Some(_), Some(_)) => return Err(format!("More than one note starts with {}", id)),
+            }
+        }
+    };
+    if note.confidential {
+        return Err(format!(
+            "\"{}\" is confidential; unlock it in the app instead",
+            note.title
+        ));
+    }
+    Ok(note)
+}
+
+fn setting(db: &Database, key: &str) -> Option<String> {
+    db.get_setting(key)
+        .ok()
+        .flatten()
+        .filter(|value| !value.is_empty())
+}
+
+fn print_notes(notes: impl IntoIterator<Item = Note>) {
+    for note in notes {
+        println!(
+            "{}\t{}\t{}",
+            note.id,
+            note.started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            note.title
+        );
+    }
+}
+
+fn show(db: &Database, note: &Note) -> Result<(), String> {
+    let local = |t: chrono::DateTime<chrono::Utc>| {
+        t.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    println!("{}", note.title);
+    println!("ID:           {}", note.id);
+    println!("Started:      {}", local(note.started_at));
+    if let Some(ended_at) = note.ended_at {
+        println!("Ended:        {}", local(ended_at));
+    }
+    if let Some(participants) = &note.participants {
+        println!("Participants: {}", participants);
+    }
+    let segments = db
+        .get_transcript_segments(&note.id)
+        .map_err(|e| e.to_string())?;
+    println!("Transcript:   {} segments", segments.len());
+    if let Some(description) = note.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        println!("\n{}", description.trim());
+    }
+
+    for summary in db.get_summaries(&note.id).map_err(|e| e.to_string())? {
+        println!(
+            "\n## {} ({})\n\n{}",
+            summary.summary_type.as_str(),
+            local(summary.created_at),
+            summary.content.trim()
+        );
+    }
+    Ok(())
+}
+
+fn export(db: &Database, note: &Note, format: ExportFormat) -> Result<String, String> {
+    Ok(match format {
+        ExportFormat::Markdown => note_markdown(db, &note.id)?.markdown,
+        ExportFormat::Srt => {
+            let segments = db
+                .get_transcript_segments(&note.id)
+                .map_err(|e| e.to_string())?;
+            transcript_srt(&segments)
+        }
+        ExportFormat::Json => {
+            let value = serde_json::json!({
+                "note": note,
+                "transcript": db.get_transcript_segments(&note.id).map_err(|e| e.to_string())?,
+                "summaries": db.get_summaries(&note.id).map_err(|e| e.to_string())?,
+            });
+            serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?
+        }
+    })
+}
+
+fn format_transcription(
+    result: &TranscriptionResult,
+    format: TranscriptFormat,
+) -> Result<String, String> {
+    Ok(match format {
+        TranscriptFormat::Text => result
+            .segments
+            .iter()
+            .map(|s| s.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        TranscriptFormat::Srt => result
+            .segments
+            .iter()
+            .filter(|s| !s.text.trim().is_empty())
+            .enumerate()
+            .map(|(i, s)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    i + 1,
+                    format_srt_timestamp(s.start_time),
+                    format_srt_timestamp(s.end_time),
+                    s.text.trim()
+                )
+            })
+            .collect(),
+        TranscriptFormat::Json => {
+            serde_json::to_string_pretty(result).map_err(|e| e.to_string())?
+        }
+    })
+}
+
+fn write_output(content: &str, output: Option<&Path>) -> Result<(), String> {
+    match output {
+        Some(path) => std::fs::write(path, content).map_err(|e| e.to_string()),
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(content.as_bytes())
+                .and_then(|()| {
+                    if content.ends_with('\n') {
+                        Ok(())
+                    } else {
+                        stdout.write_all(b"\n")
+                    }
+                })
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let data_dir = args
+        .data_dir
+        .or_else(|| std::env::var_os("NETNOTE_DATA_DIR").map(PathBuf::from))
+        .or_else(default_data_dir)
+        .ok_or("Could not find the NetNote data directory; pass --data-dir")?;
+    let db = open_library(&data_dir)?;
+
+    match args.command {
+        Command::List { limit } => {
+            let notes = filtered_notes(&db, &NoteFilter::default())?
+                .into_iter()
+                .filter(|note| !note.confidential)
+                .take(limit.unwrap_or(usize::MAX));
+            print_notes(notes);
+        }
+        Command::Show { note } => show(&db, &resolve_note(&db, &note)?)?,
+        Command::Search { query } => {
+            let notes = matching_notes(&db, &query)?
+                .into_iter()
+                .filter(|note| !note.confidential);
+            print_notes(notes);
+        }
+        Command::Export {
+            note,
+            format,
+            output,
+        } => {
+            let note = resolve_note(&db, &note)?;
+            write_output(&export(&db, &note, format)?, output.as_deref())?;
+        }
+        Command::Transcribe {
+            file,
+            model,
+            language,
+            format,
+        } => {
+            let model = model
+                .or_else(|| setting(&db, WHISPER_MODEL_KEY))
+                .ok_or("No Whisper model chosen; pass --model")?;
+            let model_path = ModelManager::new(data_dir.clone())
+                .model_path(&model)
+                .filter(|path| path.exists())
+                .ok_or_else(|| format!("Whisper model {} is not downloaded", model))?;
+            let language = language
+                .or_else(|| setting(&db, WHISPER_LANGUAGE_KEY))
+                .filter(|l| l != "auto");
+
+            let transcriber = Transcriber::new(&model_path).map_err(|e| e.to_string())?;
+            let result = transcriber
+                .transcribe(&file, language)
+                .map_err(|e| e.to_string())?;
+            write_output(&format_transcription(&result, format)?, None)?;
+        }
+        Command::Summarize {
+            note,
+            summary_type,
+            prompt,
+            model,
+        } => {
+            let note = resolve_note(&db, &note)?;
+            let model = model
+                .or_else(|| setting(&db, OLLAMA_MODEL_KEY))
+                .ok_or("No Ollama model chosen; pass --model")?;
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+            let summary = runtime.block_on(summarize_note(
+                &OllamaClient::new(),
+                &model,
+                &db,
+                &note.id,
+                summary_type,
+                prompt,
+            ))?;
+            write_output(&summary.content, None)?;
+        }
+        Command::Help => println!("{}", USAGE),
+    }
+    Ok(())
+}
+
+/// Entry point of the `netnote-cli` binary
+pub fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("netnote-cli: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("netnote-cli: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse(&["--data-dir", "/tmp/lib", "export", "abc", "--format=srt"]),
+            Ok(Args {
+                data_dir: Some(PathBuf::from("/tmp/lib")),
+                command: Command::Export {
+                    note: "abc".to_string(),
+                    format: ExportFormat::Srt,
+                    output: None,
+                },
+            })
+        );
+        assert_eq!(
+            parse(&["search", "budget", "review"]).map(|a| a.command),
+            Ok(Command::Search {
+                query: "budget review".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["summarize", "abc", "--type", "action_items"]).map(|a| a.command),
+            Ok(Command::Summarize {
+                note: "abc".to_string(),
+                summary_type: SummaryType::ActionItems,
+                prompt: None,
+                model: None,
+            })
+        );
+        assert_eq!(
+            parse(&["list", "--help"]).map(|a| a.command),
+            Ok(Command::Help)
+        );
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["show"]).is_err());
+        assert!(parse(&["show", "abc", "def"]).is_err());
+        assert!(parse(&["export", "abc", "--format", "pdf"]).is_err());
+        assert!(parse(&["summarize", "abc", "--type", "haiku"]).is_err());
+        assert!(parse(&["list", "--limit"]).is_err());
+        assert!(parse(&["list", "--verbose", "yes"]).is_err());
+    }
+}
@@ -1,16 +1,42 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use serde::Serialize;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::audio::{
-    self, aec, is_system_audio_available, mix_wav_files, RecordingPhase, RecordingState,
-    SystemAudioCapture,
+    self, aec, denoise, devices, is_system_audio_available, mix_tracks, mix_wav_files, waveform,
+    AudioSource, DevicePreferences, InputDevice, MixMode, MixOptions, MixTrack,
+    NoiseSuppressionOptions, RecordingPhase, RecordingState, SystemAudioCapture, WaveformPeaks,
 };
+use crate::commands::calendar::match_recording_to_event;
+use crate::commands::privacy::{ensure_note_accessible, PrivacyState};
 use crate::db::Database;
 
+/// Settings key holding the JSON-encoded [`MixOptions`]
+const MIX_OPTIONS_KEY: &str = "mix_options";
+
+/// Settings key holding whether echo cancellation is enabled ("true"/"false")
+pub const AEC_ENABLED_KEY: &str = "aec_enabled";
+
+/// Settings key holding the JSON-encoded [`NoiseSuppressionOptions`]
+pub const NOISE_SUPPRESSION_KEY: &str = "noise_suppression";
+
+/// Settings key holding the JSON map of device id -> [`DevicePreferences`]
+const DEVICE_PREFERENCES_KEY: &str = "device_preferences";
+
+/// Event emitted when the input device is lost, failed over, or switched
+pub const DEVICE_CHANGE_EVENT: &str = "audio-device-changed";
+
+/// How often the device monitor checks the recorder
+const DEVICE_MONITOR_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A failover device that is lost again within this window counts as a failed failover
+const FAILOVER_GRACE: Duration = Duration::from_secs(5);
+
 /// Result of dual recording containing paths to all recorded files
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -44,57 +70,134 @@ impl Default for AudioState {
     }
 }
 
-// Struct for sending device info to frontend
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AudioDeviceInfo {
-    pub id: String,
-    pub name: String,
-    pub is_default: bool,
+/// List input devices with their ids and supported configurations
+#[tauri::command]
+pub fn get_audio_input_devices() -> Result<Vec<InputDevice>, String> {
+    devices::list_input_devices().map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-pub fn get_audio_input_devices() -> Result<Vec<AudioDeviceInfo>, String> {
-    use cpal::traits::{DeviceTrait, HostTrait};
+/// Apply the saved echo cancellation and noise suppression settings
+pub fn load_audio_settings(db: &Database) {
+    if let Ok(Some(value)) = db.get_setting(AEC_ENABLED_KEY) {
+        aec::set_aec_enabled(value == "true");
+    }
+    if let Some(options) = db
+        .get_setting(NOISE_SUPPRESSION_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+    {
+        denoise::set_noise_suppression_options(options);
+    }
+}
 
-    let host = cpal::default_host();
-    let default_device = host.default_input_device();
-    let default_name = default_device.as_ref().and_then(|d| d.name().ok());
-
-    let devices = host.input_devices().map_err(|e| e.to_string())?;
-    let mut result = Vec::new();
-
-    for device in devices {
-        if let Ok(name) = device.name() {
-            // Filter out output devices if any slipped through (though input_devices() should be inputs only)
-            // Windows loopback devices might appear, we keep them for now as user might want them
-            
-            // Generate a stable-ish ID. On Windows name is unique enough usually.
-            // cpal doesn't expose stable IDs easily across platforms without extra work.
-            // For now, we use the name as the ID, or a combination if needed.
-            let id = name.clone(); 
-            
-            let is_default = Some(&name) == default_name.as_ref();
-
-            result.push(AudioDeviceInfo {
-                id,
-                name,
-                is_default,
-            });
-        }
+/// Load the saved per-device input preferences into the recorder
+pub fn load_device_preferences(state: &AudioState, db: &Database) {
+    let saved: HashMap<String, DevicePreferences> = db
+        .get_setting(DEVICE_PREFERENCES_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if let Ok(mut prefs) = state.recording.device_preferences.lock() {
+        *prefs = saved;
     }
+}
 
-    Ok(result)
+/// Get the input preferences (sample rate, channel selection) for a device
+#[tauri::command]
+pub fn get_device_preferences(device_id: String, state: State<AudioState>) -> DevicePreferences {
+    state.recording.preferences_for(&device_id)
 }
 
+/// Save the input preferences for a device (applied from the next recording)
+#[tauri::command]
+pub fn set_device_preferences(
+    device_id: String,
+    preferences: DevicePreferences,
+    state: State<AudioState>,
+    db: State<Database>,
+) -> Result<(), String> {
+    let json = {
+        let mut prefs = state
+            .recording
+            .device_preferences
+            .lock()
+            .map_err(|e| e.to_string())?;
+        if preferences == DevicePreferences::default() {
+            prefs.remove(&device_id);
+        } else {
+            prefs.insert(device_id, preferences);
+        }
+        serde_json::to_string(&*prefs).map_err(|e| e.to_string())?
+    };
+    db.set_setting(DEVICE_PREFERENCES_KEY, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Start a mic-only recording in a new segment of the note
 #[tauri::command]
 pub fn start_recording(
     app: AppHandle,
     state: State<AudioState>,
+    db: State<Database>,
     note_id: String,
     device_id: Option<String>,
 ) -> Result<String, String> {
-    // Get app data directory for storing recordings
+    let result = begin_note_recording(&app, &state, &db, &note_id, device_id, false)?;
+    Ok(result.mic_path)
+}
+
+/// Stop a mic-only recording
+/// Returns the note's playback file, mixed from every recorded segment
+#[tauri::command]
+pub fn stop_recording(
+    app: AppHandle,
+    state: State<AudioState>,
+    db: State<Database>,
+) -> Result<Option<String>, String> {
+    // Capture the note and segment duration before the recorder state is reset
+    let note_id = state
+        .recording
+        .current_note_id
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let segment_id = state.recording.current_segment_db_id.load(Ordering::SeqCst);
+    let segment_duration_ms = state.recording.get_segment_elapsed_ms();
+
+    let path = audio::stop_recording(&state.recording).map_err(|e| e.to_string())?;
+    if segment_id > 0 {
+        let _ = db.update_segment_duration(segment_id, segment_duration_ms);
+    }
+    if let Some(path) = &path {
+        crate::crypto::seal_if_enabled(path).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(note_id) = note_id {
+        match ensure_playback_file(&app, &db, &note_id) {
+            Ok(playback) => return Ok(Some(playback.to_string_lossy().to_string())),
+            Err(e) => eprintln!("Failed to mix the playback file: {}", e),
+        }
+    }
+    Ok(path.map(|p| p.to_string_lossy().to_string()))
+}
+
+/// Start recording a note in a new segment: registers the note and segment with the
+/// recorder (so device failover, storage and the API see them), then starts the mic
+/// and, with `with_system`, system audio
+fn begin_note_recording(
+    app: &AppHandle,
+    state: &AudioState,
+    db: &Database,
+    note_id: &str,
+    mic_device_id: Option<String>,
+    with_system: bool,
+) -> Result<DualRecordingResult, String> {
+    if state.recording.get_phase() == RecordingPhase::Recording {
+        return Err("Already recording".to_string());
+    }
+
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -103,19 +206,68 @@ pub fn start_recording(
     let recordings_dir = app_data_dir.join("recordings");
     std::fs::create_dir_all(&recordings_dir).map_err(|e| e.to_string())?;
 
-    let filename = format!("{}.wav", note_id);
-    let output_path = recordings_dir.join(&filename);
+    // Reset state for new recording session
+    state.recording.reset_for_new_session();
+    {
+        let mut current_note = state
+            .recording
+            .current_note_id
+            .lock()
+            .map_err(|e| e.to_string())?;
+        *current_note = Some(note_id.to_string());
+    }
 
-    audio::start_recording(state.recording.clone(), output_path.clone(), device_id)
+    // 0 for a new note; a note recorded before continues after its last segment
+    let segment_index = db
+        .get_next_segment_index(note_id)
+        .map_err(|e| e.to_string())?;
+    let start_offset_ms = db
+        .get_total_segment_duration(note_id)
         .map_err(|e| e.to_string())?;
+    state
+        .recording
+        .current_segment_index
+        .store(segment_index as u32, Ordering::SeqCst);
+    state
+        .recording
+        .segment_start_offset_ms
+        .store(start_offset_ms, Ordering::SeqCst);
 
-    Ok(output_path.to_string_lossy().to_string())
-}
+    let mic_path = recordings_dir.join(format!("{}_mic_seg{}.wav", note_id, segment_index));
+    let system_path = recordings_dir.join(format!("{}_system_seg{}.wav", note_id, segment_index));
 
-#[tauri::command]
-pub fn stop_recording(state: State<AudioState>) -> Result<Option<String>, String> {
-    let path = audio::stop_recording(&state.recording).map_err(|e| e.to_string())?;
-    Ok(path.map(|p| p.to_string_lossy().to_string()))
+    let segment_id = db
+        .add_audio_segment(
+            note_id,
+            segment_index,
+            mic_path.to_string_lossy().as_ref(),
+            with_system
+                .then(|| system_path.to_string_lossy().to_string())
+                .as_deref(),
+            start_offset_ms,
+            mic_file_denoised(),
+        )
+        .map_err(|e| e.to_string())?;
+    state
+        .recording
+        .current_segment_db_id
+        .store(segment_id, Ordering::SeqCst);
+
+    audio::start_recording(state.recording.clone(), mic_path.clone(), mic_device_id)
+        .map_err(|e| e.to_string())?;
+
+    let system_started = with_system && start_system_capture(state, &system_path)?;
+    match_recording_to_event(app, db, note_id);
+
+    Ok(DualRecordingResult {
+        mic_path: mic_path.to_string_lossy().to_string(),
+        system_path: if system_started {
+            Some(system_path.to_string_lossy().to_string())
+        } else {
+            None
+        },
+        playback_path: None,
+    })
 }
 
 #[tauri::command]
@@ -261,67 +413,17 @@ pub fn request_microphone_permission() -> bool {
     has_microphone_available()
 }
 
-/// Start dual recording (mic + system audio)
+/// Start dual recording (mic + system audio) in a new segment of the note
 /// Returns paths to both recording files
 #[tauri::command]
 pub fn start_dual_recording(
     app: AppHandle,
     state: State<AudioState>,
+    db: State<Database>,
     note_id: String,
     mic_device_id: Option<String>,
 ) -> Result<DualRecordingResult, String> {
-    // Get app data directory for storing recordings
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-
-    let recordings_dir = app_data_dir.join("recordings");
-    std::fs::create_dir_all(&recordings_dir).map_err(|e| e.to_string())?;
-
-    // Mic recording path
-    let mic_filename = format!("{}_mic.wav", note_id);
-    let mic_path = recordings_dir.join(&mic_filename);
-
-    // System audio recording path
-    let system_filename = format!("{}_system.wav", note_id);
-    let system_path = recordings_dir.join(&system_filename);
-
-    // Start mic recording
-    audio::start_recording(state.recording.clone(), mic_path.clone(), mic_device_id)
-        .map_err(|e| e.to_string())?;
-
-    // Try to start system audio recording if available
-    let system_started = {
-        let capture = state.system_capture.lock().map_err(|e| e.to_string())?;
-
-        if let Some(cap) = capture.as_ref() {
-            match cap.start(system_path.clone()) {
-                Ok(()) => {
-                    // Store the system output path
-                    let mut sys_path = state.system_output_path.lock().map_err(|e| e.to_string())?;
-                    *sys_path = Some(system_path.clone());
-                    true
-                }
-                Err(e) => {
-                    eprintln!("Failed to start system audio capture: {}", e);
-                    false
-                }
-            }
-        } else {
-            false
-        }
-    };
-
-    Ok(DualRecordingResult {
-        mic_path: mic_path.to_string_lossy().to_string(),
-        system_path: if system_started {
-            Some(system_path.to_string_lossy().to_string())
-        } else {
-            None
-        },
-        playback_path: None, // Will be set when recording stops
-    })
+    begin_note_recording(&app, &state, &db, &note_id, mic_device_id, true)
 }
 
 /// Stop dual recording and merge files for playback
@@ -330,8 +432,14 @@ pub fn start_dual_recording(
 pub fn stop_dual_recording(
     app: AppHandle,
     state: State<AudioState>,
+    db: State<Database>,
     note_id: String,
+    meeting_app: Option<String>,
 ) -> Result<DualRecordingResult, String> {
+    // Capture the segment duration before the recorder state is reset
+    let segment_id = state.recording.current_segment_db_id.load(Ordering::SeqCst);
+    let segment_duration_ms = state.recording.get_segment_elapsed_ms();
+
     // Stop mic recording
     let mic_path = audio::stop_recording(&state.recording)
         .map_err(|e| e.to_string())?
@@ -354,30 +462,64 @@ pub fn stop_dual_recording(
         *sys_path = None;
     }
 
-    // Merge files if we have both
-    let playback_path = if let Some(ref sys_path) = system_path {
-        let app_data_dir = app
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-
-        let recordings_dir = app_data_dir.join("recordings");
-        let playback_filename = format!("{}.wav", note_id);
-        let playback_file = recordings_dir.join(&playback_filename);
+    // Record the final segment's duration so later offsets line up
+    if segment_id > 0 {
+        let _ = db.update_segment_duration(segment_id, segment_duration_ms);
+    }
 
-        // Merge the two files
-        match mix_wav_files(&mic_path, sys_path, &playback_file) {
-            Ok(()) => Some(playback_file.to_string_lossy().to_string()),
-            Err(e) => {
-                eprintln!("Failed to merge audio files: {}", e);
-                // Fall back to mic path as playback
-                None
-            }
+    // Encrypt every finished segment before the mixer reads them back
+    let finished = note_mix_tracks(&db, &note_id)
+        .into_iter()
+        .map(|t| t.path)
+        .chain(std::iter::once(mic_path.clone()))
+        .chain(system_path.clone());
+    for path in finished {
+        if let Err(e) = crate::crypto::seal_if_enabled(&path) {
+            eprintln!("Failed to encrypt {}: {}", path.display(), e);
         }
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let recordings_dir = app_data_dir.join("recordings");
+    let playback_file = recordings_dir.join(format!("{}.wav", note_id));
+    let options = load_mix_options(&db);
+
+    // Merge every recorded segment when the note has them, otherwise just this pair
+    let tracks = note_mix_tracks(&db, &note_id);
+    let merge_result = if !tracks.is_empty() {
+        Some(mix_tracks(&tracks, &playback_file, &options))
     } else {
-        None
+        system_path
+            .as_ref()
+            .map(|sys_path| mix_wav_files(&mic_path, sys_path, &playback_file, &options))
+    };
+
+    let playback_path = match merge_result {
+        Some(Ok(())) => Some(playback_file.to_string_lossy().to_string()),
+        Some(Err(e)) => {
+            eprintln!("Failed to merge audio files: {}", e);
+            // Fall back to mic path as playback
+            None
+        }
+        None => None,
     };
 
+    // Re-run the live transcript through a batch pass once the worker is free
+    crate::commands::jobs::queue_refinement(&app, &note_id);
+
+    // Then mix, transcribe, summarize and title it when the pipeline is enabled
+    if let Err(e) = crate::commands::pipeline::start_pipeline_after_recording(
+        &app,
+        &db,
+        &note_id,
+        meeting_app.as_deref(),
+    ) {
+        eprintln!("Failed to start the post-recording pipeline: {}", e);
+    }
+
     Ok(DualRecordingResult {
         mic_path: mic_path.to_string_lossy().to_string(),
         system_path: system_path.map(|p| p.to_string_lossy().to_string()),
@@ -385,6 +527,195 @@ pub fn stop_dual_recording(
     })
 }
 
+/// Load the playback mix options from settings, falling back to defaults
+fn load_mix_options(db: &Database) -> MixOptions {
+    db.get_setting(MIX_OPTIONS_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Build the mixer input for every recorded segment of a note
+fn note_mix_tracks(db: &Database, note_id: &str) -> Vec<MixTrack> {
+    let segments = db.get_audio_segments(note_id).unwrap_or_default();
+    let mut tracks = Vec::new();
+
+    // Audio removed by the retention policy leaves the segment rows behind
+    for segment in segments.into_iter().filter(|s| PathBuf::from(&s.mic_path).exists()) {
+        tracks.push(MixTrack {
+            path: PathBuf::from(&segment.mic_path),
+            source: AudioSource::Microphone,
+            offset_ms: segment.start_offset_ms,
+        });
+        if let Some(system_path) = segment.system_path {
+            tracks.push(MixTrack {
+                path: PathBuf::from(system_path),
+                source: AudioSource::SystemAudio,
+                offset_ms: segment.start_offset_ms,
+            });
+        }
+    }
+
+    tracks
+}
+
+/// Get the current playback mix options
+#[tauri::command]
+pub fn get_mix_options(db: State<Database>) -> MixOptions {
+    load_mix_options(&db)
+}
+
+/// Save the playback mix options
+#[tauri::command]
+pub fn set_mix_options(options: MixOptions, db: State<Database>) -> Result<(), String> {
+    let json = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+    db.set_setting(MIX_OPTIONS_KEY, &json).map_err(|e| e.to_string())
+}
+
+/// Re-render the playback file of a note from its recorded segments
+/// A stereo-split render is written next to the regular one as `<note_id>_split.wav`
+#[tauri::command]
+pub fn remix_note_playback(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+    mode: Option<MixMode>,
+) -> Result<String, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    let tracks = note_mix_tracks(&db, &note_id);
+    if tracks.is_empty() {
+        return Err("No recorded audio segments for this note".to_string());
+    }
+
+    let mut options = load_mix_options(&db);
+    if let Some(mode) = mode {
+        options.mode = mode;
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let filename = match options.mode {
+        MixMode::Balanced => format!("{}.wav", note_id),
+        MixMode::StereoSplit => format!("{}_split.wav", note_id),
+    };
+    let output = app_data_dir.join("recordings").join(filename);
+
+    mix_tracks(&tracks, &output, &options).map_err(|e| e.to_string())?;
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// One recorded session within a note's stitched playback timeline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybackSession {
+    pub segment_index: i32,
+    pub start_offset_ms: i64,
+    pub duration_ms: Option<i64>,
+}
+
+/// A note's continuous playback file and where each session sits on its timeline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotePlayback {
+    /// File path; load it through the `netnote-audio` protocol, which decrypts and serves byte ranges
+    pub path: String,
+    pub duration_ms: i64,
+    pub sessions: Vec<PlaybackSession>,
+}
+
+/// Return the note's stitched playback file, re-rendering it when missing or
+/// older than any of its segment files
+pub(crate) fn ensure_playback_file(app: &AppHandle, db: &Database, note_id: &str) -> Result<PathBuf, String> {
+    let tracks = note_mix_tracks(db, note_id);
+    if tracks.is_empty() {
+        // Notes recorded before segments existed only have a single file
+        return db
+            .get_note_audio_path(note_id)
+            .map_err(|e| e.to_string())?
+            .map(PathBuf::from)
+            .filter(|p| p.exists())
+            .ok_or_else(|| "No recorded audio for this note".to_string());
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let playback_file = app_data_dir.join("recordings").join(format!("{}.wav", note_id));
+
+    let modified = |p: &std::path::Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let stale = match modified(&playback_file) {
+        Some(rendered) => tracks
+            .iter()
+            .any(|t| modified(&t.path).is_some_and(|m| m > rendered)),
+        None => true,
+    };
+
+    if stale {
+        mix_tracks(&tracks, &playback_file, &load_mix_options(db)).map_err(|e| e.to_string())?;
+    }
+    Ok(playback_file)
+}
+
+/// Get one continuous playback file spanning every recorded session of a note
+#[tauri::command]
+pub async fn get_note_playback(app: AppHandle, note_id: String) -> Result<NotePlayback, String> {
+    // Mixing and decoding a long recording takes a while, keep it off the main thread
+    tokio::task::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        ensure_note_accessible(&app.state::<PrivacyState>(), &db, &note_id)?;
+        let path = ensure_playback_file(&app, &db, &note_id)?;
+
+        let reader = audio::open_wav(&path).map_err(|e| e.to_string())?;
+        let duration_ms =
+            reader.duration() as i64 * 1000 / reader.spec().sample_rate.max(1) as i64;
+
+        let sessions = db
+            .get_audio_segments(&note_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|s| PlaybackSession {
+                segment_index: s.segment_index,
+                start_offset_ms: s.start_offset_ms,
+                duration_ms: s.duration_ms,
+            })
+            .collect();
+
+        Ok(NotePlayback {
+            path: path.to_string_lossy().to_string(),
+            duration_ms,
+            sessions,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Get downsampled min/max peaks of a note's playback file, cached next to it
+#[tauri::command]
+pub async fn get_waveform_peaks(
+    app: AppHandle,
+    note_id: String,
+    peaks_per_second: Option<u32>,
+) -> Result<WaveformPeaks, String> {
+    tokio::task::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        ensure_note_accessible(&app.state::<PrivacyState>(), &db, &note_id)?;
+        let path = ensure_playback_file(&app, &db, &note_id)?;
+        let cache_path = path.with_extension("peaks.json");
+        let peaks_per_second = peaks_per_second.unwrap_or(waveform::DEFAULT_PEAKS_PER_SECOND);
+
+        waveform::load_or_compute_peaks(&path, &cache_path, peaks_per_second)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 /// Check if dual recording is currently active
 #[tauri::command]
 pub fn is_dual_recording(state: State<AudioState>) -> bool {
@@ -406,11 +737,55 @@ pub fn is_aec_enabled() -> bool {
     aec::is_aec_enabled()
 }
 
-/// Set AEC enabled state
+/// Set AEC enabled state (persisted across restarts)
 /// Disable AEC when using headphones for better performance
 #[tauri::command]
-pub fn set_aec_enabled(enabled: bool) {
+pub fn set_aec_enabled(enabled: bool, db: State<Database>) -> Result<(), String> {
     aec::set_aec_enabled(enabled);
+    db.set_setting(AEC_ENABLED_KEY, if enabled { "true" } else { "false" })
+        .map_err(|e| e.to_string())
+}
+
+/// Get the mic noise suppression options
+#[tauri::command]
+pub fn get_noise_suppression() -> NoiseSuppressionOptions {
+    denoise::noise_suppression_options()
+}
+
+/// Set the mic noise suppression options (persisted, applied from the next recording)
+#[tauri::command]
+pub fn set_noise_suppression(options: NoiseSuppressionOptions, db: State<Database>) -> Result<(), String> {
+    let json = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+    db.set_setting(NOISE_SUPPRESSION_KEY, &json).map_err(|e| e.to_string())?;
+    denoise::set_noise_suppression_options(options);
+    Ok(())
+}
+
+/// Whether a mic capture started now writes noise-suppressed audio to its file
+fn mic_file_denoised() -> bool {
+    denoise::noise_suppression_options().target.applies_to_file()
+}
+
+/// Start system audio capture into `system_path`, if available
+/// Returns whether capture started
+fn start_system_capture(state: &AudioState, system_path: &std::path::Path) -> Result<bool, String> {
+    let capture = state.system_capture.lock().map_err(|e| e.to_string())?;
+
+    if let Some(cap) = capture.as_ref() {
+        match cap.start(system_path.to_path_buf()) {
+            Ok(()) => {
+                let mut sys_path = state.system_output_path.lock().map_err(|e| e.to_string())?;
+                *sys_path = Some(system_path.to_path_buf());
+                Ok(true)
+            }
+            Err(e) => {
+                eprintln!("Failed to start system audio capture: {}", e);
+                Ok(false)
+            }
+        }
+    } else {
+        Ok(false)
+    }
 }
 
 // ========== Pause/Resume/Continue Recording Commands ==========
@@ -540,6 +915,7 @@ pub fn resume_dual_recording(
             mic_path.to_string_lossy().as_ref(),
             Some(system_path.to_string_lossy().as_ref()),
             start_offset_ms,
+            mic_file_denoised(),
         )
         .map_err(|e| e.to_string())?;
 
@@ -554,25 +930,7 @@ pub fn resume_dual_recording(
         .map_err(|e| e.to_string())?;
 
     // Try to start system audio recording
-    let system_started = {
-        let capture = state.system_capture.lock().map_err(|e| e.to_string())?;
-
-        if let Some(cap) = capture.as_ref() {
-            match cap.start(system_path.clone()) {
-                Ok(()) => {
-                    let mut sys_path = state.system_output_path.lock().map_err(|e| e.to_string())?;
-                    *sys_path = Some(system_path.clone());
-                    true
-                }
-                Err(e) => {
-                    eprintln!("Failed to start system audio capture: {}", e);
-                    false
-                }
-            }
-        } else {
-            false
-        }
-    };
+    let system_started = start_system_capture(&state, &system_path)?;
 
     Ok(DualRecordingResult {
         mic_path: mic_path.to_string_lossy().to_string(),
@@ -675,6 +1033,7 @@ pub fn continue_note_recording(
             mic_path.to_string_lossy().as_ref(),
             Some(system_path.to_string_lossy().as_ref()),
             start_offset_ms,
+            mic_file_denoised(),
         )
         .map_err(|e| e.to_string())?;
 
@@ -689,25 +1048,7 @@ pub fn continue_note_recording(
         .map_err(|e| e.to_string())?;
 
     // Try to start system audio recording
-    let system_started = {
-        let capture = state.system_capture.lock().map_err(|e| e.to_string())?;
-
-        if let Some(cap) = capture.as_ref() {
-            match cap.start(system_path.clone()) {
-                Ok(()) => {
-                    let mut sys_path = state.system_output_path.lock().map_err(|e| e.to_string())?;
-                    *sys_path = Some(system_path.clone());
-                    true
-                }
-                Err(e) => {
-                    eprintln!("Failed to start system audio capture: {}", e);
-                    false
-                }
-            }
-        } else {
-            false
-        }
-    };
+    let system_started = start_system_capture(&state, &system_path)?;
 
     Ok(DualRecordingResult {
         mic_path: mic_path.to_string_lossy().to_string(),
@@ -721,7 +1062,7 @@ pub fn continue_note_recording(
 }
 
 /// Start dual recording with segment tracking
-/// This is an enhanced version of start_dual_recording that tracks segments in the database
+/// Kept for older frontends; `start_dual_recording` tracks segments as well
 #[tauri::command]
 pub fn start_dual_recording_with_segments(
     app: AppHandle,
@@ -730,6 +1071,67 @@ pub fn start_dual_recording_with_segments(
     note_id: String,
     mic_device_id: Option<String>,
 ) -> Result<DualRecordingResult, String> {
+    start_dual_recording(app, state, db, note_id, mic_device_id)
+}
+
+// ========== Device Monitoring / Switching ==========
+
+/// What happened to the input device
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceChangeKind {
+    /// The device disconnected or its stream failed
+    Lost,
+    /// Recording continued on the default device in a new segment
+    FailedOver,
+    /// The user switched to another device
+    Switched,
+    /// No fallback device could be opened; the recording was paused
+    FailoverFailed,
+}
+
+/// Payload of [`DEVICE_CHANGE_EVENT`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceChangeEvent {
+    pub kind: DeviceChangeKind,
+    pub note_id: Option<String>,
+    /// Device involved (None = system default)
+    pub device_id: Option<String>,
+    /// Segment the recording continues in
+    pub segment_index: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Close the current segment and continue recording into a new one on `device_id`
+/// Returns the new segment's paths and index
+fn roll_segment(
+    app: &AppHandle,
+    state: &AudioState,
+    db: &Database,
+    note_id: &str,
+    device_id: Option<String>,
+) -> Result<(DualRecordingResult, i32), String> {
+    // Close out the current segment
+    let segment_id = state.recording.current_segment_db_id.load(Ordering::SeqCst);
+    if segment_id > 0 {
+        db.update_segment_duration(segment_id, state.recording.get_segment_elapsed_ms())
+            .map_err(|e| e.to_string())?;
+    }
+
+    // Restart system audio too, so the new segment gets an aligned mic/system pair
+    let with_system = state
+        .system_output_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if with_system {
+        let capture = state.system_capture.lock().map_err(|e| e.to_string())?;
+        if let Some(cap) = capture.as_ref() {
+            let _ = cap.stop();
+        }
+    }
+
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -738,81 +1140,186 @@ pub fn start_dual_recording_with_segments(
     let recordings_dir = app_data_dir.join("recordings");
     std::fs::create_dir_all(&recordings_dir).map_err(|e| e.to_string())?;
 
-    // Reset state for new recording session
-    state.recording.reset_for_new_session();
-
-    // Store note ID
-    {
-        let mut current_note = state
-            .recording
-            .current_note_id
-            .lock()
-            .map_err(|e| e.to_string())?;
-        *current_note = Some(note_id.clone());
-    }
-
-    // Get segment index (should be 0 for new recording)
     let segment_index = db
-        .get_next_segment_index(&note_id)
+        .get_next_segment_index(note_id)
+        .map_err(|e| e.to_string())?;
+    let start_offset_ms = db
+        .get_total_segment_duration(note_id)
         .map_err(|e| e.to_string())?;
 
-    // Mic recording path with segment index
-    let mic_filename = format!("{}_mic_seg{}.wav", note_id, segment_index);
-    let mic_path = recordings_dir.join(&mic_filename);
+    state
+        .recording
+        .current_segment_index
+        .store(segment_index as u32, Ordering::SeqCst);
+    state
+        .recording
+        .segment_start_offset_ms
+        .store(start_offset_ms, Ordering::SeqCst);
 
-    // System audio recording path with segment index
-    let system_filename = format!("{}_system_seg{}.wav", note_id, segment_index);
-    let system_path = recordings_dir.join(&system_filename);
+    let mic_path = recordings_dir.join(format!("{}_mic_seg{}.wav", note_id, segment_index));
+    let system_path = recordings_dir.join(format!("{}_system_seg{}.wav", note_id, segment_index));
 
-    // Add segment to database (start_offset_ms is 0 for first segment)
     let segment_id = db
         .add_audio_segment(
-            &note_id,
+            note_id,
             segment_index,
             mic_path.to_string_lossy().as_ref(),
-            Some(system_path.to_string_lossy().as_ref()),
-            0, // First segment starts at 0
+            with_system
+                .then(|| system_path.to_string_lossy().to_string())
+                .as_deref(),
+            start_offset_ms,
+            mic_file_denoised(),
         )
         .map_err(|e| e.to_string())?;
-
-    // Store segment ID for later duration update
     state
         .recording
         .current_segment_db_id
         .store(segment_id, Ordering::SeqCst);
 
-    // Start mic recording
-    audio::start_recording(state.recording.clone(), mic_path.clone(), mic_device_id)
+    audio::switch_input_device(state.recording.clone(), mic_path.clone(), device_id)
         .map_err(|e| e.to_string())?;
 
-    // Try to start system audio recording
-    let system_started = {
-        let capture = state.system_capture.lock().map_err(|e| e.to_string())?;
+    let system_started = with_system && start_system_capture(state, &system_path)?;
 
-        if let Some(cap) = capture.as_ref() {
-            match cap.start(system_path.clone()) {
-                Ok(()) => {
-                    let mut sys_path = state.system_output_path.lock().map_err(|e| e.to_string())?;
-                    *sys_path = Some(system_path.clone());
-                    true
+    Ok((
+        DualRecordingResult {
+            mic_path: mic_path.to_string_lossy().to_string(),
+            system_path: if system_started {
+                Some(system_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            playback_path: None,
+        },
+        segment_index,
+    ))
+}
+
+/// Pause mic + system capture after a failed failover so the user can pick a device and resume
+fn pause_after_device_loss(state: &AudioState, db: &Database) {
+    if let Ok(duration_ms) = audio::pause_recording(&state.recording) {
+        let segment_id = state.recording.current_segment_db_id.load(Ordering::SeqCst);
+        if segment_id > 0 {
+            let _ = db.update_segment_duration(segment_id, duration_ms);
+        }
+    }
+    if let Ok(Some(cap)) = state.system_capture.lock().as_deref() {
+        let _ = cap.stop();
+    }
+}
+
+/// Watch the recorder for lost input devices (unplugged headset, stream errors)
+/// and fail over to the default device in a new segment without ending the note
+pub fn start_device_monitor(app: &AppHandle) {
+    let app = app.clone();
+
+    std::thread::spawn(move || {
+        let mut last_failover: Option<Instant> = None;
+
+        loop {
+            std::thread::sleep(DEVICE_MONITOR_INTERVAL);
+
+            let state = app.state::<AudioState>();
+            if state.recording.get_phase() != RecordingPhase::Recording {
+                continue;
+            }
+            let Some(reason) = state.recording.take_device_lost() else {
+                continue;
+            };
+
+            let note_id = state
+                .recording
+                .current_note_id
+                .lock()
+                .ok()
+                .and_then(|n| n.clone());
+            eprintln!("Input device lost: {}", reason);
+            let _ = app.emit(
+                DEVICE_CHANGE_EVENT,
+                DeviceChangeEvent {
+                    kind: DeviceChangeKind::Lost,
+                    note_id: note_id.clone(),
+                    device_id: state.recording.current_device_id(),
+                    segment_index: None,
+                    error: Some(reason),
+                },
+            );
+
+            let db = app.state::<Database>();
+            let recently_failed_over = last_failover.is_some_and(|t| t.elapsed() < FAILOVER_GRACE);
+            let result = match &note_id {
+                Some(_) if recently_failed_over => {
+                    Err("The fallback device was lost as well".to_string())
+                }
+                Some(note_id) => roll_segment(&app, &state, &db, note_id, None),
+                None => Err("Recording has no segments to continue in".to_string()),
+            };
+
+            match result {
+                Ok((_, segment_index)) => {
+                    last_failover = Some(Instant::now());
+                    let _ = app.emit(
+                        DEVICE_CHANGE_EVENT,
+                        DeviceChangeEvent {
+                            kind: DeviceChangeKind::FailedOver,
+                            note_id,
+                            device_id: None,
+                            segment_index: Some(segment_index),
+                            error: None,
+                        },
+                    );
                 }
                 Err(e) => {
-                    eprintln!("Failed to start system audio capture: {}", e);
-                    false
+                    pause_after_device_loss(&state, &db);
+                    let _ = app.emit(
+                        DEVICE_CHANGE_EVENT,
+                        DeviceChangeEvent {
+                            kind: DeviceChangeKind::FailoverFailed,
+                            note_id,
+                            device_id: None,
+                            segment_index: None,
+                            error: Some(e),
+                        },
+                    );
                 }
             }
-        } else {
-            false
         }
-    };
+    });
+}
 
-    Ok(DualRecordingResult {
-        mic_path: mic_path.to_string_lossy().to_string(),
-        system_path: if system_started {
-            Some(system_path.to_string_lossy().to_string())
-        } else {
-            None
+/// Switch the microphone mid-meeting (None = system default)
+/// The current segment is closed and a new one is opened without ending the note
+#[tauri::command]
+pub fn switch_recording_device(
+    app: AppHandle,
+    state: State<AudioState>,
+    db: State<Database>,
+    device_id: Option<String>,
+) -> Result<DualRecordingResult, String> {
+    if state.recording.get_phase() != RecordingPhase::Recording {
+        return Err("Not recording".to_string());
+    }
+
+    let note_id = state
+        .recording
+        .current_note_id
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("Device switching requires a segmented recording")?;
+
+    let (result, segment_index) = roll_segment(&app, &state, &db, &note_id, device_id.clone())?;
+
+    let _ = app.emit(
+        DEVICE_CHANGE_EVENT,
+        DeviceChangeEvent {
+            kind: DeviceChangeKind::Switched,
+            note_id: Some(note_id),
+            device_id,
+            segment_index: Some(segment_index),
+            error: None,
         },
-        playback_path: None,
-    })
+    );
+
+    Ok(result)
 }
@@ -0,0 +1,199 @@
+//! Calendar import: `.ics` files (or a folder of them, such as a synced CalDAV
+//! calendar) provide titles, descriptions and attendees for notes
+
+use std::path::Path;
+
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::calendar;
+use crate::commands::privacy::{
+    ensure_app_unlocked, ensure_note_accessible, is_note_hidden, PrivacyState,
+};
+use crate::db::models::CalendarEvent;
+use crate::db::Database;
+
+/// Settings key holding the `.ics` file or folder that is synced
+const CALENDAR_SOURCE_KEY: &str = "calendar_source";
+
+/// Emitted with the note id when a starting recording was matched to a calendar event
+pub const NOTE_EVENT_MATCHED: &str = "note-event-matched";
+
+/// How often the synced calendar source is re-imported
+const SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Events are imported from this far back, so recent recordings can still be matched
+const IMPORT_PAST: Duration = Duration::days(1);
+/// ... up to this far ahead
+const IMPORT_AHEAD: Duration = Duration::days(60);
+
+/// Outcome of a calendar import
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarImport {
+    pub files: usize,
+    pub events: usize,
+    /// Files that could not be read, with the reason
+    pub errors: Vec<String>,
+}
+
+/// Import every `.ics` file at `path` (a file or a folder)
+pub(crate) fn import_path(db: &Database, path: &Path) -> Result<CalendarImport, String> {
+    let from = Utc::now() - IMPORT_PAST;
+    let to = Utc::now() + IMPORT_AHEAD;
+
+    let files = calendar::ics_files(path).map_err(|e| e.to_string())?;
+    let mut import = CalendarImport {
+        files: 0,
+        events: 0,
+        errors: Vec::new(),
+    };
+    for file in files {
+        match calendar::read_ics_file(&file, from, to) {
+            Ok(events) => {
+                import.events += db
+                    .import_calendar_events(&file.to_string_lossy(), &events, from)
+                    .map_err(|e| e.to_string())?;
+                import.files += 1;
+            }
+            Err(e) => import.errors.push(format!("{}: {}", file.display(), e)),
+        }
+    }
+    Ok(import)
+}
+
+/// Re-import the configured calendar source, if any
+pub fn sync_calendar_source(db: &Database) -> Result<Option<CalendarImport>, String> {
+    match db
+        .get_setting(CALENDAR_SOURCE_KEY)
+        .map_err(|e| e.to_string())?
+    {
+        Some(source) => import_path(db, Path::new(&source)).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Re-import the synced calendar source now and every [`SYNC_INTERVAL`], in the background
+pub fn start_calendar_sync(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let db = app.state::<Database>();
+        // A passphrase-locked library has no tables until it is unlocked
+        if !db.is_locked() {
+            match sync_calendar_source(&db) {
+                Ok(Some(import)) => {
+                    for error in &import.errors {
+                        eprintln!("Failed to import calendar file {}", error);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to sync calendar: {}", e),
+            }
+        }
+        std::thread::sleep(SYNC_INTERVAL);
+    });
+}
+
+/// Match a recording that just started to the calendar event running now and
+/// fill its note in from the event. Failures are only logged; recording goes on.
+pub(crate) fn match_recording_to_event(app: &AppHandle, db: &Database, note_id: &str) {
+    match db.match_note_to_event(note_id, Utc::now()) {
+        Ok(Some(_)) => {
+            let _ = app.emit(NOTE_EVENT_MATCHED, note_id);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to match recording to a calendar event: {}", e),
+    }
+}
+
+/// Import events from an `.ics` file or a folder of them once
+#[tauri::command]
+pub fn import_calendar_file(db: State<Database>, path: String) -> Result<CalendarImport, String> {
+    import_path(&db, Path::new(&path))
+}
+
+/// Get the `.ics` file or folder that is re-imported on startup and on sync
+#[tauri::command]
+pub fn get_calendar_source(db: State<Database>) -> Result<Option<String>, String> {
+    db.get_setting(CALENDAR_SOURCE_KEY)
+        .map_err(|e| e.to_string())
+}
+
+/// Set (and import right away) or clear the synced calendar source
+#[tauri::command]
+pub fn set_calendar_source(
+    db: State<Database>,
+    path: Option<String>,
+) -> Result<Option<CalendarImport>, String> {
+    match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => {
+            let import = import_path(&db, Path::new(&path))?;
+            db.set_setting(CALENDAR_SOURCE_KEY, &path)
+                .map_err(|e| e.to_string())?;
+            Ok(Some(import))
+        }
+        None => {
+            db.delete_setting(CALENDAR_SOURCE_KEY)
+                .map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+    }
+}
+
+/// Re-import the synced calendar source
+#[tauri::command]
+pub fn sync_calendar(db: State<Database>) -> Result<Option<CalendarImport>, String> {
+    sync_calendar_source(&db)
+}
+
+/// List events from the last hour up to `days_ahead` days (7 by default)
+/// Events recorded into a locked confidential note don't point at it.
+#[tauri::command]
+pub fn list_upcoming_events(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    days_ahead: Option<u32>,
+) -> Result<Vec<CalendarEvent>, String> {
+    ensure_app_unlocked(&privacy)?;
+    let now = Utc::now();
+    let mut events = db
+        .list_calendar_events(
+            now - Duration::hours(1),
+            now + Duration::days(days_ahead.unwrap_or(7) as i64),
+        )
+        .map_err(|e| e.to_string())?;
+    for event in &mut events {
+        if let Some(note_id) = &event.note_id {
+            let confidential = db.is_note_confidential(note_id).unwrap_or(true);
+            if is_note_hidden(&privacy, note_id, confidential) {
+                event.note_id = None;
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Get the event a note was recorded for
+#[tauri::command]
+pub fn get_note_event(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<Option<CalendarEvent>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.get_note_event(&note_id).map_err(|e| e.to_string())
+}
+
+/// Link a note to an event by hand, or unlink it (`event_id: None`)
+#[tauri::command]
+pub fn link_note_to_event(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+    event_id: Option<String>,
+) -> Result<(), String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.link_note_to_event(&note_id, event_id.as_deref())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,419 @@
+//! Encryption at rest: status, unlocking, the one-time library migration and
+//! the `netnote-audio` protocol that plays encrypted recordings
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, State};
+
+use crate::api_server::apply_api_server_settings;
+use crate::audio::RecordingPhase;
+use crate::commands::audio::{load_audio_settings, load_device_preferences, AudioState};
+use crate::commands::pipeline::fail_interrupted_pipelines;
+use crate::commands::privacy::{ensure_app_unlocked, ensure_note_accessible, PrivacyState};
+use crate::commands::storage::{
+    file_note_id, is_note_recording, list_recordings, recordings_dir, spawn_retention_pass,
+};
+use crate::crypto::stream::seal_in_place;
+use crate::crypto::{self, EncryptionConfig, KeySource, LibraryKey, LibraryReader};
+use crate::db::Database;
+use crate::meeting_detection::{load_meeting_rules, MeetingDetectionState};
+
+/// URI scheme serving library audio to the webview (`convertFileSrc(path, "netnote-audio")`)
+pub const AUDIO_PROTOCOL: &str = "netnote-audio";
+
+/// Largest byte range answered at once, so seeking never decrypts a whole file
+const MAX_RANGE_BYTES: u64 = 1024 * 1024;
+
+/// Whether the library is encrypted and unlocked
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    pub key_source: Option<KeySource>,
+    /// Waiting for the passphrase; every other command fails until unlocked
+    pub locked: bool,
+    /// Existing files are still being encrypted
+    pub migration_pending: bool,
+}
+
+fn data_dir(db: &Database) -> Result<PathBuf, String> {
+    db.data_dir()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Failed to get app data dir".to_string())
+}
+
+fn load_config(db: &Database) -> Result<Option<EncryptionConfig>, String> {
+    EncryptionConfig::load(&data_dir(db)?).map_err(|e| e.to_string())
+}
+
+/// Get whether the library is encrypted, how its key is kept and whether it is unlocked
+#[tauri::command]
+pub fn get_encryption_status(db: State<Database>) -> Result<EncryptionStatus, String> {
+    let config = load_config(&db)?;
+    Ok(EncryptionStatus {
+        enabled: config.is_some(),
+        key_source: config.as_ref().map(|c| c.key_source),
+        locked: db.is_locked(),
+        migration_pending: config.is_some_and(|c| c.migration_pending),
+    })
+}
+
+/// Unlock a passphrase-protected library
+#[tauri::command]
+pub fn unlock_library(
+    app: AppHandle,
+    db: State<Database>,
+    audio_state: State<AudioState>,
+    passphrase: String,
+) -> Result<(), String> {
+    if !db.is_locked() {
+        return Ok(());
+    }
+    let config = load_config(&db)?.ok_or("The library is not encrypted")?;
+    let key = config
+        .unlock(Some(&passphrase))
+        .map_err(|e| e.to_string())?;
+
+    db.unlock(&key).map_err(|e| e.to_string())?;
+    crypto::set_library_key(Some(key));
+
+    // Settings could not be read while the database was locked
+    load_audio_settings(&db);
+    load_device_preferences(&audio_state, &db);
+    load_meeting_rules(&app.state::<Arc<MeetingDetectionState>>(), &db);
+    fail_interrupted_pipelines(&db);
+    apply_api_server_settings(&app);
+    // The startup pass skipped the locked library
+    spawn_retention_pass(&app);
+    resume_library_migration(&app);
+    Ok(())
+}
+
+/// Encrypt the existing library: a new key goes into the OS keyring, or is
+/// wrapped with `passphrase` when one is given
+#[tauri::command]
+pub async fn enable_encryption(app: AppHandle, passphrase: Option<String>) -> Result<(), String> {
+    if app.state::<AudioState>().recording.get_phase() != RecordingPhase::Idle {
+        return Err("Stop the current recording before encrypting the library".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let dir = data_dir(&db)?;
+        if load_config(&db)?.is_some() {
+            return Err("The library is already encrypted".to_string());
+        }
+
+        let key = LibraryKey::generate();
+        let mut config = match passphrase.as_deref().filter(|p| !p.is_empty()) {
+            Some(passphrase) => EncryptionConfig::with_passphrase(&key, passphrase),
+            None => EncryptionConfig::with_keyring(&key),
+        }
+        .map_err(|e| e.to_string())?;
+
+        // Saved first so an interrupted migration can resume instead of losing the key
+        config.migration_pending = true;
+        config.save(&dir).map_err(|e| e.to_string())?;
+        crypto::set_library_key(Some(key.clone()));
+
+        migrate_library(&app, &db, &key)?;
+        config.migration_pending = false;
+        config.save(&dir).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// In the background, finish a migration that was interrupted, or encrypt
+/// recordings that a crash left in plaintext before they were sealed
+pub fn resume_library_migration(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let db = app.state::<Database>();
+        let Some(key) = crypto::library_key() else {
+            return;
+        };
+        let result = data_dir(&db).and_then(|dir| {
+            let mut config = EncryptionConfig::load(&dir)
+                .map_err(|e| e.to_string())?
+                .ok_or("The library is not encrypted")?;
+            if !config.migration_pending {
+                let sealed = seal_recordings(&app, &db, &key)?;
+                if sealed > 0 {
+                    println!("Encrypted {} recordings left in plaintext", sealed);
+                }
+                return Ok(());
+            }
+            migrate_library(&app, &db, &key)?;
+            config.migration_pending = false;
+            config.save(&dir).map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to finish encrypting the library: {}", e);
+        }
+    });
+}
+
+/// Encrypt the database and every recording that is still in plaintext
+fn migrate_library(app: &AppHandle, db: &Database, key: &LibraryKey) -> Result<(), String> {
+    db.encrypt_in_place(key).map_err(|e| e.to_string())?;
+    let sealed = seal_recordings(app, db, key)?;
+    println!("Encrypted the database and {} audio files", sealed);
+    Ok(())
+}
+
+/// Encrypt every recording that is still in plaintext, except those of the
+/// note being recorded, returning how many were sealed
+fn seal_recordings(app: &AppHandle, db: &Database, key: &LibraryKey) -> Result<usize, String> {
+    let mut files: Vec<PathBuf> = recordings_dir(app)
+        .map(|dir| list_recordings(&dir))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(path, _, _)| path)
+        .collect();
+    // Older notes may reference audio outside the recordings directory
+    let referenced = db
+        .get_all_audio_segment_paths()
+        .map_err(|e| e.to_string())?;
+    for path in referenced.into_iter().map(PathBuf::from) {
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+
+    let mut sealed = 0;
+    for path in files.iter().filter(|p| is_audio_file(p) && p.exists()) {
+        if is_being_recorded(app, path) {
+            continue;
+        }
+        match seal_in_place(key, path) {
+            Ok(true) => sealed += 1,
+            Ok(false) => {}
+            Err(e) => return Err(format!("Failed to encrypt {}: {}", path.display(), e)),
+        }
+    }
+    Ok(sealed)
+}
+
+/// Whether `path` belongs to the note being recorded, so it is still being written
+fn is_being_recorded(app: &AppHandle, path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(file_note_id)
+        .is_some_and(|note_id| is_note_recording(app, note_id))
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+}
+
+/// Serve a library audio file, decrypting the requested byte range
+pub fn serve_library_audio(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    match read_audio_range(app, request) {
+        Ok(response) => response,
+        Err((status, message)) => Response::builder()
+            .status(status)
+            .body(message.into_bytes())
+            .unwrap_or_default(),
+    }
+}
+
+fn read_audio_range(
+    app: &AppHandle,
+    request: &Request<Vec<u8>>,
+) -> Result<Response<Vec<u8>>, (StatusCode, String)> {
+    let not_found = |e: String| (StatusCode::NOT_FOUND, e);
+    let path = PathBuf::from(percent_decode(request.uri().path().trim_start_matches('/')));
+
+    // Only recordings are served, never the database or key config next to them
+    let recordings = recordings_dir(app).map_err(not_found)?;
+    let path = path.canonicalize().map_err(|e| not_found(e.to_string()))?;
+    if !is_servable(&recordings, &path) {
+        return Err((StatusCode::FORBIDDEN, "Not a recording".to_string()));
+    }
+
+    // Nothing plays while the app lock is engaged, nor audio of locked confidential notes
+    let forbidden = |e: String| (StatusCode::FORBIDDEN, e);
+    let privacy = app.state::<PrivacyState>();
+    ensure_app_unlocked(&privacy).map_err(forbidden)?;
+    if let Some(note_id) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(file_note_id)
+    {
+        ensure_note_accessible(&privacy, &app.state::<Database>(), note_id).map_err(forbidden)?;
+    }
+
+    let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    let mut reader = LibraryReader::open(&path).map_err(|e| internal(e.to_string()))?;
+    let len = reader.len().map_err(|e| internal(e.to_string()))?;
+
+    let (start, end, status) = match request.headers().get(header::RANGE) {
+        None if len == 0 => (0, 0, StatusCode::OK),
+        None if len <= MAX_RANGE_BYTES => (0, len - 1, StatusCode::OK),
+        // Answered like `bytes=0-`, so no response holds a whole decrypted file
+        None => (0, MAX_RANGE_BYTES - 1, StatusCode::PARTIAL_CONTENT),
+        Some(value) => match value.to_str().ok().and_then(|v| parse_range(v, len)) {
+            Some((start, end)) => (
+                start,
+                end.min(start + MAX_RANGE_BYTES - 1),
+                StatusCode::PARTIAL_CONTENT,
+            ),
+            None => {
+                return Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                    .body(Vec::new())
+                    .map_err(|e| internal(e.to_string()));
+            }
+        },
+    };
+
+    let mut body = vec![
+        0u8;
+        if len == 0 {
+            0
+        } else {
+            (end - start + 1) as usize
+        }
+    ];
+    reader
+        .seek(SeekFrom::Start(start))
+        .and_then(|_| reader.read_exact(&mut body))
+        .map_err(|e| internal(e.to_string()))?;
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "audio/wav")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, body.len());
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, len),
+        );
+    }
+    response.body(body).map_err(|e| internal(e.to_string()))
+}
+
+/// Whether a canonical `path` is a `.wav` file inside the recordings directory
+fn is_servable(recordings_dir: &Path, path: &Path) -> bool {
+    recordings_dir
+        .canonicalize()
+        .is_ok_and(|dir| path.starts_with(dir))
+        && is_audio_file(path)
+}
+
+/// Parse a single `bytes=start-end` range against a file of `len` bytes
+fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let (start, end) = value
+        .strip_prefix("bytes=")?
+        .split(',')
+        .next()?
+        .split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        // Suffix range: the last `end` bytes
+        ("", suffix) => (
+            len.saturating_sub(suffix.parse().ok()?),
+            len.checked_sub(1)?,
+        ),
+        (start, "") => (start.parse().ok()?, len.checked_sub(1)?),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<u64>().ok()?.min(len.checked_sub(1)?),
+        ),
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// Decode `%XX` escapes (a `+` stays literal, as in URI paths)
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_recordings_are_servable() {
+        let data_dir = std::env::temp_dir().join(format!("netnote-serve-{}", uuid::Uuid::new_v4()));
+        let recordings = data_dir.join("recordings");
+        std::fs::create_dir_all(&recordings).unwrap();
+        for name in [
+            "recordings/note.wav",
+            "recordings/note.peaks.json",
+            "netnote.db",
+        ] {
+            std::fs::write(data_dir.join(name), b"").unwrap();
+        }
+        let canonical = |name: &str| data_dir.join(name).canonicalize().unwrap();
+
+        assert!(is_servable(&recordings, &canonical("recordings/note.wav")));
+        assert!(!is_servable(
+            &recordings,
+            &canonical("recordings/note.peaks.json")
+        ));
+        assert!(!is_servable(&recordings, &canonical("netnote.db")));
+        // `..` is resolved before the check
+        assert!(!is_servable(
+            &recordings,
+            &canonical("recordings/../netnote.db")
+        ));
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 999)));
+        assert_eq!(parse_range("bytes= 10 - 20 ", 1000), Some((10, 20)));
+        // Only the first of several ranges is served
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), Some((0, 1)));
+        // An end past the file is clamped to its last byte
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some((900, 999)));
+    }
+
+    #[test]
+    fn test_parse_range_suffix() {
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some((0, 999)));
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+    }
+
+    #[test]
+    fn test_parse_range_rejects_unsatisfiable() {
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=20-10", 1000), None);
+        assert_eq!(parse_range("bytes=0-10", 0), None);
+        assert_eq!(parse_range("bytes=abc-", 1000), None);
+        assert_eq!(parse_range("bytes=10", 1000), None);
+        assert_eq!(parse_range("items=0-10", 1000), None);
+    }
+}
@@ -1,7 +1,11 @@
 use std::fs;
+use std::path::PathBuf;
 use tauri::{AppHandle, Manager, State};
 
-use crate::db::models::SummaryType;
+use crate::audio::{clip, ClipFormat};
+use crate::commands::audio::ensure_playback_file;
+use crate::commands::privacy::{ensure_note_accessible, PrivacyState};
+use crate::db::models::{SummaryType, TranscriptSegment};
 use crate::db::Database;
 
 #[derive(serde::Serialize)]
@@ -13,15 +17,22 @@ pub struct ExportData {
 #[tauri::command]
 pub fn export_note_markdown(
     db: State<Database>,
+    privacy: State<PrivacyState>,
     note_id: String,
 ) -> Result<ExportData, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    note_markdown(&db, &note_id)
+}
+
+/// Render a note with its summaries and active transcript as Markdown
+pub(crate) fn note_markdown(db: &Database, note_id: &str) -> Result<ExportData, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
     // Get note
     let note: (String, Option<String>, Option<String>, String, Option<String>) = conn
         .query_row(
             "SELECT title, description, participants, started_at, ended_at FROM notes WHERE id = ?1",
-            [&note_id],
+            [note_id],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
         )
         .map_err(|e| e.to_string())?;
@@ -31,13 +42,15 @@ pub fn export_note_markdown(
     // Get transcripts
     let mut stmt = conn
         .prepare(
-            "SELECT start_time, end_time, text FROM transcript_segments
-             WHERE note_id = ?1 ORDER BY start_time ASC",
+            "SELECT s.start_time, s.end_time, s.text FROM transcript_segments s
+             JOIN transcripts t ON t.id = s.transcript_id
+             WHERE s.note_id = ?1 AND t.is_active = 1
+             ORDER BY s.start_time ASC",
         )
         .map_err(|e| e.to_string())?;
 
     let transcripts: Vec<(f64, f64, String)> = stmt
-        .query_map([&note_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .query_map([note_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
@@ -51,7 +64,7 @@ pub fn export_note_markdown(
         .map_err(|e| e.to_string())?;
 
     let summaries: Vec<(String, String, String)> = stmt
-        .query_map([&note_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .query_map([note_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
@@ -102,12 +115,7 @@ pub fn export_note_markdown(
     md.push_str("*Generato da NetNote*\n");
 
     // Generate filename
-    let safe_title = title
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
-        .collect::<String>()
-        .replace(' ', "_");
-    let filename = format!("{}.md", safe_title);
+    let filename = format!("{}.md", safe_filename(&title));
 
     Ok(ExportData { markdown: md, filename })
 }
@@ -118,12 +126,7 @@ pub fn save_export_to_file(
     content: String,
     filename: String,
 ) -> Result<String, String> {
-    let documents_dir = app
-        .path()
-        .document_dir()
-        .map_err(|e| e.to_string())?;
-
-    let export_dir = documents_dir.join("NetNote");
+    let export_dir = export_directory(&app)?;
     fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
 
     let file_path = export_dir.join(&filename);
@@ -134,13 +137,132 @@ pub fn save_export_to_file(
 
 #[tauri::command]
 pub fn get_export_directory(app: AppHandle) -> Result<String, String> {
+    Ok(export_directory(&app)?.to_string_lossy().to_string())
+}
+
+/// Files written by [`extract_clip`]
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipExport {
+    pub audio_path: String,
+    pub srt_path: Option<String>,
+}
+
+/// Cut `start..end` (seconds on the note timeline) out of the note's mixed audio
+/// and save it to the export directory, optionally with the matching SRT
+#[tauri::command]
+pub async fn extract_clip(
+    app: AppHandle,
+    note_id: String,
+    start: f64,
+    end: f64,
+    format: ClipFormat,
+    include_srt: Option<bool>,
+) -> Result<ClipExport, String> {
+    if !(start >= 0.0 && end > start) {
+        return Err("Clip end must be after its start".to_string());
+    }
+
+    // Mixing the playback file and encoding FLAC/Opus can take seconds on a long note
+    tokio::task::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        ensure_note_accessible(&app.state::<PrivacyState>(), &db, &note_id)?;
+
+        let title: String = {
+            let conn = db.conn.lock().map_err(|e| e.to_string())?;
+            conn.query_row("SELECT title FROM notes WHERE id = ?1", [&note_id], |row| {
+                row.get(0)
+            })
+            .map_err(|e| e.to_string())?
+        };
+
+        // The stitched playback file spans every recorded segment of the note
+        let source = ensure_playback_file(&app, &db, &note_id)?;
+        let clip = clip::read_clip(&source, (start * 1000.0) as i64, (end * 1000.0) as i64)
+            .map_err(|e| e.to_string())?;
+        if clip.samples.is_empty() {
+            return Err("Clip range is outside the recording".to_string());
+        }
+
+        let export_dir = export_directory(&app)?;
+        fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
+        let base = format!(
+            "{}_{}-{}",
+            safe_filename(&title),
+            format_timestamp(start).replace(':', "."),
+            format_timestamp(end).replace(':', ".")
+        );
+
+        let audio_path = export_dir.join(format!("{}.{}", base, format.extension()));
+        clip::write_clip(&clip, format, &audio_path).map_err(|e| e.to_string())?;
+
+        let srt_path = if include_srt.unwrap_or(false) {
+            let segments = db
+                .get_transcript_segments(&note_id)
+                .map_err(|e| e.to_string())?;
+            let path = export_dir.join(format!("{}.srt", base));
+            fs::write(&path, clip_srt(&segments, start, end)).map_err(|e| e.to_string())?;
+            Some(path.to_string_lossy().to_string())
+        } else {
+            None
+        };
+
+        Ok(ClipExport {
+            audio_path: audio_path.to_string_lossy().to_string(),
+            srt_path,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// SRT cues for a whole transcript
+pub(crate) fn transcript_srt(segments: &[TranscriptSegment]) -> String {
+    clip_srt(segments, 0.0, f64::INFINITY)
+}
+
+/// SRT cues for the transcript segments overlapping `start..end`, relative to `start`
+fn clip_srt(segments: &[TranscriptSegment], start: f64, end: f64) -> String {
+    let mut srt = String::new();
+    let overlapping = segments
+        .iter()
+        .filter(|s| s.end_time > start && s.start_time < end && !s.text.trim().is_empty());
+
+    for (i, segment) in overlapping.enumerate() {
+        let cue_start = (segment.start_time - start).max(0.0);
+        let cue_end = (segment.end_time.min(end) - start).max(cue_start);
+        let text = match &segment.speaker {
+            Some(speaker) => format!("{}: {}", speaker, segment.text.trim()),
+            None => segment.text.trim().to_string(),
+        };
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_srt_timestamp(cue_start),
+            format_srt_timestamp(cue_end),
+            text
+        ));
+    }
+
+    srt
+}
+
+/// Documents/NetNote, where exports are written
+fn export_directory(app: &AppHandle) -> Result<PathBuf, String> {
     let documents_dir = app
         .path()
         .document_dir()
         .map_err(|e| e.to_string())?;
 
-    let export_dir = documents_dir.join("NetNote");
-    Ok(export_dir.to_string_lossy().to_string())
+    Ok(documents_dir.join("NetNote"))
+}
+
+fn safe_filename(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .collect::<String>()
+        .replace(' ', "_")
 }
 
 fn format_datetime(datetime_str: &str) -> String {
@@ -162,6 +284,17 @@ fn format_timestamp(seconds: f64) -> String {
     }
 }
 
+pub(crate) fn format_srt_timestamp(seconds: f64) -> String {
+    let total_ms = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        total_ms / 3_600_000,
+        (total_ms % 3_600_000) / 60_000,
+        (total_ms % 60_000) / 1000,
+        total_ms % 1000
+    )
+}
+
 fn calculate_duration(start: &str, end: &str) -> String {
     let start_dt = chrono::DateTime::parse_from_rfc3339(start);
     let end_dt = chrono::DateTime::parse_from_rfc3339(end);
@@ -186,3 +319,65 @@ fn calculate_duration(start: &str, end: &str) -> String {
         _ => "Unknown".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, text: &str, speaker: Option<&str>) -> TranscriptSegment {
+        TranscriptSegment {
+            id: 0,
+            note_id: "note".to_string(),
+            transcript_id: 1,
+            start_time: start,
+            end_time: end,
+            text: text.to_string(),
+            speaker: speaker.map(str::to_string),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_format_srt_timestamp() {
+        assert_eq!(format_srt_timestamp(0.0), "00:00:00,000");
+        assert_eq!(format_srt_timestamp(1.5), "00:00:01,500");
+        assert_eq!(format_srt_timestamp(61.0004), "00:01:01,000");
+        assert_eq!(format_srt_timestamp(3723.456), "01:02:03,456");
+    }
+
+    #[test]
+    fn test_clip_srt_keeps_overlapping_segments_relative_to_start() {
+        let segments = vec![
+            segment(0.0, 5.0, "Before the clip.", None),
+            segment(8.0, 12.0, " Starts early. ", Some("Me")),
+            segment(12.0, 15.0, "   ", None),
+            segment(15.0, 25.0, "Runs past the end.", Some("Others")),
+            segment(30.0, 32.0, "After the clip.", None),
+        ];
+
+        let srt = clip_srt(&segments, 10.0, 20.0);
+
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:02,000\nMe: Starts early.\n\n\
+             2\n00:00:05,000 --> 00:00:10,000\nOthers: Runs past the end.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_clip_srt_without_overlap_is_empty() {
+        let segments = vec![segment(0.0, 5.0, "Too early.", None)];
+        assert_eq!(clip_srt(&segments, 5.0, 10.0), "");
+    }
+
+    #[test]
+    fn test_transcript_srt_covers_everything() {
+        let segments = vec![
+            segment(0.0, 1.0, "One.", None),
+            segment(3600.0, 3601.0, "Two.", None),
+        ];
+        let srt = transcript_srt(&segments);
+        assert!(srt.starts_with("1\n00:00:00,000 --> 00:00:01,000\nOne.\n\n"));
+        assert!(srt.ends_with("2\n01:00:00,000 --> 01:00:01,000\nTwo.\n\n"));
+    }
+}
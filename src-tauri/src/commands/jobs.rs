@@ -0,0 +1,614 @@
+//! Background re-transcription queue
+//!
+//! Jobs live in the `transcription_jobs` table so the queue survives restarts.
+//! A single worker thread runs them one at a time, and only while nothing else
+//! is transcribing or recording.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::audio::RecordingPhase;
+use crate::commands::audio::AudioState;
+use crate::commands::privacy::{
+    ensure_app_unlocked, ensure_note_accessible, is_note_hidden, PrivacyState,
+};
+use crate::commands::transcription::{
+    collect_segments, note_audio_sessions, transcribe_mic, TranscriptionState,
+};
+use crate::db::models::{
+    JobMode, JobStatus, NewTranscriptSegment, TranscriptSource, TranscriptionJob,
+};
+use crate::db::Database;
+use crate::transcription::Transcriber;
+
+/// Event emitted whenever a job starts, advances or finishes
+pub const JOB_PROGRESS_EVENT: &str = "transcription-job-progress";
+
+/// Settings key persisting the paused state of the queue
+const QUEUE_PAUSED_KEY: &str = "transcription_queue_paused";
+
+/// Event emitted when post-meeting refinement has replaced a live transcript
+pub const TRANSCRIPT_REFINED_EVENT: &str = "transcript-refined";
+
+/// Settings key holding the post-meeting refinement options
+const REFINEMENT_OPTIONS_KEY: &str = "post_meeting_refinement";
+
+/// Settings key of the transcription language chosen in the UI
+const WHISPER_LANGUAGE_KEY: &str = "whisper_language";
+
+/// How long the worker sleeps when idle or blocked before checking again
+const WORKER_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runtime state of the job queue
+pub struct JobQueueState {
+    paused: AtomicBool,
+    wake: (Mutex<bool>, Condvar),
+}
+
+impl Default for JobQueueState {
+    fn default() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            wake: (Mutex::new(false), Condvar::new()),
+        }
+    }
+}
+
+impl JobQueueState {
+    /// Wake the worker (new jobs, resume)
+    fn notify(&self) {
+        let (lock, cvar) = &self.wake;
+        if let Ok(mut woken) = lock.lock() {
+            *woken = true;
+            cvar.notify_one();
+        }
+    }
+
+    /// Sleep until notified or the poll interval elapses
+    fn wait(&self) {
+        let (lock, cvar) = &self.wake;
+        let Ok(guard) = lock.lock() else {
+            return;
+        };
+        if let Ok((mut woken, _)) =
+            cvar.wait_timeout_while(guard, WORKER_POLL_INTERVAL, |woken| !*woken)
+        {
+            *woken = false;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+/// Progress of a job, emitted as `JOB_PROGRESS_EVENT`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgressEvent {
+    pub job_id: i64,
+    pub note_id: String,
+    pub status: JobStatus,
+    /// Audio files transcribed so far
+    pub completed_files: usize,
+    pub total_files: usize,
+    pub error: Option<String>,
+}
+
+/// Payload of `TRANSCRIPT_REFINED_EVENT`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptRefinedEvent {
+    pub note_id: String,
+    /// The new active transcript (the live one is kept as an inactive version)
+    pub transcript_id: i64,
+    pub model_id: String,
+}
+
+/// Automatic re-transcription of live transcripts after a recording stops
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RefinementOptions {
+    pub enabled: bool,
+    /// Model for the refinement pass (None = the loaded model)
+    pub model: Option<String>,
+}
+
+/// Overall queue status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobQueueStatus {
+    pub paused: bool,
+    pub pending: i64,
+    pub running: Option<TranscriptionJob>,
+}
+
+/// Get a transcriber for `model_id`, reusing the loaded model when it matches
+fn job_transcriber(
+    state: &TranscriptionState,
+    cache: &mut Option<(String, Arc<Transcriber>)>,
+    model_id: &str,
+) -> Result<Arc<Transcriber>, String> {
+    let loaded = state.current_model.lock().map_err(|e| e.to_string())?.clone();
+    if loaded.as_deref() == Some(model_id)
+        && let Some(transcriber) = state.transcriber.lock().map_err(|e| e.to_string())?.clone()
+    {
+        return Ok(transcriber);
+    }
+
+    if let Some((cached_id, transcriber)) = cache.as_ref()
+        && cached_id == model_id
+    {
+        return Ok(transcriber.clone());
+    }
+
+    let model_path = {
+        let manager = state.model_manager.lock().map_err(|e| e.to_string())?;
+        let manager = manager.as_ref().ok_or("Model manager not initialized")?;
+        manager
+            .model_path(model_id)
+            .ok_or_else(|| format!("Unknown model: {}", model_id))?
+    };
+    if !model_path.exists() {
+        return Err(format!("Model {} is not downloaded", model_id));
+    }
+
+    // Drop the previous model before loading the next one
+    *cache = None;
+    let transcriber = Arc::new(Transcriber::new(&model_path).map_err(|e| e.to_string())?);
+    *cache = Some((model_id.to_string(), transcriber.clone()));
+    Ok(transcriber)
+}
+
+fn is_cancelled(db: &Database, job_id: i64) -> bool {
+    matches!(
+        db.get_transcription_job(job_id),
+        Ok(Some(TranscriptionJob { status: JobStatus::Cancelled, .. }))
+    )
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    job: &TranscriptionJob,
+    status: JobStatus,
+    completed: usize,
+    total: usize,
+    error: Option<String>,
+) {
+    let _ = app.emit(
+        JOB_PROGRESS_EVENT,
+        JobProgressEvent {
+            job_id: job.id,
+            note_id: job.note_id.clone(),
+            status,
+            completed_files: completed,
+            total_files: total,
+            error,
+        },
+    );
+}
+
+/// Transcribe the job's sessions and store the result.
+/// Returns the id of the stored transcript, or None when the job was cancelled while running.
+fn run_job(
+    app: &AppHandle,
+    db: &Database,
+    job: &TranscriptionJob,
+    transcriber: &Transcriber,
+) -> Result<Option<i64>, String> {
+    let mut audio = note_audio_sessions(app, db, &job.note_id);
+    if audio.is_empty() {
+        return Err("No recorded audio found for this note".to_string());
+    }
+    if job.mode == JobMode::Refine {
+        audio.retain(|session| !session.refined);
+        if audio.is_empty() {
+            return Err("Every session of this note is already refined".to_string());
+        }
+    }
+
+    let total = audio
+        .iter()
+        .map(|a| 1 + a.system_path.is_some() as usize)
+        .sum();
+    let mut completed = 0;
+    let mut segments: Vec<NewTranscriptSegment> = Vec::new();
+    emit_progress(app, job, JobStatus::Running, completed, total, None);
+
+    for session in &audio {
+        if is_cancelled(db, job.id) {
+            return Ok(None);
+        }
+        let result = transcribe_mic(
+            transcriber,
+            &session.mic_path,
+            session.system_path.as_deref(),
+            session.denoised,
+            job.language.clone(),
+        )
+        .map_err(|e| e.to_string())?;
+        collect_segments(&mut segments, &job.note_id, result.segments, session.offset_secs, Some("You"));
+        completed += 1;
+        emit_progress(app, job, JobStatus::Running, completed, total, None);
+
+        if let Some(system_path) = &session.system_path {
+            if is_cancelled(db, job.id) {
+                return Ok(None);
+            }
+            let result = transcriber
+                .transcribe(system_path, job.language.clone())
+                .map_err(|e| e.to_string())?;
+            collect_segments(&mut segments, &job.note_id, result.segments, session.offset_secs, Some("Others"));
+            completed += 1;
+            emit_progress(app, job, JobStatus::Running, completed, total, None);
+        }
+    }
+
+    if is_cancelled(db, job.id) {
+        return Ok(None);
+    }
+
+    let previous = db.get_active_transcript_id(&job.note_id).map_err(|e| e.to_string())?;
+
+    // Refinement keeps the active segments outside the re-transcribed sessions
+    if job.mode == JobMode::Refine
+        && let Some(previous) = previous
+    {
+        let refined: Vec<(f64, Option<f64>)> = audio
+            .iter()
+            .map(|session| (session.offset_secs, session.end_secs))
+            .collect();
+        let kept = db
+            .get_segments_outside(previous, &refined)
+            .map_err(|e| e.to_string())?;
+        segments.extend(kept);
+    }
+
+    segments.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    let activate = job.mode != JobMode::KeepAlternate || previous.is_none();
+    let transcript_id = db
+        .save_transcript(
+            &job.note_id,
+            TranscriptSource::Batch,
+            Some(&job.model_id),
+            job.language.as_deref(),
+            &segments,
+            activate,
+        )
+        .map_err(|e| e.to_string())?;
+
+    match job.mode {
+        JobMode::Replace => {
+            if let Some(previous) = previous {
+                db.delete_transcript(previous).map_err(|e| e.to_string())?;
+            }
+        }
+        JobMode::Refine => {
+            let refined: Vec<i64> = audio.iter().filter_map(|s| s.segment_id).collect();
+            db.mark_segments_refined(&refined).map_err(|e| e.to_string())?;
+            let _ = app.emit(
+                TRANSCRIPT_REFINED_EVENT,
+                TranscriptRefinedEvent {
+                    note_id: job.note_id.clone(),
+                    transcript_id,
+                    model_id: job.model_id.clone(),
+                },
+            );
+        }
+        JobMode::KeepAlternate => {}
+    }
+
+    Ok(Some(transcript_id))
+}
+
+/// Queue post-meeting refinement for a note transcribed live. Sessions refined earlier
+/// are skipped. Does nothing when refinement is disabled or no model is available.
+pub fn queue_refinement(app: &AppHandle, note_id: &str) {
+    let db = app.state::<Database>();
+    let options = load_refinement_options(&db);
+    if !options.enabled {
+        return;
+    }
+
+    // Only notes with live output, and only when a session is still unrefined
+    let has_live = db
+        .get_transcripts(note_id)
+        .unwrap_or_default()
+        .iter()
+        .any(|t| t.source == TranscriptSource::Live);
+    let segments = db.get_audio_segments(note_id).unwrap_or_default();
+    let has_unrefined = segments.is_empty() || segments.iter().any(|s| !s.refined);
+    if !has_live || !has_unrefined || db.has_open_transcription_job(note_id).unwrap_or(true) {
+        return;
+    }
+
+    let state = app.state::<TranscriptionState>();
+    let loaded = state.current_model.lock().ok().and_then(|m| m.clone());
+    let Some(model_id) = options.model.or(loaded) else {
+        return;
+    };
+    let language = whisper_language(&db);
+
+    match db.add_transcription_job(note_id, &model_id, language.as_deref(), JobMode::Refine) {
+        Ok(_) => app.state::<JobQueueState>().notify(),
+        Err(e) => eprintln!("Failed to queue refinement for {}: {}", note_id, e),
+    }
+}
+
+/// Queue batch transcription of a note's unrefined sessions for the post-recording
+/// pipeline, or reuse the job already open for the note when it uses the same model
+/// (one with another model is cancelled). Returns the job id, or None when every
+/// session is transcribed already.
+pub(crate) fn queue_pipeline_transcription(
+    app: &AppHandle,
+    note_id: &str,
+    model: Option<&str>,
+) -> Result<Option<i64>, String> {
+    let db = app.state::<Database>();
+    if let Some(job) = db.get_open_transcription_job(note_id).map_err(|e| e.to_string())? {
+        if model.is_none_or(|m| m == job.model_id) {
+            return Ok(Some(job.id));
+        }
+        db.cancel_transcription_job(job.id).map_err(|e| e.to_string())?;
+    }
+
+    let segments = db.get_audio_segments(note_id).map_err(|e| e.to_string())?;
+    if !segments.is_empty() && segments.iter().all(|s| s.refined) {
+        return Ok(None);
+    }
+
+    let state = app.state::<TranscriptionState>();
+    let loaded = state.current_model.lock().ok().and_then(|m| m.clone());
+    let model_id = model
+        .map(str::to_string)
+        .or(load_refinement_options(&db).model)
+        .or(loaded)
+        .ok_or("No transcription model loaded")?;
+    let language = whisper_language(&db);
+
+    let job_id = db
+        .add_transcription_job(note_id, &model_id, language.as_deref(), JobMode::Refine)
+        .map_err(|e| e.to_string())?;
+    app.state::<JobQueueState>().notify();
+    Ok(Some(job_id))
+}
+
+/// The transcription language chosen in the UI (None = detect)
+fn whisper_language(db: &Database) -> Option<String> {
+    db.get_setting(WHISPER_LANGUAGE_KEY)
+        .ok()
+        .flatten()
+        .filter(|l| l != "auto" && !l.is_empty())
+}
+
+fn load_refinement_options(db: &Database) -> RefinementOptions {
+    db.get_setting(REFINEMENT_OPTIONS_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Start the worker thread that drains the queue.
+/// Jobs left running by a previous session are put back in the queue first.
+pub fn start_job_worker(app: &AppHandle) {
+    let app = app.clone();
+
+    {
+        let db = app.state::<Database>();
+        let queue = app.state::<JobQueueState>();
+        let _ = db.requeue_running_transcription_jobs();
+        let paused = db.get_setting(QUEUE_PAUSED_KEY).ok().flatten();
+        queue.paused.store(paused.as_deref() == Some("true"), Ordering::SeqCst);
+    }
+
+    std::thread::spawn(move || {
+        let mut cache: Option<(String, Arc<Transcriber>)> = None;
+
+        loop {
+            let queue = app.state::<JobQueueState>();
+            let db = app.state::<Database>();
+            let state = app.state::<TranscriptionState>();
+            let recording = app.state::<AudioState>().recording.get_phase() != RecordingPhase::Idle
+                || state.live_state.is_running.load(Ordering::SeqCst);
+
+            if queue.is_paused() || recording {
+                queue.wait();
+                continue;
+            }
+
+            let Ok(Some(job)) = db.get_next_pending_job() else {
+                // Nothing left to do, release the extra model
+                cache = None;
+                queue.wait();
+                continue;
+            };
+
+            // Share the single transcription slot with the interactive commands
+            if state.is_transcribing.swap(true, Ordering::SeqCst) {
+                queue.wait();
+                continue;
+            }
+
+            // A cancel may have landed since the job was read
+            if !db.claim_transcription_job(job.id).unwrap_or(false) {
+                state.is_transcribing.store(false, Ordering::SeqCst);
+                continue;
+            }
+            let result = job_transcriber(&state, &mut cache, &job.model_id)
+                .and_then(|transcriber| run_job(&app, &db, &job, &transcriber));
+            state.is_transcribing.store(false, Ordering::SeqCst);
+
+            match result {
+                Ok(Some(_)) => {
+                    let _ = db.set_transcription_job_status(job.id, JobStatus::Done, None);
+                    emit_progress(&app, &job, JobStatus::Done, 0, 0, None);
+                }
+                Ok(None) => emit_progress(&app, &job, JobStatus::Cancelled, 0, 0, None),
+                Err(e) => {
+                    eprintln!("Transcription job {} failed: {}", job.id, e);
+                    let _ = db.set_transcription_job_status(job.id, JobStatus::Failed, Some(&e));
+                    emit_progress(&app, &job, JobStatus::Failed, 0, 0, Some(e));
+                }
+            }
+        }
+    });
+}
+
+/// Queue re-transcription of the given notes (or every note with audio when None)
+/// Notes that already have a pending or running job are skipped.
+#[tauri::command]
+pub fn enqueue_retranscription(
+    note_ids: Option<Vec<String>>,
+    model: String,
+    language: Option<String>,
+    mode: JobMode,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    state: State<TranscriptionState>,
+    queue: State<JobQueueState>,
+) -> Result<Vec<TranscriptionJob>, String> {
+    ensure_app_unlocked(&privacy)?;
+    {
+        let manager = state.model_manager.lock().map_err(|e| e.to_string())?;
+        let manager = manager.as_ref().ok_or("Model manager not initialized")?;
+        if !manager.is_known(&model) {
+            return Err(format!("Unknown model: {}", model));
+        }
+    }
+
+    // Named notes must be accessible, while "every note" leaves out locked confidential ones
+    let note_ids = match note_ids {
+        Some(ids) => {
+            for note_id in &ids {
+                ensure_note_accessible(&privacy, &db, note_id)?;
+            }
+            ids
+        }
+        None => {
+            let mut ids = db.get_note_ids_with_audio().map_err(|e| e.to_string())?;
+            ids.retain(|id| {
+                let confidential = db.is_note_confidential(id).unwrap_or(true);
+                !is_note_hidden(&privacy, id, confidential)
+            });
+            ids
+        }
+    };
+
+    let mut jobs = Vec::new();
+    for note_id in note_ids {
+        if db.has_open_transcription_job(&note_id).map_err(|e| e.to_string())? {
+            continue;
+        }
+        let id = db
+            .add_transcription_job(&note_id, &model, language.as_deref(), mode)
+            .map_err(|e| e.to_string())?;
+        if let Some(job) = db.get_transcription_job(id).map_err(|e| e.to_string())? {
+            jobs.push(job);
+        }
+    }
+
+    queue.notify();
+    Ok(jobs)
+}
+
+/// List all jobs, oldest first, leaving out those of locked confidential notes
+#[tauri::command]
+pub fn list_transcription_jobs(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<Vec<TranscriptionJob>, String> {
+    ensure_app_unlocked(&privacy)?;
+    let mut jobs = db.get_transcription_jobs().map_err(|e| e.to_string())?;
+    jobs.retain(|job| {
+        let confidential = db.is_note_confidential(&job.note_id).unwrap_or(true);
+        !is_note_hidden(&privacy, &job.note_id, confidential)
+    });
+    Ok(jobs)
+}
+
+/// Get the queue status
+#[tauri::command]
+pub fn get_transcription_queue_status(
+    db: State<Database>,
+    queue: State<JobQueueState>,
+) -> Result<JobQueueStatus, String> {
+    let running = db
+        .get_transcription_jobs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|job| job.status == JobStatus::Running);
+
+    Ok(JobQueueStatus {
+        paused: queue.is_paused(),
+        pending: db.count_pending_transcription_jobs().map_err(|e| e.to_string())?,
+        running,
+    })
+}
+
+/// Pause the queue. A running job finishes first.
+#[tauri::command]
+pub fn pause_transcription_queue(db: State<Database>, queue: State<JobQueueState>) -> Result<(), String> {
+    queue.paused.store(true, Ordering::SeqCst);
+    db.set_setting(QUEUE_PAUSED_KEY, "true").map_err(|e| e.to_string())
+}
+
+/// Resume the queue
+#[tauri::command]
+pub fn resume_transcription_queue(db: State<Database>, queue: State<JobQueueState>) -> Result<(), String> {
+    queue.paused.store(false, Ordering::SeqCst);
+    queue.notify();
+    db.set_setting(QUEUE_PAUSED_KEY, "false").map_err(|e| e.to_string())
+}
+
+/// Cancel a pending or running job. A running job stops after its current file.
+#[tauri::command]
+pub fn cancel_transcription_job(
+    job_id: i64,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<bool, String> {
+    ensure_app_unlocked(&privacy)?;
+    if let Some(job) = db.get_transcription_job(job_id).map_err(|e| e.to_string())? {
+        ensure_note_accessible(&privacy, &db, &job.note_id)?;
+    }
+    db.cancel_transcription_job(job_id).map_err(|e| e.to_string())
+}
+
+/// Remove finished, failed and cancelled jobs from the list
+#[tauri::command]
+pub fn clear_finished_transcription_jobs(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<usize, String> {
+    ensure_app_unlocked(&privacy)?;
+    db.clear_finished_transcription_jobs().map_err(|e| e.to_string())
+}
+
+/// Get the post-meeting refinement options
+#[tauri::command]
+pub fn get_refinement_options(db: State<Database>) -> RefinementOptions {
+    load_refinement_options(&db)
+}
+
+/// Save the post-meeting refinement options
+#[tauri::command]
+pub fn set_refinement_options(
+    options: RefinementOptions,
+    db: State<Database>,
+    state: State<TranscriptionState>,
+) -> Result<(), String> {
+    if let Some(model) = &options.model {
+        let manager = state.model_manager.lock().map_err(|e| e.to_string())?;
+        let manager = manager.as_ref().ok_or("Model manager not initialized")?;
+        if !manager.is_known(model) {
+            return Err(format!("Unknown model: {}", model));
+        }
+    }
+    let json = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+    db.set_setting(REFINEMENT_OPTIONS_KEY, &json).map_err(|e| e.to_string())
+}
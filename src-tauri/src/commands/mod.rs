@@ -1,13 +1,29 @@
 pub mod ai;
 pub mod audio;
+pub mod calendar;
+pub mod encryption;
 pub mod export;
+pub mod jobs;
 pub mod notes;
+pub mod organize;
+pub mod people;
+pub mod pipeline;
+pub mod privacy;
 pub mod settings;
+pub mod storage;
 pub mod transcription;
 
 pub use ai::*;
 pub use audio::*;
+pub use calendar::*;
+pub use encryption::*;
 pub use export::*;
+pub use jobs::*;
 pub use notes::*;
+pub use organize::*;
+pub use people::*;
+pub use pipeline::*;
+pub use privacy::*;
 pub use settings::*;
+pub use storage::*;
 pub use transcription::*;
@@ -1,112 +1,250 @@
 use chrono::Utc;
-use tauri::State;
-use uuid::Uuid;
-
-use crate::db::models::{AudioSegment, NewNote, Note, UpdateNote};
+use tauri::{AppHandle, State};
+
+use crate::commands::privacy::{
+    ensure_app_unlocked, ensure_note_accessible, is_note_hidden, PrivacyState,
+};
+use crate::commands::storage::{note_audio_files, remove_files};
+use crate::db::models::{
+    AudioSegment, NewNote, Note, NoteFilter, NoteListItem, NotePage, NoteQuery, NoteSort,
+    UpdateNote,
+};
+use crate::db::notes::{row_to_note, NOTE_COLUMNS};
+use crate::db::people::link_participants;
 use crate::db::Database;
 
 #[tauri::command]
 pub fn create_note(db: State<Database>, input: NewNote) -> Result<Note, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    let now = Utc::now();
-    let id = Uuid::new_v4().to_string();
-
-    conn.execute(
-        "INSERT INTO notes (id, title, description, participants, started_at, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        (
-            &id,
-            &input.title,
-            &input.description,
-            &input.participants,
-            now.to_rfc3339(),
-            now.to_rfc3339(),
-            now.to_rfc3339(),
-        ),
-    )
-    .map_err(|e| e.to_string())?;
+    db.insert_note(input).map_err(|e| e.to_string())
+}
 
-    Ok(Note {
-        id,
-        title: input.title,
-        description: input.description,
-        participants: input.participants,
-        started_at: now,
-        ended_at: None,
-        audio_path: None,
-        created_at: now,
-        updated_at: now,
-    })
+#[tauri::command]
+pub fn get_note(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    id: String,
+) -> Result<Option<Note>, String> {
+    ensure_note_accessible(&privacy, &db, &id)?;
+    db.find_note(&id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_note(db: State<Database>, id: String) -> Result<Option<Note>, String> {
+pub fn list_notes(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    filter: Option<NoteFilter>,
+) -> Result<Vec<Note>, String> {
+    ensure_app_unlocked(&privacy)?;
+    filtered_notes(&db, &filter.unwrap_or_default())
+}
+
+/// Notes matching `filter`, newest first, confidential ones included
+pub(crate) fn filtered_notes(db: &Database, filter: &NoteFilter) -> Result<Vec<Note>, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
-    let result = conn.query_row(
-        "SELECT id, title, description, participants, started_at, ended_at, audio_path, created_at, updated_at
-         FROM notes WHERE id = ?1",
-        [&id],
-        |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                participants: row.get(3)?,
-                started_at: parse_datetime(row.get::<_, String>(4)?),
-                ended_at: row.get::<_, Option<String>>(5)?.map(parse_datetime),
-                audio_path: row.get(6)?,
-                created_at: parse_datetime(row.get::<_, String>(7)?),
-                updated_at: parse_datetime(row.get::<_, String>(8)?),
-            })
-        },
-    );
+    let (conditions, params) = filter_conditions(filter);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM notes {} ORDER BY started_at DESC",
+            NOTE_COLUMNS,
+            where_clause(&conditions)
+        ))
+        .map_err(|e| e.to_string())?;
 
-    match result {
-        Ok(note) => Ok(Some(note)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e.to_string()),
-    }
+    let notes = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), row_to_note)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(notes)
 }
 
+/// One page of notes with duration, segment and summary counts, sorted and
+/// filtered in SQL. Pages are chained through `next_cursor`.
 #[tauri::command]
-pub fn list_notes(db: State<Database>) -> Result<Vec<Note>, String> {
+pub fn query_notes(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    query: NoteQuery,
+) -> Result<NotePage, String> {
+    ensure_app_unlocked(&privacy)?;
+    note_page(&db, &query, &privacy.unlocked_note_ids())
+}
+
+/// One page of `query`. A search leaves out confidential notes other than
+/// `unlocked`, in SQL so that pages stay full and `total` doesn't count them.
+pub(crate) fn note_page(
+    db: &Database,
+    query: &NoteQuery,
+    unlocked: &[String],
+) -> Result<NotePage, String> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500) as i64;
+
+    let hidden_condition = if unlocked.is_empty() {
+        "confidential = 0".to_string()
+    } else {
+        format!(
+            "(confidential = 0 OR id IN ({}))",
+            vec!["?"; unlocked.len()].join(", ")
+        )
+    };
+    let (mut conditions, mut params) = filter_conditions(&query.filter);
+    let search = query
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty());
+    if let Some(search) = search {
+        let (fts_query, like_pattern) = search_patterns(search);
+        conditions.push(SEARCH_CONDITION);
+        params.push(Box::new(fts_query.clone()));
+        params.push(Box::new(fts_query.clone()));
+        params.push(Box::new(fts_query));
+        params.push(Box::new(like_pattern));
+        // Like search_notes, a search never reveals locked confidential notes
+        conditions.push(&hidden_condition);
+        for id in unlocked {
+            params.push(Box::new(id.clone()));
+        }
+    }
+    if let Some(has_recording) = query.has_recording {
+        conditions.push(if has_recording {
+            "(audio_path IS NOT NULL OR id IN (SELECT note_id FROM audio_segments))"
+        } else {
+            "(audio_path IS NULL AND id NOT IN (SELECT note_id FROM audio_segments))"
+        });
+    }
+    if let Some(has_summary) = query.has_summary {
+        conditions.push(if has_summary {
+            "id IN (SELECT note_id FROM summaries)"
+        } else {
+            "id NOT IN (SELECT note_id FROM summaries)"
+        });
+    }
+    if let Some(ended) = query.ended {
+        conditions.push(if ended {
+            "ended_at IS NOT NULL"
+        } else {
+            "ended_at IS NULL"
+        });
+    }
+
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let filtered = where_clause(&conditions);
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, title, description, participants, started_at, ended_at, audio_path, created_at, updated_at
-             FROM notes ORDER BY started_at DESC",
+    let total: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM notes {}", filtered),
+            rusqlite::params_from_iter(params.iter()),
+            |row| row.get(0),
         )
         .map_err(|e| e.to_string())?;
 
-    let notes = stmt
-        .query_map([], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                participants: row.get(3)?,
-                started_at: parse_datetime(row.get::<_, String>(4)?),
-                ended_at: row.get::<_, Option<String>>(5)?.map(parse_datetime),
-                audio_path: row.get(6)?,
-                created_at: parse_datetime(row.get::<_, String>(7)?),
-                updated_at: parse_datetime(row.get::<_, String>(8)?),
-            })
+    let (sort_key, sort_column) = match query.sort {
+        NoteSort::StartedAt => ("started_at", 4),
+        NoteSort::UpdatedAt => ("updated_at", 8),
+        NoteSort::Duration => ("duration_ms", 11),
+        NoteSort::Title => ("title COLLATE NOCASE", 1),
+    };
+    let (direction, comparison) = if query.ascending {
+        ("ASC", ">")
+    } else {
+        ("DESC", "<")
+    };
+
+    // Keyset pagination on (sort key, id)
+    let mut page_condition = String::new();
+    if let Some(cursor) = query.cursor.as_deref() {
+        let (value, id) = cursor.rsplit_once('|').ok_or("Invalid cursor")?;
+        page_condition = format!(
+            "WHERE {key} {cmp} ? OR ({key} = ? AND id {cmp} ?)",
+            key = sort_key,
+            cmp = comparison
+        );
+        for _ in 0..2 {
+            if query.sort == NoteSort::Duration {
+                params.push(Box::new(
+                    value.parse::<i64>().map_err(|_| "Invalid cursor")?,
+                ));
+            } else {
+                params.push(Box::new(value.to_string()));
+            }
+        }
+        params.push(Box::new(id.to_string()));
+    }
+    // One extra row tells whether another page follows
+    params.push(Box::new(limit + 1));
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "WITH listing AS (
+                SELECT {columns},
+                       COALESCE(
+                           (SELECT SUM(duration_ms) FROM audio_segments WHERE note_id = notes.id),
+                           CAST(ROUND((julianday(ended_at) - julianday(started_at)) * 86400000) AS INTEGER),
+                           0
+                       ) AS duration_ms,
+                       (SELECT COUNT(*) FROM audio_segments WHERE note_id = notes.id) AS segment_count,
+                       (SELECT COUNT(*) FROM summaries WHERE note_id = notes.id) AS summary_count
+                FROM notes {filtered}
+             )
+             SELECT * FROM listing {page}
+             ORDER BY {key} {dir}, id {dir}
+             LIMIT ?",
+            columns = NOTE_COLUMNS,
+            filtered = filtered,
+            page = page_condition,
+            key = sort_key,
+            dir = direction
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            let cursor_value = match query.sort {
+                NoteSort::Duration => row.get::<_, i64>(sort_column)?.to_string(),
+                _ => row.get::<_, String>(sort_column)?,
+            };
+            Ok((
+                NoteListItem {
+                    note: row_to_note(row)?,
+                    duration_ms: row.get(11)?,
+                    segment_count: row.get(12)?,
+                    summary_count: row.get(13)?,
+                },
+                cursor_value,
+            ))
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(notes)
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last()
+            .map(|(item, value)| format!("{}|{}", value, item.note.id))
+    } else {
+        None
+    };
+
+    let items = rows.into_iter().map(|(item, _)| item).collect();
+
+    Ok(NotePage {
+        items,
+        next_cursor,
+        total,
+    })
 }
 
 #[tauri::command]
 pub fn update_note(
     db: State<Database>,
+    privacy: State<PrivacyState>,
     id: String,
     update: UpdateNote,
 ) -> Result<Note, String> {
+    ensure_note_accessible(&privacy, &db, &id)?;
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
     let now = Utc::now();
 
@@ -164,49 +302,50 @@ pub fn update_note(
         (None, None, None) => conn.execute(&sql, rusqlite::params![now.to_rfc3339(), id]),
     }
     .map_err(|e| e.to_string())?;
+    if let Some(participants) = &update.participants {
+        link_participants(&conn, &id, participants).map_err(|e| e.to_string())?;
+    }
 
     // Return updated note
     drop(conn);
-    get_note(db, id)?.ok_or_else(|| "Note not found".to_string())
+    db.find_note(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Note not found".to_string())
 }
 
 #[tauri::command]
-pub fn search_notes(db: State<Database>, query: String) -> Result<Vec<Note>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+pub fn search_notes(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    query: String,
+) -> Result<Vec<Note>, String> {
+    ensure_app_unlocked(&privacy)?;
+    let notes = matching_notes(&db, &query)?
+        .into_iter()
+        // Confidential notes only show up once unlocked
+        .filter(|note| !is_note_hidden(&privacy, &note.id, note.confidential))
+        .collect();
 
-    // Use FTS5 search with fallback to LIKE for simple queries
-    let search_query = if query.contains('*') || query.contains('"') {
-        query.clone()
-    } else {
-        format!("{}*", query) // Prefix search by default
-    };
+    Ok(notes)
+}
 
+/// The 50 newest notes matching a full-text query, confidential ones included
+pub(crate) fn matching_notes(db: &Database, query: &str) -> Result<Vec<Note>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let (fts_query, like_pattern) = search_patterns(query);
     let mut stmt = conn
-        .prepare(
-            "SELECT m.id, m.title, m.description, m.participants, m.started_at, m.ended_at,
-                    m.audio_path, m.created_at, m.updated_at
-             FROM notes m
-             JOIN notes_fts fts ON m.rowid = fts.rowid
-             WHERE notes_fts MATCH ?1
-             ORDER BY m.started_at DESC
-             LIMIT 50",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM notes WHERE {} ORDER BY started_at DESC LIMIT 50",
+            NOTE_COLUMNS, SEARCH_CONDITION
+        ))
         .map_err(|e| e.to_string())?;
 
     let notes = stmt
-        .query_map([&search_query], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                participants: row.get(3)?,
-                started_at: parse_datetime(row.get::<_, String>(4)?),
-                ended_at: row.get::<_, Option<String>>(5)?.map(parse_datetime),
-                audio_path: row.get(6)?,
-                created_at: parse_datetime(row.get::<_, String>(7)?),
-                updated_at: parse_datetime(row.get::<_, String>(8)?),
-            })
-        })
+        .query_map(
+            [&fts_query, &fts_query, &fts_query, &like_pattern],
+            row_to_note,
+        )
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
@@ -233,38 +372,123 @@ pub fn end_note(
 }
 
 #[tauri::command]
-pub fn delete_note(db: State<Database>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+pub fn delete_note(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    id: String,
+) -> Result<(), String> {
+    ensure_note_accessible(&privacy, &db, &id)?;
+    // Collect every audio file (segments, playback renders, caches) before the rows are gone
+    let audio_files = note_audio_files(&app, &db, &id);
 
-    // First, get the audio path before deleting
-    let audio_path: Option<String> = conn
-        .query_row(
-            "SELECT audio_path FROM notes WHERE id = ?1",
-            [&id],
-            |row| row.get(0),
-        )
-        .ok();
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
     // Delete the note record
     conn.execute("DELETE FROM notes WHERE id = ?1", [&id])
         .map_err(|e| e.to_string())?;
+    drop(conn);
 
-    // Delete the audio file if it exists
-    if let Some(path) = audio_path {
-        if !path.is_empty() {
-            if let Err(e) = std::fs::remove_file(&path) {
-                eprintln!("Failed to delete audio file {}: {}", path, e);
-            }
+    remove_files(&audio_files);
+
+    Ok(())
+}
+
+/// Matches notes by title/description/participants (FTS), tag or folder name
+/// (FTS) or the text of their active transcript version. Binds the FTS query
+/// three times, then the LIKE pattern.
+const SEARCH_CONDITION: &str = "(rowid IN (SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?)
+    OR id IN (
+        SELECT nt.note_id FROM note_tags nt
+        WHERE nt.tag_id IN (SELECT rowid FROM tags_fts WHERE tags_fts MATCH ?)
+    )
+    OR folder_id IN (SELECT rowid FROM folders_fts WHERE folders_fts MATCH ?)
+    OR id IN (
+        SELECT s.note_id FROM transcript_segments s
+        JOIN transcripts t ON t.id = s.transcript_id
+        WHERE t.is_active = 1 AND s.text LIKE ? ESCAPE '\\'
+    ))";
+
+/// SQL conditions (joined with AND) and their parameters for a `NoteFilter`
+fn filter_conditions(filter: &NoteFilter) -> (Vec<&'static str>, Vec<Box<dyn rusqlite::ToSql>>) {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    if let Some(tag_id) = filter.tag_id {
+        conditions.push("id IN (SELECT note_id FROM note_tags WHERE tag_id = ?)");
+        params.push(Box::new(tag_id));
+    }
+    if let Some(folder_id) = filter.folder_id {
+        conditions.push(if filter.include_subfolders {
+            "folder_id IN (
+                WITH RECURSIVE subtree(id) AS (
+                    SELECT ?
+                    UNION ALL
+                    SELECT f.id FROM folders f JOIN subtree ON f.parent_id = subtree.id
+                )
+                SELECT id FROM subtree
+            )"
+        } else {
+            "folder_id = ?"
+        });
+        params.push(Box::new(folder_id));
+    }
+    if let Some(after) = filter.started_after {
+        conditions.push("started_at >= ?");
+        params.push(Box::new(after.to_rfc3339()));
+    }
+    if let Some(before) = filter.started_before {
+        conditions.push("started_at < ?");
+        params.push(Box::new(before.to_rfc3339()));
+    }
+    if let Some(participant) = filter
+        .participant
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        conditions.push("participants LIKE ? ESCAPE '\\'");
+        params.push(Box::new(format!("%{}%", escape_like(participant.trim()))));
+    }
+    if let Some(person_id) = filter.person_id {
+        conditions.push(
+            "(id IN (SELECT note_id FROM note_participants WHERE person_id = ?)
+              OR id IN (SELECT note_id FROM speaker_links WHERE person_id = ?)
+              OR id IN (SELECT note_id FROM summaries WHERE owner_id = ?))",
+        );
+        for _ in 0..3 {
+            params.push(Box::new(person_id));
         }
     }
+    (conditions, params)
+}
+
+fn where_clause(conditions: &[&str]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+}
 
-    Ok(())
+/// FTS query (prefix search unless the query has its own operators) and the
+/// LIKE pattern for transcript text
+fn search_patterns(query: &str) -> (String, String) {
+    let fts_query = if query.contains('*') || query.contains('"') {
+        query.to_string()
+    } else {
+        format!("{}*", query) // Prefix search by default
+    };
+    let like_pattern = format!(
+        "%{}%",
+        escape_like(query.trim_matches(|c| c == '*' || c == '"'))
+    );
+    (fts_query, like_pattern)
 }
 
-fn parse_datetime(s: String) -> chrono::DateTime<Utc> {
-    chrono::DateTime::parse_from_rfc3339(&s)
-        .map(|dt| dt.with_timezone(&Utc))
-        .unwrap_or_else(|_| Utc::now())
+/// Escape `\`, `%` and `_` for a `LIKE ... ESCAPE '\'` pattern
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
 // ========== Pause/Resume/Continue Recording Support ==========
@@ -272,7 +496,12 @@ fn parse_datetime(s: String) -> chrono::DateTime<Utc> {
 /// Reopen a note for continued recording
 /// Clears ended_at so the note can receive more audio
 #[tauri::command]
-pub fn reopen_note(db: State<Database>, id: String) -> Result<Note, String> {
+pub fn reopen_note(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    id: String,
+) -> Result<Note, String> {
+    ensure_note_accessible(&privacy, &db, &id)?;
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
     let now = Utc::now();
 
@@ -298,18 +527,30 @@ pub fn reopen_note(db: State<Database>, id: String) -> Result<Note, String> {
 
     // Return updated note
     drop(conn);
-    get_note(db, id)?.ok_or_else(|| "Note not found".to_string())
+    db.find_note(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Note not found".to_string())
 }
 
 /// Get all audio segments for a note
 #[tauri::command]
-pub fn get_note_audio_segments(db: State<Database>, note_id: String) -> Result<Vec<AudioSegment>, String> {
+pub fn get_note_audio_segments(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<Vec<AudioSegment>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
     db.get_audio_segments(&note_id).map_err(|e| e.to_string())
 }
 
 /// Get total recording duration for a note (sum of all segment durations)
 #[tauri::command]
-pub fn get_note_total_duration(db: State<Database>, note_id: String) -> Result<i64, String> {
+pub fn get_note_total_duration(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<i64, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
     db.get_total_segment_duration(&note_id)
         .map_err(|e| e.to_string())
 }
@@ -317,7 +558,12 @@ pub fn get_note_total_duration(db: State<Database>, note_id: String) -> Result<i
 /// Delete all audio segment files and records for a note
 /// This is called when deleting a note or when starting a completely fresh recording
 #[tauri::command]
-pub fn delete_note_audio_segments(db: State<Database>, note_id: String) -> Result<(), String> {
+pub fn delete_note_audio_segments(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<(), String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
     // Get all segments first to delete files
     let segments = db.get_audio_segments(&note_id).map_err(|e| e.to_string())?;
 
@@ -340,3 +586,148 @@ pub fn delete_note_audio_segments(db: State<Database>, note_id: String) -> Resul
     db.delete_audio_segments(&note_id)
         .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_note(db: &Database, title: &str, started_at: &str) -> String {
+        let note = db
+            .insert_note(NewNote {
+                title: title.to_string(),
+                description: None,
+                participants: None,
+                folder_id: None,
+                event_id: None,
+            })
+            .unwrap();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE notes SET started_at = ?1 WHERE id = ?2",
+                (started_at, &note.id),
+            )
+            .unwrap();
+        note.id
+    }
+
+    /// Follows `next_cursor` to the end, returning every id in order and the totals seen
+    fn all_pages(
+        db: &Database,
+        mut query: NoteQuery,
+        unlocked: &[String],
+    ) -> (Vec<String>, Vec<i64>) {
+        let mut ids = Vec::new();
+        let mut totals = Vec::new();
+        loop {
+            let page = note_page(db, &query, unlocked).unwrap();
+            assert!(page.items.len() <= query.limit.unwrap() as usize);
+            ids.extend(page.items.into_iter().map(|item| item.note.id));
+            totals.push(page.total);
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => return (ids, totals),
+            }
+        }
+    }
+
+    #[test]
+    fn test_keyset_pages_through_ties_on_the_sort_key() {
+        let db = Database::open_in_memory().unwrap();
+        let mut expected: Vec<String> = (0..7)
+            .map(|i| {
+                add_note(
+                    &db,
+                    "Standup",
+                    &format!("2024-05-0{}T09:00:00+00:00", i % 2 + 1),
+                )
+            })
+            .collect();
+
+        for ascending in [false, true] {
+            let (ids, _) = all_pages(
+                &db,
+                NoteQuery {
+                    ascending,
+                    limit: Some(2),
+                    ..Default::default()
+                },
+                &[],
+            );
+            let mut unique = ids.clone();
+            unique.sort();
+            unique.dedup();
+            expected.sort();
+            assert_eq!(unique, expected);
+            assert_eq!(ids.len(), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_keyset_title_ties_ignore_case() {
+        let db = Database::open_in_memory().unwrap();
+        let mut expected = vec![
+            add_note(&db, "alpha", "2024-05-01T09:00:00+00:00"),
+            add_note(&db, "Alpha", "2024-05-02T09:00:00+00:00"),
+            add_note(&db, "ALPHA", "2024-05-03T09:00:00+00:00"),
+            add_note(&db, "beta", "2024-05-04T09:00:00+00:00"),
+        ];
+
+        let (mut ids, _) = all_pages(
+            &db,
+            NoteQuery {
+                sort: NoteSort::Title,
+                ascending: true,
+                limit: Some(1),
+                ..Default::default()
+            },
+            &[],
+        );
+        assert_eq!(ids.last(), expected.last());
+        ids.sort();
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_search_pages_leave_out_locked_confidential_notes() {
+        let db = Database::open_in_memory().unwrap();
+        let mut visible = Vec::new();
+        let mut confidential = Vec::new();
+        for i in 0..6 {
+            let id = add_note(
+                &db,
+                "Budget review",
+                &format!("2024-05-0{}T09:00:00+00:00", i + 1),
+            );
+            // The newest notes are confidential, so a filter after LIMIT would empty the first page
+            if i >= 3 {
+                db.set_note_confidential(&id, true).unwrap();
+                confidential.push(id);
+            } else {
+                visible.push(id);
+            }
+        }
+        let query = NoteQuery {
+            search: Some("budget".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let (ids, totals) = all_pages(&db, query.clone(), &[]);
+        visible.reverse();
+        assert_eq!(ids, visible);
+        assert!(totals.iter().all(|&total| total == 3));
+
+        let (ids, totals) = all_pages(&db, query.clone(), &confidential[..1]);
+        assert_eq!(ids.len(), 4);
+        assert!(ids.contains(&confidential[0]));
+        assert!(totals.iter().all(|&total| total == 4));
+
+        // Without a search the listing shows every note
+        let listing = note_page(&db, &NoteQuery::default(), &[]).unwrap();
+        assert_eq!(listing.total, 6);
+        assert_eq!(listing.items.len(), 6);
+    }
+}
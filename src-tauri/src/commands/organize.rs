@@ -0,0 +1,131 @@
+//! Tags and folders (or projects) for organizing notes
+
+use tauri::State;
+
+use crate::commands::privacy::{ensure_note_accessible, PrivacyState};
+use crate::db::models::{Folder, FolderKind, Tag};
+use crate::db::Database;
+
+fn non_empty(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    Ok(name)
+}
+
+// ========== Tags ==========
+
+/// List every tag with its note count
+#[tauri::command]
+pub fn list_tags(db: State<Database>) -> Result<Vec<Tag>, String> {
+    db.list_tags().map_err(|e| e.to_string())
+}
+
+/// Get the tags attached to a note
+#[tauri::command]
+pub fn get_note_tags(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<Vec<Tag>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.get_note_tags(&note_id).map_err(|e| e.to_string())
+}
+
+/// Create a tag; returns the existing tag's id when the name is already taken
+#[tauri::command]
+pub fn create_tag(db: State<Database>, name: String, color: Option<String>) -> Result<i64, String> {
+    db.create_tag(non_empty(&name)?, color.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Rename or recolor a tag
+#[tauri::command]
+pub fn update_tag(
+    db: State<Database>,
+    id: i64,
+    name: String,
+    color: Option<String>,
+) -> Result<(), String> {
+    db.update_tag(id, non_empty(&name)?, color.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a tag, removing it from every note
+#[tauri::command]
+pub fn delete_tag(db: State<Database>, id: i64) -> Result<(), String> {
+    db.delete_tag(id).map_err(|e| e.to_string())
+}
+
+/// Attach a tag to a note by name, creating the tag if needed; returns its id
+#[tauri::command]
+pub fn add_note_tag(db: State<Database>, note_id: String, name: String) -> Result<i64, String> {
+    let tag_id = db
+        .create_tag(non_empty(&name)?, None)
+        .map_err(|e| e.to_string())?;
+    db.add_note_tag(&note_id, tag_id)
+        .map_err(|e| e.to_string())?;
+    Ok(tag_id)
+}
+
+/// Detach a tag from a note
+#[tauri::command]
+pub fn remove_note_tag(db: State<Database>, note_id: String, tag_id: i64) -> Result<(), String> {
+    db.remove_note_tag(&note_id, tag_id)
+        .map_err(|e| e.to_string())
+}
+
+// ========== Folders ==========
+
+/// List every folder; build the tree from `parent_id`
+#[tauri::command]
+pub fn list_folders(db: State<Database>) -> Result<Vec<Folder>, String> {
+    db.list_folders().map_err(|e| e.to_string())
+}
+
+/// Create a folder or project, at the top level when `parent_id` is null
+#[tauri::command]
+pub fn create_folder(
+    db: State<Database>,
+    name: String,
+    parent_id: Option<i64>,
+    kind: Option<FolderKind>,
+) -> Result<i64, String> {
+    db.create_folder(
+        non_empty(&name)?,
+        parent_id,
+        kind.unwrap_or(FolderKind::Folder),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Rename a folder, change its kind or move it under another folder
+#[tauri::command]
+pub fn update_folder(
+    db: State<Database>,
+    id: i64,
+    name: String,
+    parent_id: Option<i64>,
+    kind: FolderKind,
+) -> Result<(), String> {
+    db.update_folder(id, non_empty(&name)?, parent_id, kind)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a folder and its subfolders; their notes move to the top level
+#[tauri::command]
+pub fn delete_folder(db: State<Database>, id: i64) -> Result<(), String> {
+    db.delete_folder(id).map_err(|e| e.to_string())
+}
+
+/// Move a note into a folder, or to the top level when `folder_id` is null
+#[tauri::command]
+pub fn move_note_to_folder(
+    db: State<Database>,
+    note_id: String,
+    folder_id: Option<i64>,
+) -> Result<(), String> {
+    db.set_note_folder(&note_id, folder_id)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,158 @@
+//! People: participants of notes, diarized speakers and summary owners
+
+use tauri::State;
+
+use crate::commands::privacy::{
+    ensure_app_unlocked, ensure_note_accessible, is_note_hidden, PrivacyState,
+};
+use crate::db::models::{ActionItem, Person, SpeakerLink};
+use crate::db::Database;
+
+fn non_empty(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    Ok(name)
+}
+
+/// Trim aliases and drop empty ones
+fn clean_aliases(aliases: Vec<String>) -> Vec<String> {
+    aliases
+        .into_iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+/// List every person by name
+#[tauri::command]
+pub fn list_people(db: State<Database>) -> Result<Vec<Person>, String> {
+    db.list_people().map_err(|e| e.to_string())
+}
+
+/// Create a person
+#[tauri::command]
+pub fn create_person(
+    db: State<Database>,
+    name: String,
+    email: Option<String>,
+    aliases: Vec<String>,
+) -> Result<i64, String> {
+    db.create_person(
+        non_empty(&name)?,
+        email.as_deref().map(str::trim).filter(|e| !e.is_empty()),
+        &clean_aliases(aliases),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Update a person's name, email and aliases
+#[tauri::command]
+pub fn update_person(
+    db: State<Database>,
+    id: i64,
+    name: String,
+    email: Option<String>,
+    aliases: Vec<String>,
+) -> Result<(), String> {
+    db.update_person(
+        id,
+        non_empty(&name)?,
+        email.as_deref().map(str::trim).filter(|e| !e.is_empty()),
+        &clean_aliases(aliases),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Delete a person, removing them from every note
+#[tauri::command]
+pub fn delete_person(db: State<Database>, id: i64) -> Result<(), String> {
+    db.delete_person(id).map_err(|e| e.to_string())
+}
+
+/// Merge a duplicate person into another one
+#[tauri::command]
+pub fn merge_people(db: State<Database>, keep_id: i64, merge_id: i64) -> Result<(), String> {
+    db.merge_people(keep_id, merge_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the people taking part in a note
+#[tauri::command]
+pub fn get_note_participants(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<Vec<Person>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.get_note_participants(&note_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Replace a note's participants
+#[tauri::command]
+pub fn set_note_participants(
+    db: State<Database>,
+    note_id: String,
+    person_ids: Vec<i64>,
+) -> Result<(), String> {
+    db.set_note_participants(&note_id, &person_ids)
+        .map_err(|e| e.to_string())
+}
+
+/// Get which diarized speaker labels of a note are linked to people
+#[tauri::command]
+pub fn get_speaker_links(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<Vec<SpeakerLink>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.get_speaker_links(&note_id).map_err(|e| e.to_string())
+}
+
+/// Link a diarized speaker label to a person, or unlink it (`person_id: None`)
+#[tauri::command]
+pub fn link_speaker(
+    db: State<Database>,
+    note_id: String,
+    speaker: String,
+    person_id: Option<i64>,
+) -> Result<(), String> {
+    db.link_speaker(&note_id, &speaker, person_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Set or clear the person responsible for a summary's action items
+#[tauri::command]
+pub fn set_summary_owner(
+    db: State<Database>,
+    summary_id: i64,
+    person_id: Option<i64>,
+) -> Result<(), String> {
+    db.set_summary_owner(summary_id, person_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Open action items for a person across all notes, newest note first.
+/// Items of locked confidential notes are left out.
+#[tauri::command]
+pub fn get_open_action_items(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    person_id: i64,
+) -> Result<Vec<ActionItem>, String> {
+    ensure_app_unlocked(&privacy)?;
+    let items = db
+        .get_open_action_items(person_id)
+        .map_err(|e| e.to_string())?;
+
+    Ok(items
+        .into_iter()
+        .filter(|item| {
+            let confidential = db.is_note_confidential(&item.note_id).unwrap_or(true);
+            !is_note_hidden(&privacy, &item.note_id, confidential)
+        })
+        .collect())
+}
@@ -0,0 +1,473 @@
+//! Post-recording pipeline: once a recording stops, mix the playback file,
+//! batch-transcribe, summarize and title the note without the user having to
+//! remember each step
+//!
+//! Runs and the status of their steps live in the `pipeline_runs` and
+//! `pipeline_steps` tables. Steps run one after the other on a background
+//! thread; a failed step does not stop the ones after it and can be retried
+//! on its own.
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::ai::{
+    generate_title, generate_title_from_summary, summarize_in_slot, AiState,
+};
+use crate::commands::audio::ensure_playback_file;
+use crate::commands::jobs::queue_pipeline_transcription;
+use crate::commands::privacy::{ensure_note_accessible, PrivacyState};
+use crate::commands::storage::is_note_recording;
+use crate::commands::transcription::TranscriptionState;
+use crate::db::models::{JobStatus, PipelineRun, PipelineStep, StepStatus, SummaryType};
+use crate::db::Database;
+
+/// Event emitted whenever a step starts, finishes or is queued for a retry
+pub const PIPELINE_PROGRESS_EVENT: &str = "pipeline-progress";
+
+/// Settings key holding the pipeline settings
+const PIPELINE_SETTINGS_KEY: &str = "post_recording_pipeline";
+
+/// How often a waiting step checks again
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Steps to run after a recording, and which recordings they are for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineTemplate {
+    pub name: String,
+    /// Detected meeting apps (as named by the meeting rules) using this template
+    #[serde(default)]
+    pub apps: Vec<String>,
+    pub steps: Vec<PipelineStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PipelineSettings {
+    /// Run automatically when a dual recording stops
+    pub enabled: bool,
+    /// Template for recordings no other template claims
+    pub default_template: String,
+    pub templates: Vec<PipelineTemplate>,
+}
+
+impl Default for PipelineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_template: "Standard".to_string(),
+            templates: vec![PipelineTemplate {
+                name: "Standard".to_string(),
+                apps: Vec::new(),
+                steps: vec![
+                    PipelineStep::Mix,
+                    PipelineStep::Transcribe { model: None },
+                    PipelineStep::Summary {
+                        summary_type: SummaryType::Overview,
+                        custom_prompt: None,
+                    },
+                    PipelineStep::Title,
+                ],
+            }],
+        }
+    }
+}
+
+impl PipelineSettings {
+    pub fn template(&self, name: &str) -> Option<&PipelineTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// The template for a recording of a meeting detected in `meeting_app`
+    pub fn template_for(&self, meeting_app: Option<&str>) -> Option<&PipelineTemplate> {
+        meeting_app
+            .and_then(|app| {
+                self.templates
+                    .iter()
+                    .find(|t| t.apps.iter().any(|a| a.eq_ignore_ascii_case(app)))
+            })
+            .or_else(|| self.template(&self.default_template))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (i, template) in self.templates.iter().enumerate() {
+            if template.name.trim().is_empty() {
+                return Err(format!("Pipeline template {} needs a name", i + 1));
+            }
+            if self.templates[..i].iter().any(|t| t.name == template.name) {
+                return Err(format!(
+                    "Two pipeline templates are named {}",
+                    template.name
+                ));
+            }
+        }
+        if self.template(&self.default_template).is_none() {
+            return Err(format!(
+                "Unknown pipeline template: {}",
+                self.default_template
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Progress of a step, emitted as `PIPELINE_PROGRESS_EVENT`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineProgressEvent {
+    pub run_id: i64,
+    pub note_id: String,
+    pub position: i32,
+    pub step: PipelineStep,
+    pub status: StepStatus,
+    pub error: Option<String>,
+}
+
+fn load_pipeline_settings(db: &Database) -> PipelineSettings {
+    db.get_setting(PIPELINE_SETTINGS_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn is_active(run: &PipelineRun) -> bool {
+    run.steps
+        .iter()
+        .any(|s| matches!(s.status, StepStatus::Pending | StepStatus::Running))
+}
+
+fn set_step_status(
+    app: &AppHandle,
+    run: &PipelineRun,
+    position: i32,
+    status: StepStatus,
+    error: Option<String>,
+) {
+    let db = app.state::<Database>();
+    if let Err(e) = db.set_pipeline_step_status(run.id, position, status, error.as_deref()) {
+        eprintln!(
+            "Failed to update pipeline step {}/{}: {}",
+            run.id, position, e
+        );
+    }
+    let Some(state) = run.steps.iter().find(|s| s.position == position) else {
+        return;
+    };
+    let _ = app.emit(
+        PIPELINE_PROGRESS_EVENT,
+        PipelineProgressEvent {
+            run_id: run.id,
+            note_id: run.note_id.clone(),
+            position,
+            step: state.step.clone(),
+            status,
+            error,
+        },
+    );
+}
+
+/// Wait until the note's recording and live transcription have stopped, so
+/// the steps see its final audio and live transcript. A recording of another
+/// note doesn't hold them up.
+fn wait_until_recorded(app: &AppHandle, note_id: &str) {
+    loop {
+        let live_state = &app.state::<TranscriptionState>().live_state;
+        let transcribing = live_state.is_running.load(Ordering::SeqCst)
+            && live_state.note_id.blocking_lock().as_deref() == Some(note_id);
+        if !is_note_recording(app, note_id) && !transcribing {
+            return;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn wait_for_job(db: &Database, job_id: i64) -> Result<StepStatus, String> {
+    loop {
+        let job = db
+            .get_transcription_job(job_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("The transcription job was removed")?;
+        match job.status {
+            JobStatus::Done => return Ok(StepStatus::Done),
+            JobStatus::Failed => {
+                return Err(job
+                    .error
+                    .unwrap_or_else(|| "Transcription failed".to_string()));
+            }
+            JobStatus::Cancelled => return Err("Transcription was cancelled".to_string()),
+            JobStatus::Pending | JobStatus::Running => std::thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+fn run_step(app: &AppHandle, note_id: &str, step: &PipelineStep) -> Result<StepStatus, String> {
+    let db = app.state::<Database>();
+    match step {
+        PipelineStep::Mix => {
+            ensure_playback_file(app, &db, note_id)?;
+            Ok(StepStatus::Done)
+        }
+        PipelineStep::Transcribe { model } => {
+            match queue_pipeline_transcription(app, note_id, model.as_deref())? {
+                Some(job_id) => wait_for_job(&db, job_id),
+                None => Ok(StepStatus::Skipped),
+            }
+        }
+        PipelineStep::Summary {
+            summary_type,
+            custom_prompt,
+        } => {
+            ensure_note_accessible(&app.state::<PrivacyState>(), &db, note_id)?;
+
+            // One summary at a time; wait for one the user started, taking the slot
+            // in the same check so nothing else starts in between
+            let ai_state = app.state::<AiState>();
+            while !ai_state.try_take_generation_slot() {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            tauri::async_runtime::block_on(summarize_in_slot(
+                &ai_state,
+                &db,
+                note_id,
+                summary_type.as_str(),
+                custom_prompt.clone(),
+            ))?;
+            Ok(StepStatus::Done)
+        }
+        PipelineStep::Title => {
+            let newest = db
+                .get_summaries(note_id)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .next();
+            match newest {
+                Some(summary) => tauri::async_runtime::block_on(generate_title_from_summary(
+                    note_id.to_string(),
+                    summary.content,
+                    app.state(),
+                    app.state(),
+                    app.state(),
+                )),
+                None => tauri::async_runtime::block_on(generate_title(
+                    note_id.to_string(),
+                    app.state(),
+                    app.state(),
+                    app.state(),
+                )),
+            }?;
+            Ok(StepStatus::Done)
+        }
+    }
+}
+
+/// Run the steps at `positions` of `run` in the background
+fn spawn_steps(app: AppHandle, run: PipelineRun, positions: Vec<i32>) {
+    std::thread::spawn(move || {
+        wait_until_recorded(&app, &run.note_id);
+
+        for state in run.steps.iter().filter(|s| positions.contains(&s.position)) {
+            set_step_status(&app, &run, state.position, StepStatus::Running, None);
+            match run_step(&app, &run.note_id, &state.step) {
+                Ok(status) => set_step_status(&app, &run, state.position, status, None),
+                Err(e) => {
+                    eprintln!(
+                        "Pipeline step {} of note {} failed: {}",
+                        state.position, run.note_id, e
+                    );
+                    set_step_status(&app, &run, state.position, StepStatus::Failed, Some(e));
+                }
+            }
+        }
+    });
+}
+
+fn start_run(
+    app: &AppHandle,
+    db: &Database,
+    note_id: &str,
+    template: &PipelineTemplate,
+) -> Result<PipelineRun, String> {
+    if let Some(run) = db
+        .get_note_pipeline_run(note_id)
+        .map_err(|e| e.to_string())?
+        && is_active(&run)
+    {
+        return Err("The pipeline is already running for this note".to_string());
+    }
+    let run = db
+        .create_pipeline_run(note_id, &template.name, &template.steps)
+        .map_err(|e| e.to_string())?;
+    let positions = run.steps.iter().map(|s| s.position).collect();
+    spawn_steps(app.clone(), run.clone(), positions);
+    Ok(run)
+}
+
+/// Run the pipeline over a note whose dual recording just stopped, when automatic
+/// runs are enabled. `meeting_app` picks the template of a detected meeting.
+pub(crate) fn start_pipeline_after_recording(
+    app: &AppHandle,
+    db: &Database,
+    note_id: &str,
+    meeting_app: Option<&str>,
+) -> Result<Option<PipelineRun>, String> {
+    let settings = load_pipeline_settings(db);
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let template = settings
+        .template_for(meeting_app)
+        .ok_or("No pipeline template configured")?;
+    start_run(app, db, note_id, template).map(Some)
+}
+
+/// Mark the steps a previous session never finished as failed
+pub fn fail_interrupted_pipelines(db: &Database) {
+    if db.is_locked() {
+        return;
+    }
+    if let Err(e) = db.fail_interrupted_pipeline_steps() {
+        eprintln!("Failed to recover pipeline runs: {}", e);
+    }
+}
+
+/// Get the pipeline settings and templates
+#[tauri::command]
+pub fn get_pipeline_settings(db: State<Database>) -> PipelineSettings {
+    load_pipeline_settings(&db)
+}
+
+/// Save the pipeline settings and templates
+#[tauri::command]
+pub fn set_pipeline_settings(
+    settings: PipelineSettings,
+    db: State<Database>,
+) -> Result<(), String> {
+    settings.validate()?;
+    let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    db.set_setting(PIPELINE_SETTINGS_KEY, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Run the pipeline over a note by hand, with `template` or the default one
+#[tauri::command]
+pub fn run_note_pipeline(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+    template: Option<String>,
+) -> Result<PipelineRun, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    let settings = load_pipeline_settings(&db);
+    let name = template.unwrap_or_else(|| settings.default_template.clone());
+    let template = settings
+        .template(&name)
+        .ok_or_else(|| format!("Unknown pipeline template: {}", name))?;
+    start_run(&app, &db, &note_id, template)
+}
+
+/// Get the latest pipeline run over a note
+#[tauri::command]
+pub fn get_note_pipeline(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<Option<PipelineRun>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.get_note_pipeline_run(&note_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Run one step of a finished run again
+#[tauri::command]
+pub fn retry_pipeline_step(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    run_id: i64,
+    position: i32,
+) -> Result<PipelineRun, String> {
+    let run = db
+        .get_pipeline_run(run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Pipeline run not found")?;
+    ensure_note_accessible(&privacy, &db, &run.note_id)?;
+    if is_active(&run) {
+        return Err("The pipeline is still running".to_string());
+    }
+    if !run.steps.iter().any(|s| s.position == position) {
+        return Err("Pipeline step not found".to_string());
+    }
+
+    set_step_status(&app, &run, position, StepStatus::Pending, None);
+    spawn_steps(app.clone(), run, vec![position]);
+    db.get_pipeline_run(run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Pipeline run not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str, apps: &[&str]) -> PipelineTemplate {
+        PipelineTemplate {
+            name: name.to_string(),
+            apps: apps.iter().map(|a| a.to_string()).collect(),
+            steps: vec![PipelineStep::Mix],
+        }
+    }
+
+    #[test]
+    fn test_template_for() {
+        let settings = PipelineSettings {
+            enabled: true,
+            default_template: "Standard".to_string(),
+            templates: vec![
+                template("Standard", &[]),
+                template("Client calls", &["Zoom", "Microsoft Teams"]),
+            ],
+        };
+        let name = |app: Option<&str>| settings.template_for(app).map(|t| t.name.as_str());
+
+        assert_eq!(name(Some("zoom")), Some("Client calls"));
+        assert_eq!(name(Some("Google Meet")), Some("Standard"));
+        assert_eq!(name(None), Some("Standard"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(PipelineSettings::default().validate().is_ok());
+
+        let mut settings = PipelineSettings::default();
+        settings.templates.push(template("Standard", &[]));
+        assert!(settings.validate().is_err());
+
+        let settings = PipelineSettings {
+            default_template: "Missing".to_string(),
+            ..PipelineSettings::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_step_json() {
+        let step: PipelineStep =
+            serde_json::from_str(r#"{"kind": "summary", "summary_type": "action_items"}"#).unwrap();
+        assert_eq!(
+            step,
+            PipelineStep::Summary {
+                summary_type: SummaryType::ActionItems,
+                custom_prompt: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&PipelineStep::Transcribe { model: None }).unwrap(),
+            r#"{"kind":"transcribe","model":null}"#
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! App lock and confidential notes
+//!
+//! The app lock is a passphrase (stored as an Argon2 hash in `settings`) that
+//! is asked for before the main window's content is shown again after it was
+//! hidden to the tray, and at startup. While it is engaged, every command that
+//! reads note data fails. Confidential notes need the app lock: they stay out
+//! of search, AI summarization and export until they are unlocked with its
+//! passphrase for the current session; hiding the window locks them again.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::crypto;
+use crate::db::Database;
+
+/// Settings key holding the Argon2 hash of the app lock passphrase
+const APP_LOCK_KEY: &str = "app_lock_passphrase";
+
+#[derive(Default)]
+pub struct PrivacyState {
+    /// The window was hidden with the app lock enabled and not unlocked since
+    app_locked: AtomicBool,
+    /// Confidential notes unlocked for this session
+    unlocked_notes: Mutex<HashSet<String>>,
+}
+
+impl PrivacyState {
+    fn is_note_unlocked(&self, note_id: &str) -> bool {
+        self.unlocked_notes
+            .lock()
+            .map(|notes| notes.contains(note_id))
+            .unwrap_or(false)
+    }
+
+    /// Confidential notes unlocked for this session
+    pub(crate) fn unlocked_note_ids(&self) -> Vec<String> {
+        self.unlocked_notes
+            .lock()
+            .map(|notes| notes.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn lock_notes(&self) {
+        if let Ok(mut notes) = self.unlocked_notes.lock() {
+            notes.clear();
+        }
+    }
+}
+
+/// Whether the app lock is set up and currently engaged
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+}
+
+fn app_lock_hash(db: &Database) -> Option<String> {
+    db.get_setting(APP_LOCK_KEY).ok().flatten()
+}
+
+/// Event emitted when the app lock engages; the frontend shows its lock screen
+pub const APP_LOCKED_EVENT: &str = "app-locked";
+
+/// Check `passphrase` against the app lock, passing when no lock is set
+fn check_passphrase(db: &Database, passphrase: Option<&str>) -> Result<(), String> {
+    match app_lock_hash(db) {
+        None => Ok(()),
+        Some(hash) if passphrase.is_some_and(|p| crypto::verify_passphrase(p, &hash)) => Ok(()),
+        Some(_) => Err("Wrong passphrase".to_string()),
+    }
+}
+
+/// Check `passphrase` against the app lock, failing when no lock is set
+fn require_passphrase(db: &Database, passphrase: Option<&str>) -> Result<(), String> {
+    if app_lock_hash(db).is_none() {
+        return Err("Set an app lock passphrase first".to_string());
+    }
+    check_passphrase(db, passphrase)
+}
+
+/// Fail while the app lock is engaged
+pub(crate) fn ensure_app_unlocked(privacy: &PrivacyState) -> Result<(), String> {
+    if privacy.app_locked.load(Ordering::SeqCst) {
+        return Err("NetNote is locked".to_string());
+    }
+    Ok(())
+}
+
+/// Fail while the app lock is engaged, and for confidential notes that have
+/// not been unlocked this session
+pub(crate) fn ensure_note_accessible(
+    privacy: &PrivacyState,
+    db: &Database,
+    note_id: &str,
+) -> Result<(), String> {
+    ensure_app_unlocked(privacy)?;
+    if db
+        .is_note_confidential(note_id)
+        .map_err(|e| e.to_string())?
+        && !privacy.is_note_unlocked(note_id)
+    {
+        return Err("This note is confidential. Unlock it first.".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a note should be left out of search results and bulk operations
+pub(crate) fn is_note_hidden(privacy: &PrivacyState, note_id: &str, confidential: bool) -> bool {
+    confidential && !privacy.is_note_unlocked(note_id)
+}
+
+/// Engage the app lock (when enabled) because the main window was hidden to
+/// the tray or the app just started
+pub fn lock_on_hide<R: Runtime>(app: &AppHandle<R>) {
+    let privacy = app.state::<PrivacyState>();
+    privacy.lock_notes();
+
+    let db = app.state::<Database>();
+    if app_lock_hash(&db).is_some() {
+        privacy.app_locked.store(true, Ordering::SeqCst);
+        let _ = app.emit(APP_LOCKED_EVENT, ());
+    }
+}
+
+/// Whether the app lock is engaged, so the window may only show the lock screen
+pub fn is_app_locked<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<PrivacyState>()
+        .app_locked
+        .load(Ordering::SeqCst)
+}
+
+/// Get whether the app lock is enabled and engaged
+#[tauri::command]
+pub fn get_app_lock_status(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<AppLockStatus, String> {
+    Ok(AppLockStatus {
+        enabled: app_lock_hash(&db).is_some(),
+        locked: privacy.app_locked.load(Ordering::SeqCst),
+    })
+}
+
+/// Set, change or remove (`passphrase: None`) the app lock passphrase.
+/// Changing or removing an existing lock needs its current passphrase, and it
+/// can't be removed while notes are flagged confidential.
+#[tauri::command]
+pub fn set_app_lock(
+    db: State<Database>,
+    passphrase: Option<String>,
+    current_passphrase: Option<String>,
+) -> Result<(), String> {
+    check_passphrase(&db, current_passphrase.as_deref())?;
+
+    match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => {
+            let hash = crypto::hash_passphrase(&passphrase).map_err(|e| e.to_string())?;
+            db.set_setting(APP_LOCK_KEY, &hash)
+        }
+        None => {
+            if db.has_confidential_notes().map_err(|e| e.to_string())? {
+                return Err(
+                    "Clear the confidential flag from every note before removing the app lock"
+                        .to_string(),
+                );
+            }
+            db.delete_setting(APP_LOCK_KEY)
+        }
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Unlock the app after the window was hidden
+#[tauri::command]
+pub fn unlock_app(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    passphrase: String,
+) -> Result<(), String> {
+    check_passphrase(&db, Some(&passphrase))?;
+    privacy.app_locked.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Flag a note as confidential (needs the app lock to be set) or clear the flag
+#[tauri::command]
+pub fn set_note_confidential(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+    confidential: bool,
+) -> Result<(), String> {
+    // Clearing the flag is the same as unlocking the note for good
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    if confidential && app_lock_hash(&db).is_none() {
+        return Err("Set an app lock passphrase before marking notes confidential".to_string());
+    }
+    db.set_note_confidential(&note_id, confidential)
+        .map_err(|e| e.to_string())
+}
+
+/// Unlock a confidential note for this session with the app lock passphrase
+#[tauri::command]
+pub fn unlock_confidential_note(
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    ensure_app_unlocked(&privacy)?;
+    require_passphrase(&db, passphrase.as_deref())?;
+    privacy
+        .unlocked_notes
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(note_id);
+    Ok(())
+}
+
+/// Lock every unlocked confidential note again
+#[tauri::command]
+pub fn lock_confidential_notes(privacy: State<PrivacyState>) {
+    privacy.lock_notes();
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::test::{mock_app, MockRuntime};
+    use tauri::App;
+
+    use super::*;
+    use crate::commands::notes::get_note;
+    use crate::db::models::NewNote;
+
+    const PASSPHRASE: &str = "correct horse";
+
+    /// A mock app with an in-memory library holding one note, and that note's id
+    fn app_with_note() -> (App<MockRuntime>, String) {
+        let app = mock_app();
+        let db = Database::open_in_memory().unwrap();
+        let note = NewNote {
+            title: "Board meeting".to_string(),
+            description: None,
+            participants: None,
+            folder_id: None,
+            event_id: None,
+        };
+        let note_id = db.insert_note(note).unwrap().id;
+        app.manage(db);
+        app.manage(PrivacyState::default());
+        (app, note_id)
+    }
+
+    fn enable_app_lock(app: &App<MockRuntime>) {
+        set_app_lock(app.state(), Some(PASSPHRASE.to_string()), None).unwrap();
+    }
+
+    fn read_note(app: &App<MockRuntime>, note_id: &str) -> Result<Option<String>, String> {
+        get_note(app.state(), app.state(), note_id.to_string()).map(|n| n.map(|n| n.title))
+    }
+
+    #[test]
+    fn test_app_lock_blocks_note_reads_until_unlocked() {
+        let (app, note_id) = app_with_note();
+        enable_app_lock(&app);
+
+        lock_on_hide(app.handle());
+        assert!(is_app_locked(app.handle()));
+        assert!(read_note(&app, &note_id).is_err());
+
+        assert!(unlock_app(app.state(), app.state(), "wrong".to_string()).is_err());
+        assert!(read_note(&app, &note_id).is_err());
+
+        unlock_app(app.state(), app.state(), PASSPHRASE.to_string()).unwrap();
+        assert!(!is_app_locked(app.handle()));
+        assert_eq!(
+            read_note(&app, &note_id).unwrap().as_deref(),
+            Some("Board meeting")
+        );
+    }
+
+    #[test]
+    fn test_hiding_without_app_lock_leaves_app_open() {
+        let (app, note_id) = app_with_note();
+
+        lock_on_hide(app.handle());
+        assert!(!is_app_locked(app.handle()));
+        assert!(read_note(&app, &note_id).is_ok());
+    }
+
+    #[test]
+    fn test_confidential_notes_need_the_app_lock() {
+        let (app, note_id) = app_with_note();
+        assert!(set_note_confidential(app.state(), app.state(), note_id.clone(), true).is_err());
+
+        // A note flagged while a lock existed can't be unlocked without one
+        app.state::<Database>()
+            .set_note_confidential(&note_id, true)
+            .unwrap();
+        assert!(unlock_confidential_note(app.state(), app.state(), note_id.clone(), None).is_err());
+        assert!(read_note(&app, &note_id).is_err());
+    }
+
+    #[test]
+    fn test_confidential_note_unlocks_until_window_is_hidden() {
+        let (app, note_id) = app_with_note();
+        enable_app_lock(&app);
+        set_note_confidential(app.state(), app.state(), note_id.clone(), true).unwrap();
+        assert!(read_note(&app, &note_id).is_err());
+
+        let unlock = |passphrase: Option<&str>| {
+            unlock_confidential_note(
+                app.state(),
+                app.state(),
+                note_id.clone(),
+                passphrase.map(str::to_string),
+            )
+        };
+        assert!(unlock(None).is_err());
+        assert!(unlock(Some("wrong")).is_err());
+        unlock(Some(PASSPHRASE)).unwrap();
+        assert!(read_note(&app, &note_id).is_ok());
+
+        lock_on_hide(app.handle());
+        unlock_app(app.state(), app.state(), PASSPHRASE.to_string()).unwrap();
+        assert!(read_note(&app, &note_id).is_err());
+    }
+
+    #[test]
+    fn test_app_lock_stays_while_notes_are_confidential() {
+        let (app, note_id) = app_with_note();
+        enable_app_lock(&app);
+        set_note_confidential(app.state(), app.state(), note_id.clone(), true).unwrap();
+
+        let remove = || set_app_lock(app.state(), None, Some(PASSPHRASE.to_string()));
+        assert!(remove().is_err());
+
+        unlock_confidential_note(
+            app.state(),
+            app.state(),
+            note_id.clone(),
+            Some(PASSPHRASE.to_string()),
+        )
+        .unwrap();
+        set_note_confidential(app.state(), app.state(), note_id, false).unwrap();
+        remove().unwrap();
+        assert!(
+            !get_app_lock_status(app.state(), app.state())
+                .unwrap()
+                .enabled
+        );
+    }
+}
@@ -0,0 +1,582 @@
+//! Disk usage, retention rules and orphan cleanup for recordings
+//!
+//! Every file under `recordings/` starts with the id of the note it belongs to
+//! (`<id>.wav`, `<id>_mic_seg2.wav`, `<id>.peaks.json`, ...), which is how files
+//! are attributed to notes without a separate index.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::audio::RecordingPhase;
+use crate::commands::audio::AudioState;
+use crate::commands::privacy::{
+    ensure_app_unlocked, ensure_note_accessible, is_note_hidden, PrivacyState,
+};
+use crate::db::Database;
+
+/// Settings key holding the JSON-encoded [`RetentionPolicy`]
+const RETENTION_POLICY_KEY: &str = "retention_policy";
+
+/// How often the retention policy is re-applied while the app runs
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Length of a note id (hyphenated UUID) at the start of recording file names
+const NOTE_ID_LEN: usize = 36;
+
+/// Automatic cleanup rules for recorded audio
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    /// Delete the audio of notes that ended more than this many days ago,
+    /// keeping their transcripts and summaries (None = keep forever)
+    pub delete_audio_after_days: Option<u32>,
+}
+
+/// Disk usage of one note's recordings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteStorage {
+    pub note_id: String,
+    pub title: String,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// Why a file in the recordings directory is considered orphaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanReason {
+    /// The note it belonged to was deleted
+    DeletedNote,
+    /// A segment file of an existing note that no `audio_segments` row references
+    UnknownSegment,
+    /// The name doesn't start with a note id
+    Unrecognized,
+}
+
+/// A recordings file that no note accounts for
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanFile {
+    pub path: String,
+    pub bytes: u64,
+    pub reason: OrphanReason,
+}
+
+/// Disk usage of the recordings directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub recordings_dir: String,
+    pub total_bytes: u64,
+    /// Notes with files on disk, largest first
+    pub notes: Vec<NoteStorage>,
+    pub orphans: Vec<OrphanFile>,
+    /// Segment files referenced by `audio_segments` that no longer exist
+    pub missing_files: Vec<String>,
+}
+
+/// Outcome of deleting audio files
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupResult {
+    pub notes: usize,
+    pub files: usize,
+    pub bytes_freed: u64,
+}
+
+pub(crate) fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(app_data_dir.join("recordings"))
+}
+
+/// The note id a recordings file name starts with, if any
+pub(crate) fn file_note_id(name: &str) -> Option<&str> {
+    let id = name.get(..NOTE_ID_LEN)?;
+    let rest = &name[NOTE_ID_LEN..];
+    (uuid::Uuid::parse_str(id).is_ok() && (rest.starts_with('.') || rest.starts_with('_')))
+        .then_some(id)
+}
+
+/// Files directly inside the recordings directory with their sizes
+pub(crate) fn list_recordings(dir: &Path) -> Vec<(PathBuf, String, u64)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                (entry.path(), name, metadata.len())
+            })
+        })
+        .collect()
+}
+
+/// Every audio file of a note: its recordings-directory files plus any
+/// referenced paths stored elsewhere
+pub(crate) fn note_audio_files(app: &AppHandle, db: &Database, note_id: &str) -> Vec<PathBuf> {
+    note_files(recordings_dir(app).ok().as_deref(), db, note_id)
+}
+
+/// [`note_audio_files`] for a given recordings directory
+fn note_files(dir: Option<&Path>, db: &Database, note_id: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = dir
+        .map(list_recordings)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, name, _)| file_note_id(name) == Some(note_id))
+        .map(|(path, _, _)| path)
+        .collect();
+
+    let segments = db.get_audio_segments(note_id).unwrap_or_default();
+    let referenced = segments
+        .into_iter()
+        .flat_map(|s| std::iter::once(s.mic_path).chain(s.system_path))
+        .chain(db.get_note_audio_path(note_id).ok().flatten());
+    for path in referenced.map(PathBuf::from) {
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+/// Whether the recorder is currently capturing (or paused on) this note
+pub(crate) fn is_note_recording(app: &AppHandle, note_id: &str) -> bool {
+    let recording = &app.state::<AudioState>().recording;
+    recording.get_phase() != RecordingPhase::Idle
+        && recording
+            .current_note_id
+            .lock()
+            .map(|id| id.as_deref() == Some(note_id))
+            .unwrap_or(true)
+}
+
+/// Remove files, returning how many existed and their total size
+pub(crate) fn remove_files(files: &[PathBuf]) -> (usize, u64) {
+    let mut removed = 0;
+    let mut bytes = 0;
+    for path in files {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            continue;
+        };
+        match std::fs::remove_file(path) {
+            Ok(()) => {
+                removed += 1;
+                bytes += metadata.len();
+            }
+            Err(e) => eprintln!("Failed to delete audio file {}: {}", path.display(), e),
+        }
+    }
+    (removed, bytes)
+}
+
+/// Delete a note's audio but keep the note, its transcripts and its segment
+/// timeline (offsets stay valid for existing transcript timestamps)
+fn purge_note_audio(dir: &Path, db: &Database, note_id: &str) -> Result<(usize, u64), String> {
+    let files = note_files(Some(dir), db, note_id);
+    let removed = remove_files(&files);
+    db.clear_note_audio_path(note_id).map_err(|e| e.to_string())?;
+    Ok(removed)
+}
+
+fn load_retention_policy(db: &Database) -> RetentionPolicy {
+    db.get_setting(RETENTION_POLICY_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Apply the saved retention policy; notes with queued or running jobs, or that
+/// `is_recording` says are being recorded, are left alone
+fn run_retention(
+    dir: &Path,
+    db: &Database,
+    is_recording: impl Fn(&str) -> bool,
+) -> Result<CleanupResult, String> {
+    let mut result = CleanupResult::default();
+    let Some(days) = load_retention_policy(db).delete_audio_after_days else {
+        return Ok(result);
+    };
+
+    let cutoff = (Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+    let note_ids = db
+        .get_notes_with_audio_ended_before(&cutoff)
+        .map_err(|e| e.to_string())?;
+
+    for note_id in note_ids {
+        if db.has_open_transcription_job(&note_id).unwrap_or(true) || is_recording(&note_id) {
+            continue;
+        }
+        let (files, bytes) = purge_note_audio(dir, db, &note_id)?;
+        if files > 0 {
+            result.notes += 1;
+            result.files += files;
+            result.bytes_freed += bytes;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Apply the retention policy for the running app
+fn apply_retention(app: &AppHandle) -> Result<CleanupResult, String> {
+    let db = app.state::<Database>();
+    run_retention(&recordings_dir(app)?, &db, |note_id| {
+        is_note_recording(app, note_id)
+    })
+}
+
+/// Apply the retention policy and log what it removed; a locked library is
+/// skipped until it is unlocked
+fn log_retention(app: &AppHandle) {
+    if app.state::<Database>().is_locked() {
+        return;
+    }
+    match apply_retention(app) {
+        Ok(result) if result.files > 0 => println!(
+            "Retention policy removed {} audio files ({} bytes) from {} notes",
+            result.files, result.bytes_freed, result.notes
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to apply retention policy: {}", e),
+    }
+}
+
+/// Apply the retention policy once in the background, e.g. right after unlocking
+pub(crate) fn spawn_retention_pass(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || log_retention(&app));
+}
+
+/// Apply the retention policy in the background after startup and then once a day
+pub fn start_retention_cleanup(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        log_retention(&app);
+        std::thread::sleep(RETENTION_INTERVAL);
+    });
+}
+
+/// Attribute every file in `dir` to a note or report it as an orphan
+fn scan_recordings(dir: &Path, db: &Database) -> Result<StorageReport, String> {
+    let titles: HashMap<String, String> = db
+        .get_note_titles()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+    let segment_paths: HashSet<PathBuf> = db
+        .get_all_audio_segment_paths()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+
+    let mut total_bytes = 0;
+    let mut notes: HashMap<String, NoteStorage> = HashMap::new();
+    let mut orphans = Vec::new();
+
+    for (path, name, bytes) in list_recordings(dir) {
+        total_bytes += bytes;
+
+        let reason = match file_note_id(&name) {
+            None => Some(OrphanReason::Unrecognized),
+            Some(id) if !titles.contains_key(id) => Some(OrphanReason::DeletedNote),
+            Some(_) if name.contains("_seg") && !segment_paths.contains(&path) => {
+                Some(OrphanReason::UnknownSegment)
+            }
+            Some(_) => None,
+        };
+
+        match (reason, file_note_id(&name)) {
+            (Some(reason), _) => orphans.push(OrphanFile {
+                path: path.to_string_lossy().to_string(),
+                bytes,
+                reason,
+            }),
+            (None, Some(id)) => {
+                let entry = notes.entry(id.to_string()).or_insert_with(|| NoteStorage {
+                    note_id: id.to_string(),
+                    title: titles.get(id).cloned().unwrap_or_default(),
+                    bytes: 0,
+                    files: 0,
+                });
+                entry.bytes += bytes;
+                entry.files += 1;
+            }
+            (None, None) => {}
+        }
+    }
+
+    let mut notes: Vec<NoteStorage> = notes.into_values().collect();
+    notes.sort_by_key(|n| std::cmp::Reverse(n.bytes));
+
+    let mut missing_files: Vec<String> = segment_paths
+        .iter()
+        .filter(|p| !p.exists())
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    missing_files.sort();
+
+    Ok(StorageReport {
+        recordings_dir: dir.to_string_lossy().to_string(),
+        total_bytes,
+        notes,
+        orphans,
+        missing_files,
+    })
+}
+
+/// Get disk usage per note and in total, plus orphaned and missing files
+/// Locked confidential notes count towards the total but aren't listed.
+#[tauri::command]
+pub fn get_storage_report(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<StorageReport, String> {
+    ensure_app_unlocked(&privacy)?;
+    let mut report = scan_recordings(&recordings_dir(&app)?, &db)?;
+    report.notes.retain(|note| {
+        let confidential = db.is_note_confidential(&note.note_id).unwrap_or(true);
+        !is_note_hidden(&privacy, &note.note_id, confidential)
+    });
+    Ok(report)
+}
+
+/// Delete orphaned recordings files (all of them, or only the given paths)
+#[tauri::command]
+pub fn delete_orphan_files(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    paths: Option<Vec<String>>,
+) -> Result<CleanupResult, String> {
+    ensure_app_unlocked(&privacy)?;
+    // Re-scan so only files that are still orphans can be deleted
+    let orphans: Vec<PathBuf> = scan_recordings(&recordings_dir(&app)?, &db)?
+        .orphans
+        .into_iter()
+        .filter(|o| paths.as_ref().is_none_or(|p| p.contains(&o.path)))
+        .map(|o| PathBuf::from(o.path))
+        .collect();
+
+    let (files, bytes_freed) = remove_files(&orphans);
+    Ok(CleanupResult {
+        notes: 0,
+        files,
+        bytes_freed,
+    })
+}
+
+/// Delete a note's recorded audio, keeping its transcripts and summaries
+#[tauri::command]
+pub fn delete_note_audio(
+    app: AppHandle,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+    note_id: String,
+) -> Result<CleanupResult, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    if is_note_recording(&app, &note_id) {
+        return Err("This note is being recorded".to_string());
+    }
+    if db.has_open_transcription_job(&note_id).map_err(|e| e.to_string())? {
+        return Err("This note has a queued transcription job".to_string());
+    }
+    let (files, bytes_freed) = purge_note_audio(&recordings_dir(&app)?, &db, &note_id)?;
+    Ok(CleanupResult {
+        notes: usize::from(files > 0),
+        files,
+        bytes_freed,
+    })
+}
+
+/// Get the audio retention policy
+#[tauri::command]
+pub fn get_retention_policy(db: State<Database>) -> RetentionPolicy {
+    load_retention_policy(&db)
+}
+
+/// Save the audio retention policy
+#[tauri::command]
+pub fn set_retention_policy(policy: RetentionPolicy, db: State<Database>) -> Result<(), String> {
+    let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+    db.set_setting(RETENTION_POLICY_KEY, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Apply the retention policy now
+#[tauri::command]
+pub fn apply_retention_policy(app: AppHandle) -> Result<CleanupResult, String> {
+    apply_retention(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::JobMode;
+
+    const NOTE_A: &str = "0b7e1c1e-3f1a-4d6b-9a59-5a3c2d1e0f01";
+    const NOTE_B: &str = "6f0c2a8e-1b4d-4c3e-8f2a-9d7b5e3c1a02";
+    const DELETED: &str = "c3d2e1f0-a9b8-4c7d-8e6f-5a4b3c2d1e03";
+
+    /// A fresh recordings directory under the system temp dir
+    fn temp_recordings() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("netnote-storage-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn touch(dir: &Path, name: &str, bytes: usize) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; bytes]).unwrap();
+        path
+    }
+
+    fn add_note(db: &Database, id: &str, ended_days_ago: i64, audio_path: Option<&Path>) {
+        let ended = (Utc::now() - chrono::Duration::days(ended_days_ago)).to_rfc3339();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO notes (id, title, started_at, ended_at, audio_path, created_at, updated_at)
+                 VALUES (?1, ?1, ?2, ?2, ?3, ?2, ?2)",
+                rusqlite::params![id, ended, audio_path.map(|p| p.to_string_lossy().to_string())],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_file_note_id() {
+        let wav = format!("{}.wav", NOTE_A);
+        let segment = format!("{}_mic_seg2.wav", NOTE_A);
+        let peaks = format!("{}.peaks.json", NOTE_A);
+        assert_eq!(file_note_id(&wav), Some(NOTE_A));
+        assert_eq!(file_note_id(&segment), Some(NOTE_A));
+        assert_eq!(file_note_id(&peaks), Some(NOTE_A));
+
+        assert_eq!(file_note_id(NOTE_A), None);
+        assert_eq!(file_note_id(&format!("{}x.wav", NOTE_A)), None);
+        assert_eq!(file_note_id("recording.wav"), None);
+        assert_eq!(file_note_id(&format!("{}.wav", "z".repeat(36))), None);
+        assert_eq!(file_note_id(&format!("a{}", "ü".repeat(20))), None);
+    }
+
+    #[test]
+    fn test_scan_recordings_attributes_files_and_orphans() {
+        let db = Database::open_in_memory().unwrap();
+        let dir = temp_recordings();
+        add_note(&db, NOTE_A, 1, None);
+        add_note(&db, NOTE_B, 1, None);
+
+        let mic = touch(&dir, &format!("{}_mic_seg1.wav", NOTE_A), 100);
+        let system = dir.join(format!("{}_system_seg1.wav", NOTE_A));
+        db.add_audio_segment(
+            NOTE_A,
+            1,
+            &mic.to_string_lossy(),
+            Some(&system.to_string_lossy()),
+            0,
+            false,
+        )
+        .unwrap();
+        touch(&dir, &format!("{}.wav", NOTE_A), 50);
+        touch(&dir, &format!("{}.wav", NOTE_B), 10);
+        let stray_segment = touch(&dir, &format!("{}_mic_seg9.wav", NOTE_A), 5);
+        let deleted = touch(&dir, &format!("{}.wav", DELETED), 7);
+        let unknown = touch(&dir, "notes.txt", 3);
+
+        let report = scan_recordings(&dir, &db).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.total_bytes, 175);
+        let notes: Vec<_> = report
+            .notes
+            .iter()
+            .map(|n| (n.note_id.as_str(), n.files, n.bytes))
+            .collect();
+        assert_eq!(notes, [(NOTE_A, 2, 150), (NOTE_B, 1, 10)]);
+
+        let mut orphans: Vec<_> = report
+            .orphans
+            .iter()
+            .map(|o| (PathBuf::from(&o.path), o.reason))
+            .collect();
+        orphans.sort_by_key(|(_, reason)| *reason as u8);
+        assert_eq!(
+            orphans,
+            [
+                (deleted, OrphanReason::DeletedNote),
+                (stray_segment, OrphanReason::UnknownSegment),
+                (unknown, OrphanReason::Unrecognized),
+            ]
+        );
+        assert_eq!(report.missing_files, [system.to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn test_run_retention_removes_only_eligible_audio() {
+        let db = Database::open_in_memory().unwrap();
+        let dir = temp_recordings();
+
+        let old_audio = touch(&dir, &format!("{}.wav", NOTE_A), 100);
+        touch(&dir, &format!("{}.peaks.json", NOTE_A), 10);
+        add_note(&db, NOTE_A, 40, Some(&old_audio));
+        let recent_audio = touch(&dir, &format!("{}.wav", NOTE_B), 100);
+        add_note(&db, NOTE_B, 5, Some(&recent_audio));
+        let queued_audio = touch(&dir, &format!("{}.wav", DELETED), 100);
+        add_note(&db, DELETED, 40, Some(&queued_audio));
+        db.add_transcription_job(DELETED, "base", None, JobMode::Replace)
+            .unwrap();
+
+        // No policy saved: audio is kept forever
+        let kept = run_retention(&dir, &db, |_| false).unwrap();
+        assert_eq!(kept.files, 0);
+        assert!(old_audio.exists());
+
+        let policy = RetentionPolicy {
+            delete_audio_after_days: Some(30),
+        };
+        db.set_setting(
+            RETENTION_POLICY_KEY,
+            &serde_json::to_string(&policy).unwrap(),
+        )
+        .unwrap();
+
+        // A note that is being recorded again is left alone
+        let recording = run_retention(&dir, &db, |id| id == NOTE_A).unwrap();
+        assert_eq!(recording.files, 0);
+        assert!(old_audio.exists());
+
+        let result = run_retention(&dir, &db, |_| false).unwrap();
+        let remaining: Vec<_> = list_recordings(&dir)
+            .into_iter()
+            .map(|(p, _, _)| p)
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            (result.notes, result.files, result.bytes_freed),
+            (1, 2, 110)
+        );
+        assert!(!remaining.contains(&old_audio));
+        assert!(remaining.contains(&recent_audio));
+        assert!(remaining.contains(&queued_audio));
+        assert_eq!(db.get_note_audio_path(NOTE_A).unwrap(), None);
+    }
+}
@@ -1,18 +1,28 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Manager, State};
 use whisper_rs::{WhisperContext, WhisperContextParameters};
 
+use crate::audio::{aec, denoise};
 use crate::commands::audio::AudioState;
+use crate::commands::privacy::{ensure_app_unlocked, ensure_note_accessible, PrivacyState};
+use crate::db::models::{
+    AudioSegment, NewTranscriptSegment, Transcript, TranscriptSegment, TranscriptSource,
+};
 use crate::db::Database;
+use crate::events::EventSink;
+use crate::transcription::transcriber::load_audio_16k_mono;
 use crate::transcription::{
-    live, LiveTranscriptionState, ModelInfo, ModelManager, ModelSize, TranscriptionResult,
-    Transcriber,
+    live, LiveTranscriptionState, ModelInfo, ModelManager, TranscriptionError, TranscriptionResult,
+    TranscriptionSegment, Transcriber,
 };
 
+/// Settings key holding the model download mirror URL
+const MODEL_MIRROR_KEY: &str = "model_mirror_url";
+
 /// Check if a transcript segment should be skipped (blank audio, inaudible, etc.)
-fn should_skip_segment(text: &str) -> bool {
+pub(crate) fn should_skip_segment(text: &str) -> bool {
     let text_lower = text.to_lowercase();
     text_lower.contains("[blank_audio]")
         || text_lower.contains("[inaudible]")
@@ -24,12 +34,179 @@ fn should_skip_segment(text: &str) -> bool {
         || text.trim().is_empty()
 }
 
+/// Append the non-blank segments of a transcription, shifted by `offset_secs` onto the note timeline
+pub(crate) fn collect_segments(
+    out: &mut Vec<NewTranscriptSegment>,
+    note_id: &str,
+    segments: Vec<TranscriptionSegment>,
+    offset_secs: f64,
+    speaker: Option<&str>,
+) {
+    for segment in segments {
+        if !should_skip_segment(&segment.text) {
+            out.push(NewTranscriptSegment {
+                note_id: note_id.to_string(),
+                start_time: segment.start_time + offset_secs,
+                end_time: segment.end_time + offset_secs,
+                text: segment.text,
+                speaker: speaker.map(str::to_string),
+            });
+        }
+    }
+}
+
+/// Transcribe a mic recording, first cancelling speaker echo against `reference_path`
+/// (when AEC is enabled) and suppressing background noise (when enabled for transcription
+/// and the file was not already noise-suppressed while recording)
+pub(crate) fn transcribe_mic(
+    transcriber: &Transcriber,
+    mic_path: &Path,
+    reference_path: Option<&Path>,
+    denoised: bool,
+    language: Option<String>,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    let reference_path = reference_path.filter(|_| aec::is_aec_enabled());
+    let noise = denoise::noise_suppression_options();
+    let denoise_enabled = !denoised && noise.target.applies_to_transcription();
+
+    if reference_path.is_none() && !denoise_enabled {
+        return transcriber.transcribe(mic_path, language);
+    }
+
+    let mut samples = load_audio_16k_mono(mic_path)?;
+    if let Some(reference_path) = reference_path {
+        let reference = load_audio_16k_mono(reference_path)?;
+        samples = aec::cancel_echo(&samples, &reference, 16000);
+    }
+    if denoise_enabled {
+        samples = denoise::denoise(&samples, 16000, noise.reduction_db);
+    }
+    transcriber.transcribe_samples(&samples, language)
+}
+
+/// One recorded session of a note
+pub(crate) struct AudioSession {
+    /// `audio_segments` row, None for legacy single-session notes
+    pub segment_id: Option<i64>,
+    pub mic_path: PathBuf,
+    pub system_path: Option<PathBuf>,
+    /// Position of the session in the note timeline
+    pub offset_secs: f64,
+    /// End of the session in the note timeline (None = open-ended)
+    pub end_secs: Option<f64>,
+    pub refined: bool,
+    /// The mic file was noise-suppressed while recording
+    pub denoised: bool,
+}
+
+impl AudioSession {
+    /// A recording without segment rows, starting at the beginning of the note
+    fn single(mic_path: PathBuf, system_path: Option<PathBuf>) -> Self {
+        Self {
+            segment_id: None,
+            mic_path,
+            system_path,
+            offset_secs: 0.0,
+            end_secs: None,
+            refined: false,
+            denoised: false,
+        }
+    }
+}
+
+/// Sessions of a note's recorded segments (in segment order), skipping missing files
+fn sessions_from_segments(segments: Vec<AudioSegment>) -> Vec<AudioSession> {
+    let next_offsets: Vec<Option<i64>> = segments
+        .iter()
+        .skip(1)
+        .map(|s| Some(s.start_offset_ms))
+        .chain(std::iter::once(None))
+        .collect();
+
+    segments
+        .into_iter()
+        .zip(next_offsets)
+        .map(|(segment, next_offset)| AudioSession {
+            segment_id: Some(segment.id),
+            mic_path: PathBuf::from(segment.mic_path),
+            system_path: segment.system_path.map(PathBuf::from),
+            offset_secs: segment.start_offset_ms as f64 / 1000.0,
+            end_secs: segment
+                .duration_ms
+                .map(|d| segment.start_offset_ms + d)
+                .or(next_offset)
+                .map(|ms| ms as f64 / 1000.0),
+            refined: segment.refined,
+            denoised: segment.denoised,
+        })
+        .filter(|session| session.mic_path.exists())
+        .collect()
+}
+
+/// The audio files of a note, from its recorded segments or the legacy single-session files
+pub(crate) fn note_audio_sessions(app: &AppHandle, db: &Database, note_id: &str) -> Vec<AudioSession> {
+    let segments = db.get_audio_segments(note_id).unwrap_or_default();
+    if !segments.is_empty() {
+        return sessions_from_segments(segments);
+    }
+
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return Vec::new();
+    };
+    let recordings_dir = app_data_dir.join("recordings");
+    let mic_path = recordings_dir.join(format!("{}_mic.wav", note_id));
+    if !mic_path.exists() {
+        return Vec::new();
+    }
+    let system_path = recordings_dir.join(format!("{}_system.wav", note_id));
+
+    vec![AudioSession::single(mic_path, system_path.exists().then_some(system_path))]
+}
+
+/// Offset (in seconds) of the recorded segment a file belongs to, 0 when it isn't a segment file
+fn session_offset_for_path(db: &Database, note_id: &str, path: &str) -> f64 {
+    db.get_audio_segments(note_id)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|s| s.mic_path == path || s.system_path.as_deref() == Some(path))
+        .map(|s| s.start_offset_ms as f64 / 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// Merge per-session results into one, shifting each session's timestamps by its offset
+fn merge_session_results(results: Vec<(TranscriptionResult, f64)>) -> Option<TranscriptionResult> {
+    if results.is_empty() {
+        return None;
+    }
+
+    let mut merged = TranscriptionResult {
+        segments: Vec::new(),
+        full_text: String::new(),
+        language: None,
+    };
+    for (result, offset) in results {
+        merged.segments.extend(result.segments.into_iter().map(|mut segment| {
+            segment.start_time += offset;
+            segment.end_time += offset;
+            segment
+        }));
+        if !result.full_text.trim().is_empty() {
+            if !merged.full_text.is_empty() {
+                merged.full_text.push(' ');
+            }
+            merged.full_text.push_str(result.full_text.trim());
+        }
+        merged.language = merged.language.or(result.language);
+    }
+    Some(merged)
+}
+
 /// State for transcription operations
 pub struct TranscriptionState {
     pub model_manager: Mutex<Option<ModelManager>>,
     pub transcriber: Mutex<Option<Arc<Transcriber>>>,
     pub whisper_ctx: Mutex<Option<Arc<WhisperContext>>>,
-    pub current_model: Mutex<Option<ModelSize>>,
+    pub current_model: Mutex<Option<String>>,
     pub is_transcribing: AtomicBool,
     pub download_progress: Arc<AtomicU8>,
     pub is_downloading: AtomicBool,
@@ -51,10 +228,10 @@ impl Default for TranscriptionState {
     }
 }
 
-/// Initialize transcription state with app data directory
-pub fn init_transcription_state(app: &AppHandle) -> TranscriptionState {
-    let app_data_dir = app.path().app_data_dir().expect("Failed to get app data dir");
+/// Initialize transcription state with models kept under `app_data_dir`
+pub fn init_transcription_state(app_data_dir: PathBuf) -> TranscriptionState {
     let model_manager = ModelManager::new(app_data_dir);
+    model_manager.cleanup_stale_partials();
 
     TranscriptionState {
         model_manager: Mutex::new(Some(model_manager)),
@@ -81,8 +258,10 @@ pub fn list_models(state: State<TranscriptionState>) -> Result<Vec<ModelInfo>, S
 pub async fn download_model(
     size: String,
     state: State<'_, TranscriptionState>,
+    db: State<'_, Database>,
 ) -> Result<String, String> {
-    let model_size = parse_model_size(&size)?;
+    let model_id = resolve_model_id(&state, &size)?;
+    let mirror_url = db.get_setting(MODEL_MIRROR_KEY).ok().flatten();
 
     // Check if already downloading
     if state.is_downloading.swap(true, Ordering::SeqCst) {
@@ -108,7 +287,9 @@ pub async fn download_model(
     };
 
     // Perform download
-    let result = manager.download_model(model_size, on_progress).await;
+    let result = manager
+        .download_model(&model_id, mirror_url.as_deref(), on_progress)
+        .await;
 
     // Reset downloading flag
     state.is_downloading.store(false, Ordering::SeqCst);
@@ -119,6 +300,34 @@ pub async fn download_model(
     }
 }
 
+/// Register a local ggml `.bin` file as a model
+#[tauri::command]
+pub fn add_custom_model(
+    path: String,
+    name: Option<String>,
+    state: State<TranscriptionState>,
+) -> Result<ModelInfo, String> {
+    let manager = state.model_manager.lock().map_err(|e| e.to_string())?;
+    let manager = manager.as_ref().ok_or("Model manager not initialized")?;
+    manager
+        .add_custom_model(&PathBuf::from(path), name)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the mirror URL models are downloaded from (None = catalog URLs)
+#[tauri::command]
+pub fn get_model_mirror(db: State<Database>) -> Result<Option<String>, String> {
+    db.get_setting(MODEL_MIRROR_KEY).map_err(|e| e.to_string())
+}
+
+/// Set a mirror URL for model downloads, e.g. a local server on air-gapped machines
+/// Files are fetched from `<mirror>/<filename>`; an empty value restores the catalog URLs
+#[tauri::command]
+pub fn set_model_mirror(url: Option<String>, db: State<Database>) -> Result<(), String> {
+    let url = url.map(|u| u.trim().to_string()).unwrap_or_default();
+    db.set_setting(MODEL_MIRROR_KEY, &url).map_err(|e| e.to_string())
+}
+
 /// Get current download progress (0-100)
 #[tauri::command]
 pub fn get_download_progress(state: State<TranscriptionState>) -> u8 {
@@ -137,12 +346,12 @@ pub async fn delete_model(
     size: String,
     state: State<'_, TranscriptionState>,
 ) -> Result<(), String> {
-    let model_size = parse_model_size(&size)?;
+    let model_id = resolve_model_id(&state, &size)?;
 
     // Check if this model is currently loaded
     {
         let current = state.current_model.lock().map_err(|e| e.to_string())?;
-        if current.as_ref() == Some(&model_size) {
+        if current.as_ref() == Some(&model_id) {
             // Unload the transcriber
             let mut transcriber = state.transcriber.lock().map_err(|e| e.to_string())?;
             *transcriber = None;
@@ -158,18 +367,18 @@ pub async fn delete_model(
         guard.as_ref().ok_or("Model manager not initialized")?.clone()
     };
 
-    manager.delete_model(model_size).await.map_err(|e| e.to_string())
+    manager.delete_model(&model_id).await.map_err(|e| e.to_string())
 }
 
 /// Load a model for transcription
 #[tauri::command]
 pub fn load_model(size: String, state: State<TranscriptionState>) -> Result<(), String> {
-    let model_size = parse_model_size(&size)?;
+    let model_id = resolve_model_id(&state, &size)?;
 
     // Check if already loaded
     {
         let current = state.current_model.lock().map_err(|e| e.to_string())?;
-        if current.as_ref() == Some(&model_size) {
+        if current.as_ref() == Some(&model_id) {
             return Ok(()); // Already loaded
         }
     }
@@ -178,7 +387,9 @@ pub fn load_model(size: String, state: State<TranscriptionState>) -> Result<(),
     let model_path = {
         let manager = state.model_manager.lock().map_err(|e| e.to_string())?;
         let manager = manager.as_ref().ok_or("Model manager not initialized")?;
-        manager.model_path(model_size)
+        manager
+            .model_path(&model_id)
+            .ok_or_else(|| format!("Unknown model: {}", size))?
     };
 
     if !model_path.exists() {
@@ -210,7 +421,7 @@ pub fn load_model(size: String, state: State<TranscriptionState>) -> Result<(),
     // Update current model
     {
         let mut current = state.current_model.lock().map_err(|e| e.to_string())?;
-        *current = Some(model_size);
+        *current = Some(model_id);
     }
 
     Ok(())
@@ -220,7 +431,7 @@ pub fn load_model(size: String, state: State<TranscriptionState>) -> Result<(),
 #[tauri::command]
 pub fn get_loaded_model(state: State<TranscriptionState>) -> Option<String> {
     let current = state.current_model.lock().ok()?;
-    current.as_ref().map(|m| m.as_str().to_string())
+    current.clone()
 }
 
 /// Transcribe an audio file
@@ -232,7 +443,10 @@ pub async fn transcribe_audio(
     speaker: Option<String>,
     state: State<'_, TranscriptionState>,
     db: State<'_, Database>,
+    privacy: State<'_, PrivacyState>,
 ) -> Result<TranscriptionResult, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+
     // Check if already transcribing
     if state.is_transcribing.swap(true, Ordering::SeqCst) {
         return Err("Already transcribing".to_string());
@@ -251,9 +465,17 @@ pub async fn transcribe_audio(
     };
 
     // Run transcription in a blocking task (since whisper-rs is synchronous)
+    // Mic recordings (no speaker or "You") go through the mic clean-up stages
     let path = PathBuf::from(&audio_path);
     let lang = language.clone();
-    let result = tokio::task::spawn_blocking(move || transcriber.transcribe(&path, lang))
+    let is_mic = speaker.as_deref().is_none_or(|s| s == "You");
+    let result = tokio::task::spawn_blocking(move || {
+        if is_mic {
+            transcribe_mic(&transcriber, &path, None, false, lang)
+        } else {
+            transcriber.transcribe(&path, lang)
+        }
+    })
         .await
         .map_err(|e| {
             state.is_transcribing.store(false, Ordering::SeqCst);
@@ -264,15 +486,24 @@ pub async fn transcribe_audio(
             e.to_string()
         })?;
 
-    // Save segments to database (skip blank/noise segments)
-    for segment in &result.segments {
-        if !should_skip_segment(&segment.text) {
-            db.add_transcript_segment(&note_id, segment.start_time, segment.end_time, &segment.text, speaker.as_deref())
-                .map_err(|e| e.to_string())?;
-        }
-    }
-
     state.is_transcribing.store(false, Ordering::SeqCst);
+
+    // Save as a new active transcript version (skip blank/noise segments),
+    // placed on the note timeline when the file is one of its recorded segments
+    let offset = session_offset_for_path(&db, &note_id, &audio_path);
+    let mut segments = Vec::new();
+    collect_segments(&mut segments, &note_id, result.segments.clone(), offset, speaker.as_deref());
+    let model_id = state.current_model.lock().ok().and_then(|m| m.clone());
+    db.save_transcript(
+        &note_id,
+        TranscriptSource::Batch,
+        model_id.as_deref(),
+        language.as_deref(),
+        &segments,
+        true,
+    )
+    .map_err(|e| e.to_string())?;
+
     Ok(result)
 }
 
@@ -299,6 +530,9 @@ pub struct DualTranscriptionResult {
 /// - mic_path: Path to the microphone recording (labeled as "You")
 /// - system_path: Optional path to system audio recording (labeled as "Others")
 /// - note_id: The note ID to associate segments with
+///
+/// Notes recorded in several sessions (pause/resume/continue) are transcribed session by
+/// session, with timestamps shifted by each segment's `start_offset_ms`.
 #[tauri::command]
 pub async fn transcribe_dual_audio(
     mic_path: String,
@@ -307,7 +541,10 @@ pub async fn transcribe_dual_audio(
     language: Option<String>,
     state: State<'_, TranscriptionState>,
     db: State<'_, Database>,
+    privacy: State<'_, PrivacyState>,
 ) -> Result<DualTranscriptionResult, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+
     // Check if already transcribing
     if state.is_transcribing.swap(true, Ordering::SeqCst) {
         return Err("Already transcribing".to_string());
@@ -325,13 +562,49 @@ pub async fn transcribe_dual_audio(
         })?
     };
 
-    let mut total_segments = 0;
+    // Transcribe every recorded session of the note when it has segments,
+    // otherwise just the given pair of files
+    let recorded = db.get_audio_segments(&note_id).map_err(|e| {
+        state.is_transcribing.store(false, Ordering::SeqCst);
+        e.to_string()
+    })?;
+    let sessions = if recorded.is_empty() {
+        vec![AudioSession::single(
+            PathBuf::from(&mic_path),
+            system_path.as_ref().map(PathBuf::from),
+        )]
+    } else {
+        sessions_from_segments(recorded)
+    };
 
-    // Transcribe mic audio (labeled as "You")
-    let mic_path_buf = PathBuf::from(&mic_path);
-    let transcriber_clone = transcriber.clone();
     let lang = language.clone();
-    let mic_result = tokio::task::spawn_blocking(move || transcriber_clone.transcribe(&mic_path_buf, lang))
+    let results = tokio::task::spawn_blocking(move || {
+        let mut mic_results = Vec::new();
+        let mut system_results = Vec::new();
+
+        for session in &sessions {
+            // Mic audio (labeled as "You"), echo-cancelled against the session's system audio
+            let reference_path = session.system_path.as_deref().filter(|p| p.exists());
+            let result = transcribe_mic(
+                &transcriber,
+                &session.mic_path,
+                reference_path,
+                session.denoised,
+                lang.clone(),
+            )?;
+            mic_results.push((result, session.offset_secs));
+
+            // System audio (labeled as "Others"); a failure only loses this track
+            if let Some(sys_path) = &session.system_path {
+                match transcriber.transcribe(sys_path, lang.clone()) {
+                    Ok(result) => system_results.push((result, session.offset_secs)),
+                    Err(e) => eprintln!("Failed to transcribe system audio: {}", e),
+                }
+            }
+        }
+
+        Ok::<_, TranscriptionError>((mic_results, system_results))
+    })
         .await
         .map_err(|e| {
             state.is_transcribing.store(false, Ordering::SeqCst);
@@ -342,60 +615,36 @@ pub async fn transcribe_dual_audio(
             e.to_string()
         })?;
 
-    // Save mic segments to database with "You" speaker label (skip blank/noise)
-    for segment in &mic_result.segments {
-        if !should_skip_segment(&segment.text) {
-            db.add_transcript_segment(
-                &note_id,
-                segment.start_time,
-                segment.end_time,
-                &segment.text,
-                Some("You"),
-            )
-            .map_err(|e| e.to_string())?;
-            total_segments += 1;
-        }
+    let (mic_results, system_results) = results;
+    let mic_result = merge_session_results(mic_results).ok_or_else(|| {
+        state.is_transcribing.store(false, Ordering::SeqCst);
+        "No recorded audio found for this note".to_string()
+    })?;
+    let system_result = merge_session_results(system_results);
+
+    // Timestamps are already on the note timeline; skip blank/noise segments
+    let mut segments = Vec::new();
+    collect_segments(&mut segments, &note_id, mic_result.segments.clone(), 0.0, Some("You"));
+    if let Some(result) = &system_result {
+        collect_segments(&mut segments, &note_id, result.segments.clone(), 0.0, Some("Others"));
     }
 
-    // Transcribe system audio if provided (labeled as "Others")
-    let system_result = if let Some(sys_path) = system_path {
-        let sys_path_buf = PathBuf::from(&sys_path);
-        let transcriber_clone = transcriber.clone();
-        let lang = language.clone();
-
-        match tokio::task::spawn_blocking(move || transcriber_clone.transcribe(&sys_path_buf, lang)).await {
-            Ok(Ok(result)) => {
-                // Save system segments to database with "Others" speaker label (skip blank/noise)
-                for segment in &result.segments {
-                    if !should_skip_segment(&segment.text) {
-                        db.add_transcript_segment(
-                            &note_id,
-                            segment.start_time,
-                            segment.end_time,
-                            &segment.text,
-                            Some("Others"),
-                        )
-                        .map_err(|e| e.to_string())?;
-                        total_segments += 1;
-                    }
-                }
-                Some(result)
-            }
-            Ok(Err(e)) => {
-                eprintln!("Failed to transcribe system audio: {}", e);
-                None
-            }
-            Err(e) => {
-                eprintln!("Failed to spawn system audio transcription task: {}", e);
-                None
-            }
-        }
-    } else {
-        None
-    };
-
     state.is_transcribing.store(false, Ordering::SeqCst);
 
+    // Store the run as a new active version so re-runs don't duplicate the live transcript
+    segments.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    let total_segments = segments.len();
+    let model_id = state.current_model.lock().ok().and_then(|m| m.clone());
+    db.save_transcript(
+        &note_id,
+        TranscriptSource::Batch,
+        model_id.as_deref(),
+        language.as_deref(),
+        &segments,
+        true,
+    )
+    .map_err(|e| e.to_string())?;
+
     Ok(DualTranscriptionResult {
         mic_result,
         system_result,
@@ -403,16 +652,19 @@ pub async fn transcribe_dual_audio(
     })
 }
 
-/// Get transcript segments for a note
+/// Get the segments of a note's active transcript
 #[tauri::command]
 pub fn get_transcript(
     note_id: String,
     db: State<Database>,
-) -> Result<Vec<crate::db::models::TranscriptSegment>, String> {
+    privacy: State<PrivacyState>,
+) -> Result<Vec<TranscriptSegment>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
     db.get_transcript_segments(&note_id).map_err(|e| e.to_string())
 }
 
 /// Add a transcript segment directly (for seeding/testing)
+/// The segment goes into the active imported transcript, or a new imported one
 #[tauri::command]
 pub fn add_transcript_segment(
     note_id: String,
@@ -421,11 +673,74 @@ pub fn add_transcript_segment(
     text: String,
     speaker: Option<String>,
     db: State<Database>,
+    privacy: State<PrivacyState>,
 ) -> Result<i64, String> {
-    db.add_transcript_segment(&note_id, start_time, end_time, &text, speaker.as_deref())
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    let transcript_id = db
+        .get_or_create_active_transcript(&note_id, TranscriptSource::Import, None, None)
+        .map_err(|e| e.to_string())?;
+    db.add_transcript_segment(transcript_id, &note_id, start_time, end_time, &text, speaker.as_deref())
         .map_err(|e| e.to_string())
 }
 
+/// List the transcript versions of a note, newest first
+#[tauri::command]
+pub fn list_transcripts(
+    note_id: String,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<Vec<Transcript>, String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.get_transcripts(&note_id).map_err(|e| e.to_string())
+}
+
+/// Get the segments of one transcript version (e.g. to compare live and post-meeting runs)
+#[tauri::command]
+pub fn get_transcript_version(
+    transcript_id: i64,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<Vec<TranscriptSegment>, String> {
+    ensure_app_unlocked(&privacy)?;
+    let segments = db
+        .get_transcript_version_segments(transcript_id)
+        .map_err(|e| e.to_string())?;
+    if let Some(segment) = segments.first() {
+        ensure_note_accessible(&privacy, &db, &segment.note_id)?;
+    }
+    Ok(segments)
+}
+
+/// Make a transcript version the one used by summaries, search and export
+#[tauri::command]
+pub fn set_active_transcript(
+    note_id: String,
+    transcript_id: i64,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<(), String> {
+    ensure_note_accessible(&privacy, &db, &note_id)?;
+    db.set_active_transcript(&note_id, transcript_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a transcript version
+#[tauri::command]
+pub fn delete_transcript(
+    transcript_id: i64,
+    db: State<Database>,
+    privacy: State<PrivacyState>,
+) -> Result<(), String> {
+    ensure_app_unlocked(&privacy)?;
+    if let Some(note_id) = db
+        .get_transcript_note_id(transcript_id)
+        .map_err(|e| e.to_string())?
+    {
+        ensure_note_accessible(&privacy, &db, &note_id)?;
+    }
+    db.delete_transcript(transcript_id).map_err(|e| e.to_string())
+}
+
 /// Start live transcription during recording
 #[tauri::command]
 pub async fn start_live_transcription(
@@ -443,8 +758,17 @@ pub async fn start_live_transcription(
 
     let recording_state = audio_state.recording.clone();
     let live_state = state.live_state.clone();
+    let model_id = state.current_model.lock().ok().and_then(|m| m.clone());
 
-    live::start_live_transcription(app, note_id, language, recording_state, live_state, whisper_ctx)
+    live::start_live_transcription(
+        app,
+        note_id,
+        language,
+        recording_state,
+        live_state,
+        whisper_ctx,
+        model_id,
+    )
         .await
         .map_err(|e| e.to_string())
 }
@@ -467,8 +791,9 @@ pub async fn stop_live_transcription(
         segments: vec![],
         is_final: true,
         audio_source: crate::transcription::AudioSource::Mic, // Default for final event
+        transcript_id: None,
     };
-    let _ = app.emit("transcription-update", event);
+    app.emit_event(live::TRANSCRIPTION_UPDATE_EVENT, &event);
 
     Ok(result)
 }
@@ -479,13 +804,14 @@ pub fn is_live_transcribing(state: State<TranscriptionState>) -> bool {
     state.live_state.is_running.load(Ordering::SeqCst)
 }
 
-fn parse_model_size(size: &str) -> Result<ModelSize, String> {
-    match size.to_lowercase().as_str() {
-        "tiny" => Ok(ModelSize::Tiny),
-        "base" => Ok(ModelSize::Base),
-        "small" => Ok(ModelSize::Small),
-        "medium" => Ok(ModelSize::Medium),
-        "large" => Ok(ModelSize::Large),
-        _ => Err(format!("Invalid model size: {}", size)),
-    }
+/// Validate a model id against the catalog and the registered custom models
+fn resolve_model_id(state: &TranscriptionState, id: &str) -> Result<String, String> {
+    let manager = state.model_manager.lock().map_err(|e| e.to_string())?;
+    let manager = manager.as_ref().ok_or("Model manager not initialized")?;
+
+    // Catalog ids used to be case-insensitive sizes ("Tiny")
+    [id.to_string(), id.to_lowercase()]
+        .into_iter()
+        .find(|candidate| manager.is_known(candidate))
+        .ok_or_else(|| format!("Unknown model: {}", id))
 }
@@ -0,0 +1,284 @@
+//! Encryption at rest for the library (database and recordings)
+//!
+//! A random 256-bit library key encrypts the SQLCipher database and every
+//! recording. The key is kept in the OS keyring, or wrapped with a key derived
+//! from a passphrase (Argon2id) and stored in `encryption.json` next to the
+//! database. That file is the only plaintext the library needs to start.
+
+pub mod stream;
+
+pub use stream::{seal_if_enabled, LibraryReader};
+
+use std::path::Path;
+use std::sync::RwLock;
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// File in the app data dir describing how the library key is stored
+pub const CONFIG_FILE: &str = "encryption.json";
+
+/// Keyring entry holding the library key
+const KEYRING_SERVICE: &str = "com.netnote.app";
+const KEYRING_USER: &str = "library-key";
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const XNONCE_LEN: usize = 24;
+
+/// Key of the unlocked library, shared by the database and audio file access
+static LIBRARY_KEY: RwLock<Option<LibraryKey>> = RwLock::new(None);
+
+#[derive(Error, Debug)]
+pub enum CryptoError {
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+
+    #[error("The library is locked")]
+    Locked,
+
+    #[error("Encrypted file is corrupted or was modified")]
+    Corrupted,
+
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(String),
+
+    #[error("Keyring error: {0}")]
+    Keyring(#[from] keyring::Error),
+
+    #[error("Invalid encryption config: {0}")]
+    Config(#[from] serde_json::Error),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// The 256-bit key protecting the library, wiped from memory on drop
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct LibraryKey([u8; KEY_LEN]);
+
+impl LibraryKey {
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+
+    /// Raw-key literal for SQLCipher's `PRAGMA key`
+    pub fn sqlcipher_key(&self) -> String {
+        format!("\"x'{}'\"", to_hex(&self.0))
+    }
+
+    fn from_slice(bytes: &[u8]) -> Option<Self> {
+        let key: [u8; KEY_LEN] = bytes.try_into().ok()?;
+        Some(Self(key))
+    }
+}
+
+/// Where the library key is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// macOS Keychain, Windows Credential Manager or the Secret Service
+    Keyring,
+    /// Wrapped with a passphrase that is asked for on every start
+    Passphrase,
+}
+
+/// Contents of `encryption.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    pub version: u32,
+    pub key_source: KeySource,
+    /// Argon2id salt (hex), passphrase mode only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    /// Nonce followed by the encrypted library key (hex), passphrase mode only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wrapped_key: Option<String>,
+    /// Set until every existing file of the library has been encrypted, so an
+    /// interrupted migration resumes on the next start
+    #[serde(default)]
+    pub migration_pending: bool,
+}
+
+impl EncryptionConfig {
+    /// Read the config, `None` when the library is not encrypted
+    pub fn load(dir: &Path) -> Result<Option<Self>, CryptoError> {
+        match std::fs::read_to_string(dir.join(CONFIG_FILE)) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the config atomically
+    pub fn save(&self, dir: &Path) -> Result<(), CryptoError> {
+        let path = dir.join(CONFIG_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Store `key` in the OS keyring
+    pub fn with_keyring(key: &LibraryKey) -> Result<Self, CryptoError> {
+        keyring_entry()?.set_secret(key.as_bytes())?;
+        Ok(Self {
+            version: 1,
+            key_source: KeySource::Keyring,
+            salt: None,
+            wrapped_key: None,
+            migration_pending: false,
+        })
+    }
+
+    /// Wrap `key` with a key derived from `passphrase`
+    pub fn with_passphrase(key: &LibraryKey, passphrase: &str) -> Result<Self, CryptoError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; XNONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let kek = derive_key(passphrase, &salt)?;
+        let cipher = XChaCha20Poly1305::new(kek.as_bytes().into());
+        let wrapped = cipher
+            .encrypt(XNonce::from_slice(&nonce), key.as_bytes().as_slice())
+            .map_err(|_| CryptoError::Corrupted)?;
+
+        Ok(Self {
+            version: 1,
+            key_source: KeySource::Passphrase,
+            salt: Some(to_hex(&salt)),
+            wrapped_key: Some(to_hex(&[nonce.as_slice(), &wrapped].concat())),
+            migration_pending: false,
+        })
+    }
+
+    /// Recover the library key (the passphrase is only used in passphrase mode)
+    pub fn unlock(&self, passphrase: Option<&str>) -> Result<LibraryKey, CryptoError> {
+        match self.key_source {
+            KeySource::Keyring => {
+                let secret = keyring_entry()?.get_secret()?;
+                LibraryKey::from_slice(&secret).ok_or(CryptoError::Corrupted)
+            }
+            KeySource::Passphrase => {
+                let passphrase = passphrase.ok_or(CryptoError::Locked)?;
+                let salt = self.salt.as_deref().and_then(from_hex);
+                let wrapped = self.wrapped_key.as_deref().and_then(from_hex);
+                let (Some(salt), Some(wrapped)) = (salt, wrapped) else {
+                    return Err(CryptoError::Corrupted);
+                };
+                if wrapped.len() <= XNONCE_LEN {
+                    return Err(CryptoError::Corrupted);
+                }
+
+                let kek = derive_key(passphrase, &salt)?;
+                let cipher = XChaCha20Poly1305::new(kek.as_bytes().into());
+                let (nonce, ciphertext) = wrapped.split_at(XNONCE_LEN);
+                let mut plain = cipher
+                    .decrypt(XNonce::from_slice(nonce), ciphertext)
+                    .map_err(|_| CryptoError::WrongPassphrase)?;
+                let key = LibraryKey::from_slice(&plain).ok_or(CryptoError::Corrupted);
+                plain.zeroize();
+                key
+            }
+        }
+    }
+}
+
+fn keyring_entry() -> Result<keyring::Entry, CryptoError> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?)
+}
+
+/// Argon2id key derivation with the crate's default (OWASP) parameters
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LibraryKey, CryptoError> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    Ok(LibraryKey(key))
+}
+
+/// Hash a passphrase for storage (Argon2id PHC string)
+pub fn hash_passphrase(passphrase: &str) -> Result<String, CryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let salt =
+        SaltString::encode_b64(&salt).map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))
+}
+
+/// Check a passphrase against a hash from [`hash_passphrase`]
+pub fn verify_passphrase(passphrase: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|parsed| {
+        Argon2::default()
+            .verify_password(passphrase.as_bytes(), &parsed)
+            .is_ok()
+    })
+}
+
+/// Make `key` the key used for reading and writing library files
+pub fn set_library_key(key: Option<LibraryKey>) {
+    if let Ok(mut current) = LIBRARY_KEY.write() {
+        *current = key;
+    }
+}
+
+/// The unlocked library key, `None` when encryption is off or the library is locked
+pub fn library_key() -> Option<LibraryKey> {
+    LIBRARY_KEY.read().ok().and_then(|key| key.clone())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_wrapping_round_trips() {
+        let key = LibraryKey::generate();
+        let config = EncryptionConfig::with_passphrase(&key, "correct horse").unwrap();
+
+        let unlocked = config.unlock(Some("correct horse")).unwrap();
+        assert_eq!(unlocked.as_bytes(), key.as_bytes());
+        assert!(matches!(
+            config.unlock(Some("wrong")),
+            Err(CryptoError::WrongPassphrase)
+        ));
+        assert!(matches!(config.unlock(None), Err(CryptoError::Locked)));
+    }
+
+    #[test]
+    fn test_passphrase_hash_verifies() {
+        let hash = hash_passphrase("open sesame").unwrap();
+        assert!(verify_passphrase("open sesame", &hash));
+        assert!(!verify_passphrase("open sesame!", &hash));
+        assert!(!verify_passphrase("open sesame", "not a hash"));
+    }
+}
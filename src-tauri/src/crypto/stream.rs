@@ -0,0 +1,337 @@
+//! Chunked authenticated encryption for recordings
+//!
+//! Layout: `MAGIC | nonce prefix | chunk...`. Each chunk holds up to
+//! [`CHUNK_SIZE`] plaintext bytes plus a Poly1305 tag and is sealed with
+//! XChaCha20-Poly1305 under the nonce `prefix | chunk index (BE32) | last flag`
+//! (the STREAM construction). Chunks can be decrypted on their own, so readers
+//! can seek, while reordering, truncation and appending are still detected.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use super::{library_key, CryptoError, LibraryKey};
+
+/// Identifies an encrypted library file (format version 1)
+const MAGIC: &[u8; 8] = b"NNENC\x01\0\0";
+const PREFIX_LEN: usize = 19;
+const HEADER_LEN: u64 = (MAGIC.len() + PREFIX_LEN) as u64;
+const TAG_LEN: usize = 16;
+
+/// Plaintext bytes per chunk
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+fn chunk_nonce(prefix: &[u8; PREFIX_LEN], index: u32, last: bool) -> XNonce {
+    let mut nonce = [0u8; 24];
+    nonce[..PREFIX_LEN].copy_from_slice(prefix);
+    nonce[PREFIX_LEN..PREFIX_LEN + 4].copy_from_slice(&index.to_be_bytes());
+    nonce[23] = last as u8;
+    XNonce::from(nonce)
+}
+
+fn header(prefix: &[u8; PREFIX_LEN]) -> Vec<u8> {
+    [MAGIC.as_slice(), prefix.as_slice()].concat()
+}
+
+/// Whether a file starts with the encrypted-file magic
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut magic = [0u8; MAGIC.len()];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && &magic == MAGIC
+}
+
+/// Read until `buf` is full or the reader is exhausted
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypt `src` into `dst`
+pub fn encrypt_file(key: &LibraryKey, src: &Path, dst: &Path) -> Result<(), CryptoError> {
+    let cipher = XChaCha20Poly1305::new(key.as_bytes().into());
+    let mut prefix = [0u8; PREFIX_LEN];
+    OsRng.fill_bytes(&mut prefix);
+    let aad = header(&prefix);
+
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut writer = BufWriter::new(File::create(dst)?);
+    writer.write_all(&aad)?;
+
+    // Read one chunk ahead so the final chunk can be flagged as last
+    let mut current = vec![0u8; CHUNK_SIZE];
+    let mut next = vec![0u8; CHUNK_SIZE];
+    let mut current_len = read_full(&mut reader, &mut current)?;
+    let mut index: u32 = 0;
+    loop {
+        let next_len = if current_len == CHUNK_SIZE {
+            read_full(&mut reader, &mut next)?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+
+        let sealed = cipher
+            .encrypt(
+                &chunk_nonce(&prefix, index, last),
+                Payload {
+                    msg: &current[..current_len],
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| CryptoError::Corrupted)?;
+        writer.write_all(&sealed)?;
+
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+        index = index.checked_add(1).ok_or(CryptoError::Corrupted)?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+    Ok(())
+}
+
+/// Encrypt a plaintext file in place (via a temporary file and rename).
+/// Returns false when the file was already encrypted.
+pub fn seal_in_place(key: &LibraryKey, path: &Path) -> Result<bool, CryptoError> {
+    if is_encrypted(path) {
+        return Ok(false);
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".sealing");
+    let tmp = PathBuf::from(tmp);
+
+    if let Err(e) = encrypt_file(key, path, &tmp) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(true)
+}
+
+/// Encrypt a finished library file in place when the library is encrypted
+pub fn seal_if_enabled(path: &Path) -> Result<(), CryptoError> {
+    match library_key() {
+        Some(key) => seal_in_place(&key, path).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Random-access reader over an encrypted file
+pub struct EncryptedReader {
+    file: File,
+    cipher: XChaCha20Poly1305,
+    prefix: [u8; PREFIX_LEN],
+    chunks: u64,
+    len: u64,
+    pos: u64,
+    /// Index and plaintext of the most recently decrypted chunk
+    cached: Option<(u64, Vec<u8>)>,
+}
+
+impl EncryptedReader {
+    pub fn open(key: &LibraryKey, path: &Path) -> Result<Self, CryptoError> {
+        let mut file = File::open(path)?;
+        let mut head = [0u8; HEADER_LEN as usize];
+        file.read_exact(&mut head)
+            .map_err(|_| CryptoError::Corrupted)?;
+        if &head[..MAGIC.len()] != MAGIC {
+            return Err(CryptoError::Corrupted);
+        }
+        let mut prefix = [0u8; PREFIX_LEN];
+        prefix.copy_from_slice(&head[MAGIC.len()..]);
+
+        let body = file.metadata()?.len().saturating_sub(HEADER_LEN);
+        let sealed_chunk = (CHUNK_SIZE + TAG_LEN) as u64;
+        let chunks = body.div_ceil(sealed_chunk);
+        let remainder = body % sealed_chunk;
+        if chunks == 0 || (remainder != 0 && remainder < TAG_LEN as u64) {
+            return Err(CryptoError::Corrupted);
+        }
+
+        Ok(Self {
+            file,
+            cipher: XChaCha20Poly1305::new(key.as_bytes().into()),
+            prefix,
+            chunks,
+            len: body - chunks * TAG_LEN as u64,
+            pos: 0,
+            cached: None,
+        })
+    }
+
+    /// Plaintext length
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn load_chunk(&mut self, index: u64) -> io::Result<()> {
+        if self.cached.as_ref().is_some_and(|(i, _)| *i == index) {
+            return Ok(());
+        }
+
+        let sealed_chunk = (CHUNK_SIZE + TAG_LEN) as u64;
+        let mut sealed = vec![0u8; sealed_chunk as usize];
+        self.file
+            .seek(SeekFrom::Start(HEADER_LEN + index * sealed_chunk))?;
+        let n = read_full(&mut self.file, &mut sealed)?;
+        sealed.truncate(n);
+
+        let aad = header(&self.prefix);
+        let last = index + 1 == self.chunks;
+        let plain = self
+            .cipher
+            .decrypt(
+                &chunk_nonce(&self.prefix, index as u32, last),
+                Payload {
+                    msg: &sealed,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, CryptoError::Corrupted))?;
+        self.cached = Some((index, plain));
+        Ok(())
+    }
+}
+
+impl Read for EncryptedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let index = self.pos / CHUNK_SIZE as u64;
+        self.load_chunk(index)?;
+
+        let Some((_, chunk)) = &self.cached else {
+            return Ok(0);
+        };
+        let offset = (self.pos % CHUNK_SIZE as u64) as usize;
+        let n = buf.len().min(chunk.len().saturating_sub(offset));
+        buf[..n].copy_from_slice(&chunk[offset..offset + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for EncryptedReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => p as i128,
+            SeekFrom::End(d) => self.len as i128 + d as i128,
+            SeekFrom::Current(d) => self.pos as i128 + d as i128,
+        };
+        if target < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before start of file",
+            ));
+        }
+        self.pos = target as u64;
+        Ok(self.pos)
+    }
+}
+
+/// A library file opened for reading, decrypted on the fly when needed
+pub enum LibraryReader {
+    Plain(BufReader<File>),
+    Encrypted(EncryptedReader),
+}
+
+impl LibraryReader {
+    /// Open a file, using the unlocked library key if the file is encrypted
+    pub fn open(path: &Path) -> Result<Self, CryptoError> {
+        if is_encrypted(path) {
+            let key = library_key().ok_or(CryptoError::Locked)?;
+            Ok(Self::Encrypted(EncryptedReader::open(&key, path)?))
+        } else {
+            Ok(Self::Plain(BufReader::new(File::open(path)?)))
+        }
+    }
+
+    /// Plaintext length
+    pub fn len(&self) -> io::Result<u64> {
+        match self {
+            Self::Plain(reader) => Ok(reader.get_ref().metadata()?.len()),
+            Self::Encrypted(reader) => Ok(reader.len()),
+        }
+    }
+}
+
+impl Read for LibraryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            Self::Encrypted(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for LibraryReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::Plain(reader) => reader.seek(pos),
+            Self::Encrypted(reader) => reader.seek(pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_file_round_trips_and_seeks() {
+        let dir = std::env::temp_dir().join(format!("netnote-enc-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audio.wav");
+
+        // Two full chunks and a partial one
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 1000)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let key = LibraryKey::generate();
+        assert!(seal_in_place(&key, &path).unwrap());
+        assert!(is_encrypted(&path));
+        assert!(!seal_in_place(&key, &path).unwrap());
+
+        let mut reader = EncryptedReader::open(&key, &path).unwrap();
+        assert_eq!(reader.len(), data.len() as u64);
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, data);
+
+        // Reads across a chunk boundary after seeking
+        let mut window = [0u8; 100];
+        reader
+            .seek(SeekFrom::Start(CHUNK_SIZE as u64 - 50))
+            .unwrap();
+        reader.read_exact(&mut window).unwrap();
+        assert_eq!(&window[..], &data[CHUNK_SIZE - 50..CHUNK_SIZE + 50]);
+
+        // Truncating the last chunk is detected
+        let sealed = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &sealed[..sealed.len() - 1000 - TAG_LEN]).unwrap();
+        let mut truncated = EncryptedReader::open(&key, &path).unwrap();
+        assert!(truncated.read_to_end(&mut Vec::new()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,274 @@
+//! Imported calendar events and the notes recorded for them
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::calendar::IcsEvent;
+use crate::db::models::CalendarEvent;
+use crate::db::people::link_participants;
+use crate::db::Database;
+
+/// A recording started this long before an event still belongs to it
+pub const EARLY_START: Duration = Duration::minutes(10);
+
+/// Events without an end are matched as if they lasted this long
+const DEFAULT_LENGTH: Duration = Duration::minutes(30);
+
+/// Title the app gives notes before they are named
+const PLACEHOLDER_TITLE: &str = "Untitled";
+
+const EVENT_COLUMNS: &str =
+    "id, title, description, location, participants, starts_at, ends_at, source, note_id";
+
+fn row_to_event(row: &rusqlite::Row) -> rusqlite::Result<CalendarEvent> {
+    Ok(CalendarEvent {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        location: row.get(3)?,
+        participants: row.get(4)?,
+        starts_at: row
+            .get::<_, String>(5)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        ends_at: row
+            .get::<_, String>(6)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        source: row.get(7)?,
+        note_id: row.get(8)?,
+    })
+}
+
+/// Get an event by ID
+pub fn get_event(conn: &Connection, id: &str) -> rusqlite::Result<Option<CalendarEvent>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM calendar_events WHERE id = ?1",
+            EVENT_COLUMNS
+        ),
+        [id],
+        row_to_event,
+    )
+    .optional()
+}
+
+/// The event without a note yet that a recording started at `at` belongs to:
+/// running at that time, or starting within [`EARLY_START`]. The event
+/// starting closest to `at` wins.
+pub fn find_event_at(
+    conn: &Connection,
+    at: DateTime<Utc>,
+) -> rusqlite::Result<Option<CalendarEvent>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM calendar_events
+             WHERE note_id IS NULL
+               AND starts_at <= ?1
+               AND (ends_at >= ?2 OR (ends_at = starts_at AND starts_at >= ?3))
+             ORDER BY ABS(julianday(starts_at) - julianday(?2))
+             LIMIT 1",
+            EVENT_COLUMNS
+        ),
+        params![
+            (at + EARLY_START).to_rfc3339(),
+            at.to_rfc3339(),
+            (at - DEFAULT_LENGTH).to_rfc3339()
+        ],
+        row_to_event,
+    )
+    .optional()
+}
+
+/// Link a note to an event (`None` unlinks the note), replacing any previous link of the note
+pub fn link_event(
+    conn: &Connection,
+    note_id: &str,
+    event_id: Option<&str>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE calendar_events SET note_id = NULL WHERE note_id = ?1",
+        [note_id],
+    )?;
+    if let Some(event_id) = event_id {
+        conn.execute(
+            "UPDATE calendar_events SET note_id = ?1 WHERE id = ?2",
+            params![note_id, event_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Link a note to an event and fill in the title (when still the placeholder),
+/// description and participants the note leaves empty
+pub fn apply_event(
+    conn: &Connection,
+    note_id: &str,
+    event: &CalendarEvent,
+) -> rusqlite::Result<()> {
+    link_event(conn, note_id, Some(&event.id))?;
+
+    let had_participants: bool = conn.query_row(
+        "SELECT COALESCE(TRIM(participants), '') != '' FROM notes WHERE id = ?1",
+        [note_id],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "UPDATE notes SET
+            title = CASE WHEN TRIM(title) IN ('', ?1) THEN ?2 ELSE title END,
+            description = COALESCE(NULLIF(TRIM(description), ''), ?3),
+            participants = COALESCE(NULLIF(TRIM(participants), ''), ?4),
+            updated_at = ?5
+         WHERE id = ?6",
+        params![
+            PLACEHOLDER_TITLE,
+            event.title,
+            event.description,
+            event.participants,
+            Utc::now().to_rfc3339(),
+            note_id
+        ],
+    )?;
+    if let (false, Some(participants)) = (had_participants, &event.participants) {
+        link_participants(conn, note_id, participants)?;
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Match a note whose recording started at `at` to the calendar event
+    /// running then (see [`find_event_at`]) and fill it in from the event.
+    /// Notes already linked to an event are left alone.
+    pub fn match_note_to_event(
+        &self,
+        note_id: &str,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<Option<CalendarEvent>> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+
+        let linked: bool = tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM calendar_events WHERE note_id = ?1)",
+            [note_id],
+            |row| row.get(0),
+        )?;
+        if linked {
+            return Ok(None);
+        }
+        let Some(event) = find_event_at(&tx, at)? else {
+            return Ok(None);
+        };
+        apply_event(&tx, note_id, &event)?;
+
+        tx.commit()?;
+        Ok(Some(event))
+    }
+
+    /// Replace the events of `source` ending after `from` with `events`.
+    /// Events already linked to a note are updated but never dropped.
+    pub fn import_calendar_events(
+        &self,
+        source: &str,
+        events: &[IcsEvent],
+        from: DateTime<Utc>,
+    ) -> anyhow::Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        let now = Utc::now().to_rfc3339();
+
+        tx.execute(
+            "DELETE FROM calendar_events WHERE source = ?1 AND note_id IS NULL AND ends_at >= ?2",
+            params![source, from.to_rfc3339()],
+        )?;
+        for event in events {
+            tx.execute(
+                "INSERT INTO calendar_events
+                    (id, title, description, location, participants, starts_at, ends_at, source, imported_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(id) DO UPDATE SET
+                    title = excluded.title,
+                    description = excluded.description,
+                    location = excluded.location,
+                    participants = excluded.participants,
+                    starts_at = excluded.starts_at,
+                    ends_at = excluded.ends_at,
+                    source = excluded.source,
+                    imported_at = excluded.imported_at",
+                params![
+                    event.id,
+                    event.title,
+                    event.description,
+                    event.location,
+                    event.participants,
+                    event.starts_at.to_rfc3339(),
+                    event.ends_at.to_rfc3339(),
+                    source,
+                    now
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(events.len())
+    }
+
+    /// Events overlapping `[from, to)`, by start time
+    pub fn list_calendar_events(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CalendarEvent>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM calendar_events
+             WHERE ends_at >= ?1 AND starts_at < ?2
+             ORDER BY starts_at",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt
+            .query_map(params![from.to_rfc3339(), to.to_rfc3339()], row_to_event)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// The event a note was recorded for
+    pub fn get_note_event(&self, note_id: &str) -> anyhow::Result<Option<CalendarEvent>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let event = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM calendar_events WHERE note_id = ?1",
+                    EVENT_COLUMNS
+                ),
+                [note_id],
+                row_to_event,
+            )
+            .optional()?;
+        Ok(event)
+    }
+
+    /// Link a note to an event, or unlink it
+    pub fn link_note_to_event(&self, note_id: &str, event_id: Option<&str>) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        link_event(&conn, note_id, event_id)?;
+        Ok(())
+    }
+
+    /// The title of a note recorded for a calendar event, which generated titles must not replace
+    pub fn get_event_note_title(&self, note_id: &str) -> anyhow::Result<Option<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let title = conn
+            .query_row(
+                "SELECT n.title FROM notes n
+                 JOIN calendar_events e ON e.note_id = n.id
+                 WHERE n.id = ?1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(title)
+    }
+}
@@ -1,45 +1,353 @@
+pub mod calendar;
 pub mod models;
+pub mod notes;
+pub mod people;
+pub mod pipeline;
 pub mod schema;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 
 use chrono::Utc;
 use rusqlite::{params, Connection};
-use tauri::{AppHandle, Manager};
 
-use crate::db::models::{AudioSegment, Summary, SummaryType, TranscriptSegment};
+use crate::crypto::{self, CryptoError, EncryptionConfig, LibraryKey};
+use crate::db::models::{
+    AudioSegment, Folder, FolderKind, JobMode, JobStatus, NewTranscriptSegment, Summary,
+    SummaryType, Tag, Transcript, TranscriptSegment, TranscriptSource, TranscriptionJob,
+};
 use crate::db::schema::run_migrations;
 
+/// Gives long-running tasks access to the library database without tying them
+/// to where it is kept (the app state, or a plain `Arc` in tests and the CLI)
+pub trait DatabaseHandle: Send + Sync + 'static {
+    fn with_db<R>(&self, f: impl FnOnce(&Database) -> R) -> R;
+}
+
+impl DatabaseHandle for std::sync::Arc<Database> {
+    fn with_db<R>(&self, f: impl FnOnce(&Database) -> R) -> R {
+        f(self)
+    }
+}
+
 pub struct Database {
     pub conn: Mutex<Connection>,
+    path: PathBuf,
+    locked: AtomicBool,
 }
 
 impl Database {
-    pub fn new(app_handle: &AppHandle) -> anyhow::Result<Self> {
-        let db_path = get_db_path(app_handle)?;
-
+    /// Open the library database at `db_path`, decrypting it with the keyring key
+    /// when the library is encrypted. A passphrase-protected library opens locked
+    /// until [`Database::unlock`] is called.
+    pub fn open(db_path: PathBuf) -> anyhow::Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)?;
+        let config = match db_path.parent() {
+            Some(dir) => EncryptionConfig::load(dir)?,
+            None => None,
+        };
+        let key = match config {
+            None => None,
+            Some(config) => match config.unlock(None) {
+                Ok(key) => Some(key),
+                Err(CryptoError::Locked) => {
+                    // No tables until unlocked, so queries fail instead of showing an empty library
+                    return Ok(Self {
+                        conn: Mutex::new(Connection::open_in_memory()?),
+                        path: db_path,
+                        locked: AtomicBool::new(true),
+                    });
+                }
+                Err(e) => return Err(e.into()),
+            },
+        };
 
-        // Enable foreign keys
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        // An interrupted migration can leave the database itself still in plaintext
+        let conn = open_connection(&db_path, key.as_ref().filter(|_| !is_plaintext_db(&db_path)))?;
+        crypto::set_library_key(key);
 
-        // Run migrations
+        Ok(Self {
+            conn: Mutex::new(conn),
+            path: db_path,
+            locked: AtomicBool::new(false),
+        })
+    }
+
+    /// Open an empty, unencrypted library that lives only in memory (for tests)
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         run_migrations(&conn)?;
 
         Ok(Self {
             conn: Mutex::new(conn),
+            path: PathBuf::new(),
+            locked: AtomicBool::new(false),
         })
     }
 
-    /// Add a transcript segment to the database
+    /// Whether the database is waiting for the library passphrase
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    /// Directory holding the database, the encryption config and `recordings/`
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.path.parent()
+    }
+
+    /// Open the encrypted database with the unlocked library key
+    pub fn unlock(&self, key: &LibraryKey) -> anyhow::Result<()> {
+        let key = Some(key).filter(|_| !is_plaintext_db(&self.path));
+        let conn = open_connection(&self.path, key)?;
+        *self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))? = conn;
+        self.locked.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Re-encrypt a plaintext database with `key`, replacing the file on disk.
+    /// Does nothing when the file is already encrypted.
+    pub fn encrypt_in_place(&self, key: &LibraryKey) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        if !is_plaintext_db(&self.path) {
+            return Ok(());
+        }
+
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".encrypting");
+        let tmp = PathBuf::from(tmp);
+        let _ = std::fs::remove_file(&tmp);
+
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS encrypted KEY {}", key.sqlcipher_key()),
+            [tmp.to_string_lossy()],
+        )?;
+        let exported = conn
+            .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .map_err(anyhow::Error::from);
+        conn.execute_batch("DETACH DATABASE encrypted;")?;
+        if let Err(e) = exported {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+
+        // Close the plaintext file before replacing it (required on Windows)
+        *conn = Connection::open_in_memory()?;
+        std::fs::rename(&tmp, &self.path)?;
+        *conn = open_connection(&self.path, Some(key))?;
+        Ok(())
+    }
+
+    // ========== Transcripts (versions) ==========
+
+    /// Create a transcript version for a note, optionally making it the active one
+    pub fn create_transcript(
+        &self,
+        note_id: &str,
+        source: TranscriptSource,
+        model_id: Option<&str>,
+        language: Option<&str>,
+        activate: bool,
+    ) -> anyhow::Result<i64> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        let id = insert_transcript(&tx, note_id, source, model_id, language, activate)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Get the id of the note's active transcript
+    pub fn get_active_transcript_id(&self, note_id: &str) -> anyhow::Result<Option<i64>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let id = conn
+            .query_row(
+                "SELECT id FROM transcripts WHERE note_id = ?1 AND is_active = 1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .ok();
+        Ok(id)
+    }
+
+    /// Get the note's active transcript if it came from `source`, otherwise create
+    /// a new active version from `source` (so live lines never land in a batch version).
+    /// The new version starts with the previous active version's segments, so earlier
+    /// sessions stay in the active transcript.
+    pub fn get_or_create_active_transcript(
+        &self,
+        note_id: &str,
+        source: TranscriptSource,
+        model_id: Option<&str>,
+        language: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let active: Option<(i64, String)> = {
+            let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+            conn.query_row(
+                "SELECT id, source FROM transcripts WHERE note_id = ?1 AND is_active = 1",
+                [note_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok()
+        };
+        if let Some((id, active_source)) = &active
+            && active_source == source.as_str()
+        {
+            return Ok(*id);
+        }
+
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        let id = insert_transcript(&tx, note_id, source, model_id, language, true)?;
+        if let Some((previous, _)) = active {
+            tx.execute(
+                "INSERT INTO transcript_segments (note_id, transcript_id, start_time, end_time, text, speaker, created_at)
+                 SELECT note_id, ?1, start_time, end_time, text, speaker, created_at
+                 FROM transcript_segments WHERE transcript_id = ?2",
+                params![id, previous],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Store a complete transcript with its segments in a single transaction.
+    /// With `activate` the new version becomes the active one.
+    pub fn save_transcript(
+        &self,
+        note_id: &str,
+        source: TranscriptSource,
+        model_id: Option<&str>,
+        language: Option<&str>,
+        segments: &[NewTranscriptSegment],
+        activate: bool,
+    ) -> anyhow::Result<i64> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let now = Utc::now().to_rfc3339();
+
+        let tx = conn.transaction()?;
+        let transcript_id = insert_transcript(&tx, note_id, source, model_id, language, activate)?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO transcript_segments (note_id, transcript_id, start_time, end_time, text, speaker, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+
+            for segment in segments {
+                stmt.execute(params![
+                    note_id,
+                    transcript_id,
+                    segment.start_time,
+                    segment.end_time,
+                    segment.text,
+                    segment.speaker.as_deref(),
+                    &now
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(transcript_id)
+    }
+
+    /// Get all transcript versions of a note, newest first
+    pub fn get_transcripts(&self, note_id: &str) -> anyhow::Result<Vec<Transcript>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.note_id, t.model_id, t.language, t.source, t.is_active, t.created_at,
+                    (SELECT COUNT(*) FROM transcript_segments s WHERE s.transcript_id = t.id)
+             FROM transcripts t
+             WHERE t.note_id = ?1
+             ORDER BY t.created_at DESC, t.id DESC",
+        )?;
+
+        let transcripts = stmt
+            .query_map([note_id], |row| {
+                Ok(Transcript {
+                    id: row.get(0)?,
+                    note_id: row.get(1)?,
+                    model_id: row.get(2)?,
+                    language: row.get(3)?,
+                    source: TranscriptSource::from_str(&row.get::<_, String>(4)?),
+                    is_active: row.get(5)?,
+                    created_at: row.get::<_, String>(6)?.parse().unwrap_or_else(|_| Utc::now()),
+                    segment_count: row.get(7)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(transcripts)
+    }
+
+    /// Make a transcript the active version of its note
+    pub fn set_active_transcript(&self, note_id: &str, transcript_id: i64) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        activate_transcript(&tx, note_id, transcript_id)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get the id of the note a transcript version belongs to
+    pub fn get_transcript_note_id(&self, transcript_id: i64) -> anyhow::Result<Option<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let note_id = conn
+            .query_row(
+                "SELECT note_id FROM transcripts WHERE id = ?1",
+                [transcript_id],
+                |row| row.get(0),
+            )
+            .ok();
+        Ok(note_id)
+    }
+
+    /// Delete a transcript version and its segments.
+    /// When the active version is deleted, the newest remaining one becomes active.
+    pub fn delete_transcript(&self, transcript_id: i64) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+
+        let (note_id, was_active): (String, bool) = tx.query_row(
+            "SELECT note_id, is_active FROM transcripts WHERE id = ?1",
+            [transcript_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        tx.execute(
+            "DELETE FROM transcript_segments WHERE transcript_id = ?1",
+            [transcript_id],
+        )?;
+        tx.execute("DELETE FROM transcripts WHERE id = ?1", [transcript_id])?;
+
+        if was_active {
+            let newest: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM transcripts WHERE note_id = ?1
+                     ORDER BY created_at DESC, id DESC LIMIT 1",
+                    [&note_id],
+                    |row| row.get(0),
+                )
+                .ok();
+            if let Some(id) = newest {
+                activate_transcript(&tx, &note_id, id)?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Add a transcript segment to a transcript version
     pub fn add_transcript_segment(
         &self,
+        transcript_id: i64,
         note_id: &str,
         start_time: f64,
         end_time: f64,
@@ -50,17 +358,18 @@ impl Database {
         let now = Utc::now();
 
         conn.execute(
-            "INSERT INTO transcript_segments (note_id, start_time, end_time, text, speaker, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![note_id, start_time, end_time, text, speaker, now.to_rfc3339()],
+            "INSERT INTO transcript_segments (note_id, transcript_id, start_time, end_time, text, speaker, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![note_id, transcript_id, start_time, end_time, text, speaker, now.to_rfc3339()],
         )?;
 
         Ok(conn.last_insert_rowid())
     }
 
-    /// Add multiple transcript segments in a single transaction (batch insert)
+    /// Add multiple transcript segments to a transcript version in a single transaction (batch insert)
     pub fn add_transcript_segments_batch(
         &self,
+        transcript_id: i64,
         segments: &[(String, f64, f64, String, Option<String>)], // (note_id, start, end, text, speaker)
     ) -> anyhow::Result<usize> {
         let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -71,12 +380,12 @@ impl Database {
 
         {
             let mut stmt = tx.prepare_cached(
-                "INSERT INTO transcript_segments (note_id, start_time, end_time, text, speaker, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO transcript_segments (note_id, transcript_id, start_time, end_time, text, speaker, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             )?;
 
             for (note_id, start_time, end_time, text, speaker) in segments {
-                stmt.execute(params![note_id, start_time, end_time, text, speaker.as_deref(), &now])?;
+                stmt.execute(params![note_id, transcript_id, start_time, end_time, text, speaker.as_deref(), &now])?;
                 count += 1;
             }
         }
@@ -85,35 +394,74 @@ impl Database {
         Ok(count)
     }
 
-    /// Get all transcript segments for a note
+    /// Get the segments of the note's active transcript
     pub fn get_transcript_segments(&self, note_id: &str) -> anyhow::Result<Vec<TranscriptSegment>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
 
         let mut stmt = conn.prepare(
-            "SELECT id, note_id, start_time, end_time, text, speaker, created_at
+            "SELECT s.id, s.note_id, s.transcript_id, s.start_time, s.end_time, s.text, s.speaker, s.created_at
+             FROM transcript_segments s
+             JOIN transcripts t ON t.id = s.transcript_id
+             WHERE s.note_id = ?1 AND t.is_active = 1
+             ORDER BY s.start_time ASC",
+        )?;
+
+        let segments = stmt
+            .query_map([note_id], segment_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(segments)
+    }
+
+    /// Get the segments of a specific transcript version
+    pub fn get_transcript_version_segments(
+        &self,
+        transcript_id: i64,
+    ) -> anyhow::Result<Vec<TranscriptSegment>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, note_id, transcript_id, start_time, end_time, text, speaker, created_at
              FROM transcript_segments
-             WHERE note_id = ?1
+             WHERE transcript_id = ?1
              ORDER BY start_time ASC",
         )?;
 
         let segments = stmt
-            .query_map([note_id], |row| {
-                Ok(TranscriptSegment {
-                    id: row.get(0)?,
-                    note_id: row.get(1)?,
-                    start_time: row.get(2)?,
-                    end_time: row.get(3)?,
-                    text: row.get(4)?,
-                    speaker: row.get(5)?,
-                    created_at: row.get::<_, String>(6)?.parse().unwrap_or_else(|_| Utc::now()),
-                })
-            })?
+            .query_map([transcript_id], segment_from_row)?
             .filter_map(|r| r.ok())
             .collect();
 
         Ok(segments)
     }
 
+    /// Segments of a transcript version that start outside every `(start, end)` range,
+    /// ready to be saved into another version (an open end runs to the end of the note)
+    pub fn get_segments_outside(
+        &self,
+        transcript_id: i64,
+        ranges: &[(f64, Option<f64>)],
+    ) -> anyhow::Result<Vec<NewTranscriptSegment>> {
+        let in_range = |time: f64| {
+            ranges
+                .iter()
+                .any(|&(start, end)| time >= start && end.is_none_or(|end| time < end))
+        };
+        Ok(self
+            .get_transcript_version_segments(transcript_id)?
+            .into_iter()
+            .filter(|segment| !in_range(segment.start_time))
+            .map(|segment| NewTranscriptSegment {
+                note_id: segment.note_id,
+                start_time: segment.start_time,
+                end_time: segment.end_time,
+                text: segment.text,
+                speaker: segment.speaker,
+            })
+            .collect())
+    }
+
     /// Delete all transcript segments for a note
     #[allow(dead_code)]
     pub fn delete_transcript_segments(&self, note_id: &str) -> anyhow::Result<()> {
@@ -149,7 +497,7 @@ impl Database {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
 
         let mut stmt = conn.prepare(
-            "SELECT id, note_id, summary_type, content, created_at
+            "SELECT id, note_id, summary_type, content, created_at, owner_id
              FROM summaries WHERE id = ?1",
         )?;
 
@@ -161,6 +509,7 @@ impl Database {
                     summary_type: SummaryType::from_str(&row.get::<_, String>(2)?),
                     content: row.get(3)?,
                     created_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| Utc::now()),
+                    owner_id: row.get(5)?,
                 })
             })
             .ok();
@@ -173,7 +522,7 @@ impl Database {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
 
         let mut stmt = conn.prepare(
-            "SELECT id, note_id, summary_type, content, created_at
+            "SELECT id, note_id, summary_type, content, created_at, owner_id
              FROM summaries
              WHERE note_id = ?1
              ORDER BY created_at DESC",
@@ -187,6 +536,7 @@ impl Database {
                     summary_type: SummaryType::from_str(&row.get::<_, String>(2)?),
                     content: row.get(3)?,
                     created_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| Utc::now()),
+                    owner_id: row.get(5)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -224,6 +574,20 @@ impl Database {
         Ok(description)
     }
 
+    /// Get the stored audio path of a note (single-file recordings)
+    pub fn get_note_audio_path(&self, note_id: &str) -> anyhow::Result<Option<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let audio_path: Option<String> = conn
+            .query_row(
+                "SELECT audio_path FROM notes WHERE id = ?1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .ok()
+            .flatten();
+        Ok(audio_path)
+    }
+
     /// Get a setting value
     pub fn get_setting(&self, key: &str) -> anyhow::Result<Option<String>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -247,9 +611,16 @@ impl Database {
         Ok(())
     }
 
+    /// Remove a setting
+    pub fn delete_setting(&self, key: &str) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute("DELETE FROM settings WHERE key = ?1", [key])?;
+        Ok(())
+    }
+
     // ========== Audio Segments (for pause/resume/continue) ==========
 
-    /// Add a new audio segment for a note
+    /// Add a new audio segment for a note (`denoised` = the mic file is noise-suppressed)
     pub fn add_audio_segment(
         &self,
         note_id: &str,
@@ -257,14 +628,15 @@ impl Database {
         mic_path: &str,
         system_path: Option<&str>,
         start_offset_ms: i64,
+        denoised: bool,
     ) -> anyhow::Result<i64> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
         let now = Utc::now();
 
         conn.execute(
-            "INSERT INTO audio_segments (note_id, segment_index, mic_path, system_path, start_offset_ms, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![note_id, segment_index, mic_path, system_path, start_offset_ms, now.to_rfc3339()],
+            "INSERT INTO audio_segments (note_id, segment_index, mic_path, system_path, start_offset_ms, denoised, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![note_id, segment_index, mic_path, system_path, start_offset_ms, denoised, now.to_rfc3339()],
         )?;
 
         Ok(conn.last_insert_rowid())
@@ -285,7 +657,7 @@ impl Database {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
 
         let mut stmt = conn.prepare(
-            "SELECT id, note_id, segment_index, mic_path, system_path, start_offset_ms, duration_ms, created_at
+            "SELECT id, note_id, segment_index, mic_path, system_path, start_offset_ms, duration_ms, refined, denoised, created_at
              FROM audio_segments
              WHERE note_id = ?1
              ORDER BY segment_index ASC",
@@ -301,7 +673,9 @@ impl Database {
                     system_path: row.get(4)?,
                     start_offset_ms: row.get(5)?,
                     duration_ms: row.get(6)?,
-                    created_at: row.get::<_, String>(7)?.parse().unwrap_or_else(|_| Utc::now()),
+                    refined: row.get(7)?,
+                    denoised: row.get(8)?,
+                    created_at: row.get::<_, String>(9)?.parse().unwrap_or_else(|_| Utc::now()),
                 })
             })?
             .filter_map(|r| r.ok())
@@ -310,6 +684,45 @@ impl Database {
         Ok(segments)
     }
 
+    /// Mark audio segments as re-transcribed by post-meeting refinement
+    pub fn mark_segments_refined(&self, segment_ids: &[i64]) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        for id in segment_ids {
+            conn.execute("UPDATE audio_segments SET refined = 1 WHERE id = ?1", [id])?;
+        }
+        Ok(())
+    }
+
+    /// Get an audio segment by ID
+    pub fn get_audio_segment(&self, id: i64) -> anyhow::Result<Option<AudioSegment>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let segment = conn
+            .query_row(
+                "SELECT id, note_id, segment_index, mic_path, system_path, start_offset_ms, duration_ms, refined, denoised, created_at
+                 FROM audio_segments
+                 WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok(AudioSegment {
+                        id: row.get(0)?,
+                        note_id: row.get(1)?,
+                        segment_index: row.get(2)?,
+                        mic_path: row.get(3)?,
+                        system_path: row.get(4)?,
+                        start_offset_ms: row.get(5)?,
+                        duration_ms: row.get(6)?,
+                        refined: row.get(7)?,
+                        denoised: row.get(8)?,
+                        created_at: row.get::<_, String>(9)?.parse().unwrap_or_else(|_| Utc::now()),
+                    })
+                },
+            )
+            .ok();
+
+        Ok(segment)
+    }
+
     /// Get the next segment index for a note
     pub fn get_next_segment_index(&self, note_id: &str) -> anyhow::Result<i32> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -348,6 +761,482 @@ impl Database {
         Ok(())
     }
 
+    /// Ids of notes that have recorded audio, oldest first
+    pub fn get_note_ids_with_audio(&self) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id FROM notes
+             WHERE audio_path IS NOT NULL
+                OR EXISTS (SELECT 1 FROM audio_segments s WHERE s.note_id = notes.id)
+             ORDER BY started_at ASC",
+        )?;
+
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(ids)
+    }
+
+    /// Ids of ended notes with recorded audio whose recording ended before `cutoff` (RFC 3339)
+    pub fn get_notes_with_audio_ended_before(&self, cutoff: &str) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id FROM notes
+             WHERE ended_at IS NOT NULL AND ended_at < ?1
+               AND (audio_path IS NOT NULL
+                    OR EXISTS (SELECT 1 FROM audio_segments s WHERE s.note_id = notes.id))
+             ORDER BY ended_at ASC",
+        )?;
+
+        let ids = stmt
+            .query_map([cutoff], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(ids)
+    }
+
+    /// Ids and titles of all notes
+    pub fn get_note_titles(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare("SELECT id, title FROM notes ORDER BY started_at DESC")?;
+        let notes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(notes)
+    }
+
+    /// Every mic and system file path referenced by `audio_segments`
+    pub fn get_all_audio_segment_paths(&self) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT mic_path FROM audio_segments
+             UNION ALL
+             SELECT system_path FROM audio_segments WHERE system_path IS NOT NULL",
+        )?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(paths)
+    }
+
+    /// Forget the single-file audio path of a note once its audio is deleted
+    pub fn clear_note_audio_path(&self, note_id: &str) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE notes SET audio_path = NULL, updated_at = ?1 WHERE id = ?2",
+            (Utc::now().to_rfc3339(), note_id),
+        )?;
+        Ok(())
+    }
+
+    // ========== Confidential notes ==========
+
+    /// Whether a note is flagged confidential
+    pub fn is_note_confidential(&self, note_id: &str) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let confidential = conn
+            .query_row(
+                "SELECT confidential FROM notes WHERE id = ?1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        Ok(confidential)
+    }
+
+    /// Whether any note is flagged confidential
+    pub fn has_confidential_notes(&self) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let found = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM notes WHERE confidential = 1)",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(found)
+    }
+
+    /// Flag a note as confidential or clear the flag
+    pub fn set_note_confidential(&self, note_id: &str, confidential: bool) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE notes SET confidential = ?1, updated_at = ?2 WHERE id = ?3",
+            (confidential, Utc::now().to_rfc3339(), note_id),
+        )?;
+        Ok(())
+    }
+
+    // ========== Tags ==========
+
+    /// All tags with the number of notes carrying each, by name
+    pub fn list_tags(&self) -> anyhow::Result<Vec<Tag>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, t.created_at,
+                    (SELECT COUNT(*) FROM note_tags nt WHERE nt.tag_id = t.id)
+             FROM tags t ORDER BY t.name COLLATE NOCASE",
+        )?;
+        let tags = stmt
+            .query_map([], row_to_tag)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tags)
+    }
+
+    /// Tags attached to a note, by name
+    pub fn get_note_tags(&self, note_id: &str) -> anyhow::Result<Vec<Tag>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, t.created_at,
+                    (SELECT COUNT(*) FROM note_tags c WHERE c.tag_id = t.id)
+             FROM tags t JOIN note_tags nt ON nt.tag_id = t.id
+             WHERE nt.note_id = ?1
+             ORDER BY t.name COLLATE NOCASE",
+        )?;
+        let tags = stmt
+            .query_map([note_id], row_to_tag)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tags)
+    }
+
+    /// Create a tag, or return the existing one with the same name (case-insensitive)
+    pub fn create_tag(&self, name: &str, color: Option<&str>) -> anyhow::Result<i64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (name, color, created_at) VALUES (?1, ?2, ?3)",
+            params![name, color, Utc::now().to_rfc3339()],
+        )?;
+        let id = conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |row| {
+            row.get(0)
+        })?;
+        Ok(id)
+    }
+
+    /// Rename or recolor a tag
+    pub fn update_tag(&self, id: i64, name: &str, color: Option<&str>) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+            params![name, color, id],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a tag and detach it from every note
+    pub fn delete_tag(&self, id: i64) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Attach a tag to a note (no-op when already attached)
+    pub fn add_note_tag(&self, note_id: &str, tag_id: i64) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?1, ?2)",
+            params![note_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    /// Detach a tag from a note
+    pub fn remove_note_tag(&self, note_id: &str, tag_id: i64) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "DELETE FROM note_tags WHERE note_id = ?1 AND tag_id = ?2",
+            params![note_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    // ========== Folders ==========
+
+    /// All folders with their direct note counts; the client builds the tree from `parent_id`
+    pub fn list_folders(&self) -> anyhow::Result<Vec<Folder>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.name, f.parent_id, f.kind, f.created_at, f.updated_at,
+                    (SELECT COUNT(*) FROM notes n WHERE n.folder_id = f.id)
+             FROM folders f ORDER BY f.name COLLATE NOCASE",
+        )?;
+        let folders = stmt
+            .query_map([], |row| {
+                Ok(Folder {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    kind: FolderKind::from_str(&row.get::<_, String>(3)?),
+                    created_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| Utc::now()),
+                    updated_at: row.get::<_, String>(5)?.parse().unwrap_or_else(|_| Utc::now()),
+                    note_count: row.get(6)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(folders)
+    }
+
+    /// Create a folder, at the top level when `parent_id` is `None`
+    pub fn create_folder(
+        &self,
+        name: &str,
+        parent_id: Option<i64>,
+        kind: FolderKind,
+    ) -> anyhow::Result<i64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO folders (name, parent_id, kind, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![name, parent_id, kind.as_str(), now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Rename a folder, change its kind and move it under `parent_id`.
+    /// Moving a folder into itself or one of its subfolders is rejected.
+    pub fn update_folder(
+        &self,
+        id: i64,
+        name: &str,
+        parent_id: Option<i64>,
+        kind: FolderKind,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        if let Some(parent_id) = parent_id {
+            let creates_cycle: bool = conn.query_row(
+                "WITH RECURSIVE ancestors(id) AS (
+                    SELECT ?1
+                    UNION ALL
+                    SELECT f.parent_id FROM folders f JOIN ancestors a ON f.id = a.id
+                    WHERE f.parent_id IS NOT NULL
+                 )
+                 SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = ?2)",
+                params![parent_id, id],
+                |row| row.get(0),
+            )?;
+            if creates_cycle {
+                anyhow::bail!("A folder cannot be moved into itself or one of its subfolders");
+            }
+        }
+
+        conn.execute(
+            "UPDATE folders SET name = ?1, parent_id = ?2, kind = ?3, updated_at = ?4 WHERE id = ?5",
+            params![name, parent_id, kind.as_str(), Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a folder and its subfolders; their notes move to the top level
+    pub fn delete_folder(&self, id: i64) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute("DELETE FROM folders WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Move a note into a folder, or to the top level with `None`
+    pub fn set_note_folder(&self, note_id: &str, folder_id: Option<i64>) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE notes SET folder_id = ?1, updated_at = ?2 WHERE id = ?3",
+            params![folder_id, Utc::now().to_rfc3339(), note_id],
+        )?;
+        Ok(())
+    }
+
+    // ========== Transcription Jobs (background re-transcription queue) ==========
+
+    /// Queue a re-transcription job for a note
+    pub fn add_transcription_job(
+        &self,
+        note_id: &str,
+        model_id: &str,
+        language: Option<&str>,
+        mode: JobMode,
+    ) -> anyhow::Result<i64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO transcription_jobs (note_id, model_id, language, mode, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![note_id, model_id, language, mode.as_str(), JobStatus::Pending.as_str(), now],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get a transcription job by ID
+    pub fn get_transcription_job(&self, id: i64) -> anyhow::Result<Option<TranscriptionJob>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let job = conn
+            .query_row(
+                "SELECT id, note_id, model_id, language, mode, status, error, created_at, updated_at
+                 FROM transcription_jobs WHERE id = ?1",
+                [id],
+                job_from_row,
+            )
+            .ok();
+
+        Ok(job)
+    }
+
+    /// Get all transcription jobs, oldest first
+    pub fn get_transcription_jobs(&self) -> anyhow::Result<Vec<TranscriptionJob>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, note_id, model_id, language, mode, status, error, created_at, updated_at
+             FROM transcription_jobs
+             ORDER BY id ASC",
+        )?;
+
+        let jobs = stmt
+            .query_map([], job_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(jobs)
+    }
+
+    /// Get the oldest pending job
+    pub fn get_next_pending_job(&self) -> anyhow::Result<Option<TranscriptionJob>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let job = conn
+            .query_row(
+                "SELECT id, note_id, model_id, language, mode, status, error, created_at, updated_at
+                 FROM transcription_jobs
+                 WHERE status = 'pending'
+                 ORDER BY id ASC
+                 LIMIT 1",
+                [],
+                job_from_row,
+            )
+            .ok();
+
+        Ok(job)
+    }
+
+    /// Whether a note already has a pending or running job
+    pub fn has_open_transcription_job(&self, note_id: &str) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM transcription_jobs
+             WHERE note_id = ?1 AND status IN ('pending', 'running')",
+            [note_id],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// The pending or running job of a note, if any
+    pub fn get_open_transcription_job(&self, note_id: &str) -> anyhow::Result<Option<TranscriptionJob>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let job = conn
+            .query_row(
+                "SELECT id, note_id, model_id, language, mode, status, error, created_at, updated_at
+                 FROM transcription_jobs
+                 WHERE note_id = ?1 AND status IN ('pending', 'running')
+                 ORDER BY id ASC
+                 LIMIT 1",
+                [note_id],
+                job_from_row,
+            )
+            .ok();
+
+        Ok(job)
+    }
+
+    /// Number of jobs waiting to run
+    pub fn count_pending_transcription_jobs(&self) -> anyhow::Result<i64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM transcription_jobs WHERE status = 'pending'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Update a job's status (and error message)
+    pub fn set_transcription_job_status(
+        &self,
+        id: i64,
+        status: JobStatus,
+        error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE transcription_jobs SET status = ?1, error = ?2, updated_at = ?3 WHERE id = ?4",
+            params![status.as_str(), error, Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Move a pending job to running. Returns false if it was cancelled (or
+    /// claimed) since it was read.
+    pub fn claim_transcription_job(&self, id: i64) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let changed = conn.execute(
+            "UPDATE transcription_jobs SET status = 'running', error = NULL, updated_at = ?1
+             WHERE id = ?2 AND status = 'pending'",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Cancel a job that hasn't finished yet. Returns false if it already finished.
+    pub fn cancel_transcription_job(&self, id: i64) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let changed = conn.execute(
+            "UPDATE transcription_jobs SET status = 'cancelled', updated_at = ?1
+             WHERE id = ?2 AND status IN ('pending', 'running')",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Put jobs interrupted by a quit or crash back in the queue
+    pub fn requeue_running_transcription_jobs(&self) -> anyhow::Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let changed = conn.execute(
+            "UPDATE transcription_jobs SET status = 'pending', updated_at = ?1 WHERE status = 'running'",
+            [Utc::now().to_rfc3339()],
+        )?;
+        Ok(changed)
+    }
+
+    /// Remove finished, failed and cancelled jobs
+    pub fn clear_finished_transcription_jobs(&self) -> anyhow::Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let changed = conn.execute(
+            "DELETE FROM transcription_jobs WHERE status IN ('done', 'failed', 'cancelled')",
+            [],
+        )?;
+        Ok(changed)
+    }
+
     /// Get the latest (most recent) segment for a note
     #[allow(dead_code)]
     pub fn get_latest_segment(&self, note_id: &str) -> anyhow::Result<Option<AudioSegment>> {
@@ -355,7 +1244,7 @@ impl Database {
 
         let segment = conn
             .query_row(
-                "SELECT id, note_id, segment_index, mic_path, system_path, start_offset_ms, duration_ms, created_at
+                "SELECT id, note_id, segment_index, mic_path, system_path, start_offset_ms, duration_ms, refined, denoised, created_at
                  FROM audio_segments
                  WHERE note_id = ?1
                  ORDER BY segment_index DESC
@@ -370,7 +1259,9 @@ impl Database {
                         system_path: row.get(4)?,
                         start_offset_ms: row.get(5)?,
                         duration_ms: row.get(6)?,
-                        created_at: row.get::<_, String>(7)?.parse().unwrap_or_else(|_| Utc::now()),
+                        refined: row.get(7)?,
+                        denoised: row.get(8)?,
+                        created_at: row.get::<_, String>(9)?.parse().unwrap_or_else(|_| Utc::now()),
                     })
                 },
             )
@@ -380,11 +1271,269 @@ impl Database {
     }
 }
 
-fn get_db_path(app_handle: &AppHandle) -> anyhow::Result<PathBuf> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?;
+/// Insert a transcript row, deactivating the note's other versions when `activate` is set
+fn insert_transcript(
+    conn: &Connection,
+    note_id: &str,
+    source: TranscriptSource,
+    model_id: Option<&str>,
+    language: Option<&str>,
+    activate: bool,
+) -> rusqlite::Result<i64> {
+    if activate {
+        conn.execute(
+            "UPDATE transcripts SET is_active = 0 WHERE note_id = ?1",
+            [note_id],
+        )?;
+    }
+    conn.execute(
+        "INSERT INTO transcripts (note_id, model_id, language, source, is_active, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![note_id, model_id, language, source.as_str(), activate, Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Mark one transcript as the note's active version
+fn activate_transcript(conn: &Connection, note_id: &str, transcript_id: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transcripts SET is_active = 0 WHERE note_id = ?1",
+        [note_id],
+    )?;
+    let changed = conn.execute(
+        "UPDATE transcripts SET is_active = 1 WHERE id = ?1 AND note_id = ?2",
+        params![transcript_id, note_id],
+    )?;
+    if changed == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    Ok(())
+}
+
+/// Map a `transcript_segments` row (selected in column order) to a segment
+fn segment_from_row(row: &rusqlite::Row) -> rusqlite::Result<TranscriptSegment> {
+    Ok(TranscriptSegment {
+        id: row.get(0)?,
+        note_id: row.get(1)?,
+        transcript_id: row.get(2)?,
+        start_time: row.get(3)?,
+        end_time: row.get(4)?,
+        text: row.get(5)?,
+        speaker: row.get(6)?,
+        created_at: row.get::<_, String>(7)?.parse().unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Map a `transcription_jobs` row (selected in column order) to a job
+fn job_from_row(row: &rusqlite::Row) -> rusqlite::Result<TranscriptionJob> {
+    Ok(TranscriptionJob {
+        id: row.get(0)?,
+        note_id: row.get(1)?,
+        model_id: row.get(2)?,
+        language: row.get(3)?,
+        mode: JobMode::from_str(&row.get::<_, String>(4)?),
+        status: JobStatus::from_str(&row.get::<_, String>(5)?),
+        error: row.get(6)?,
+        created_at: row.get::<_, String>(7)?.parse().unwrap_or_else(|_| Utc::now()),
+        updated_at: row.get::<_, String>(8)?.parse().unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Open the database file, keyed with `key` when the library is encrypted
+fn open_connection(path: &Path, key: Option<&LibraryKey>) -> anyhow::Result<Connection> {
+    let conn = Connection::open(path)?;
+
+    if let Some(key) = key {
+        conn.execute_batch(&format!("PRAGMA key = {};", key.sqlcipher_key()))?;
+        // SQLCipher only checks the key on first access
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+            .map_err(|e| anyhow::anyhow!("Failed to decrypt the database: {}", e))?;
+    }
+
+    // Enable foreign keys
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+
+    // Run migrations
+    run_migrations(&conn)?;
+
+    Ok(conn)
+}
+
+/// Whether the database file is an unencrypted SQLite file
+fn is_plaintext_db(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .is_ok()
+        && &header == b"SQLite format 3\0"
+}
+
+fn row_to_tag(row: &rusqlite::Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        created_at: row.get::<_, String>(3)?.parse().unwrap_or_else(|_| Utc::now()),
+        note_count: row.get(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_note(db: &Database, id: &str) {
+        let now = Utc::now().to_rfc3339();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO notes (id, title, started_at, created_at, updated_at)
+                 VALUES (?1, ?1, ?2, ?2, ?2)",
+                params![id, now],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_encrypt_in_place_keeps_data_behind_the_key() {
+        let dir = std::env::temp_dir().join(format!("netnote-db-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("netnote.db");
+        let db = Database::open(path.clone()).unwrap();
+        add_note(&db, "n1");
+        assert!(is_plaintext_db(&path));
+
+        let key = LibraryKey::generate();
+        db.encrypt_in_place(&key).unwrap();
+        assert!(!is_plaintext_db(&path));
+        assert!(!dir.join("netnote.db.encrypting").exists());
+        // The open handle switches to the encrypted file, and a second call is a no-op
+        db.encrypt_in_place(&key).unwrap();
+        assert_eq!(db.get_note_titles().unwrap().len(), 1);
+
+        assert!(open_connection(&path, None).is_err());
+        assert!(open_connection(&path, Some(&LibraryKey::generate())).is_err());
+        let conn = open_connection(&path, Some(&key)).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
 
-    Ok(app_data_dir.join("netnote.db"))
+        drop((conn, db));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_claim_skips_cancelled_job() {
+        let db = Database::open_in_memory().unwrap();
+        add_note(&db, "n1");
+        let job = db
+            .add_transcription_job("n1", "base", None, JobMode::Replace)
+            .unwrap();
+
+        // Cancelled between the worker reading it and claiming it
+        assert!(db.cancel_transcription_job(job).unwrap());
+        assert!(!db.claim_transcription_job(job).unwrap());
+        let status = db.get_transcription_job(job).unwrap().unwrap().status;
+        assert_eq!(status, JobStatus::Cancelled);
+
+        let next = db
+            .add_transcription_job("n1", "base", None, JobMode::Replace)
+            .unwrap();
+        assert!(db.claim_transcription_job(next).unwrap());
+        assert!(!db.claim_transcription_job(next).unwrap());
+    }
+
+    #[test]
+    fn test_live_transcript_not_appended_to_batch_version() {
+        let db = Database::open_in_memory().unwrap();
+        add_note(&db, "n1");
+
+        let live = db
+            .get_or_create_active_transcript("n1", TranscriptSource::Live, None, None)
+            .unwrap();
+        let again = db
+            .get_or_create_active_transcript("n1", TranscriptSource::Live, None, None)
+            .unwrap();
+        assert_eq!(live, again);
+
+        // Refined by a batch run, then recording resumes
+        let batch = db
+            .save_transcript("n1", TranscriptSource::Batch, Some("base"), None, &[], true)
+            .unwrap();
+        let resumed = db
+            .get_or_create_active_transcript("n1", TranscriptSource::Live, None, None)
+            .unwrap();
+        assert_ne!(resumed, batch);
+        assert_ne!(resumed, live);
+        assert_eq!(db.get_active_transcript_id("n1").unwrap(), Some(resumed));
+    }
+
+    #[test]
+    fn test_refine_continue_refine_keeps_earlier_sessions() {
+        let db = Database::open_in_memory().unwrap();
+        add_note(&db, "n1");
+        let segment = |start: f64, text: &str| NewTranscriptSegment {
+            note_id: "n1".to_string(),
+            start_time: start,
+            end_time: start + 5.0,
+            text: text.to_string(),
+            speaker: Some("You".to_string()),
+        };
+        let texts = |db: &Database| -> Vec<String> {
+            let active = db.get_active_transcript_id("n1").unwrap().unwrap();
+            db.get_transcript_version_segments(active)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.text)
+                .collect()
+        };
+
+        // First session recorded live, then refined
+        let live = db
+            .get_or_create_active_transcript("n1", TranscriptSource::Live, None, None)
+            .unwrap();
+        db.add_transcript_segments_batch(
+            live,
+            &[("n1".to_string(), 0.0, 5.0, "live one".to_string(), None)],
+        )
+        .unwrap();
+        db.save_transcript(
+            "n1",
+            TranscriptSource::Batch,
+            Some("base"),
+            None,
+            &[segment(0.0, "refined one")],
+            true,
+        )
+        .unwrap();
+
+        // The note is continued from 60 s
+        let resumed = db
+            .get_or_create_active_transcript("n1", TranscriptSource::Live, None, None)
+            .unwrap();
+        db.add_transcript_segments_batch(
+            resumed,
+            &[("n1".to_string(), 60.0, 65.0, "live two".to_string(), None)],
+        )
+        .unwrap();
+        assert_eq!(texts(&db), ["refined one", "live two"]);
+
+        // Refining the second session keeps the first one's refined text
+        let mut segments = vec![segment(60.0, "refined two")];
+        segments.extend(db.get_segments_outside(resumed, &[(60.0, None)]).unwrap());
+        segments.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        db.save_transcript(
+            "n1",
+            TranscriptSource::Batch,
+            Some("base"),
+            None,
+            &segments,
+            true,
+        )
+        .unwrap();
+        assert_eq!(texts(&db), ["refined one", "refined two"]);
+    }
 }
@@ -6,18 +6,187 @@ pub struct Note {
     pub id: String,
     pub title: String,
     pub description: Option<String>,
-    pub participants: Option<String>, // Comma-separated list
+    pub participants: Option<String>, // Comma-separated display list, linked to `people` rows
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
     pub audio_path: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Excluded from search, AI summarization and export until unlocked
+    pub confidential: bool,
+    pub folder_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    /// Number of notes carrying the tag
+    pub note_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderKind {
+    Folder,
+    Project,
+}
+
+impl FolderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FolderKind::Folder => "folder",
+            FolderKind::Project => "project",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "project" => FolderKind::Project,
+            _ => FolderKind::Folder,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: i64,
+    pub name: String,
+    /// `None` for top-level folders
+    pub parent_id: Option<i64>,
+    pub kind: FolderKind,
+    /// Notes directly inside the folder (not counting subfolders)
+    pub note_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Filters for `list_notes` and `query_notes`; every field that is set must match
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NoteFilter {
+    pub tag_id: Option<i64>,
+    pub folder_id: Option<i64>,
+    /// Also match notes in subfolders of `folder_id`
+    pub include_subfolders: bool,
+    /// Only notes started at or after this time
+    pub started_after: Option<DateTime<Utc>>,
+    /// Only notes started before this time
+    pub started_before: Option<DateTime<Utc>>,
+    /// Case-insensitive substring of the participants list
+    pub participant: Option<String>,
+    /// Notes the person took part in, spoke in or owns a summary of
+    pub person_id: Option<i64>,
+}
+
+/// Sort order for `query_notes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSort {
+    #[default]
+    StartedAt,
+    UpdatedAt,
+    Duration,
+    Title,
+}
+
+/// A page request for `query_notes`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NoteQuery {
+    #[serde(flatten)]
+    pub filter: NoteFilter,
+    /// Full-text search over titles, descriptions, participants, tags, folders and transcripts
+    pub search: Option<String>,
+    /// Has audio (a recording file or segments)
+    pub has_recording: Option<bool>,
+    /// Has at least one summary
+    pub has_summary: Option<bool>,
+    /// `true` for ended notes, `false` for ongoing ones
+    pub ended: Option<bool>,
+    pub sort: NoteSort,
+    /// Oldest/smallest first instead of newest/largest first
+    pub ascending: bool,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Page size, 50 when unset
+    pub limit: Option<u32>,
+}
+
+/// A note in a listing, with aggregates over its segments and summaries
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteListItem {
+    #[serde(flatten)]
+    pub note: Note,
+    /// Recorded duration: the sum of segment durations, or ended_at - started_at without segments
+    pub duration_ms: i64,
+    pub segment_count: i64,
+    pub summary_count: i64,
+}
+
+/// One page of `query_notes`
+#[derive(Debug, Clone, Serialize)]
+pub struct NotePage {
+    pub items: Vec<NoteListItem>,
+    /// Pass back as `cursor` for the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Notes matching the filters across all pages
+    pub total: i64,
+}
+
+/// An event imported from a calendar file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    /// Organizer and attendees (`Name <email>, ...`)
+    pub participants: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// The `.ics` file the event came from
+    pub source: String,
+    /// Note recorded for the event
+    pub note_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Person {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+    /// Other names the person goes by (nicknames, diarization labels, ...)
+    pub aliases: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A diarized speaker label of a note linked to a person
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerLink {
+    pub note_id: String,
+    pub speaker: String,
+    pub person_id: i64,
+}
+
+/// One line of an action items summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub note_id: String,
+    pub note_title: String,
+    pub note_started_at: DateTime<Utc>,
+    pub summary_id: i64,
+    pub text: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptSegment {
     pub id: i64,
     pub note_id: String,
+    pub transcript_id: i64,
     pub start_time: f64,  // seconds from note start
     pub end_time: f64,
     pub text: String,
@@ -32,9 +201,11 @@ pub struct Summary {
     pub summary_type: SummaryType,
     pub content: String,
     pub created_at: DateTime<Utc>,
+    /// Person responsible for the summary's action items
+    pub owner_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SummaryType {
     Overview,
@@ -69,6 +240,9 @@ pub struct NewNote {
     pub title: String,
     pub description: Option<String>,
     pub participants: Option<String>,
+    pub folder_id: Option<i64>,
+    /// Calendar event the note is recorded for; fills in what the note leaves empty
+    pub event_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -78,8 +252,7 @@ pub struct UpdateNote {
     pub participants: Option<String>,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewTranscriptSegment {
     pub note_id: String,
     pub start_time: f64,
@@ -98,6 +271,10 @@ pub struct AudioSegment {
     pub system_path: Option<String>,
     pub start_offset_ms: i64,
     pub duration_ms: Option<i64>,
+    /// Whether post-meeting refinement has re-transcribed this segment
+    pub refined: bool,
+    /// Whether the mic file was noise-suppressed while recording
+    pub denoised: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -110,3 +287,201 @@ pub struct NewAudioSegment {
     pub system_path: Option<String>,
     pub start_offset_ms: i64,
 }
+
+/// What a re-transcription job does with the note's existing transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobMode {
+    /// Store the result as the active version, deleting the previously active one
+    Replace,
+    /// Leave the active transcript alone and store the result as an inactive version
+    KeepAlternate,
+    /// Post-meeting refinement: re-transcribe the sessions not refined yet and store the
+    /// merged result as the active version, keeping the live one for comparison
+    Refine,
+}
+
+impl JobMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobMode::Replace => "replace",
+            JobMode::KeepAlternate => "keep_alternate",
+            JobMode::Refine => "refine",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "keep_alternate" => JobMode::KeepAlternate,
+            "refine" => JobMode::Refine,
+            _ => JobMode::Replace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "running" => JobStatus::Running,
+            "done" => JobStatus::Done,
+            "failed" => JobStatus::Failed,
+            "cancelled" => JobStatus::Cancelled,
+            _ => JobStatus::Pending,
+        }
+    }
+}
+
+/// A queued background re-transcription of one note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionJob {
+    pub id: i64,
+    pub note_id: String,
+    pub model_id: String,
+    pub language: Option<String>,
+    pub mode: JobMode,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What produced a transcript version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptSource {
+    /// Live transcription during recording
+    Live,
+    /// Transcription of the stored recordings after the meeting
+    Batch,
+    /// Segments added from outside (seeding, imports)
+    Import,
+    /// Segments recorded before transcripts were versioned
+    Legacy,
+}
+
+impl TranscriptSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptSource::Live => "live",
+            TranscriptSource::Batch => "batch",
+            TranscriptSource::Import => "import",
+            TranscriptSource::Legacy => "legacy",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "live" => TranscriptSource::Live,
+            "batch" => TranscriptSource::Batch,
+            "import" => TranscriptSource::Import,
+            _ => TranscriptSource::Legacy,
+        }
+    }
+}
+
+/// One transcript version of a note. Exactly one version per note is active.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    pub id: i64,
+    pub note_id: String,
+    pub model_id: Option<String>,
+    pub language: Option<String>,
+    pub source: TranscriptSource,
+    pub is_active: bool,
+    pub segment_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One step of the post-recording pipeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PipelineStep {
+    /// Render the note's playback file from its recorded sessions
+    Mix,
+    /// Batch-transcribe the sessions not refined yet (`model: None` = the
+    /// refinement model, or the loaded one)
+    Transcribe {
+        #[serde(default)]
+        model: Option<String>,
+    },
+    Summary {
+        summary_type: SummaryType,
+        #[serde(default)]
+        custom_prompt: Option<String>,
+    },
+    /// From the newest summary when the note has one, from the transcript otherwise
+    Title,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+    /// Nothing to do (e.g. every session is already transcribed)
+    Skipped,
+}
+
+impl StepStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepStatus::Pending => "pending",
+            StepStatus::Running => "running",
+            StepStatus::Done => "done",
+            StepStatus::Failed => "failed",
+            StepStatus::Skipped => "skipped",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "running" => StepStatus::Running,
+            "done" => StepStatus::Done,
+            "failed" => StepStatus::Failed,
+            "skipped" => StepStatus::Skipped,
+            _ => StepStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStepState {
+    pub position: i32,
+    pub step: PipelineStep,
+    pub status: StepStatus,
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One run of the post-recording pipeline over a note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineRun {
+    pub id: i64,
+    pub note_id: String,
+    /// Name of the pipeline template the steps came from
+    pub template: String,
+    pub steps: Vec<PipelineStepState>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,100 @@
+//! Creating notes and reading them back
+
+use chrono::Utc;
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::db::calendar;
+use crate::db::models::{NewNote, Note};
+use crate::db::people::link_participants;
+use crate::db::Database;
+
+/// Columns read by `row_to_note`, in order
+pub(crate) const NOTE_COLUMNS: &str = "id, title, description, participants, started_at, ended_at, audio_path, created_at, updated_at, confidential, folder_id";
+
+pub(crate) fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<Note> {
+    Ok(Note {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        participants: row.get(3)?,
+        started_at: parse_datetime(row.get::<_, String>(4)?),
+        ended_at: row.get::<_, Option<String>>(5)?.map(parse_datetime),
+        audio_path: row.get(6)?,
+        created_at: parse_datetime(row.get::<_, String>(7)?),
+        updated_at: parse_datetime(row.get::<_, String>(8)?),
+        confidential: row.get(9)?,
+        folder_id: row.get(10)?,
+    })
+}
+
+fn parse_datetime(s: String) -> chrono::DateTime<Utc> {
+    chrono::DateTime::parse_from_rfc3339(&s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+impl Database {
+    /// Insert a note, linking its participants and filling it in from its calendar event
+    pub fn insert_note(&self, input: NewNote) -> anyhow::Result<Note> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let now = Utc::now();
+        let id = Uuid::new_v4().to_string();
+
+        conn.execute(
+            "INSERT INTO notes (id, title, description, participants, started_at, created_at, updated_at, folder_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                &id,
+                &input.title,
+                &input.description,
+                &input.participants,
+                now.to_rfc3339(),
+                now.to_rfc3339(),
+                now.to_rfc3339(),
+                input.folder_id,
+            ),
+        )?;
+        if let Some(participants) = &input.participants {
+            link_participants(&conn, &id, participants)?;
+        }
+
+        // A note created for a calendar event takes what it leaves empty from the event
+        if let Some(event_id) = &input.event_id {
+            let event = calendar::get_event(&conn, event_id)?
+                .ok_or_else(|| anyhow::anyhow!("Calendar event not found"))?;
+            calendar::apply_event(&conn, &id, &event)?;
+            drop(conn);
+            return self
+                .find_note(&id)?
+                .ok_or_else(|| anyhow::anyhow!("Note not found"));
+        }
+
+        Ok(Note {
+            id,
+            title: input.title,
+            description: input.description,
+            participants: input.participants,
+            started_at: now,
+            ended_at: None,
+            audio_path: None,
+            created_at: now,
+            updated_at: now,
+            confidential: false,
+            folder_id: input.folder_id,
+        })
+    }
+
+    /// A note by id
+    pub fn find_note(&self, id: &str) -> anyhow::Result<Option<Note>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let note = conn
+            .query_row(
+                &format!("SELECT {} FROM notes WHERE id = ?1", NOTE_COLUMNS),
+                [id],
+                row_to_note,
+            )
+            .optional()?;
+        Ok(note)
+    }
+}
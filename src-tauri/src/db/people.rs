@@ -0,0 +1,558 @@
+//! People taking part in notes
+//!
+//! `notes.participants` stays the display and full-text search string; the
+//! `people` and `note_participants` tables are its queryable form and are kept
+//! in sync with it. Diarized speaker labels (`speaker_links`) and summary
+//! owners (`summaries.owner_id`) point at the same person records.
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::models::{ActionItem, Person, SpeakerLink, SummaryType};
+use crate::db::Database;
+
+/// Split a participants string (`"Alice, Bob <bob@example.com>; carol@example.com"`)
+/// into names with optional (lowercased) emails
+pub fn parse_participants(participants: &str) -> Vec<(String, Option<String>)> {
+    participants
+        .split([',', ';', '\n'])
+        .filter_map(|part| {
+            let part = part.trim();
+            if part.is_empty() {
+                return None;
+            }
+
+            // "Name <email>"
+            if let Some((name, rest)) = part.split_once('<') {
+                let email = rest.trim_end_matches('>').trim().to_lowercase();
+                let name = name.trim().trim_matches('"').trim();
+                return match (name.is_empty(), email.is_empty()) {
+                    (true, true) => None,
+                    (true, false) => Some((email.clone(), Some(email))),
+                    (false, true) => Some((name.to_string(), None)),
+                    (false, false) => Some((name.to_string(), Some(email))),
+                };
+            }
+
+            if part.contains('@') && !part.contains(char::is_whitespace) {
+                return Some((part.to_string(), Some(part.to_lowercase())));
+            }
+            Some((part.to_string(), None))
+        })
+        .collect()
+}
+
+/// Find a person by email, or else by name or alias (case-insensitive), creating them if unknown
+fn find_or_create_person(
+    conn: &Connection,
+    name: &str,
+    email: Option<&str>,
+) -> rusqlite::Result<i64> {
+    if let Some(email) = email {
+        let id = conn
+            .query_row(
+                "SELECT id FROM people WHERE LOWER(email) = LOWER(?1) LIMIT 1",
+                [email],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(id) = id {
+            return Ok(id);
+        }
+    }
+
+    let id = conn
+        .query_row(
+            "SELECT id FROM people
+             WHERE LOWER(name) = LOWER(?1)
+                OR EXISTS (SELECT 1 FROM json_each(people.aliases) WHERE LOWER(value) = LOWER(?1))
+             ORDER BY id LIMIT 1",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = id {
+        if let Some(email) = email {
+            conn.execute(
+                "UPDATE people SET email = ?1 WHERE id = ?2 AND email IS NULL",
+                params![email, id],
+            )?;
+        }
+        return Ok(id);
+    }
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO people (name, email, aliases, created_at, updated_at)
+         VALUES (?1, ?2, '[]', ?3, ?3)",
+        params![name, email, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replace a note's participant links with the people named in `participants`
+pub fn link_participants(
+    conn: &Connection,
+    note_id: &str,
+    participants: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM note_participants WHERE note_id = ?1",
+        [note_id],
+    )?;
+    for (name, email) in parse_participants(participants) {
+        let person_id = find_or_create_person(conn, &name, email.as_deref())?;
+        conn.execute(
+            "INSERT OR IGNORE INTO note_participants (note_id, person_id) VALUES (?1, ?2)",
+            params![note_id, person_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Rewrite `notes.participants` from the note's linked people
+fn sync_participants_text(conn: &Connection, note_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE notes SET participants = (
+            SELECT GROUP_CONCAT(name, ', ') FROM (
+                SELECT p.name FROM people p
+                JOIN note_participants np ON np.person_id = p.id
+                WHERE np.note_id = ?1
+                ORDER BY p.name COLLATE NOCASE
+            )
+         )
+         WHERE id = ?1",
+        [note_id],
+    )?;
+    Ok(())
+}
+
+/// The not-yet-done list items of an action items summary. Items checked off
+/// (`[x]`) or struck through (`~~...~~`) are skipped.
+pub fn extract_open_items(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let item = line
+                .strip_prefix(['-', '*', '+'])
+                .or_else(|| {
+                    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+                    (digits > 0).then(|| line[digits..].strip_prefix(['.', ')']))?
+                })
+                // A list marker is followed by a space ("**Bold**" is not an item)
+                .filter(|rest| rest.starts_with(char::is_whitespace))?
+                .trim();
+
+            let item = match item.get(..3) {
+                Some("[x]") | Some("[X]") => return None,
+                Some("[ ]") => item[3..].trim(),
+                _ => item,
+            };
+            if item.is_empty() || (item.starts_with("~~") && item.ends_with("~~")) {
+                return None;
+            }
+            Some(item.to_string())
+        })
+        .collect()
+}
+
+/// Whether `text` mentions `name` as a whole word (case-insensitive)
+pub fn mentions(text: &str, name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+    let text = text.to_lowercase();
+    text.match_indices(&name).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + name.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn row_to_person(row: &rusqlite::Row) -> rusqlite::Result<Person> {
+    Ok(Person {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        email: row.get(2)?,
+        aliases: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+        created_at: row
+            .get::<_, String>(4)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        updated_at: row
+            .get::<_, String>(5)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+impl Database {
+    /// All people, by name
+    pub fn list_people(&self) -> anyhow::Result<Vec<Person>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, email, aliases, created_at, updated_at
+             FROM people ORDER BY name COLLATE NOCASE",
+        )?;
+        let people = stmt
+            .query_map([], row_to_person)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(people)
+    }
+
+    /// Get a person by ID
+    pub fn get_person(&self, id: i64) -> anyhow::Result<Option<Person>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let person = conn
+            .query_row(
+                "SELECT id, name, email, aliases, created_at, updated_at FROM people WHERE id = ?1",
+                [id],
+                row_to_person,
+            )
+            .optional()?;
+        Ok(person)
+    }
+
+    /// Create a person
+    pub fn create_person(
+        &self,
+        name: &str,
+        email: Option<&str>,
+        aliases: &[String],
+    ) -> anyhow::Result<i64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO people (name, email, aliases, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![name, email, serde_json::to_string(aliases)?, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Update a person's name, email and aliases, refreshing the participant
+    /// strings of their notes
+    pub fn update_person(
+        &self,
+        id: i64,
+        name: &str,
+        email: Option<&str>,
+        aliases: &[String],
+    ) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE people SET name = ?1, email = ?2, aliases = ?3, updated_at = ?4 WHERE id = ?5",
+            params![
+                name,
+                email,
+                serde_json::to_string(aliases)?,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )?;
+        for note_id in person_note_ids(&tx, id)? {
+            sync_participants_text(&tx, &note_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete a person, removing them from every note
+    pub fn delete_person(&self, id: i64) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        let note_ids = person_note_ids(&tx, id)?;
+        tx.execute("DELETE FROM people WHERE id = ?1", [id])?;
+        for note_id in note_ids {
+            sync_participants_text(&tx, &note_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Fold `merge_id` into `keep_id`: notes, speaker labels and summaries move
+    /// over and the merged name and aliases become aliases of the kept person
+    pub fn merge_people(&self, keep_id: i64, merge_id: i64) -> anyhow::Result<()> {
+        if keep_id == merge_id {
+            return Ok(());
+        }
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+
+        let load = |id: i64| {
+            tx.query_row(
+                "SELECT id, name, email, aliases, created_at, updated_at FROM people WHERE id = ?1",
+                [id],
+                row_to_person,
+            )
+        };
+        let keep = load(keep_id)?;
+        let merge = load(merge_id)?;
+
+        let mut aliases = keep.aliases.clone();
+        for alias in std::iter::once(merge.name).chain(merge.aliases) {
+            let known = alias.eq_ignore_ascii_case(&keep.name)
+                || aliases.iter().any(|a| a.eq_ignore_ascii_case(&alias));
+            if !known {
+                aliases.push(alias);
+            }
+        }
+
+        tx.execute(
+            "UPDATE people SET email = COALESCE(email, ?1), aliases = ?2, updated_at = ?3 WHERE id = ?4",
+            params![merge.email, serde_json::to_string(&aliases)?, Utc::now().to_rfc3339(), keep_id],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO note_participants (note_id, person_id)
+             SELECT note_id, ?1 FROM note_participants WHERE person_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        tx.execute(
+            "UPDATE speaker_links SET person_id = ?1 WHERE person_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        tx.execute(
+            "UPDATE summaries SET owner_id = ?1 WHERE owner_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+
+        let note_ids = person_note_ids(&tx, merge_id)?;
+        tx.execute("DELETE FROM people WHERE id = ?1", [merge_id])?;
+        for note_id in note_ids {
+            sync_participants_text(&tx, &note_id)?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// People taking part in a note, by name
+    pub fn get_note_participants(&self, note_id: &str) -> anyhow::Result<Vec<Person>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.name, p.email, p.aliases, p.created_at, p.updated_at
+             FROM people p JOIN note_participants np ON np.person_id = p.id
+             WHERE np.note_id = ?1
+             ORDER BY p.name COLLATE NOCASE",
+        )?;
+        let people = stmt
+            .query_map([note_id], row_to_person)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(people)
+    }
+
+    /// Replace a note's participants, rewriting its participants string to match
+    pub fn set_note_participants(&self, note_id: &str, person_ids: &[i64]) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM note_participants WHERE note_id = ?1",
+            [note_id],
+        )?;
+        for person_id in person_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO note_participants (note_id, person_id) VALUES (?1, ?2)",
+                params![note_id, person_id],
+            )?;
+        }
+        sync_participants_text(&tx, note_id)?;
+        tx.execute(
+            "UPDATE notes SET updated_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), note_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Speaker labels of a note that are linked to people
+    pub fn get_speaker_links(&self, note_id: &str) -> anyhow::Result<Vec<SpeakerLink>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT note_id, speaker, person_id FROM speaker_links
+             WHERE note_id = ?1 ORDER BY speaker",
+        )?;
+        let links = stmt
+            .query_map([note_id], |row| {
+                Ok(SpeakerLink {
+                    note_id: row.get(0)?,
+                    speaker: row.get(1)?,
+                    person_id: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(links)
+    }
+
+    /// Link a diarized speaker label of a note to a person (`None` removes the link).
+    /// Linked speakers also count as participants of the note.
+    pub fn link_speaker(
+        &self,
+        note_id: &str,
+        speaker: &str,
+        person_id: Option<i64>,
+    ) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        match person_id {
+            Some(person_id) => {
+                tx.execute(
+                    "INSERT OR REPLACE INTO speaker_links (note_id, speaker, person_id)
+                     VALUES (?1, ?2, ?3)",
+                    params![note_id, speaker, person_id],
+                )?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO note_participants (note_id, person_id) VALUES (?1, ?2)",
+                    params![note_id, person_id],
+                )?;
+                sync_participants_text(&tx, note_id)?;
+            }
+            None => {
+                tx.execute(
+                    "DELETE FROM speaker_links WHERE note_id = ?1 AND speaker = ?2",
+                    params![note_id, speaker],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Set or clear the person responsible for a summary's action items
+    pub fn set_summary_owner(&self, summary_id: i64, person_id: Option<i64>) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE summaries SET owner_id = ?1 WHERE id = ?2",
+            params![person_id, summary_id],
+        )?;
+        Ok(())
+    }
+
+    /// Open items of each note's latest action items summary that belong to a
+    /// person: every item of summaries they own, plus items mentioning their
+    /// name or one of their aliases
+    pub fn get_open_action_items(&self, person_id: i64) -> anyhow::Result<Vec<ActionItem>> {
+        let Some(person) = self.get_person(person_id)? else {
+            return Ok(Vec::new());
+        };
+        let names: Vec<&str> = std::iter::once(person.name.as_str())
+            .chain(person.aliases.iter().map(String::as_str))
+            .collect();
+
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.content, s.owner_id, n.id, n.title, n.started_at
+             FROM summaries s JOIN notes n ON n.id = s.note_id
+             WHERE s.summary_type = ?1
+               AND s.id = (
+                   SELECT MAX(latest.id) FROM summaries latest
+                   WHERE latest.note_id = s.note_id AND latest.summary_type = ?1
+               )
+             ORDER BY n.started_at DESC",
+        )?;
+        let summaries = stmt
+            .query_map([SummaryType::ActionItems.as_str()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })?
+            .filter_map(|r| r.ok());
+
+        let mut items = Vec::new();
+        for (summary_id, content, owner_id, note_id, note_title, started_at) in summaries {
+            let owned = owner_id == Some(person_id);
+            for text in extract_open_items(&content) {
+                if owned || names.iter().any(|name| mentions(&text, name)) {
+                    items.push(ActionItem {
+                        note_id: note_id.clone(),
+                        note_title: note_title.clone(),
+                        note_started_at: started_at.parse().unwrap_or_else(|_| Utc::now()),
+                        summary_id,
+                        text,
+                    });
+                }
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+/// Notes a person took part in, spoke in or owns a summary of
+fn person_note_ids(conn: &Connection, person_id: i64) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM notes
+         WHERE id IN (SELECT note_id FROM note_participants WHERE person_id = ?1)
+            OR id IN (SELECT note_id FROM speaker_links WHERE person_id = ?1)
+            OR id IN (SELECT note_id FROM summaries WHERE owner_id = ?1)
+         ORDER BY started_at DESC",
+    )?;
+    let ids = stmt.query_map([person_id], |row| row.get(0))?;
+    ids.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_participants() {
+        let parsed =
+            parse_participants("Alice, Bob <Bob@Example.com>; carol@example.com,\n ,<dave@x.io>");
+        assert_eq!(
+            parsed,
+            vec![
+                ("Alice".to_string(), None),
+                ("Bob".to_string(), Some("bob@example.com".to_string())),
+                (
+                    "carol@example.com".to_string(),
+                    Some("carol@example.com".to_string())
+                ),
+                ("dave@x.io".to_string(), Some("dave@x.io".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_open_items() {
+        let content = "**Action items**\n\
+            1. Alice sends the contract by Friday\n\
+            2) **Bob**: book the venue\n\
+            - [x] Carol already shared the slides\n\
+            - [ ] Alice follows up with legal\n\
+            * ~~Dropped: rename the project~~\n\
+            Nothing else.";
+        assert_eq!(
+            extract_open_items(content),
+            vec![
+                "Alice sends the contract by Friday",
+                "**Bob**: book the venue",
+                "Alice follows up with legal",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mentions_whole_words_only() {
+        assert!(mentions("**Alice**: send the deck", "alice"));
+        assert!(mentions("Ask Bob Smith about it", "Bob Smith"));
+        assert!(!mentions("Check the Alicente account", "Alice"));
+        assert!(!mentions("anything", " "));
+    }
+}
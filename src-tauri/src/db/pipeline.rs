@@ -0,0 +1,159 @@
+//! Post-recording pipeline runs and the status of their steps
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::models::{PipelineRun, PipelineStep, PipelineStepState, StepStatus};
+use crate::db::Database;
+
+fn run_steps(conn: &Connection, run_id: i64) -> rusqlite::Result<Vec<PipelineStepState>> {
+    let mut stmt = conn.prepare(
+        "SELECT position, step, status, error, updated_at
+         FROM pipeline_steps
+         WHERE run_id = ?1
+         ORDER BY position",
+    )?;
+    let steps = stmt
+        .query_map([run_id], |row| {
+            Ok((
+                row.get::<_, i32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .filter_map(|r| r.ok())
+        // Steps this version does not know are left out
+        .filter_map(|(position, step, status, error, updated_at)| {
+            Some(PipelineStepState {
+                position,
+                step: serde_json::from_str(&step).ok()?,
+                status: StepStatus::from_str(&status),
+                error,
+                updated_at: updated_at.parse().unwrap_or_else(|_| Utc::now()),
+            })
+        })
+        .collect();
+    Ok(steps)
+}
+
+fn get_run(conn: &Connection, run_id: i64) -> rusqlite::Result<Option<PipelineRun>> {
+    let run = conn
+        .query_row(
+            "SELECT id, note_id, template, created_at FROM pipeline_runs WHERE id = ?1",
+            [run_id],
+            |row| {
+                Ok(PipelineRun {
+                    id: row.get(0)?,
+                    note_id: row.get(1)?,
+                    template: row.get(2)?,
+                    steps: Vec::new(),
+                    created_at: row
+                        .get::<_, String>(3)?
+                        .parse()
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            },
+        )
+        .optional()?;
+    match run {
+        Some(mut run) => {
+            run.steps = run_steps(conn, run.id)?;
+            Ok(Some(run))
+        }
+        None => Ok(None),
+    }
+}
+
+impl Database {
+    /// Create a run of `steps` over a note, every step pending
+    pub fn create_pipeline_run(
+        &self,
+        note_id: &str,
+        template: &str,
+        steps: &[PipelineStep],
+    ) -> anyhow::Result<PipelineRun> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let tx = conn.transaction()?;
+        let now = Utc::now().to_rfc3339();
+
+        tx.execute(
+            "INSERT INTO pipeline_runs (note_id, template, created_at) VALUES (?1, ?2, ?3)",
+            params![note_id, template, now],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        for (position, step) in steps.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO pipeline_steps (run_id, position, step, status, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    run_id,
+                    position as i32,
+                    serde_json::to_string(step)?,
+                    StepStatus::Pending.as_str(),
+                    now
+                ],
+            )?;
+        }
+        let run = get_run(&tx, run_id)?.ok_or_else(|| anyhow::anyhow!("Run not found"))?;
+
+        tx.commit()?;
+        Ok(run)
+    }
+
+    /// Get a run with its steps
+    pub fn get_pipeline_run(&self, run_id: i64) -> anyhow::Result<Option<PipelineRun>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(get_run(&conn, run_id)?)
+    }
+
+    /// The latest run over a note
+    pub fn get_note_pipeline_run(&self, note_id: &str) -> anyhow::Result<Option<PipelineRun>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let run_id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM pipeline_runs WHERE note_id = ?1 ORDER BY id DESC LIMIT 1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match run_id {
+            Some(run_id) => Ok(get_run(&conn, run_id)?),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_pipeline_step_status(
+        &self,
+        run_id: i64,
+        position: i32,
+        status: StepStatus,
+        error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        conn.execute(
+            "UPDATE pipeline_steps SET status = ?1, error = ?2, updated_at = ?3
+             WHERE run_id = ?4 AND position = ?5",
+            params![
+                status.as_str(),
+                error,
+                Utc::now().to_rfc3339(),
+                run_id,
+                position
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Fail the steps a previous session left pending or running, so they can be retried
+    pub fn fail_interrupted_pipeline_steps(&self) -> anyhow::Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let count = conn.execute(
+            "UPDATE pipeline_steps SET status = 'failed', error = 'Interrupted', updated_at = ?1
+             WHERE status IN ('pending', 'running')",
+            [Utc::now().to_rfc3339()],
+        )?;
+        Ok(count)
+    }
+}
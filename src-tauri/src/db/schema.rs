@@ -1,7 +1,7 @@
 use rusqlite::Connection;
 
 #[allow(dead_code)]
-pub const SCHEMA_VERSION: i32 = 4;
+pub const SCHEMA_VERSION: i32 = 13;
 
 pub fn run_migrations(conn: &Connection) -> rusqlite::Result<()> {
     let version = get_schema_version(conn)?;
@@ -18,6 +18,33 @@ pub fn run_migrations(conn: &Connection) -> rusqlite::Result<()> {
     if version < 4 {
         migrate_v4(conn)?;
     }
+    if version < 5 {
+        migrate_v5(conn)?;
+    }
+    if version < 6 {
+        migrate_v6(conn)?;
+    }
+    if version < 7 {
+        migrate_v7(conn)?;
+    }
+    if version < 8 {
+        migrate_v8(conn)?;
+    }
+    if version < 9 {
+        migrate_v9(conn)?;
+    }
+    if version < 10 {
+        migrate_v10(conn)?;
+    }
+    if version < 11 {
+        migrate_v11(conn)?;
+    }
+    if version < 12 {
+        migrate_v12(conn)?;
+    }
+    if version < 13 {
+        migrate_v13(conn)?;
+    }
 
     Ok(())
 }
@@ -212,3 +239,358 @@ fn migrate_v4(conn: &Connection) -> rusqlite::Result<()> {
 
     Ok(())
 }
+
+fn migrate_v5(conn: &Connection) -> rusqlite::Result<()> {
+    // Persistent queue for background re-transcription
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS transcription_jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id TEXT NOT NULL,
+            model_id TEXT NOT NULL,
+            language TEXT,
+            mode TEXT NOT NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_transcription_jobs_status
+         ON transcription_jobs(status)",
+        [],
+    )?;
+
+    set_schema_version(conn, 5)?;
+
+    Ok(())
+}
+
+fn migrate_v6(conn: &Connection) -> rusqlite::Result<()> {
+    // Transcript versions: every segment belongs to one transcript, one transcript per note is active
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS transcripts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id TEXT NOT NULL,
+            model_id TEXT,
+            language TEXT,
+            source TEXT NOT NULL,
+            is_active INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_transcripts_note
+         ON transcripts(note_id)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_transcripts_active
+         ON transcripts(note_id) WHERE is_active = 1",
+        [],
+    )?;
+
+    conn.execute(
+        "ALTER TABLE transcript_segments
+         ADD COLUMN transcript_id INTEGER REFERENCES transcripts(id) ON DELETE CASCADE",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_transcript_segments_transcript
+         ON transcript_segments(transcript_id)",
+        [],
+    )?;
+
+    // Existing segments become the active legacy version of their note
+    conn.execute(
+        "INSERT INTO transcripts (note_id, source, is_active, created_at)
+         SELECT note_id, 'legacy', 1, MIN(created_at)
+         FROM transcript_segments
+         GROUP BY note_id",
+        [],
+    )?;
+    conn.execute(
+        "UPDATE transcript_segments SET transcript_id = (
+            SELECT t.id FROM transcripts t WHERE t.note_id = transcript_segments.note_id
+         )",
+        [],
+    )?;
+
+    set_schema_version(conn, 6)?;
+
+    Ok(())
+}
+
+fn migrate_v7(conn: &Connection) -> rusqlite::Result<()> {
+    // Track which recorded segments post-meeting refinement has already re-transcribed
+    conn.execute(
+        "ALTER TABLE audio_segments ADD COLUMN refined INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+
+    set_schema_version(conn, 7)?;
+
+    Ok(())
+}
+
+fn migrate_v8(conn: &Connection) -> rusqlite::Result<()> {
+    // Confidential notes stay out of search, AI and export until unlocked
+    conn.execute(
+        "ALTER TABLE notes ADD COLUMN confidential INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+
+    set_schema_version(conn, 8)?;
+
+    Ok(())
+}
+
+fn migrate_v9(conn: &Connection) -> rusqlite::Result<()> {
+    // Hierarchical folders (a project is a folder of kind 'project')
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            parent_id INTEGER,
+            kind TEXT NOT NULL DEFAULT 'folder',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (parent_id) REFERENCES folders(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_folders_parent
+         ON folders(parent_id)",
+        [],
+    )?;
+
+    // Notes of a deleted folder fall back to the top level
+    conn.execute(
+        "ALTER TABLE notes ADD COLUMN folder_id INTEGER REFERENCES folders(id) ON DELETE SET NULL",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_notes_folder
+         ON notes(folder_id)",
+        [],
+    )?;
+
+    // Tags and the notes they are attached to
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS note_tags (
+            note_id TEXT NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (note_id, tag_id),
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_note_tags_tag
+         ON note_tags(tag_id)",
+        [],
+    )?;
+
+    // Full-text indexes so search also matches tag and folder names
+    for table in ["tags", "folders"] {
+        conn.execute_batch(&format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS {table}_fts USING fts5(
+                name,
+                content='{table}',
+                content_rowid='id'
+            );
+            CREATE TRIGGER IF NOT EXISTS {table}_ai AFTER INSERT ON {table} BEGIN
+                INSERT INTO {table}_fts(rowid, name) VALUES (NEW.id, NEW.name);
+            END;
+            CREATE TRIGGER IF NOT EXISTS {table}_ad AFTER DELETE ON {table} BEGIN
+                INSERT INTO {table}_fts({table}_fts, rowid, name) VALUES ('delete', OLD.id, OLD.name);
+            END;
+            CREATE TRIGGER IF NOT EXISTS {table}_au AFTER UPDATE OF name ON {table} BEGIN
+                INSERT INTO {table}_fts({table}_fts, rowid, name) VALUES ('delete', OLD.id, OLD.name);
+                INSERT INTO {table}_fts(rowid, name) VALUES (NEW.id, NEW.name);
+            END;"
+        ))?;
+    }
+
+    set_schema_version(conn, 9)?;
+
+    Ok(())
+}
+
+fn migrate_v10(conn: &Connection) -> rusqlite::Result<()> {
+    // People (aliases stored as a JSON array) and the notes they took part in
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS people (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            email TEXT,
+            aliases TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS note_participants (
+            note_id TEXT NOT NULL,
+            person_id INTEGER NOT NULL,
+            PRIMARY KEY (note_id, person_id),
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+            FOREIGN KEY (person_id) REFERENCES people(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_note_participants_person
+         ON note_participants(person_id)",
+        [],
+    )?;
+
+    // Diarized speaker labels ("SPEAKER_01") of a note mapped to people
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS speaker_links (
+            note_id TEXT NOT NULL,
+            speaker TEXT NOT NULL,
+            person_id INTEGER NOT NULL,
+            PRIMARY KEY (note_id, speaker),
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+            FOREIGN KEY (person_id) REFERENCES people(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "ALTER TABLE summaries ADD COLUMN owner_id INTEGER REFERENCES people(id) ON DELETE SET NULL",
+        [],
+    )?;
+
+    // Turn the existing comma-separated participant strings into people
+    let notes: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, participants FROM notes
+             WHERE participants IS NOT NULL AND TRIM(participants) != ''",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (note_id, participants) in notes {
+        crate::db::people::link_participants(conn, &note_id, &participants)?;
+    }
+
+    set_schema_version(conn, 10)?;
+
+    Ok(())
+}
+
+fn migrate_v11(conn: &Connection) -> rusqlite::Result<()> {
+    // Events imported from .ics files; `note_id` is the note recorded for the event
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS calendar_events (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            description TEXT,
+            location TEXT,
+            participants TEXT,
+            starts_at TEXT NOT NULL,
+            ends_at TEXT NOT NULL,
+            source TEXT NOT NULL,
+            note_id TEXT,
+            imported_at TEXT NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_calendar_events_starts
+         ON calendar_events(starts_at)",
+        [],
+    )?;
+
+    // A note belongs to at most one event
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_events_note
+         ON calendar_events(note_id) WHERE note_id IS NOT NULL",
+        [],
+    )?;
+
+    set_schema_version(conn, 11)?;
+
+    Ok(())
+}
+
+fn migrate_v12(conn: &Connection) -> rusqlite::Result<()> {
+    // Post-recording pipeline runs; `template` names the pipeline template used
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pipeline_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id TEXT NOT NULL,
+            template TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pipeline_runs_note
+         ON pipeline_runs(note_id)",
+        [],
+    )?;
+
+    // `step` is the step definition as JSON
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pipeline_steps (
+            run_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            step TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            error TEXT,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (run_id, position),
+            FOREIGN KEY (run_id) REFERENCES pipeline_runs(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    set_schema_version(conn, 12)?;
+
+    Ok(())
+}
+
+fn migrate_v13(conn: &Connection) -> rusqlite::Result<()> {
+    // Segments whose mic file was noise-suppressed while recording
+    conn.execute(
+        "ALTER TABLE audio_segments ADD COLUMN denoised INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+
+    set_schema_version(conn, 13)?;
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+/// Receives the events core tasks raise while they run. The app forwards them
+/// to the webview; tests and the CLI can collect or ignore them.
+pub trait EventSink: Send + Sync {
+    fn emit_json(&self, event: &str, payload: serde_json::Value);
+
+    /// Serialize `payload` and hand it to the sink
+    fn emit_event<T: Serialize>(&self, event: &str, payload: &T)
+    where
+        Self: Sized,
+    {
+        match serde_json::to_value(payload) {
+            Ok(value) => self.emit_json(event, value),
+            Err(e) => eprintln!("Failed to serialize {} event: {}", event, e),
+        }
+    }
+}
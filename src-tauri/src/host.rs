@@ -0,0 +1,37 @@
+//! The Tauri side of the core traits: events go to the webview and the
+//! database comes from managed app state.
+
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::{Database, DatabaseHandle};
+use crate::events::EventSink;
+
+impl EventSink for AppHandle {
+    fn emit_json(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+}
+
+impl DatabaseHandle for AppHandle {
+    fn with_db<R>(&self, f: impl FnOnce(&Database) -> R) -> R {
+        f(&self.state::<Database>())
+    }
+}
+
+impl Database {
+    /// Open the library database in the app data directory
+    pub fn new(app_handle: &AppHandle) -> anyhow::Result<Self> {
+        Self::open(get_db_path(app_handle)?)
+    }
+}
+
+fn get_db_path(app_handle: &AppHandle) -> anyhow::Result<PathBuf> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("netnote.db"))
+}
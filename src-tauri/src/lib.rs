@@ -1,8 +1,15 @@
 mod ai;
+mod api_server;
 mod audio;
+mod calendar;
+pub mod cli;
 mod commands;
+mod crypto;
 mod db;
+mod events;
+mod host;
 mod meeting_detection;
+pub mod netnote_core;
 mod transcription;
 
 use commands::{init_transcription_state, AiState, AudioState};
@@ -115,9 +122,24 @@ fn show_main_window(app: tauri::AppHandle) {
         return;
     }
 
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.set_focus();
+    reveal_main_window(&app, None);
+}
+
+/// Show and focus the main window. While the app lock is engaged the frontend
+/// is told to show its lock screen first and `action`, a tray event for the
+/// frontend, is dropped.
+fn reveal_main_window(app: &tauri::AppHandle, action: Option<&str>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let locked = commands::is_app_locked(app);
+    if locked {
+        let _ = window.emit(commands::APP_LOCKED_EVENT, ());
+    }
+    let _ = window.show();
+    let _ = window.set_focus();
+    if let Some(event) = action.filter(|_| !locked) {
+        let _ = window.emit(event, ());
     }
 }
 
@@ -130,6 +152,9 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .register_uri_scheme_protocol(commands::AUDIO_PROTOCOL, |ctx, request| {
+            commands::serve_library_audio(ctx.app_handle(), &request)
+        })
         .setup(|app| {
             // Check if app was launched with --minimized flag (from autostart)
             let args: Vec<String> = std::env::args().collect();
@@ -145,18 +170,49 @@ pub fn run() {
             ))?;
 
             let db = Database::new(app.handle())?;
+            commands::load_audio_settings(&db);
+            let audio_state = AudioState::default();
+            commands::load_device_preferences(&audio_state, &db);
             app.manage(db);
-            app.manage(AudioState::default());
+            app.manage(audio_state);
             app.manage(AiState::default());
-            let transcription_state = init_transcription_state(app.handle());
+            let transcription_state = init_transcription_state(app.path().app_data_dir()?);
             app.manage(transcription_state);
+            app.manage(commands::JobQueueState::default());
+            app.manage(commands::PrivacyState::default());
+            // Start locked, as if the window had been hidden to the tray
+            commands::lock_on_hide(app.handle());
 
             // Meeting detection state
-            app.manage(Arc::new(MeetingDetectionState::default()));
+            let meeting_state = Arc::new(MeetingDetectionState::default());
+            meeting_detection::load_meeting_rules(&meeting_state, &app.state::<Database>());
+            app.manage(meeting_state);
 
             // Start meeting detection
             meeting_detection::start_meeting_detection(app.handle());
 
+            // Watch for unplugged / failing input devices during recording
+            commands::start_device_monitor(app.handle());
+
+            // Work through queued re-transcription jobs in the background
+            commands::start_job_worker(app.handle());
+
+            // Steps a previous session left unfinished can be retried from the note
+            commands::fail_interrupted_pipelines(&app.state::<Database>());
+
+            // Keep imported calendar events current
+            commands::start_calendar_sync(app.handle());
+
+            // Drop audio past the retention window
+            commands::start_retention_cleanup(app.handle());
+
+            // Local API for scripts, when the user enabled it
+            app.manage(api_server::ApiServerState::default());
+            api_server::apply_api_server_settings(app.handle());
+
+            // Finish encrypting the library if a migration was interrupted
+            commands::resume_library_migration(app.handle());
+
             // Create custom application menu (macOS) with Hide instead of Quit on Cmd+Q
             #[cfg(target_os = "macos")]
             {
@@ -203,6 +259,7 @@ pub fn run() {
                         "hide_window" => {
                             if let Some(window) = app_handle.get_webview_window("main") {
                                 let _ = window.hide();
+                                commands::lock_on_hide(app_handle);
                             }
                         }
                         "quit_app" => {
@@ -235,26 +292,9 @@ pub fn run() {
                 .menu(&menu)
                 .show_menu_on_left_click(true)
                 .on_menu_event(|app: &tauri::AppHandle, event| match event.id.as_ref() {
-                    "open" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "new_note" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.emit("tray-new-note", ());
-                        }
-                    }
-                    "settings" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.emit("tray-open-settings", ());
-                        }
-                    }
+                    "open" => reveal_main_window(app, None),
+                    "new_note" => reveal_main_window(app, Some("tray-new-note")),
+                    "settings" => reveal_main_window(app, Some("tray-open-settings")),
                     "install_update" => {
                         if let Some(window) = app.get_webview_window("main") {
                             let _ = window.emit("tray-install-update", ());
@@ -275,26 +315,9 @@ pub fn run() {
                 .menu(&menu)
                 .show_menu_on_left_click(true)
                 .on_menu_event(|app: &tauri::AppHandle, event| match event.id.as_ref() {
-                    "open" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "new_note" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.emit("tray-new-note", ());
-                        }
-                    }
-                    "settings" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.emit("tray-open-settings", ());
-                        }
-                    }
+                    "open" => reveal_main_window(app, None),
+                    "new_note" => reveal_main_window(app, Some("tray-new-note")),
+                    "settings" => reveal_main_window(app, Some("tray-open-settings")),
                     "install_update" => {
                         if let Some(window) = app.get_webview_window("main") {
                             let _ = window.emit("tray-install-update", ());
@@ -323,6 +346,7 @@ pub fn run() {
             if let WindowEvent::CloseRequested { api, .. } = event {
                 let _ = window.hide();
                 api.prevent_close();
+                commands::lock_on_hide(window.app_handle());
             }
         })
         .invoke_handler(tauri::generate_handler![
@@ -331,10 +355,41 @@ pub fn run() {
             commands::create_note,
             commands::get_note,
             commands::list_notes,
+            commands::query_notes,
             commands::end_note,
             commands::delete_note,
             commands::update_note,
             commands::search_notes,
+            commands::list_tags,
+            commands::get_note_tags,
+            commands::create_tag,
+            commands::update_tag,
+            commands::delete_tag,
+            commands::add_note_tag,
+            commands::remove_note_tag,
+            commands::list_folders,
+            commands::create_folder,
+            commands::update_folder,
+            commands::delete_folder,
+            commands::move_note_to_folder,
+            commands::import_calendar_file,
+            commands::get_calendar_source,
+            commands::set_calendar_source,
+            commands::sync_calendar,
+            commands::list_upcoming_events,
+            commands::get_note_event,
+            commands::link_note_to_event,
+            commands::list_people,
+            commands::create_person,
+            commands::update_person,
+            commands::delete_person,
+            commands::merge_people,
+            commands::get_note_participants,
+            commands::set_note_participants,
+            commands::get_speaker_links,
+            commands::link_speaker,
+            commands::set_summary_owner,
+            commands::get_open_action_items,
             commands::start_recording,
             commands::stop_recording,
             commands::get_recording_status,
@@ -347,11 +402,30 @@ pub fn run() {
             commands::get_microphone_auth_status,
             commands::request_microphone_permission,
             commands::get_audio_input_devices,
+            commands::switch_recording_device,
+            commands::get_device_preferences,
+            commands::set_device_preferences,
             commands::start_dual_recording,
             commands::stop_dual_recording,
             commands::is_dual_recording,
+            commands::get_mix_options,
+            commands::set_mix_options,
+            commands::remix_note_playback,
+            commands::get_note_playback,
+            commands::get_waveform_peaks,
+            commands::get_encryption_status,
+            commands::unlock_library,
+            commands::enable_encryption,
+            commands::get_app_lock_status,
+            commands::set_app_lock,
+            commands::unlock_app,
+            commands::set_note_confidential,
+            commands::unlock_confidential_note,
+            commands::lock_confidential_notes,
             commands::is_aec_enabled,
             commands::set_aec_enabled,
+            commands::get_noise_suppression,
+            commands::set_noise_suppression,
             // Pause/Resume/Continue recording commands
             commands::get_recording_phase,
             commands::pause_recording_cmd,
@@ -370,12 +444,33 @@ pub fn run() {
             commands::is_downloading,
             commands::delete_model,
             commands::load_model,
+            commands::add_custom_model,
+            commands::get_model_mirror,
+            commands::set_model_mirror,
+            commands::enqueue_retranscription,
+            commands::list_transcription_jobs,
+            commands::get_transcription_queue_status,
+            commands::pause_transcription_queue,
+            commands::resume_transcription_queue,
+            commands::cancel_transcription_job,
+            commands::clear_finished_transcription_jobs,
+            commands::get_refinement_options,
+            commands::set_refinement_options,
+            commands::get_pipeline_settings,
+            commands::set_pipeline_settings,
+            commands::run_note_pipeline,
+            commands::get_note_pipeline,
+            commands::retry_pipeline_step,
             commands::get_loaded_model,
             commands::transcribe_audio,
             commands::transcribe_dual_audio,
             commands::is_transcribing,
             commands::get_transcript,
             commands::add_transcript_segment,
+            commands::list_transcripts,
+            commands::get_transcript_version,
+            commands::set_active_transcript,
+            commands::delete_transcript,
             commands::start_live_transcription,
             commands::stop_live_transcription,
             commands::is_live_transcribing,
@@ -395,6 +490,13 @@ pub fn run() {
             commands::export_note_markdown,
             commands::save_export_to_file,
             commands::get_export_directory,
+            commands::extract_clip,
+            commands::get_storage_report,
+            commands::delete_orphan_files,
+            commands::delete_note_audio,
+            commands::get_retention_policy,
+            commands::set_retention_policy,
+            commands::apply_retention_policy,
             // Settings commands
             commands::get_theme_preference,
             commands::set_theme_preference,
@@ -409,6 +511,12 @@ pub fn run() {
             meeting_detection::set_meeting_detection_enabled,
             meeting_detection::is_meeting_detection_enabled,
             meeting_detection::clear_detected_meetings,
+            meeting_detection::get_meeting_rules,
+            meeting_detection::set_meeting_rules,
+            meeting_detection::reset_meeting_rules,
+            api_server::get_api_server_status,
+            api_server::set_api_server_settings,
+            api_server::regenerate_api_token,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -420,6 +528,7 @@ pub fn run() {
                 if let Some(window) = app_handle.get_webview_window("main") {
                     let _ = window.hide();
                 }
+                commands::lock_on_hide(app_handle);
             }
         });
 }
@@ -0,0 +1,810 @@
+//! Meeting detection module for detecting when meeting apps start
+//! Supports browser-based meetings (Google Meet, etc.) via window title monitoring
+//! on macOS (CGWindowList), Windows (EnumWindows) and Linux (X11 `_NET_CLIENT_LIST`),
+//! plus PipeWire capture streams on Linux for meeting apps using the microphone.
+//! Which titles are meetings, and what happens then, is up to the user's [`rules`].
+
+mod rules;
+
+pub use rules::{MeetingAction, MeetingEndAction, MeetingRule, RuleSet};
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::Database;
+
+/// Settings key holding the user's rules as JSON
+const MEETING_RULES_KEY: &str = "meeting_rules";
+
+/// Emitted with [`MeetingDetected`] when a meeting starts
+pub const MEETING_DETECTED: &str = "meeting-detected";
+/// Emitted with [`MeetingEnded`] when a detected meeting's window is gone
+pub const MEETING_ENDED: &str = "meeting-ended";
+
+/// How often the backends are polled
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Polls in a row a meeting must be missing from before it ends (15 s), so
+/// switching tabs or a title changing for a moment doesn't end it
+const MISSED_POLLS_TO_END: u32 = 5;
+
+/// Meeting apps recognized by the executable capturing the microphone (Linux / PipeWire)
+#[cfg(target_os = "linux")]
+const MIC_APPS: &[(&str, &str)] = &[
+    ("zoom", "Zoom"),
+    ("teams", "Microsoft Teams"),
+    ("slack", "Slack Huddle"),
+    ("discord", "Discord"),
+    ("skype", "Skype"),
+    ("webex", "Webex"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MeetingDetected {
+    /// Stays the same for as long as the meeting goes on
+    pub meeting_id: String,
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    pub is_browser: bool,
+    pub action: MeetingAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MeetingEnded {
+    pub meeting_id: String,
+    pub app_name: String,
+    /// What to do with a recording started for the meeting
+    pub on_end: MeetingEndAction,
+}
+
+/// Something a backend saw during one poll
+#[derive(Debug, Clone)]
+pub enum Seen {
+    Window(String),
+    /// A meeting app capturing the microphone
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    Microphone {
+        app_name: &'static str,
+        binary: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeetingChange {
+    Detected(MeetingDetected),
+    Ended(MeetingEnded),
+}
+
+/// The meeting `seen` belongs to according to `rules`, unless it is ignored
+fn identify(rules: &RuleSet, seen: &Seen) -> Option<(MeetingDetected, MeetingEndAction)> {
+    let (meeting_id, app_name, bundle_id, is_browser, action, on_end) = match seen {
+        Seen::Window(title) => {
+            let rule = rules.match_title(title)?;
+            (
+                browser_meeting_id(&rule.app_name, title),
+                rule.app_name.clone(),
+                None,
+                true,
+                rule.action,
+                rule.on_end,
+            )
+        }
+        Seen::Microphone { app_name, binary } => {
+            // Apps without rules of their own are announced
+            let (action, on_end) = rules
+                .match_app(app_name)
+                .map_or((MeetingAction::Notify, MeetingEndAction::Nothing), |rule| {
+                    (rule.action, rule.on_end)
+                });
+            (
+                format!("mic:{}", app_name),
+                app_name.to_string(),
+                Some(binary.clone()),
+                false,
+                action,
+                on_end,
+            )
+        }
+    };
+    if action == MeetingAction::Ignore {
+        return None;
+    }
+    Some((
+        MeetingDetected {
+            meeting_id,
+            app_name,
+            bundle_id,
+            is_browser,
+            action,
+        },
+        on_end,
+    ))
+}
+
+/// Id of a meeting in a window titled `title`: the meeting URL or code when
+/// the title has one, so that it survives the rest of the title changing
+/// (speaker icons, unread counts, the page's own updates). Without one there
+/// is a single meeting per app.
+fn browser_meeting_id(app_name: &str, title: &str) -> String {
+    static MEETING_CODE: OnceLock<Regex> = OnceLock::new();
+    let code = MEETING_CODE.get_or_init(|| {
+        // A URL, a Google Meet code (abc-defg-hij) or a Zoom meeting number
+        Regex::new(r"https?://\S+|\b[a-z]{3}-[a-z]{4}-[a-z]{3}\b|\b\d{3} ?\d{3,4} ?\d{3,4}\b")
+            .expect("meeting code pattern is valid")
+    });
+    match code.find(title) {
+        Some(code) => format!("{}:{}", app_name, code.as_str().replace(' ', "")),
+        None => app_name.to_string(),
+    }
+}
+
+/// A meeting seen on an earlier poll
+struct ActiveMeeting {
+    ended: MeetingEnded,
+    /// Polls in a row it was not seen on
+    missed: u32,
+}
+
+/// Tells meetings starting and ending apart from one poll to the next
+#[derive(Default)]
+pub struct Detector {
+    /// Meetings seen recently, by id
+    active: HashMap<String, ActiveMeeting>,
+}
+
+impl Detector {
+    /// Compare what was seen now with the earlier polls. A meeting ends once it
+    /// has been missing for [`MISSED_POLLS_TO_END`] polls. Meetings that ended come first.
+    pub fn update(&mut self, rules: &RuleSet, seen: &[Seen]) -> Vec<MeetingChange> {
+        let mut present: Vec<(MeetingDetected, MeetingEndAction)> = Vec::new();
+        for (meeting, on_end) in seen.iter().filter_map(|s| identify(rules, s)) {
+            if !present
+                .iter()
+                .any(|(m, _)| m.meeting_id == meeting.meeting_id)
+            {
+                present.push((meeting, on_end));
+            }
+        }
+
+        let mut changes = Vec::new();
+        self.active.retain(|id, active| {
+            if present.iter().any(|(m, _)| &m.meeting_id == id) {
+                active.missed = 0;
+                return true;
+            }
+            active.missed += 1;
+            if active.missed < MISSED_POLLS_TO_END {
+                return true;
+            }
+            changes.push(MeetingChange::Ended(active.ended.clone()));
+            false
+        });
+
+        for (meeting, on_end) in present {
+            if self.active.contains_key(&meeting.meeting_id) {
+                continue;
+            }
+            self.active.insert(
+                meeting.meeting_id.clone(),
+                ActiveMeeting {
+                    ended: MeetingEnded {
+                        meeting_id: meeting.meeting_id.clone(),
+                        app_name: meeting.app_name.clone(),
+                        on_end,
+                    },
+                    missed: 0,
+                },
+            );
+            changes.push(MeetingChange::Detected(meeting));
+        }
+        changes
+    }
+
+    /// Forget the meetings seen so far, so they are detected again
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+}
+
+/// State for meeting detection
+pub struct MeetingDetectionState {
+    enabled: AtomicBool,
+    running: AtomicBool,
+    rules: Mutex<RuleSet>,
+    detector: Mutex<Detector>,
+}
+
+impl Default for MeetingDetectionState {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            running: AtomicBool::new(false),
+            rules: Mutex::new(RuleSet::default()),
+            detector: Mutex::new(Detector::default()),
+        }
+    }
+}
+
+impl MeetingDetectionState {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Clear all detected meetings
+    pub fn clear_all_detected(&self) {
+        if let Ok(mut detector) = self.detector.lock() {
+            detector.clear();
+        }
+    }
+
+    pub fn rules(&self) -> Vec<MeetingRule> {
+        self.rules
+            .lock()
+            .map(|rules| rules.rules().to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn set_rules(&self, rules: RuleSet) {
+        if let Ok(mut current) = self.rules.lock() {
+            *current = rules;
+        }
+    }
+}
+
+/// Apply the saved meeting rules; invalid or missing ones leave the built-in rules
+pub fn load_meeting_rules(state: &MeetingDetectionState, db: &Database) {
+    let saved = db
+        .get_setting(MEETING_RULES_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str::<Vec<MeetingRule>>(&json).ok())
+        .and_then(|rules| RuleSet::new(rules).ok());
+    if let Some(rules) = saved {
+        state.set_rules(rules);
+    }
+}
+
+/// Start meeting detection (call from setup)
+pub fn start_meeting_detection(app: &AppHandle) {
+    let state = app.state::<Arc<MeetingDetectionState>>();
+
+    if state.running.swap(true, Ordering::SeqCst) {
+        // Already running
+        return;
+    }
+
+    // Start window title monitoring for meetings
+    #[cfg(target_os = "macos")]
+    start_detection_loop(app.clone(), || {
+        macos::window_titles()
+            .into_iter()
+            .map(Seen::Window)
+            .collect()
+    });
+
+    #[cfg(target_os = "windows")]
+    start_detection_loop(app.clone(), || {
+        win32::window_titles()
+            .into_iter()
+            .map(Seen::Window)
+            .collect()
+    });
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut x11 = linux::X11Windows::default();
+        start_detection_loop(app.clone(), move || {
+            let mut seen: Vec<Seen> = x11.window_titles().into_iter().map(Seen::Window).collect();
+            seen.extend(linux::microphone_streams());
+            seen
+        });
+    }
+}
+
+/// Poll `poll` every few seconds and emit the meetings starting and ending
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "windows", target_os = "linux")),
+    allow(dead_code)
+)]
+fn start_detection_loop<F>(app: AppHandle, mut poll: F)
+where
+    F: FnMut() -> Vec<Seen> + Send + 'static,
+{
+    thread::spawn(move || loop {
+        let state = match app.try_state::<Arc<MeetingDetectionState>>() {
+            Some(s) => s,
+            None => {
+                thread::sleep(Duration::from_secs(5));
+                continue;
+            }
+        };
+
+        if !state.is_enabled() {
+            thread::sleep(Duration::from_secs(5));
+            continue;
+        }
+
+        let seen = poll();
+        let changes = {
+            let rules = state.rules.lock().unwrap();
+            state.detector.lock().unwrap().update(&rules, &seen)
+        };
+
+        for change in changes {
+            match change {
+                MeetingChange::Detected(meeting) => {
+                    println!(
+                        "[meeting-detection] Detected {} meeting: '{}'",
+                        meeting.app_name, meeting.meeting_id
+                    );
+                    let _ = app.emit(MEETING_DETECTED, &meeting);
+                }
+                MeetingChange::Ended(meeting) => {
+                    println!(
+                        "[meeting-detection] {} meeting ended: '{}'",
+                        meeting.app_name, meeting.meeting_id
+                    );
+                    let _ = app.emit(MEETING_ENDED, &meeting);
+                }
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use core_foundation::array::CFArray;
+    use core_foundation::base::{CFGetTypeID, TCFType};
+    use core_foundation::string::{CFString, CFStringGetTypeID};
+    use core_graphics::window::{
+        kCGNullWindowID, kCGWindowListExcludeDesktopElements, kCGWindowListOptionAll,
+        CGWindowListCopyWindowInfo,
+    };
+
+    /// Titles of all windows, minimized ones and those on other spaces included
+    pub fn window_titles() -> Vec<String> {
+        let mut titles = Vec::new();
+
+        // Not just on-screen windows: a minimized meeting is still going on
+        let windows_ptr = unsafe {
+            CGWindowListCopyWindowInfo(
+                kCGWindowListOptionAll | kCGWindowListExcludeDesktopElements,
+                kCGNullWindowID,
+            )
+        };
+        if windows_ptr.is_null() {
+            return titles;
+        }
+        let windows: CFArray<*const std::ffi::c_void> =
+            unsafe { CFArray::wrap_under_create_rule(windows_ptr) };
+
+        for i in 0..windows.len() {
+            // Get window dictionary
+            let Some(window_dict) = windows.get(i) else {
+                continue;
+            };
+
+            // Get window title
+            let name_key = CFString::new("kCGWindowName");
+            let name_ptr = unsafe {
+                core_foundation::dictionary::CFDictionaryGetValue(
+                    *window_dict as *const _,
+                    name_key.as_concrete_TypeRef() as *const _,
+                )
+            };
+
+            if !name_ptr.is_null() {
+                let type_id = unsafe { CFGetTypeID(name_ptr) };
+                if type_id == unsafe { CFStringGetTypeID() } {
+                    let window_title: CFString =
+                        unsafe { CFString::wrap_under_get_rule(name_ptr as *const _) };
+                    titles.push(window_title.to_string());
+                }
+            }
+        }
+        titles
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use windows::core::BOOL;
+    use windows::Win32::Foundation::{HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowTextLengthW, GetWindowTextW, IsWindowVisible,
+    };
+
+    /// Titles of all visible top-level windows
+    pub fn window_titles() -> Vec<String> {
+        let mut titles: Vec<String> = Vec::new();
+        unsafe {
+            let _ = EnumWindows(
+                Some(collect_title),
+                LPARAM(&mut titles as *mut Vec<String> as isize),
+            );
+        }
+        titles
+    }
+
+    unsafe extern "system" fn collect_title(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: `lparam` is the `Vec` passed to `EnumWindows`, which outlives the enumeration
+        let titles = unsafe { &mut *(lparam.0 as *mut Vec<String>) };
+        unsafe {
+            if IsWindowVisible(hwnd).as_bool() {
+                let len = GetWindowTextLengthW(hwnd);
+                if len > 0 {
+                    let mut buf = vec![0u16; len as usize + 1];
+                    let copied = GetWindowTextW(hwnd, &mut buf);
+                    if copied > 0 {
+                        titles.push(String::from_utf16_lossy(&buf[..copied as usize]));
+                    }
+                }
+            }
+        }
+        // Keep enumerating
+        BOOL(1)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::process::Command;
+
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    use super::{Seen, MIC_APPS};
+
+    /// Longest window title read, in 32-bit units
+    const MAX_TITLE_LEN: u32 = 1024;
+
+    struct X11Connection {
+        conn: RustConnection,
+        root: Window,
+        client_list: Atom,
+        net_wm_name: Atom,
+        utf8_string: Atom,
+    }
+
+    impl X11Connection {
+        fn open() -> Option<Self> {
+            let (conn, screen_num) = x11rb::connect(None).ok()?;
+            let root = conn.setup().roots.get(screen_num)?.root;
+            let atom = |name: &[u8]| -> Option<Atom> {
+                Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom)
+            };
+            let client_list = atom(b"_NET_CLIENT_LIST")?;
+            let net_wm_name = atom(b"_NET_WM_NAME")?;
+            let utf8_string = atom(b"UTF8_STRING")?;
+            Some(Self {
+                conn,
+                root,
+                client_list,
+                net_wm_name,
+                utf8_string,
+            })
+        }
+
+        fn window_titles(&self) -> Option<Vec<String>> {
+            let clients = self
+                .conn
+                .get_property(
+                    false,
+                    self.root,
+                    self.client_list,
+                    AtomEnum::WINDOW,
+                    0,
+                    u32::MAX,
+                )
+                .ok()?
+                .reply()
+                .ok()?;
+            let titles = clients
+                .value32()?
+                .filter_map(|window| self.window_title(window))
+                .collect();
+            Some(titles)
+        }
+
+        /// `_NET_WM_NAME`, falling back to the legacy `WM_NAME`
+        fn window_title(&self, window: Window) -> Option<String> {
+            let property = |name: Atom, kind: Atom| {
+                self.conn
+                    .get_property(false, window, name, kind, 0, MAX_TITLE_LEN)
+                    .ok()?
+                    .reply()
+                    .ok()
+                    .filter(|reply| !reply.value.is_empty())
+            };
+            let reply = property(self.net_wm_name, self.utf8_string)
+                .or_else(|| property(AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()))?;
+            Some(String::from_utf8_lossy(&reply.value).into_owned())
+        }
+    }
+
+    /// Window titles from the X11 window manager, reconnecting when the
+    /// connection breaks. Wayland sessions without XWayland have no titles.
+    #[derive(Default)]
+    pub struct X11Windows {
+        connection: Option<X11Connection>,
+    }
+
+    impl X11Windows {
+        pub fn window_titles(&mut self) -> Vec<String> {
+            if self.connection.is_none() {
+                self.connection = X11Connection::open();
+            }
+            let titles = self
+                .connection
+                .as_ref()
+                .and_then(X11Connection::window_titles);
+            if titles.is_none() {
+                self.connection = None;
+            }
+            titles.unwrap_or_default()
+        }
+    }
+
+    /// Meeting apps currently capturing audio, according to `pw-dump`
+    pub fn microphone_streams() -> Vec<Seen> {
+        let Ok(output) = Command::new("pw-dump").output() else {
+            // No PipeWire tools installed
+            return Vec::new();
+        };
+        if !output.status.success() {
+            return Vec::new();
+        }
+        meeting_mic_streams(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .map(|(app_name, binary)| Seen::Microphone { app_name, binary })
+            .collect()
+    }
+
+    /// Running audio capture streams (`Stream/Input/Audio`) of meeting apps in
+    /// `pw-dump` JSON, as (meeting app, executable)
+    pub fn meeting_mic_streams(pw_dump: &str) -> Vec<(&'static str, String)> {
+        let Ok(serde_json::Value::Array(objects)) = serde_json::from_str(pw_dump) else {
+            return Vec::new();
+        };
+
+        let mut streams: Vec<(&'static str, String)> = Vec::new();
+        for object in &objects {
+            let info = &object["info"];
+            let props = &info["props"];
+            let is_capture = props["media.class"].as_str() == Some("Stream/Input/Audio");
+            let is_running = info["state"].as_str() == Some("running");
+            if !(is_capture && is_running) {
+                continue;
+            }
+
+            let binary = props["application.process.binary"]
+                .as_str()
+                .or_else(|| props["application.name"].as_str())
+                .unwrap_or_default()
+                .to_string();
+            let lower = binary.to_lowercase();
+            let app = MIC_APPS
+                .iter()
+                .find(|(pattern, _)| lower.contains(pattern))
+                .map(|(_, app_name)| *app_name);
+            if let Some(app) = app.filter(|app| !streams.iter().any(|(a, _)| a == app)) {
+                streams.push((app, binary));
+            }
+        }
+        streams
+    }
+}
+
+/// Tauri command to enable/disable meeting detection
+#[tauri::command]
+pub fn set_meeting_detection_enabled(
+    state: tauri::State<Arc<MeetingDetectionState>>,
+    enabled: bool,
+) {
+    state.set_enabled(enabled);
+}
+
+/// Tauri command to check if meeting detection is enabled
+#[tauri::command]
+pub fn is_meeting_detection_enabled(state: tauri::State<Arc<MeetingDetectionState>>) -> bool {
+    state.is_enabled()
+}
+
+/// Tauri command to clear all detected meetings (allows re-detection)
+#[tauri::command]
+pub fn clear_detected_meetings(state: tauri::State<Arc<MeetingDetectionState>>) {
+    state.clear_all_detected();
+    println!("[meeting-detection] Cleared all detected meetings");
+}
+
+/// Get the meeting detection rules, in the order they are tried
+#[tauri::command]
+pub fn get_meeting_rules(state: tauri::State<Arc<MeetingDetectionState>>) -> Vec<MeetingRule> {
+    state.rules()
+}
+
+/// Replace the meeting detection rules
+#[tauri::command]
+pub fn set_meeting_rules(
+    state: tauri::State<Arc<MeetingDetectionState>>,
+    db: tauri::State<Database>,
+    rules: Vec<MeetingRule>,
+) -> Result<(), String> {
+    let json = serde_json::to_string(&rules).map_err(|e| e.to_string())?;
+    let rule_set = RuleSet::new(rules).map_err(|e| e.to_string())?;
+    db.set_setting(MEETING_RULES_KEY, &json)
+        .map_err(|e| e.to_string())?;
+    state.set_rules(rule_set);
+    Ok(())
+}
+
+/// Go back to the built-in meeting detection rules
+#[tauri::command]
+pub fn reset_meeting_rules(
+    state: tauri::State<Arc<MeetingDetectionState>>,
+    db: tauri::State<Database>,
+) -> Result<Vec<MeetingRule>, String> {
+    db.delete_setting(MEETING_RULES_KEY)
+        .map_err(|e| e.to_string())?;
+    state.set_rules(RuleSet::default());
+    Ok(state.rules())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windows(titles: &[&str]) -> Vec<Seen> {
+        titles.iter().map(|t| Seen::Window(t.to_string())).collect()
+    }
+
+    #[test]
+    fn test_detector() {
+        let rules = RuleSet::default();
+        let mut detector = Detector::default();
+
+        let changes = detector.update(
+            &rules,
+            &windows(&[
+                "Inbox - Mail",
+                "Meet - abc-defg-hij - Google Chrome",
+                "Chat | Microsoft Teams",
+            ]),
+        );
+        assert_eq!(
+            changes,
+            vec![MeetingChange::Detected(MeetingDetected {
+                meeting_id: "Google Meet:abc-defg-hij".to_string(),
+                app_name: "Google Meet".to_string(),
+                bundle_id: None,
+                is_browser: true,
+                action: MeetingAction::Notify,
+            })]
+        );
+
+        // Still going: nothing new
+        let still = windows(&["Meet - abc-defg-hij - Google Chrome"]);
+        assert!(detector.update(&rules, &still).is_empty());
+
+        for _ in 1..MISSED_POLLS_TO_END {
+            assert!(detector.update(&rules, &[]).is_empty());
+        }
+        assert_eq!(
+            detector.update(&rules, &[]),
+            vec![MeetingChange::Ended(MeetingEnded {
+                meeting_id: "Google Meet:abc-defg-hij".to_string(),
+                app_name: "Google Meet".to_string(),
+                on_end: MeetingEndAction::Nothing,
+            })]
+        );
+
+        // Detected again after clearing
+        detector.update(&rules, &still);
+        detector.clear();
+        assert_eq!(detector.update(&rules, &still).len(), 1);
+    }
+
+    #[test]
+    fn test_detector_flapping_title() {
+        let rules = RuleSet::default();
+        let mut detector = Detector::default();
+
+        let changes = detector.update(&rules, &windows(&["Meet - abc-defg-hij - Google Chrome"]));
+        assert_eq!(changes.len(), 1);
+
+        // Switching tabs for a moment, the speaker icon and unread counts are the same meeting
+        for titles in [
+            &["Inbox (3) - Gmail - Google Chrome"][..],
+            &["Meet - abc-defg-hij 🔊 - Google Chrome"],
+            &[],
+            &["(1) Meet - abc-defg-hij - Google Chrome"],
+            &["Meet – abc-defg-hij – Google Chrome"],
+        ] {
+            assert!(detector.update(&rules, &windows(titles)).is_empty());
+        }
+
+        // A different meeting is detected while the first one is still going
+        let changes = detector.update(
+            &rules,
+            &windows(&[
+                "Meet - abc-defg-hij - Google Chrome",
+                "Meet - xyz-wxyz-xyz - Firefox",
+            ]),
+        );
+        assert!(matches!(
+            &changes[..],
+            [MeetingChange::Detected(m)] if m.meeting_id == "Google Meet:xyz-wxyz-xyz"
+        ));
+    }
+
+    #[test]
+    fn test_detector_custom_rules() {
+        let rules = RuleSet::new(vec![MeetingRule {
+            app_name: "Webex".to_string(),
+            pattern: r"^Webex \| .+".to_string(),
+            regex: true,
+            action: MeetingAction::Record,
+            on_end: MeetingEndAction::Stop,
+        }])
+        .unwrap();
+        let mut detector = Detector::default();
+
+        // The speaker icon coming and going is the same meeting
+        let changes = detector.update(&rules, &windows(&["Webex | Standup 🔊"]));
+        assert!(matches!(
+            &changes[..],
+            [MeetingChange::Detected(m)] if m.action == MeetingAction::Record
+        ));
+        assert!(detector
+            .update(&rules, &windows(&["Webex | Standup"]))
+            .is_empty());
+
+        let other = windows(&["Meet - abc-defg-hij"]);
+        for _ in 1..MISSED_POLLS_TO_END {
+            assert!(detector.update(&rules, &other).is_empty());
+        }
+        let changes = detector.update(&rules, &other);
+        assert!(matches!(
+            &changes[..],
+            [MeetingChange::Ended(m)] if m.on_end == MeetingEndAction::Stop
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_meeting_mic_streams() {
+        let pw_dump = r#"[
+            {"id": 30, "type": "PipeWire:Interface:Node", "info": {"state": "running",
+                "props": {"media.class": "Audio/Source", "node.name": "alsa_input.usb"}}},
+            {"id": 81, "type": "PipeWire:Interface:Node", "info": {"state": "running",
+                "props": {"media.class": "Stream/Input/Audio", "application.name": "ZOOM VoiceEngine",
+                          "application.process.binary": "zoom"}}},
+            {"id": 82, "type": "PipeWire:Interface:Node", "info": {"state": "running",
+                "props": {"media.class": "Stream/Input/Audio", "application.process.binary": "zoom"}}},
+            {"id": 90, "type": "PipeWire:Interface:Node", "info": {"state": "suspended",
+                "props": {"media.class": "Stream/Input/Audio", "application.process.binary": "Discord"}}},
+            {"id": 95, "type": "PipeWire:Interface:Node", "info": {"state": "running",
+                "props": {"media.class": "Stream/Output/Audio", "application.process.binary": "slack"}}},
+            {"id": 99, "type": "PipeWire:Interface:Client", "info": {"props": {}}}
+        ]"#;
+
+        assert_eq!(
+            linux::meeting_mic_streams(pw_dump),
+            vec![("Zoom", "zoom".to_string())]
+        );
+        assert!(linux::meeting_mic_streams("not json").is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! User-editable rules deciding which window titles are meetings and what
+//! happens when one starts or ends
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// What to do when a rule matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingAction {
+    /// Ask whether to record
+    #[default]
+    Notify,
+    /// Start a (dual, when system audio is available) recording right away
+    Record,
+    /// Not a meeting: later rules are not tried
+    Ignore,
+}
+
+/// What to do with a recording started for a meeting once its window is gone
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingEndAction {
+    #[default]
+    Nothing,
+    Pause,
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeetingRule {
+    /// Meeting app shown to the user; may be empty for `Ignore` rules
+    pub app_name: String,
+    /// Text the window title contains, or a regular expression when `regex` is set
+    pub pattern: String,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub action: MeetingAction,
+    #[serde(default)]
+    pub on_end: MeetingEndAction,
+}
+
+impl MeetingRule {
+    fn new(app_name: &str, pattern: &str, action: MeetingAction) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            pattern: pattern.to_string(),
+            regex: false,
+            action,
+            on_end: MeetingEndAction::Nothing,
+        }
+    }
+
+    fn regex(app_name: &str, pattern: &str) -> Self {
+        Self {
+            regex: true,
+            ..Self::new(app_name, pattern, MeetingAction::Notify)
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    #[error("Rule {0} has an empty pattern")]
+    EmptyPattern(usize),
+    #[error("Rule {0} needs an app name")]
+    MissingAppName(usize),
+    #[error("Rule {index} has an invalid regular expression: {source}")]
+    InvalidRegex {
+        index: usize,
+        #[source]
+        source: regex::Error,
+    },
+}
+
+enum Matcher {
+    Contains(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn is_match(&self, title: &str) -> bool {
+        match self {
+            Matcher::Contains(text) => title.contains(text.as_str()),
+            Matcher::Regex(regex) => regex.is_match(title),
+        }
+    }
+}
+
+/// Validated rules, tried in order: the first one matching a title decides
+pub struct RuleSet {
+    rules: Vec<MeetingRule>,
+    matchers: Vec<Matcher>,
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<MeetingRule>) -> Result<Self, RuleError> {
+        let mut matchers = Vec::with_capacity(rules.len());
+        for (i, rule) in rules.iter().enumerate() {
+            // Numbered from 1, as shown to the user
+            let index = i + 1;
+            if rule.pattern.is_empty() {
+                return Err(RuleError::EmptyPattern(index));
+            }
+            if rule.action != MeetingAction::Ignore && rule.app_name.trim().is_empty() {
+                return Err(RuleError::MissingAppName(index));
+            }
+            matchers.push(if rule.regex {
+                Matcher::Regex(
+                    Regex::new(&rule.pattern)
+                        .map_err(|source| RuleError::InvalidRegex { index, source })?,
+                )
+            } else {
+                Matcher::Contains(rule.pattern.clone())
+            });
+        }
+        Ok(Self { rules, matchers })
+    }
+
+    pub fn rules(&self) -> &[MeetingRule] {
+        &self.rules
+    }
+
+    /// The first rule matching a window title
+    pub fn match_title(&self, title: &str) -> Option<&MeetingRule> {
+        self.rules
+            .iter()
+            .zip(&self.matchers)
+            .find(|(_, matcher)| matcher.is_match(title))
+            .map(|(rule, _)| rule)
+    }
+
+    /// The rule deciding what happens when `app_name` is heard using the
+    /// microphone: its first rule that is not `Ignore`, or an `Ignore` rule
+    /// when that is all the app has
+    pub fn match_app(&self, app_name: &str) -> Option<&MeetingRule> {
+        let mut app_rules = self
+            .rules
+            .iter()
+            .filter(|rule| rule.app_name.eq_ignore_ascii_case(app_name));
+        let first = app_rules.next()?;
+        if first.action != MeetingAction::Ignore {
+            return Some(first);
+        }
+        app_rules
+            .find(|rule| rule.action != MeetingAction::Ignore)
+            .or(Some(first))
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::new(default_rules()).expect("default meeting rules are valid")
+    }
+}
+
+/// The built-in rules: pages that are open but not in a meeting first, then
+/// titles of active meetings, then apps showing the speaker icon during a call
+pub fn default_rules() -> Vec<MeetingRule> {
+    use MeetingAction::{Ignore, Notify};
+
+    vec![
+        MeetingRule::new("", "New meeting", Ignore),
+        MeetingRule::new("", "Join a meeting", Ignore),
+        MeetingRule::new("", "Start a meeting", Ignore),
+        MeetingRule::new("", "Schedule a meeting", Ignore),
+        MeetingRule::new("Microsoft Teams", "Home | Microsoft Teams", Ignore),
+        MeetingRule::new("Microsoft Teams", "Chat | Microsoft Teams", Ignore),
+        MeetingRule::new("Microsoft Teams", "Teams | Microsoft Teams", Ignore),
+        MeetingRule::new("Microsoft Teams", "Calendar | Microsoft Teams", Ignore),
+        // "room-name | Jitsi Meet", not the bare "Jitsi Meet" start page.
+        // Before Google Meet, as "... | Jitsi Meet - Firefox" contains "Meet - "
+        MeetingRule::new("Jitsi Meet", " | Jitsi Meet", Notify),
+        // Google Meet - only when actually in a meeting: "Meet - abc-defg-hij"
+        MeetingRule::new("Google Meet", "Meet – ", Notify), // en-dash in actual titles
+        MeetingRule::new("Google Meet", "Meet - ", Notify),
+        MeetingRule::new("Zoom", "Zoom Meeting", Notify),
+        // "Microsoft Teams meeting | Microsoft Teams" or "Name | Personal | email | Microsoft Teams"
+        MeetingRule::new("Microsoft Teams", "Teams meeting", Notify),
+        MeetingRule::new("Microsoft Teams", "| Personal |", Notify),
+        // "Huddle: #channel – Workspace – Slack 🎤"
+        MeetingRule::new("Slack Huddle", "Huddle:", Notify),
+        MeetingRule::new("Discord", "Discord | ", Notify),
+        // "Webex meeting" but not the "Cisco Webex Meetings" launcher
+        MeetingRule::regex("Webex", r"(?i)\bwebex meeting\b"),
+        MeetingRule::regex("Microsoft Teams", "🔊.*Microsoft Teams|Microsoft Teams.*🔊"),
+        MeetingRule::regex("Slack", "🔊.*Slack|Slack.*🔊"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(rules: &RuleSet, title: &str) -> Option<(String, MeetingAction)> {
+        rules
+            .match_title(title)
+            .map(|rule| (rule.app_name.clone(), rule.action))
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let rules = RuleSet::default();
+        let notify = |name: &str| Some((name.to_string(), MeetingAction::Notify));
+
+        assert_eq!(app(&rules, "Meet - abc-defg-hij"), notify("Google Meet"));
+        assert_eq!(app(&rules, "Zoom Meeting"), notify("Zoom"));
+        assert_eq!(
+            app(&rules, "Standup 🔊 | Microsoft Teams"),
+            notify("Microsoft Teams")
+        );
+        assert_eq!(app(&rules, "Webex meeting - Weekly sync"), notify("Webex"));
+        assert_eq!(app(&rules, "Cisco Webex Meetings"), None);
+        assert_eq!(
+            app(&rules, "team-retro | Jitsi Meet - Firefox"),
+            notify("Jitsi Meet")
+        );
+        assert_eq!(app(&rules, "Jitsi Meet"), None);
+        assert_eq!(app(&rules, "Slack | general | Acme"), None);
+        assert_eq!(
+            app(&rules, "Chat | Microsoft Teams"),
+            Some(("Microsoft Teams".to_string(), MeetingAction::Ignore))
+        );
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let mut rule = MeetingRule::regex("Webex", "(unclosed");
+        assert!(matches!(
+            RuleSet::new(vec![rule.clone()]),
+            Err(RuleError::InvalidRegex { index: 1, .. })
+        ));
+
+        rule.regex = false;
+        assert!(RuleSet::new(vec![rule.clone()]).is_ok());
+
+        rule.app_name = " ".to_string();
+        assert!(matches!(
+            RuleSet::new(vec![rule.clone()]),
+            Err(RuleError::MissingAppName(1))
+        ));
+        rule.action = MeetingAction::Ignore;
+        assert!(RuleSet::new(vec![rule]).is_ok());
+    }
+
+    #[test]
+    fn test_match_app() {
+        let rules = RuleSet::default();
+        let action = |name: &str| rules.match_app(name).map(|rule| rule.action);
+
+        assert_eq!(action("Microsoft Teams"), Some(MeetingAction::Notify));
+        assert_eq!(action("zoom"), Some(MeetingAction::Notify));
+        assert_eq!(action("Skype"), None);
+
+        let rules = RuleSet::new(vec![MeetingRule::new(
+            "Zoom",
+            "Zoom",
+            MeetingAction::Ignore,
+        )])
+        .unwrap();
+        assert_eq!(
+            rules.match_app("Zoom").map(|rule| rule.action),
+            Some(MeetingAction::Ignore)
+        );
+    }
+}
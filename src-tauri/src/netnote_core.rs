@@ -0,0 +1,21 @@
+//! The app-independent core: the library database, live transcription over
+//! injected audio and speech sources, and summaries over any [`LlmBackend`].
+//!
+//! Nothing here needs a running Tauri app. The app plugs in through
+//! [`EventSink`] and [`DatabaseHandle`] (implemented for `AppHandle`), which
+//! is also how integration tests and the CLI drive the same code.
+
+pub use crate::ai::summarizer::summarize_note;
+pub use crate::ai::{LlmBackend, OllamaClient};
+pub use crate::db::models;
+pub use crate::db::{Database, DatabaseHandle};
+pub use crate::events::EventSink;
+pub use crate::transcription::live::{
+    start_live_transcription, stop_live_transcription, LiveAudioSource, SpeechToText,
+    TRANSCRIPTION_UPDATE_EVENT,
+};
+pub use crate::transcription::transcriber::load_audio_16k_mono;
+pub use crate::transcription::{
+    LiveTranscriptionState, TranscriptionError, TranscriptionResult, TranscriptionSegment,
+    TranscriptionUpdateEvent,
+};
@@ -2,16 +2,19 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 use tokio::time::interval;
 
-use crate::audio::{take_system_audio_samples, RecordingState};
-use crate::db::Database;
+use crate::audio::{aec, take_system_audio_samples, RecordingState};
+use crate::db::models::TranscriptSource;
+use crate::db::DatabaseHandle;
+use crate::events::EventSink;
 use crate::transcription::{TranscriptionError, TranscriptionResult, TranscriptionSegment};
-use tauri::Manager;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext};
 
+/// Event carrying [`TranscriptionUpdateEvent`] payloads
+pub const TRANSCRIPTION_UPDATE_EVENT: &str = "transcription-update";
+
 /// Check if a transcript segment should be skipped (blank audio, inaudible, etc.)
 fn should_skip_segment(text: &str) -> bool {
     let text_lower = text.to_lowercase();
@@ -39,7 +42,8 @@ fn has_voice_activity(samples: &[f32], threshold: f32) -> bool {
 }
 
 /// Fast check if a mic segment is likely an echo of system audio
-/// Uses simple first-words comparison for speed
+/// Uses simple first-words comparison for speed; catches residual echo
+/// that the adaptive canceller didn't remove (e.g. while it converges)
 fn is_echo_of_system(
     mic_text: &str,
     mic_start: f64,
@@ -85,6 +89,8 @@ fn is_echo_of_system(
 /// Live transcription state
 pub struct LiveTranscriptionState {
     pub is_running: AtomicBool,
+    /// Note of the current (or last) session
+    pub note_id: Mutex<Option<String>>,
     /// Offset in seconds for mic segment timestamps
     pub mic_time_offset: Mutex<f64>,
     /// Offset in seconds for system audio segment timestamps
@@ -99,6 +105,7 @@ impl LiveTranscriptionState {
     pub fn new() -> Self {
         Self {
             is_running: AtomicBool::new(false),
+            note_id: Mutex::new(None),
             mic_time_offset: Mutex::new(0.0),
             system_time_offset: Mutex::new(0.0),
             segments: Mutex::new(Vec::new()),
@@ -131,34 +138,128 @@ pub struct TranscriptionUpdateEvent {
     pub is_final: bool,
     /// The source of the audio (mic or system)
     pub audio_source: AudioSource,
+    /// Transcript version the segments were saved to
+    pub transcript_id: Option<i64>,
+}
+
+/// Audio feeding a live transcription session, pulled every few seconds
+pub trait LiveAudioSource: Send + Sync {
+    /// Whether audio is still being captured; the session ends once it isn't
+    fn is_active(&self) -> bool;
+
+    /// Mic samples captured since the last call, with their sample rate and channel count
+    fn take_mic_samples(&self) -> (Vec<f32>, u32, usize);
+
+    /// System audio captured since the last call, as 16kHz mono
+    fn take_system_samples(&self) -> Vec<f32>;
+
+    /// Database id of the recorded audio segment this session belongs to
+    fn current_segment_id(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl LiveAudioSource for RecordingState {
+    fn is_active(&self) -> bool {
+        self.is_recording.load(Ordering::SeqCst)
+    }
+
+    fn take_mic_samples(&self) -> (Vec<f32>, u32, usize) {
+        let samples = self.take_audio_buffer();
+        let rate = self.sample_rate.load(Ordering::SeqCst);
+        let channels = self.channels.load(Ordering::SeqCst) as usize;
+        (samples, rate, channels)
+    }
+
+    fn take_system_samples(&self) -> Vec<f32> {
+        take_system_audio_samples()
+    }
+
+    fn current_segment_id(&self) -> Option<i64> {
+        Some(self.current_segment_db_id.load(Ordering::SeqCst)).filter(|id| *id > 0)
+    }
+}
+
+/// Speech recognition over 16kHz mono audio
+pub trait SpeechToText: Send + Sync {
+    /// Transcribe `samples`, shifting segment times by `time_offset` seconds
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        time_offset: f64,
+        language: Option<&str>,
+    ) -> Result<TranscriptionResult, TranscriptionError>;
+}
+
+impl SpeechToText for WhisperContext {
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        time_offset: f64,
+        language: Option<&str>,
+    ) -> Result<TranscriptionResult, TranscriptionError> {
+        transcribe_samples(self, samples, 16000, 1, time_offset, language)
+    }
 }
 
 /// Start live transcription
 /// Runs every 3 seconds, transcribes accumulated audio in parallel, saves to DB, emits events
-pub async fn start_live_transcription(
-    app: AppHandle,
+pub async fn start_live_transcription<H>(
+    host: H,
     note_id: String,
     language: Option<String>,
-    recording_state: Arc<RecordingState>,
+    audio: Arc<dyn LiveAudioSource>,
     live_state: Arc<LiveTranscriptionState>,
-    whisper_ctx: Arc<WhisperContext>,
-) -> Result<(), TranscriptionError> {
+    engine: Arc<dyn SpeechToText>,
+    model_id: Option<String>,
+) -> Result<(), TranscriptionError>
+where
+    H: DatabaseHandle + EventSink + Clone,
+{
     if live_state.is_running.swap(true, Ordering::SeqCst) {
         return Err(TranscriptionError::AlreadyTranscribing);
     }
 
+    // Live segments extend the note's active transcript (a new live version if it has none)
+    let transcript_id = match host.with_db(|db| {
+        db.get_or_create_active_transcript(
+            &note_id,
+            TranscriptSource::Live,
+            model_id.as_deref(),
+            language.as_deref(),
+        )
+    }) {
+        Ok(id) => id,
+        Err(e) => {
+            live_state.is_running.store(false, Ordering::SeqCst);
+            return Err(TranscriptionError::TranscriptionFailed(e.to_string()));
+        }
+    };
+
+    // Timestamps start where the current recorded segment sits in the note timeline,
+    // so resumed and continued sessions don't overlap earlier ones
+    let session_offset = audio
+        .current_segment_id()
+        .and_then(|segment_id| host.with_db(|db| db.get_audio_segment(segment_id).ok().flatten()))
+        .map(|segment| segment.start_offset_ms as f64 / 1000.0)
+        .unwrap_or(0.0);
+
     // Reset state
-    *live_state.mic_time_offset.lock().await = 0.0;
-    *live_state.system_time_offset.lock().await = 0.0;
+    *live_state.note_id.lock().await = Some(note_id.clone());
+    *live_state.mic_time_offset.lock().await = session_offset;
+    *live_state.system_time_offset.lock().await = session_offset;
     live_state.segments.lock().await.clear();
     live_state.recent_system_segments.lock().await.clear();
 
-    let app_clone = app.clone();
+    // Fresh echo canceller for this session (mic is resampled to 16kHz like system audio)
+    aec::init_aec(16000);
+
+    let host_clone = host.clone();
     let note_id_clone = note_id.clone();
     let language_clone = language.clone();
-    let recording_state_clone = recording_state.clone();
+    let audio_clone = audio.clone();
     let live_state_clone = live_state.clone();
-    let whisper_ctx_clone = whisper_ctx.clone();
+    let engine_clone = engine.clone();
 
     // Spawn the live transcription task
     tokio::spawn(async move {
@@ -174,21 +275,19 @@ pub async fn start_live_transcription(
             }
 
             // Check if still recording
-            if !recording_state_clone.is_recording.load(Ordering::SeqCst) {
+            if !audio_clone.is_active() {
                 break;
             }
 
             // Get audio buffers - both mic and system audio
-            let mic_samples = recording_state_clone.take_audio_buffer();
-            let system_samples = take_system_audio_samples();
+            let (mic_samples, rate, ch) = audio_clone.take_mic_samples();
+            let system_samples = audio_clone.take_system_samples();
 
             // Build list of audio sources to process
             let mut audio_sources: Vec<(Vec<f32>, u32, usize, AudioSource)> = Vec::new();
 
             // Add mic samples if available and has voice activity
             if !mic_samples.is_empty() {
-                let rate = recording_state_clone.sample_rate.load(Ordering::SeqCst);
-                let ch = recording_state_clone.channels.load(Ordering::SeqCst) as usize;
                 if rate > 0 && ch > 0 {
                     // Convert mic to mono first if needed
                     let mono_mic: Vec<f32> = if ch > 1 {
@@ -200,16 +299,20 @@ pub async fn start_live_transcription(
                         mic_samples
                     };
 
-                    // Only process if there's voice activity (RMS > 0.01)
-                    // This filters out silence and low background noise
-                    if has_voice_activity(&mono_mic, 0.01) {
-                        // Resample mic to 16kHz for Whisper
-                        let mic_16k = if rate != 16000 {
-                            resample(&mono_mic, rate, 16000)
-                        } else {
-                            mono_mic
-                        };
+                    // Resample mic to 16kHz for Whisper
+                    let mic_16k = if rate != 16000 {
+                        resample(&mono_mic, rate, 16000)
+                    } else {
+                        mono_mic
+                    };
+
+                    // Remove speaker echo using system audio as the reference.
+                    // Runs on every chunk so the adaptive filter stays in sync.
+                    let mic_16k = aec::apply_aec(&mic_16k, &system_samples);
 
+                    // Only process if there's voice activity (RMS > 0.01)
+                    // This filters out silence, low background noise and cancelled echo
+                    if has_voice_activity(&mic_16k, 0.01) {
                         audio_sources.push((mic_16k, 16000_u32, 1_usize, AudioSource::Mic));
                     }
                 }
@@ -235,18 +338,18 @@ pub async fn start_live_transcription(
             };
 
             // Process mic and system audio in PARALLEL
-            let whisper_ctx_mic = whisper_ctx_clone.clone();
-            let whisper_ctx_sys = whisper_ctx_clone.clone();
+            let engine_mic = engine_clone.clone();
+            let engine_sys = engine_clone.clone();
 
             let lang_mic = lang.clone();
             let lang_sys = lang.clone();
 
             let mic_future = async {
                 if let Some((samples, time_offset)) = mic_data {
-                    let ctx = whisper_ctx_mic;
+                    let engine = engine_mic;
                     let language = lang_mic;
                     tokio::task::spawn_blocking(move || {
-                        transcribe_samples(&ctx, &samples, 16000, 1, time_offset, language.as_deref())
+                        engine.transcribe(&samples, time_offset, language.as_deref())
                     })
                     .await
                     .ok()
@@ -258,10 +361,10 @@ pub async fn start_live_transcription(
 
             let system_future = async {
                 if let Some((samples, time_offset)) = system_data {
-                    let ctx = whisper_ctx_sys;
+                    let engine = engine_sys;
                     let language = lang_sys;
                     tokio::task::spawn_blocking(move || {
-                        transcribe_samples(&ctx, &samples, 16000, 1, time_offset, language.as_deref())
+                        engine.transcribe(&samples, time_offset, language.as_deref())
                     })
                     .await
                     .ok()
@@ -347,6 +450,7 @@ pub async fn start_live_transcription(
                             segments: valid_segments,
                             is_final: false,
                             audio_source: AudioSource::Mic,
+                            transcript_id: Some(transcript_id),
                         });
                     }
                 }
@@ -379,24 +483,27 @@ pub async fn start_live_transcription(
                     segments: current_system_segments,
                     is_final: false,
                     audio_source: AudioSource::System,
+                    transcript_id: Some(transcript_id),
                 });
             }
 
             // Batch insert all segments into database
             if !db_segments.is_empty() {
-                let db = app_clone.state::<Database>();
-                if let Err(e) = db.add_transcript_segments_batch(&db_segments) {
+                let saved = host_clone
+                    .with_db(|db| db.add_transcript_segments_batch(transcript_id, &db_segments));
+                if let Err(e) = saved {
                     eprintln!("Failed to batch save transcript segments: {}", e);
                 }
             }
 
             // Emit all events
             for event in all_events {
-                let _ = app_clone.emit("transcription-update", event);
+                host_clone.emit_event(TRANSCRIPTION_UPDATE_EVENT, &event);
             }
         }
 
         live_state_clone.is_running.store(false, Ordering::SeqCst);
+        aec::reset_aec();
     });
 
     Ok(())
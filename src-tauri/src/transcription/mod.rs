@@ -3,7 +3,7 @@ pub mod model;
 pub mod transcriber;
 
 pub use live::{AudioSource, LiveTranscriptionState, TranscriptionUpdateEvent};
-pub use model::{ModelInfo, ModelManager, ModelSize};
+pub use model::{ModelInfo, ModelManager};
 pub use transcriber::{TranscriptionResult, TranscriptionSegment, Transcriber};
 
 use thiserror::Error;
@@ -16,6 +16,9 @@ pub enum TranscriptionError {
     #[error("Model download failed: {0}")]
     DownloadError(String),
 
+    #[error("Checksum mismatch for {0}, the download was discarded")]
+    ChecksumMismatch(String),
+
     #[error("Failed to load model: {0}")]
     ModelLoadError(String),
 
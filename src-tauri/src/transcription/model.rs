@@ -1,84 +1,69 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
 use super::TranscriptionError;
 
-/// Available Whisper model sizes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum ModelSize {
-    Tiny,
-    Base,
-    Small,
-    Medium,
-    Large,
-}
+/// Catalog bundled with the app
+const BUILTIN_CATALOG: &str = include_str!("models.json");
 
-impl ModelSize {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            ModelSize::Tiny => "tiny",
-            ModelSize::Base => "base",
-            ModelSize::Small => "small",
-            ModelSize::Medium => "medium",
-            ModelSize::Large => "large",
-        }
-    }
+/// Optional catalog in the models directory; entries override/extend the bundled ones
+const CATALOG_OVERRIDE_FILE: &str = "catalog.json";
 
-    /// Get download URL for the model from Hugging Face
-    pub fn download_url(&self) -> &'static str {
-        match self {
-            ModelSize::Tiny => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny-q5_1.bin",
-            ModelSize::Base => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base-q5_1.bin",
-            ModelSize::Small => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small-q5_1.bin",
-            ModelSize::Medium => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium-q5_0.bin",
-            ModelSize::Large => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-q5_0.bin",
-        }
-    }
+/// User-registered local model files
+const CUSTOM_MODELS_FILE: &str = "custom_models.json";
 
-    /// Get approximate file size in MB
-    pub fn size_mb(&self) -> u64 {
-        match self {
-            ModelSize::Tiny => 31,
-            ModelSize::Base => 57,
-            ModelSize::Small => 182,
-            ModelSize::Medium => 515,
-            ModelSize::Large => 1030,
-        }
-    }
+/// Prefix of custom model ids
+const CUSTOM_ID_PREFIX: &str = "custom:";
 
-    /// Get filename for the model
-    pub fn filename(&self) -> &'static str {
-        match self {
-            ModelSize::Tiny => "ggml-tiny-q5_1.bin",
-            ModelSize::Base => "ggml-base-q5_1.bin",
-            ModelSize::Small => "ggml-small-q5_1.bin",
-            ModelSize::Medium => "ggml-medium-q5_0.bin",
-            ModelSize::Large => "ggml-large-v3-q5_0.bin",
-        }
-    }
+/// Partial downloads older than this are removed at startup instead of resumed
+const STALE_PARTIAL_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
-    pub fn all() -> &'static [ModelSize] {
-        &[
-            ModelSize::Tiny,
-            ModelSize::Base,
-            ModelSize::Small,
-            ModelSize::Medium,
-            ModelSize::Large,
-        ]
-    }
+/// A downloadable model in the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub filename: String,
+    pub url: String,
+    /// Hex SHA-256 of the file; downloads are verified when present
+    #[serde(default)]
+    pub sha256: Option<String>,
+    pub size_mb: u64,
+    #[serde(default)]
+    pub english_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModelCatalog {
+    models: Vec<CatalogEntry>,
+}
+
+/// A model file the user pointed us at (never downloaded or deleted by us)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CustomModel {
+    id: String,
+    name: String,
+    path: String,
 }
 
 /// Information about a model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
-    pub size: ModelSize,
+    /// Catalog id (the model size for the original entries, `custom:<name>` for local files)
+    pub size: String,
     pub name: String,
+    pub description: Option<String>,
     pub downloaded: bool,
     pub path: Option<String>,
     pub size_mb: u64,
+    pub english_only: bool,
+    pub custom: bool,
 }
 
 /// Manages whisper model downloads and paths
@@ -105,42 +90,170 @@ impl ModelManager {
         Ok(())
     }
 
-    /// Get the path to a model file
-    pub fn model_path(&self, size: ModelSize) -> PathBuf {
-        self.models_dir.join(size.filename())
+    /// The bundled catalog merged with the optional override file
+    pub fn catalog(&self) -> Vec<CatalogEntry> {
+        let mut entries = serde_json::from_str::<ModelCatalog>(BUILTIN_CATALOG)
+            .map(|c| c.models)
+            .unwrap_or_default();
+
+        let overrides = std::fs::read_to_string(self.models_dir.join(CATALOG_OVERRIDE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str::<ModelCatalog>(&json).ok());
+        for entry in overrides.map(|c| c.models).unwrap_or_default() {
+            match entries.iter_mut().find(|e| e.id == entry.id) {
+                Some(existing) => *existing = entry,
+                None => entries.push(entry),
+            }
+        }
+
+        entries
+    }
+
+    fn catalog_entry(&self, id: &str) -> Option<CatalogEntry> {
+        self.catalog().into_iter().find(|e| e.id == id)
     }
 
-    /// Check if a model is downloaded
-    pub fn is_downloaded(&self, size: ModelSize) -> bool {
-        self.model_path(size).exists()
+    fn custom_models(&self) -> Vec<CustomModel> {
+        std::fs::read_to_string(self.models_dir.join(CUSTOM_MODELS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
     }
 
-    /// Get info for all models
+    fn save_custom_models(&self, models: &[CustomModel]) -> Result<(), TranscriptionError> {
+        std::fs::create_dir_all(&self.models_dir)?;
+        let json = serde_json::to_string_pretty(models)
+            .map_err(|e| TranscriptionError::ModelLoadError(e.to_string()))?;
+        std::fs::write(self.models_dir.join(CUSTOM_MODELS_FILE), json)?;
+        Ok(())
+    }
+
+    /// Check whether an id refers to a known catalog or custom model
+    pub fn is_known(&self, id: &str) -> bool {
+        self.catalog_entry(id).is_some() || self.custom_models().iter().any(|m| m.id == id)
+    }
+
+    /// Get the path to a model file
+    pub fn model_path(&self, id: &str) -> Option<PathBuf> {
+        if let Some(custom) = self.custom_models().into_iter().find(|m| m.id == id) {
+            return Some(PathBuf::from(custom.path));
+        }
+        self.catalog_entry(id)
+            .map(|entry| self.models_dir.join(entry.filename))
+    }
+
+    /// Get info for all catalog and custom models
     pub fn list_models(&self) -> Vec<ModelInfo> {
-        ModelSize::all()
-            .iter()
-            .map(|&size| {
-                let downloaded = self.is_downloaded(size);
-                let path = if downloaded {
-                    Some(self.model_path(size).to_string_lossy().to_string())
-                } else {
-                    None
-                };
-                ModelInfo {
-                    size,
-                    name: size.as_str().to_string(),
-                    downloaded,
-                    path,
-                    size_mb: size.size_mb(),
-                }
-            })
-            .collect()
-    }
-
-    /// Download a model with progress callback
+        let catalog = self.catalog().into_iter().map(|entry| {
+            let path = self.models_dir.join(&entry.filename);
+            let downloaded = path.exists();
+            ModelInfo {
+                size: entry.id,
+                name: entry.name,
+                description: entry.description,
+                downloaded,
+                path: downloaded.then(|| path.to_string_lossy().to_string()),
+                size_mb: entry.size_mb,
+                english_only: entry.english_only,
+                custom: false,
+            }
+        });
+
+        let custom = self.custom_models().into_iter().map(|model| {
+            let path = PathBuf::from(&model.path);
+            let size_mb = std::fs::metadata(&path)
+                .map(|m| m.len() / (1024 * 1024))
+                .unwrap_or(0);
+            ModelInfo {
+                size: model.id,
+                name: model.name,
+                description: Some(model.path.clone()),
+                downloaded: path.exists(),
+                path: Some(model.path),
+                size_mb,
+                english_only: false,
+                custom: true,
+            }
+        });
+
+        catalog.chain(custom).collect()
+    }
+
+    /// Register a local ggml `.bin` file as a model
+    pub fn add_custom_model(
+        &self,
+        path: &Path,
+        name: Option<String>,
+    ) -> Result<ModelInfo, TranscriptionError> {
+        if !path.is_file() {
+            return Err(TranscriptionError::ModelNotFound(
+                path.to_string_lossy().to_string(),
+            ));
+        }
+        if path.extension().and_then(|e| e.to_str()) != Some("bin") {
+            return Err(TranscriptionError::ModelLoadError(
+                "Custom models must be ggml .bin files".to_string(),
+            ));
+        }
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "model".to_string());
+        let name = name.filter(|n| !n.trim().is_empty()).unwrap_or(stem.clone());
+
+        let mut models = self.custom_models();
+        let mut id = format!("{}{}", CUSTOM_ID_PREFIX, stem);
+        let mut n = 2;
+        while models.iter().any(|m| m.id == id) {
+            id = format!("{}{}-{}", CUSTOM_ID_PREFIX, stem, n);
+            n += 1;
+        }
+
+        models.push(CustomModel {
+            id: id.clone(),
+            name,
+            path: path.to_string_lossy().to_string(),
+        });
+        self.save_custom_models(&models)?;
+
+        self.list_models()
+            .into_iter()
+            .find(|m| m.size == id)
+            .ok_or(TranscriptionError::ModelNotFound(id))
+    }
+
+    /// Remove `.tmp` partial downloads that are too old to be worth resuming
+    pub fn cleanup_stale_partials(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.models_dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("tmp") {
+                continue;
+            }
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age > STALE_PARTIAL_AGE);
+            if stale {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    /// Download a model with progress callback.
+    ///
+    /// A partial `.tmp` file left by an interrupted download is resumed with an
+    /// HTTP range request. `mirror_url` replaces the catalog host: the file is
+    /// fetched from `<mirror_url>/<filename>` (for air-gapped setups).
     pub async fn download_model<F>(
         &self,
-        size: ModelSize,
+        id: &str,
+        mirror_url: Option<&str>,
         on_progress: F,
     ) -> Result<PathBuf, TranscriptionError>
     where
@@ -148,39 +261,88 @@ impl ModelManager {
     {
         self.init().await?;
 
-        let url = size.download_url();
-        let path = self.model_path(size);
+        let entry = self
+            .catalog_entry(id)
+            .ok_or_else(|| TranscriptionError::ModelNotFound(id.to_string()))?;
+        let path = self.models_dir.join(&entry.filename);
 
         // If already downloaded, return the path
         if path.exists() {
             return Ok(path);
         }
 
-        // Download the model
-        let response = reqwest::get(url)
+        // Entries without a hash yet (see scripts/update-model-hashes.py) download unverified
+        let expected = entry.sha256.clone();
+        if let Some(hash) = &expected
+            && !is_sha256_hex(hash)
+        {
+            return Err(TranscriptionError::DownloadError(format!(
+                "Invalid checksum for {} in the model catalog",
+                entry.id
+            )));
+        }
+
+        let url = match mirror_url.map(str::trim).filter(|m| !m.is_empty()) {
+            Some(mirror) => format!("{}/{}", mirror.trim_end_matches('/'), entry.filename),
+            None => entry.url.clone(),
+        };
+
+        // Resume a previous partial download if there is one
+        let temp_path = path.with_extension("tmp");
+        let existing = fs::metadata(&temp_path).await.map(|m| m.len()).unwrap_or(0);
+
+        let mut request = reqwest::Client::new().get(&url);
+        if existing > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+        }
+        let response = request
+            .send()
             .await
             .map_err(|e| TranscriptionError::DownloadError(e.to_string()))?;
 
-        let total_size = response.content_length().unwrap_or(0);
-        let mut downloaded: u64 = 0;
+        let status = response.status();
+        let complete = status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && existing > 0;
+        if !complete {
+            if !status.is_success() {
+                return Err(TranscriptionError::DownloadError(format!(
+                    "{} returned {}",
+                    url, status
+                )));
+            }
 
-        // Create temporary file
-        let temp_path = path.with_extension("tmp");
-        let mut file = fs::File::create(&temp_path).await?;
+            // 206 appends to the partial file; a plain 200 means the server ignored the range
+            let resuming = status == reqwest::StatusCode::PARTIAL_CONTENT;
+            let mut downloaded = if resuming { existing } else { 0 };
+            let total_size = response.content_length().unwrap_or(0) + downloaded;
+
+            let mut file = if resuming {
+                fs::OpenOptions::new().append(true).open(&temp_path).await?
+            } else {
+                fs::File::create(&temp_path).await?
+            };
+
+            // Stream the download; the partial file is kept on network errors so it can resume
+            use futures_util::StreamExt;
+            let mut stream = response.bytes_stream();
 
-        // Stream the download
-        use futures_util::StreamExt;
-        let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(|e| TranscriptionError::DownloadError(e.to_string()))?;
+                file.write_all(&chunk).await?;
+                downloaded += chunk.len() as u64;
+                on_progress(downloaded, total_size);
+            }
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| TranscriptionError::DownloadError(e.to_string()))?;
-            file.write_all(&chunk).await?;
-            downloaded += chunk.len() as u64;
-            on_progress(downloaded, total_size);
+            file.flush().await?;
         }
 
-        file.flush().await?;
-        drop(file);
+        // Verify the checksum before the model can be loaded
+        if let Some(expected) = expected {
+            let actual = sha256_file(&temp_path).await?;
+            if !actual.eq_ignore_ascii_case(&expected) {
+                let _ = fs::remove_file(&temp_path).await;
+                return Err(TranscriptionError::ChecksumMismatch(entry.filename));
+            }
+        }
 
         // Rename temp file to final path
         fs::rename(&temp_path, &path).await?;
@@ -188,12 +350,69 @@ impl ModelManager {
         Ok(path)
     }
 
-    /// Delete a downloaded model
-    pub async fn delete_model(&self, size: ModelSize) -> Result<(), TranscriptionError> {
-        let path = self.model_path(size);
+    /// Delete a downloaded model (custom models are only unregistered, never deleted)
+    pub async fn delete_model(&self, id: &str) -> Result<(), TranscriptionError> {
+        let mut custom = self.custom_models();
+        if let Some(index) = custom.iter().position(|m| m.id == id) {
+            custom.remove(index);
+            return self.save_custom_models(&custom);
+        }
+
+        let entry = self
+            .catalog_entry(id)
+            .ok_or_else(|| TranscriptionError::ModelNotFound(id.to_string()))?;
+        let path = self.models_dir.join(entry.filename);
         if path.exists() {
-            fs::remove_file(path).await?;
+            fs::remove_file(&path).await?;
+        }
+        // Drop any partial download as well
+        let temp_path = path.with_extension("tmp");
+        if temp_path.exists() {
+            fs::remove_file(temp_path).await?;
         }
         Ok(())
     }
 }
+
+/// Whether `hash` looks like a hex SHA-256 digest
+fn is_sha256_hex(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Hex SHA-256 of a file
+async fn sha256_file(path: &Path) -> Result<String, TranscriptionError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await
+    .map_err(|e| TranscriptionError::DownloadError(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_catalog_checksums_are_valid() {
+        let catalog: ModelCatalog = serde_json::from_str(BUILTIN_CATALOG).unwrap();
+        assert!(!catalog.models.is_empty());
+        for entry in &catalog.models {
+            if let Some(hash) = entry.sha256.as_deref() {
+                assert!(is_sha256_hex(hash), "{} has an invalid sha256", entry.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_sha256_hex() {
+        assert!(is_sha256_hex(&"a1".repeat(32)));
+        assert!(is_sha256_hex(&"A1".repeat(32)));
+        assert!(!is_sha256_hex(&"a1".repeat(31)));
+        assert!(!is_sha256_hex(&"g1".repeat(32)));
+        assert!(!is_sha256_hex(""));
+    }
+}
@@ -65,16 +65,25 @@ impl Transcriber {
         result
     }
 
-    fn transcribe_internal(&self, audio_path: &Path, language: Option<String>) -> Result<TranscriptionResult, TranscriptionError> {
-        if !audio_path.exists() {
-            return Err(TranscriptionError::AudioNotFound(
-                audio_path.to_string_lossy().to_string(),
-            ));
+    /// Transcribe 16kHz mono f32 samples that are already in memory
+    /// (e.g. after echo cancellation)
+    pub fn transcribe_samples(&self, samples: &[f32], language: Option<String>) -> Result<TranscriptionResult, TranscriptionError> {
+        if self.is_transcribing.swap(true, Ordering::SeqCst) {
+            return Err(TranscriptionError::AlreadyTranscribing);
         }
 
+        let result = self.run_whisper(samples, language);
+        self.is_transcribing.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn transcribe_internal(&self, audio_path: &Path, language: Option<String>) -> Result<TranscriptionResult, TranscriptionError> {
         // Read the WAV file and convert to f32 samples
-        let samples = self.load_audio(audio_path)?;
+        let samples = load_audio_16k_mono(audio_path)?;
+        self.run_whisper(&samples, language)
+    }
 
+    fn run_whisper(&self, samples: &[f32], language: Option<String>) -> Result<TranscriptionResult, TranscriptionError> {
         // Create whisper state
         let mut state = self
             .ctx
@@ -101,7 +110,7 @@ impl Transcriber {
 
         // Run the transcription
         state
-            .full(params, &samples)
+            .full(params, samples)
             .map_err(|e| TranscriptionError::TranscriptionFailed(e.to_string()))?;
 
         // Extract segments
@@ -146,55 +155,61 @@ impl Transcriber {
             language: Some("en".to_string()),
         })
     }
+}
 
-    /// Load audio file and convert to 16kHz mono f32 samples
-    fn load_audio(&self, audio_path: &Path) -> Result<Vec<f32>, TranscriptionError> {
-        let reader = hound::WavReader::open(audio_path)
-            .map_err(|e| TranscriptionError::TranscriptionFailed(format!("Failed to open WAV: {}", e)))?;
-
-        let spec = reader.spec();
-        let sample_rate = spec.sample_rate;
-        let channels = spec.channels as usize;
-
-        // Read samples based on format
-        let samples: Vec<f32> = match spec.sample_format {
-            hound::SampleFormat::Float => {
-                reader
-                    .into_samples::<f32>()
-                    .filter_map(|s| s.ok())
-                    .collect()
-            }
-            hound::SampleFormat::Int => {
-                let bits = spec.bits_per_sample;
-                let max_val = (1 << (bits - 1)) as f32;
-                reader
-                    .into_samples::<i32>()
-                    .filter_map(|s| s.ok())
-                    .map(|s| s as f32 / max_val)
-                    .collect()
-            }
-        };
+/// Load a WAV file and convert it to 16kHz mono f32 samples (what Whisper expects)
+pub fn load_audio_16k_mono(audio_path: &Path) -> Result<Vec<f32>, TranscriptionError> {
+    if !audio_path.exists() {
+        return Err(TranscriptionError::AudioNotFound(
+            audio_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let reader = crate::audio::open_wav(audio_path)
+        .map_err(|e| TranscriptionError::TranscriptionFailed(format!("Failed to open WAV: {}", e)))?;
 
-        // Convert to mono if stereo
-        let mono_samples: Vec<f32> = if channels > 1 {
-            samples
-                .chunks(channels)
-                .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
+    let spec = reader.spec();
+    let sample_rate = spec.sample_rate;
+    let channels = spec.channels as usize;
+
+    // Read samples based on format
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => {
+            reader
+                .into_samples::<f32>()
+                .filter_map(|s| s.ok())
                 .collect()
-        } else {
-            samples
-        };
-
-        // Resample to 16kHz if needed (Whisper requires 16kHz)
-        let target_rate = 16000;
-        let resampled = if sample_rate != target_rate {
-            resample(&mono_samples, sample_rate, target_rate)
-        } else {
-            mono_samples
-        };
-
-        Ok(resampled)
-    }
+        }
+        hound::SampleFormat::Int => {
+            let bits = spec.bits_per_sample;
+            let max_val = (1 << (bits - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .filter_map(|s| s.ok())
+                .map(|s| s as f32 / max_val)
+                .collect()
+        }
+    };
+
+    // Convert to mono if stereo
+    let mono_samples: Vec<f32> = if channels > 1 {
+        samples
+            .chunks(channels)
+            .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
+            .collect()
+    } else {
+        samples
+    };
+
+    // Resample to 16kHz if needed (Whisper requires 16kHz)
+    let target_rate = 16000;
+    let resampled = if sample_rate != target_rate {
+        resample(&mono_samples, sample_rate, target_rate)
+    } else {
+        mono_samples
+    };
+
+    Ok(resampled)
 }
 
 /// Get the number of CPU threads to use
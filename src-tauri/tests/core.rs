@@ -0,0 +1,295 @@
+//! Integration tests for the core layer, without a running app: an in-memory
+//! library, live transcription fed from a WAV fixture and summaries from a
+//! mock Ollama server.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use netnote_lib::netnote_core::models::{NewNote, SummaryType, TranscriptSource};
+use netnote_lib::netnote_core::{
+    load_audio_16k_mono, start_live_transcription, stop_live_transcription, summarize_note,
+    Database, DatabaseHandle, EventSink, LiveAudioSource, LiveTranscriptionState, OllamaClient,
+    SpeechToText, TranscriptionError, TranscriptionResult, TranscriptionSegment,
+    TRANSCRIPTION_UPDATE_EVENT,
+};
+use tiny_http::{Response, Server};
+
+const FIXTURE: &str = "tests/fixtures/tone-16k-mono.wav";
+
+fn new_note(db: &Database, title: &str, description: Option<&str>) -> String {
+    db.insert_note(NewNote {
+        title: title.to_string(),
+        description: description.map(str::to_string),
+        participants: None,
+        folder_id: None,
+        event_id: None,
+    })
+    .unwrap()
+    .id
+}
+
+fn add_transcript(db: &Database, note_id: &str, lines: &[&str]) {
+    let transcript_id = db
+        .get_or_create_active_transcript(note_id, TranscriptSource::Live, None, None)
+        .unwrap();
+    let segments: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let start = i as f64 * 5.0;
+            (
+                note_id.to_string(),
+                start,
+                start + 5.0,
+                text.to_string(),
+                None,
+            )
+        })
+        .collect();
+    db.add_transcript_segments_batch(transcript_id, &segments)
+        .unwrap();
+}
+
+/// Ollama stand-in that answers every generate request with the same
+/// response and keeps the prompts it was sent
+struct MockLlm {
+    server: Arc<Server>,
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockLlm {
+    fn start(response: &str) -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+
+        let listener = server.clone();
+        let received = prompts.clone();
+        let reply = serde_json::json!({ "response": response, "done": true }).to_string();
+        thread::spawn(move || {
+            for mut request in listener.incoming_requests() {
+                if request.url() != "/api/generate" {
+                    let _ = request.respond(Response::empty(404));
+                    continue;
+                }
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                let body: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+                if let Some(prompt) = body["prompt"].as_str() {
+                    received.lock().unwrap().push(prompt.to_string());
+                }
+                let _ = request.respond(Response::from_string(reply.clone()));
+            }
+        });
+
+        Self { server, prompts }
+    }
+
+    fn client(&self) -> OllamaClient {
+        let port = self.server.server_addr().to_ip().unwrap().port();
+        OllamaClient::with_base_url(format!("http://127.0.0.1:{}", port))
+    }
+
+    fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockLlm {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+/// Plays a fixture as system audio in one chunk, then stays silent until stopped
+struct FixtureSource {
+    system: Mutex<Option<Vec<f32>>>,
+    active: AtomicBool,
+}
+
+impl FixtureSource {
+    fn new(path: &str) -> Self {
+        Self {
+            system: Mutex::new(Some(load_audio_16k_mono(Path::new(path)).unwrap())),
+            active: AtomicBool::new(true),
+        }
+    }
+}
+
+impl LiveAudioSource for FixtureSource {
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    fn take_mic_samples(&self) -> (Vec<f32>, u32, usize) {
+        (Vec::new(), 16000, 1)
+    }
+
+    fn take_system_samples(&self) -> Vec<f32> {
+        self.system.lock().unwrap().take().unwrap_or_default()
+    }
+}
+
+/// Recognizes one fixed sentence spanning whatever audio it is given
+struct FakeSpeech;
+
+impl SpeechToText for FakeSpeech {
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        time_offset: f64,
+        _language: Option<&str>,
+    ) -> Result<TranscriptionResult, TranscriptionError> {
+        let text = "Hello from the fixture".to_string();
+        Ok(TranscriptionResult {
+            segments: vec![TranscriptionSegment {
+                start_time: time_offset,
+                end_time: time_offset + samples.len() as f64 / 16000.0,
+                text: text.clone(),
+            }],
+            full_text: text,
+            language: None,
+        })
+    }
+}
+
+/// The database and event sink the app would otherwise provide
+#[derive(Clone)]
+struct TestHost {
+    db: Arc<Database>,
+    events: Arc<Mutex<Vec<(String, serde_json::Value)>>>,
+}
+
+impl DatabaseHandle for TestHost {
+    fn with_db<R>(&self, f: impl FnOnce(&Database) -> R) -> R {
+        f(&self.db)
+    }
+}
+
+impl EventSink for TestHost {
+    fn emit_json(&self, event: &str, payload: serde_json::Value) {
+        self.events
+            .lock()
+            .unwrap()
+            .push((event.to_string(), payload));
+    }
+}
+
+#[test]
+fn in_memory_database_keeps_notes_and_transcripts() {
+    let db = Database::open_in_memory().unwrap();
+    let note_id = new_note(&db, "Weekly sync", Some("Agenda: budget"));
+    add_transcript(&db, &note_id, &["First point.", "Second point."]);
+
+    let note = db.find_note(&note_id).unwrap().unwrap();
+    assert_eq!(note.title, "Weekly sync");
+    assert_eq!(
+        db.get_note_description(&note_id).unwrap().as_deref(),
+        Some("Agenda: budget")
+    );
+
+    let segments = db.get_transcript_segments(&note_id).unwrap();
+    let texts: Vec<_> = segments.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, ["First point.", "Second point."]);
+    assert!(db.data_dir().is_none());
+}
+
+#[tokio::test]
+async fn summarizes_transcript_with_llm_backend() {
+    let llm = MockLlm::start("<think>Looking for decisions</think>\n- The budget was approved");
+    let db = Database::open_in_memory().unwrap();
+    let note_id = new_note(&db, "Budget review", None);
+    add_transcript(&db, &note_id, &["We approved the budget.", "[BLANK_AUDIO]"]);
+
+    let summary = summarize_note(
+        &llm.client(),
+        "mock-model",
+        &db,
+        &note_id,
+        SummaryType::Overview,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(summary.content, "- The budget was approved");
+    assert_eq!(summary.summary_type, SummaryType::Overview);
+    assert_eq!(db.get_summaries(&note_id).unwrap().len(), 1);
+
+    let prompts = llm.prompts();
+    assert_eq!(prompts.len(), 1);
+    assert!(prompts[0].contains("We approved the budget."));
+    assert!(!prompts[0].contains("[BLANK_AUDIO]"));
+}
+
+#[tokio::test]
+async fn refuses_to_summarize_an_empty_note() {
+    let llm = MockLlm::start("unused");
+    let db = Database::open_in_memory().unwrap();
+    let note_id = new_note(&db, "Empty", None);
+
+    let result = summarize_note(
+        &llm.client(),
+        "mock-model",
+        &db,
+        &note_id,
+        SummaryType::ActionItems,
+        None,
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(llm.prompts().is_empty());
+    assert!(db.get_summaries(&note_id).unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn live_transcription_saves_and_emits_fixture_segments() {
+    let host = TestHost {
+        db: Arc::new(Database::open_in_memory().unwrap()),
+        events: Arc::new(Mutex::new(Vec::new())),
+    };
+    let note_id = new_note(&host.db, "Call", None);
+    let source = Arc::new(FixtureSource::new(FIXTURE));
+    let live_state = Arc::new(LiveTranscriptionState::new());
+
+    start_live_transcription(
+        host.clone(),
+        note_id.clone(),
+        None,
+        source.clone(),
+        live_state.clone(),
+        Arc::new(FakeSpeech),
+        Some("fake".to_string()),
+    )
+    .await
+    .unwrap();
+
+    // The first pass runs right away and picks up the whole fixture
+    let started = Instant::now();
+    while host.events.lock().unwrap().is_empty() {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "no transcription update"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    source.active.store(false, Ordering::SeqCst);
+    let result = stop_live_transcription(live_state).await;
+    assert_eq!(result.full_text, "Hello from the fixture");
+
+    let segments = host.db.get_transcript_segments(&note_id).unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].speaker.as_deref(), Some("Others"));
+    assert!((segments[0].end_time - 1.5).abs() < 0.01);
+
+    let events = host.events.lock().unwrap();
+    let (event, payload) = &events[0];
+    assert_eq!(event, TRANSCRIPTION_UPDATE_EVENT);
+    assert_eq!(payload["note_id"], note_id.as_str());
+    assert_eq!(payload["audio_source"], "system");
+    assert_eq!(payload["transcript_id"], segments[0].transcript_id);
+    assert_eq!(payload["segments"][0]["text"], "Hello from the fixture");
+}
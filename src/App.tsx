@@ -1,4 +1,4 @@
-import React, { useState, useMemo, useEffect, useCallback } from "react";
+import React, { useState, useMemo, useEffect, useCallback, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 import {
@@ -9,10 +9,14 @@ import {
   AudioPlayer,
   UpdateNotification,
   MeetingDetectedPopup,
+  LockScreen,
+  UnlockNoteDialog,
 } from "./components";
 import { Dashboard } from "./components/Dashboard";
 import { AvatarIcons } from "./components/settings/AvatarIcons";
-import { exportApi, aiApi } from "./api";
+import { exportApi, aiApi, pipelineApi, privacyApi } from "./api";
+import { APP_LOCKED_EVENT } from "./api/privacy";
+import { API_RECORDING_EVENT, type ApiRecordingAction } from "./api/apiServer";
 import {
   useNotes,
   useModels,
@@ -25,12 +29,16 @@ import {
   useSystemStatus,
 } from "./hooks";
 import { useThemeStore } from "./stores/themeStore";
-import type { Note, TranscriptSegment } from "./types";
+import type { MeetingDetected, MeetingEnded } from "./stores/meetingStore";
+import type { Note, PipelineProgress, TranscriptSegment } from "./types";
 
 
 function App() {
   const {
     notes,
+    total: totalNotes,
+    hasMore: hasMoreNotes,
+    loadMore: loadMoreNotes,
     loading,
     refresh: refreshNotes,
     createNote,
@@ -107,8 +115,15 @@ function App() {
   const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
   const [noteToDelete, setNoteToDelete] = useState<Note | null>(null);
   const [recordingNoteId, setRecordingNoteId] = useState<string | null>(null);
+  // Detected meeting the current recording was started for
+  const meetingRecordingRef = useRef<MeetingDetected | null>(null);
   const [isGeneratingSummaryTitle, setIsGeneratingSummaryTitle] = useState(false);
   const [summariesRefreshKey, setSummariesRefreshKey] = useState(0);
+  // The app lock is engaged until the passphrase is entered again
+  const [appLocked, setAppLocked] = useState(false);
+  // Confidential notes unlocked in this session, and the one waiting for its passphrase
+  const [unlockedNoteIds, setUnlockedNoteIds] = useState<Set<string>>(new Set());
+  const [noteToUnlock, setNoteToUnlock] = useState<Note | null>(null);
 
   // Context menu state
   const [contextMenu, setContextMenu] = useState<{
@@ -236,6 +251,52 @@ function App() {
     };
   }, [isRecording, loadedModel, ollamaRunning, ollamaModel, handleStartRecording, handleNewNote]);
 
+  // A recording that started during a calendar event took the event's title and attendees
+  useEffect(() => {
+    const unlisten = listen<string>("note-event-matched", () => {
+      refreshNotes();
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [refreshNotes]);
+
+  // Show what the post-recording pipeline produced as its steps finish
+  useEffect(() => {
+    const unlisten = listen<PipelineProgress>("pipeline-progress", (event) => {
+      const { step, status } = event.payload;
+      if (status !== "done") return;
+      if (step.kind === "summary") {
+        setSummariesRefreshKey((k) => k + 1);
+      } else if (step.kind === "title") {
+        refreshNotes();
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [refreshNotes]);
+
+  // The backend starts locked and locks again whenever the window is hidden
+  useEffect(() => {
+    privacyApi.getAppLockStatus().then((status) => setAppLocked(status.locked));
+
+    const unlisten = listen(APP_LOCKED_EVENT, () => {
+      setAppLocked(true);
+      setUnlockedNoteIds(new Set());
+      setNoteToUnlock(null);
+      setSelectedNoteId(null);
+      setNoteTranscripts({});
+      setShowSettings(false);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Listen for tray "Settings" event
   useEffect(() => {
     const unlisten = listen("tray-open-settings", () => {
@@ -343,6 +404,27 @@ function App() {
     });
   };
 
+  // Flag or unflag a note; confidential notes need the app lock, and unflagging needs the note unlocked
+  const handleToggleConfidential = async (note: Note) => {
+    if (note.confidential) {
+      if (!unlockedNoteIds.has(note.id)) {
+        setNoteToUnlock(note);
+        return;
+      }
+      await privacyApi.setNoteConfidential(note.id, false);
+    } else {
+      const status = await privacyApi.getAppLockStatus();
+      if (!status.enabled) {
+        setSettingsTab("privacy");
+        setShowSettings(true);
+        return;
+      }
+      await privacyApi.setNoteConfidential(note.id, true);
+      setUnlockedNoteIds((prev) => new Set(prev).add(note.id));
+    }
+    await refreshNotes();
+  };
+
   // Context menu actions
   const handleContextMenuAction = (action: string) => {
     if (action === "delete" && contextMenu?.noteId) {
@@ -351,6 +433,13 @@ function App() {
         setNoteToDelete(note);
         setShowDeleteConfirm(true);
       }
+    } else if (action === "confidential" && contextMenu?.noteId) {
+      const note = notes.find((n) => n.id === contextMenu.noteId);
+      if (note) {
+        handleToggleConfidential(note).catch((err) =>
+          console.error("Failed to change confidential flag:", err)
+        );
+      }
     } else if (action === "settings") {
       setSettingsTab("about");
       setShowSettings(true);
@@ -365,11 +454,13 @@ function App() {
   };
 
   const handleStopRecording = async () => {
+    const meetingApp = meetingRecordingRef.current?.app_name ?? null;
+    meetingRecordingRef.current = null;
     if (recordingNoteId) {
       const noteId = recordingNoteId;
       // Save segments before stopping (to avoid stale closure)
       const segmentsToSave = [...liveSegments];
-      const audioPath = await stopRecording();
+      const audioPath = await stopRecording(undefined, meetingApp);
       // Stop live transcription and save segments to database
       await stopLiveTranscription(noteId);
       await endNote(noteId, audioPath ?? undefined);
@@ -387,8 +478,14 @@ function App() {
       // Always refresh notes to update ended_at
       await refreshNotes();
 
+      // The post-recording pipeline summarizes and titles the note when it is enabled
+      const run = await pipelineApi.getNoteRun(noteId).catch(() => null);
+      const pipelineRunning = run?.steps.some(
+        (s) => s.status === "pending" || s.status === "running"
+      );
+
       // Auto-generate summary and title if we have transcript
-      if (transcriptToUse.length > 0) {
+      if (transcriptToUse.length > 0 && !pipelineRunning) {
         setActiveTab("summary");
         setIsGeneratingSummaryTitle(true);
         try {
@@ -409,6 +506,80 @@ function App() {
     }
   };
 
+  const handleMeetingRecording = (meeting: MeetingDetected) => {
+    if (isRecording || isPaused) return;
+    meetingRecordingRef.current = meeting;
+    handleStartRecording();
+  };
+
+  const handlePauseRecording = async () => {
+    try {
+      await pauseRecording();
+    } catch (error) {
+      console.error("Pause recording failed:", error);
+    }
+  };
+
+  const handleResumeRecording = async () => {
+    try {
+      // Check microphone permission before resuming
+      const status = await refreshSystemStatus();
+      if (!status.micAvailable || !status.micPermission) {
+        setSettingsTab("system");
+        setShowSettings(true);
+        return;
+      }
+
+      if (recordingNoteId) {
+        await resumeRecording(recordingNoteId);
+        // Pass current liveSegments to preserve them when resuming
+        await startLiveTranscription(recordingNoteId, profile?.name || "Me", liveSegments);
+      }
+    } catch (error) {
+      console.error("Resume recording failed:", error);
+    }
+  };
+
+  // Recording control from the local API runs through the same handlers as the buttons
+  const apiRecordingRef = useRef<(action: ApiRecordingAction) => void>(() => {});
+  apiRecordingRef.current = (action) => {
+    if (action === "start" && !isRecording && !isPaused) {
+      handleStartRecording();
+    } else if (action === "stop" && (isRecording || isPaused)) {
+      handleStopRecording();
+    } else if (action === "pause" && isRecording && !isPaused) {
+      handlePauseRecording();
+    } else if (action === "resume" && isPaused) {
+      handleResumeRecording();
+    }
+  };
+
+  useEffect(() => {
+    const unlisten = listen<ApiRecordingAction>(API_RECORDING_EVENT, (event) => {
+      apiRecordingRef.current(event.payload);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Pause or stop the recording of a detected meeting once its window is gone, as its rule says
+  const handleMeetingEnded = async (meeting: MeetingEnded) => {
+    if (meetingRecordingRef.current?.meeting_id !== meeting.meeting_id) return;
+    meetingRecordingRef.current = null;
+    if (!isRecording) return;
+    try {
+      if (meeting.on_end === "stop") {
+        await handleStopRecording();
+      } else if (meeting.on_end === "pause") {
+        await pauseRecording();
+      }
+    } catch (error) {
+      console.error("Failed to end meeting recording:", error);
+    }
+  };
+
   // Keyboard shortcut: Cmd/Ctrl + S to stop recording
   useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
@@ -446,6 +617,14 @@ function App() {
   };
 
   const handleSelectNote = async (note: Note) => {
+    if (note.confidential && !unlockedNoteIds.has(note.id)) {
+      setNoteToUnlock(note);
+      return;
+    }
+    await openNote(note);
+  };
+
+  const openNote = async (note: Note) => {
     setSelectedNoteId(note.id);
     setActiveTab("summary");
     if (!noteTranscripts[note.id]) {
@@ -584,10 +763,26 @@ function App() {
                     }}
                   >
                     <div
-                      className="text-sm font-medium truncate"
+                      className="text-sm font-medium truncate flex items-center gap-1.5"
                       style={{ color: "var(--color-text)" }}
                     >
-                      {note.title}
+                      {note.confidential && (
+                        <svg
+                          className="w-3.5 h-3.5 flex-shrink-0"
+                          fill="none"
+                          stroke="currentColor"
+                          viewBox="0 0 24 24"
+                          style={{ color: "var(--color-text-secondary)" }}
+                        >
+                          <path
+                            strokeLinecap="round"
+                            strokeLinejoin="round"
+                            strokeWidth={1.5}
+                            d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z"
+                          />
+                        </svg>
+                      )}
+                      <span className="truncate">{note.title}</span>
                     </div>
                     <div
                       className="text-xs"
@@ -617,6 +812,18 @@ function App() {
               </div>
             ))
           )}
+          {hasMoreNotes && (
+            <button
+              onClick={() => loadMoreNotes()}
+              className="mx-2 mb-2 px-3 py-2 rounded-xl text-xs text-center transition-colors"
+              style={{
+                width: "calc(100% - 16px)",
+                color: "var(--color-text-secondary)",
+              }}
+            >
+              Carica altre note ({notes.length}/{totalNotes})
+            </button>
+          )}
         </div>
 
         {/* Sidebar Footer */}
@@ -755,32 +962,8 @@ function App() {
             onUpdateTitle={handleUpdateTitle}
             onUpdateDescription={handleUpdateDescription}
             onStopRecording={handleStopRecording}
-            onPauseRecording={async () => {
-              try {
-                await pauseRecording();
-              } catch (error) {
-                console.error("Pause recording failed:", error);
-              }
-            }}
-            onResumeRecording={async () => {
-              try {
-                // Check microphone permission before resuming
-                const status = await refreshSystemStatus();
-                if (!status.micAvailable || !status.micPermission) {
-                  setSettingsTab("system");
-                  setShowSettings(true);
-                  return;
-                }
-
-                if (recordingNoteId) {
-                  await resumeRecording(recordingNoteId);
-                  // Pass current liveSegments to preserve them when resuming
-                  await startLiveTranscription(recordingNoteId, profile?.name || "Me", liveSegments);
-                }
-              } catch (error) {
-                console.error("Resume recording failed:", error);
-              }
-            }}
+            onPauseRecording={handlePauseRecording}
+            onResumeRecording={handleResumeRecording}
             onContinueRecording={async () => {
               try {
                 // Check microphone permission before continuing
@@ -823,7 +1006,7 @@ function App() {
               setShowSettings(true);
             }}
             stats={{
-              totalNotes: notes.length,
+              totalNotes,
               totalRecordings: notes.filter((n) => n.audio_path).length,
             }}
           />
@@ -958,16 +1141,41 @@ function App() {
         />
       )}
 
+      {noteToUnlock && (
+        <UnlockNoteDialog
+          note={noteToUnlock}
+          onUnlocked={() => {
+            const note = noteToUnlock;
+            setUnlockedNoteIds((prev) => new Set(prev).add(note.id));
+            setNoteToUnlock(null);
+            openNote(note);
+          }}
+          onCancel={() => setNoteToUnlock(null)}
+        />
+      )}
+
       {/* Context Menu */}
       {contextMenu && (
         <ContextMenu
           x={contextMenu.x}
           y={contextMenu.y}
           type={contextMenu.type}
+          noteConfidential={
+            notes.find((n) => n.id === contextMenu.noteId)?.confidential ?? false
+          }
           onAction={handleContextMenuAction}
         />
       )}
 
+      {appLocked && (
+        <LockScreen
+          onUnlocked={() => {
+            setAppLocked(false);
+            refreshNotes();
+          }}
+        />
+      )}
+
       {/* Update Notification */}
       <UpdateNotification
         onOpenSettings={() => {
@@ -977,7 +1185,10 @@ function App() {
       />
 
       {/* Meeting Detected Popup */}
-      <MeetingDetectedPopup onStartListening={handleStartRecording} />
+      <MeetingDetectedPopup
+        onStartListening={handleMeetingRecording}
+        onMeetingEnded={handleMeetingEnded}
+      />
     </div>
   );
 }
@@ -1456,10 +1667,11 @@ interface ContextMenuProps {
   x: number;
   y: number;
   type: "note" | "general";
+  noteConfidential: boolean;
   onAction: (action: string) => void;
 }
 
-function ContextMenu({ x, y, type, onAction }: ContextMenuProps) {
+function ContextMenu({ x, y, type, noteConfidential, onAction }: ContextMenuProps) {
   // Adjust position to keep menu in viewport
   const menuRef = (node: HTMLDivElement | null) => {
     if (node) {
@@ -1476,6 +1688,25 @@ function ContextMenu({ x, y, type, onAction }: ContextMenuProps) {
   const menuItems =
     type === "note"
       ? [
+        {
+          id: "confidential",
+          label: noteConfidential ? "Rimuovi riservatezza" : "Rendi riservata",
+          icon: (
+            <svg
+              className="w-4 h-4"
+              fill="none"
+              stroke="currentColor"
+              viewBox="0 0 24 24"
+            >
+              <path
+                strokeLinecap="round"
+                strokeLinejoin="round"
+                strokeWidth={1.5}
+                d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z"
+              />
+            </svg>
+          ),
+        },
         {
           id: "delete",
           label: "Elimina",
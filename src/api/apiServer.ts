@@ -0,0 +1,36 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Local HTTP API settings (matches Rust ApiServerStatus) */
+export interface ApiServerStatus {
+  enabled: boolean;
+  /** Port on 127.0.0.1 */
+  port: number;
+  /** Bearer token scripts send in the Authorization header */
+  token: string;
+  running: boolean;
+  /** Why the server is not running although enabled (e.g. port in use) */
+  error: string | null;
+}
+
+/** Recording action requested through the local API */
+export type ApiRecordingAction = "start" | "stop" | "pause" | "resume";
+
+/** Event asking the app to run an ApiRecordingAction */
+export const API_RECORDING_EVENT = "api-recording";
+
+export const apiServerApi = {
+  /** Get the API server settings, token included, and whether it is running */
+  getStatus: (): Promise<ApiServerStatus> => {
+    return invoke("get_api_server_status");
+  },
+
+  /** Enable or disable the API server, or move it to another port */
+  setSettings: (enabled: boolean, port: number): Promise<ApiServerStatus> => {
+    return invoke("set_api_server_settings", { enabled, port });
+  },
+
+  /** Replace the API token; scripts using the old one stop working */
+  regenerateToken: (): Promise<ApiServerStatus> => {
+    return invoke("regenerate_api_token");
+  },
+};
@@ -10,6 +10,107 @@ export interface DualRecordingResult {
   playbackPath: string | null;
 }
 
+/** Which channels of an input device are recorded */
+export type ChannelSelection =
+  | { mode: "all" }
+  | { mode: "downmix" }
+  /** A single 0-based channel, e.g. one input of an audio interface */
+  | { mode: "single"; channel: number };
+
+/** Saved input settings for one device */
+export interface DevicePreferences {
+  /** Requested sample rate (null = device default) */
+  sampleRate: number | null;
+  channels: ChannelSelection;
+}
+
+/** A stream configuration range supported by an input device */
+export interface SupportedInputConfig {
+  channels: number;
+  minSampleRate: number;
+  maxSampleRate: number;
+  sampleFormat: string;
+}
+
+/**
+ * An input device with its id (host, name and ordinal among same-named devices).
+ * Unplugging one of two identical devices renumbers the other.
+ */
+export interface InputDevice {
+  id: string;
+  name: string;
+  isDefault: boolean;
+  defaultSampleRate: number | null;
+  defaultChannels: number | null;
+  configs: SupportedInputConfig[];
+}
+
+/** Tauri event emitted when the input device is lost, failed over, or switched */
+export const DEVICE_CHANGE_EVENT = "audio-device-changed";
+
+/** Payload of the audio-device-changed event */
+export interface DeviceChangeEvent {
+  kind: "lost" | "failed_over" | "switched" | "failover_failed";
+  noteId: string | null;
+  /** Device involved (null = system default) */
+  deviceId: string | null;
+  /** Segment the recording continues in */
+  segmentIndex: number | null;
+  error: string | null;
+}
+
+/** How the mic and system tracks are placed in the merged playback file */
+export type MixMode = "balanced" | "stereo_split";
+
+/** Options for rendering the merged playback file */
+export interface MixOptions {
+  mode: MixMode;
+  /** Integrated loudness each track is normalized to (LUFS) */
+  targetLufs: number;
+  /** Maximum boost applied to a quiet track (dB) */
+  maxBoostDb: number;
+  /** Pan position for the mic track (-1 = left, 1 = right) */
+  micPan: number;
+  /** Pan position for the system audio track */
+  systemPan: number;
+  /** Peak ceiling of the soft limiter (0-1) */
+  limiterCeiling: number;
+}
+
+/** One recorded session within a note's stitched playback timeline */
+export interface PlaybackSession {
+  segmentIndex: number;
+  startOffsetMs: number;
+  durationMs: number | null;
+}
+
+/** A note's continuous playback file spanning all of its sessions */
+export interface NotePlayback {
+  /** File path; load it with convertFileSrc(path, "netnote-audio"), which decrypts and serves byte ranges for seeking */
+  path: string;
+  durationMs: number;
+  sessions: PlaybackSession[];
+}
+
+/** Min/max sample values per time bucket, for drawing a waveform */
+export interface WaveformPeaks {
+  sampleRate: number;
+  durationMs: number;
+  peaksPerSecond: number;
+  min: number[];
+  max: number[];
+}
+
+/** Where mic noise suppression is applied */
+export type NoiseSuppressionTarget = "off" | "stored_file" | "transcription" | "both";
+
+/** Mic noise suppression settings */
+export interface NoiseSuppressionOptions {
+  target: NoiseSuppressionTarget;
+  /** Maximum attenuation applied to noise-only frequencies (dB) */
+  reductionDb: number;
+}
+
 export const audioApi = {
   // Basic recording (mic only)
   startRecording: (noteId: string, deviceId?: string | null): Promise<string> => {
@@ -28,6 +129,22 @@ export const audioApi = {
     return invoke("get_audio_level");
   },
 
+  // Input devices
+  /** List input devices with their ids and supported configurations */
+  getAudioInputDevices: (): Promise<InputDevice[]> => {
+    return invoke("get_audio_input_devices");
+  },
+
+  /** Get the saved sample rate / channel selection for a device */
+  getDevicePreferences: (deviceId: string): Promise<DevicePreferences> => {
+    return invoke("get_device_preferences", { deviceId });
+  },
+
+  /** Save the sample rate / channel selection for a device (applies from the next recording) */
+  setDevicePreferences: (deviceId: string, preferences: DevicePreferences): Promise<void> => {
+    return invoke("set_device_preferences", { deviceId, preferences });
+  },
+
   // System audio support (macOS only)
   /** Check if system audio capture is available on this platform */
   isSystemAudioSupported: (): Promise<boolean> => {
@@ -50,9 +167,14 @@ export const audioApi = {
     return invoke("start_dual_recording", { noteId, micDeviceId });
   },
 
-  /** Stop dual recording and merge files for playback */
-  stopDualRecording: (noteId: string): Promise<DualRecordingResult> => {
-    return invoke("stop_dual_recording", { noteId });
+  /** Stop dual recording and merge files for playback; meetingApp picks the pipeline template */
+  stopDualRecording: (noteId: string, meetingApp?: string | null): Promise<DualRecordingResult> => {
+    return invoke("stop_dual_recording", { noteId, meetingApp: meetingApp ?? null });
+  },
+
+  /** Switch the microphone mid-meeting; continues the note in a new segment (null = default device) */
+  switchRecordingDevice: (deviceId?: string | null): Promise<DualRecordingResult> => {
+    return invoke("switch_recording_device", { deviceId });
   },
 
   /** Check if dual recording is currently active */
@@ -60,6 +182,32 @@ export const audioApi = {
     return invoke("is_dual_recording");
   },
 
+  // Playback mix settings
+  /** Get the loudness/pan options used for the merged playback file */
+  getMixOptions: (): Promise<MixOptions> => {
+    return invoke("get_mix_options");
+  },
+
+  /** Save the loudness/pan options used for the merged playback file */
+  setMixOptions: (options: MixOptions): Promise<void> => {
+    return invoke("set_mix_options", { options });
+  },
+
+  /** Re-render a note's playback file (stereo_split writes a separate review file) */
+  remixNotePlayback: (noteId: string, mode?: MixMode | null): Promise<string> => {
+    return invoke("remix_note_playback", { noteId, mode });
+  },
+
+  /** Get one playback file spanning every session of a note (re-rendered when segments changed) */
+  getNotePlayback: (noteId: string): Promise<NotePlayback> => {
+    return invoke("get_note_playback", { noteId });
+  },
+
+  /** Get cached waveform peaks of a note's playback file (default 50 per second) */
+  getWaveformPeaks: (noteId: string, peaksPerSecond?: number | null): Promise<WaveformPeaks> => {
+    return invoke("get_waveform_peaks", { noteId, peaksPerSecond });
+  },
+
   // AEC (Acoustic Echo Cancellation) settings
   /** Check if AEC is enabled */
   isAecEnabled: (): Promise<boolean> => {
@@ -71,6 +219,17 @@ export const audioApi = {
     return invoke("set_aec_enabled", { enabled });
   },
 
+  // Noise suppression settings
+  /** Get the mic noise suppression settings */
+  getNoiseSuppression: (): Promise<NoiseSuppressionOptions> => {
+    return invoke("get_noise_suppression");
+  },
+
+  /** Set where mic noise suppression is applied (stored file, transcription, or both) */
+  setNoiseSuppression: (options: NoiseSuppressionOptions): Promise<void> => {
+    return invoke("set_noise_suppression", { options });
+  },
+
   // ========== Pause/Resume/Continue Recording ==========
 
   /** Get the current recording phase (0=Idle, 1=Recording, 2=Paused) */
@@ -0,0 +1,39 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { CalendarEvent, CalendarImport } from "../types";
+
+export const calendarApi = {
+  /** Import events from an .ics file or a folder of them once */
+  importFile: (path: string): Promise<CalendarImport> => {
+    return invoke("import_calendar_file", { path });
+  },
+
+  /** Get the .ics file or folder that is re-imported on startup and every 15 minutes */
+  getSource: (): Promise<string | null> => {
+    return invoke("get_calendar_source");
+  },
+
+  /** Set (and import right away) or clear the synced calendar source */
+  setSource: (path: string | null): Promise<CalendarImport | null> => {
+    return invoke("set_calendar_source", { path });
+  },
+
+  /** Re-import the synced calendar source now */
+  sync: (): Promise<CalendarImport | null> => {
+    return invoke("sync_calendar");
+  },
+
+  /** List events from the last hour up to daysAhead days (7 by default) */
+  listUpcoming: (daysAhead?: number): Promise<CalendarEvent[]> => {
+    return invoke("list_upcoming_events", { daysAhead });
+  },
+
+  /** Get the event a note was recorded for */
+  getNoteEvent: (noteId: string): Promise<CalendarEvent | null> => {
+    return invoke("get_note_event", { noteId });
+  },
+
+  /** Link a note to an event by hand, or unlink it with null */
+  linkNote: (noteId: string, eventId: string | null): Promise<void> => {
+    return invoke("link_note_to_event", { noteId, eventId });
+  },
+};
@@ -0,0 +1,31 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Where the library key is kept */
+export type KeySource = "keyring" | "passphrase";
+
+/** Whether the library is encrypted at rest and unlocked */
+export interface EncryptionStatus {
+  enabled: boolean;
+  keySource: KeySource | null;
+  /** Waiting for the passphrase; other commands fail until the library is unlocked */
+  locked: boolean;
+  /** Existing files are still being encrypted */
+  migrationPending: boolean;
+}
+
+export const encryptionApi = {
+  /** Get whether the library is encrypted and whether it is unlocked */
+  getEncryptionStatus: (): Promise<EncryptionStatus> => {
+    return invoke("get_encryption_status");
+  },
+
+  /** Unlock a passphrase-protected library */
+  unlockLibrary: (passphrase: string): Promise<void> => {
+    return invoke("unlock_library", { passphrase });
+  },
+
+  /** Encrypt the existing library, keeping the key in the OS keyring or behind a passphrase */
+  enableEncryption: (passphrase?: string | null): Promise<void> => {
+    return invoke("enable_encryption", { passphrase });
+  },
+};
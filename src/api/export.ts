@@ -13,6 +13,16 @@ export interface ExportData {
   filename: string;
 }
 
+/** Audio format of an extracted clip */
+export type ClipFormat = "wav" | "flac" | "opus";
+
+/** Files written by extractClip */
+export interface ClipExport {
+  audioPath: string;
+  /** Matching subtitles (only when requested) */
+  srtPath: string | null;
+}
+
 function fixSpacedText(text: string): string {
   // Fix "s p a c e d" text - sequences of single letters separated by spaces
   const words = text.split(" ");
@@ -216,6 +226,17 @@ export const exportApi = {
     return invoke("export_note_markdown", { noteId });
   },
 
+  /** Save start..end (seconds) of a note's audio to the export directory, optionally with an SRT */
+  extractClip: (
+    noteId: string,
+    start: number,
+    end: number,
+    format: ClipFormat,
+    includeSrt?: boolean
+  ): Promise<ClipExport> => {
+    return invoke("extract_clip", { noteId, start, end, format, includeSrt });
+  },
+
   saveToFileWithDialog: async (content: string, defaultFilename: string): Promise<string | null> => {
     const filePath = await save({
       defaultPath: defaultFilename,
@@ -1,6 +1,14 @@
 export { aiApi } from "./ai";
+export { apiServerApi } from "./apiServer";
 export { audioApi } from "./audio";
+export { calendarApi } from "./calendar";
+export { encryptionApi } from "./encryption";
 export { exportApi } from "./export";
 export { notesApi } from "./notes";
+export { organizeApi } from "./organize";
+export { peopleApi } from "./people";
+export { pipelineApi } from "./pipeline";
+export { privacyApi } from "./privacy";
 export { settingsApi } from "./settings";
+export { storageApi } from "./storage";
 export { transcriptionApi } from "./transcription";
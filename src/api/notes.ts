@@ -1,5 +1,13 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Note, NewNote, UpdateNote, AudioSegment } from "../types";
+import type {
+  Note,
+  NewNote,
+  UpdateNote,
+  AudioSegment,
+  NoteFilter,
+  NotePage,
+  NoteQuery,
+} from "../types";
 
 export const notesApi = {
   create: (input: NewNote): Promise<Note> => {
@@ -10,8 +18,14 @@ export const notesApi = {
     return invoke("get_note", { id });
   },
 
-  list: (): Promise<Note[]> => {
-    return invoke("list_notes");
+  /** List notes, newest first, optionally filtered by tag, folder, date range and participant */
+  list: (filter?: NoteFilter): Promise<Note[]> => {
+    return invoke("list_notes", { filter });
+  },
+
+  /** One page of notes with aggregates; pass next_cursor back as cursor for the next page */
+  query: (query: NoteQuery = {}): Promise<NotePage> => {
+    return invoke("query_notes", { query });
   },
 
   update: (id: string, update: UpdateNote): Promise<Note> => {
@@ -0,0 +1,73 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Folder, FolderKind, Tag } from "../types";
+
+export const organizeApi = {
+  // ========== Tags ==========
+
+  /** List every tag with its note count */
+  listTags: (): Promise<Tag[]> => {
+    return invoke("list_tags");
+  },
+
+  /** Get the tags attached to a note */
+  getNoteTags: (noteId: string): Promise<Tag[]> => {
+    return invoke("get_note_tags", { noteId });
+  },
+
+  /** Create a tag; returns the existing tag's id when the name is taken */
+  createTag: (name: string, color?: string | null): Promise<number> => {
+    return invoke("create_tag", { name, color });
+  },
+
+  /** Rename or recolor a tag */
+  updateTag: (id: number, name: string, color?: string | null): Promise<void> => {
+    return invoke("update_tag", { id, name, color });
+  },
+
+  /** Delete a tag, removing it from every note */
+  deleteTag: (id: number): Promise<void> => {
+    return invoke("delete_tag", { id });
+  },
+
+  /** Attach a tag to a note by name, creating it if needed; returns the tag id */
+  addNoteTag: (noteId: string, name: string): Promise<number> => {
+    return invoke("add_note_tag", { noteId, name });
+  },
+
+  /** Detach a tag from a note */
+  removeNoteTag: (noteId: string, tagId: number): Promise<void> => {
+    return invoke("remove_note_tag", { noteId, tagId });
+  },
+
+  // ========== Folders ==========
+
+  /** List every folder; build the tree from parent_id */
+  listFolders: (): Promise<Folder[]> => {
+    return invoke("list_folders");
+  },
+
+  /** Create a folder or project, at the top level when parentId is null */
+  createFolder: (name: string, parentId?: number | null, kind?: FolderKind): Promise<number> => {
+    return invoke("create_folder", { name, parentId, kind });
+  },
+
+  /** Rename a folder, change its kind or move it under another folder */
+  updateFolder: (
+    id: number,
+    name: string,
+    parentId: number | null,
+    kind: FolderKind
+  ): Promise<void> => {
+    return invoke("update_folder", { id, name, parentId, kind });
+  },
+
+  /** Delete a folder and its subfolders; their notes move to the top level */
+  deleteFolder: (id: number): Promise<void> => {
+    return invoke("delete_folder", { id });
+  },
+
+  /** Move a note into a folder, or to the top level with null */
+  moveNoteToFolder: (noteId: string, folderId: number | null): Promise<void> => {
+    return invoke("move_note_to_folder", { noteId, folderId });
+  },
+};
@@ -0,0 +1,64 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ActionItem, Person, SpeakerLink } from "../types";
+
+export const peopleApi = {
+  /** List every person by name */
+  listPeople: (): Promise<Person[]> => {
+    return invoke("list_people");
+  },
+
+  /** Create a person; returns their id */
+  createPerson: (name: string, email: string | null, aliases: string[] = []): Promise<number> => {
+    return invoke("create_person", { name, email, aliases });
+  },
+
+  /** Update a person's name, email and aliases */
+  updatePerson: (
+    id: number,
+    name: string,
+    email: string | null,
+    aliases: string[]
+  ): Promise<void> => {
+    return invoke("update_person", { id, name, email, aliases });
+  },
+
+  /** Delete a person, removing them from every note */
+  deletePerson: (id: number): Promise<void> => {
+    return invoke("delete_person", { id });
+  },
+
+  /** Merge a duplicate person into another one */
+  mergePeople: (keepId: number, mergeId: number): Promise<void> => {
+    return invoke("merge_people", { keepId, mergeId });
+  },
+
+  /** Get the people taking part in a note */
+  getNoteParticipants: (noteId: string): Promise<Person[]> => {
+    return invoke("get_note_participants", { noteId });
+  },
+
+  /** Replace a note's participants */
+  setNoteParticipants: (noteId: string, personIds: number[]): Promise<void> => {
+    return invoke("set_note_participants", { noteId, personIds });
+  },
+
+  /** Get which diarized speaker labels of a note are linked to people */
+  getSpeakerLinks: (noteId: string): Promise<SpeakerLink[]> => {
+    return invoke("get_speaker_links", { noteId });
+  },
+
+  /** Link a speaker label to a person, or unlink it with null */
+  linkSpeaker: (noteId: string, speaker: string, personId: number | null): Promise<void> => {
+    return invoke("link_speaker", { noteId, speaker, personId });
+  },
+
+  /** Set or clear the person responsible for a summary's action items */
+  setSummaryOwner: (summaryId: number, personId: number | null): Promise<void> => {
+    return invoke("set_summary_owner", { summaryId, personId });
+  },
+
+  /** Open action items for a person across all notes */
+  getOpenActionItems: (personId: number): Promise<ActionItem[]> => {
+    return invoke("get_open_action_items", { personId });
+  },
+};
@@ -0,0 +1,29 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { PipelineRun, PipelineSettings } from "../types";
+
+export const pipelineApi = {
+  /** Get the post-recording pipeline settings and templates */
+  getSettings: (): Promise<PipelineSettings> => {
+    return invoke("get_pipeline_settings");
+  },
+
+  /** Save the pipeline settings and templates */
+  setSettings: (settings: PipelineSettings): Promise<void> => {
+    return invoke("set_pipeline_settings", { settings });
+  },
+
+  /** Run the pipeline over a note by hand (null = the default template) */
+  runNote: (noteId: string, template: string | null = null): Promise<PipelineRun> => {
+    return invoke("run_note_pipeline", { noteId, template });
+  },
+
+  /** The latest pipeline run over a note */
+  getNoteRun: (noteId: string): Promise<PipelineRun | null> => {
+    return invoke("get_note_pipeline", { noteId });
+  },
+
+  /** Run one step of a finished run again */
+  retryStep: (runId: number, position: number): Promise<PipelineRun> => {
+    return invoke("retry_pipeline_step", { runId, position });
+  },
+};
@@ -0,0 +1,43 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Whether the app lock is set up and currently engaged */
+export interface AppLockStatus {
+  enabled: boolean;
+  /** The window was hidden to the tray; show the lock screen until unlocked */
+  locked: boolean;
+}
+
+/** Event emitted when hiding the window engages the app lock */
+export const APP_LOCKED_EVENT = "app-locked";
+
+export const privacyApi = {
+  /** Get whether the app lock is enabled and engaged */
+  getAppLockStatus: (): Promise<AppLockStatus> => {
+    return invoke("get_app_lock_status");
+  },
+
+  /** Set, change or remove (null) the app lock passphrase; an existing lock needs its current passphrase */
+  setAppLock: (passphrase: string | null, currentPassphrase?: string | null): Promise<void> => {
+    return invoke("set_app_lock", { passphrase, currentPassphrase });
+  },
+
+  /** Unlock the app after the window was hidden */
+  unlockApp: (passphrase: string): Promise<void> => {
+    return invoke("unlock_app", { passphrase });
+  },
+
+  /** Flag a note as confidential or clear the flag */
+  setNoteConfidential: (noteId: string, confidential: boolean): Promise<void> => {
+    return invoke("set_note_confidential", { noteId, confidential });
+  },
+
+  /** Unlock a confidential note for this session (passphrase needed when the app lock is set) */
+  unlockConfidentialNote: (noteId: string, passphrase?: string | null): Promise<void> => {
+    return invoke("unlock_confidential_note", { noteId, passphrase });
+  },
+
+  /** Lock every unlocked confidential note again */
+  lockConfidentialNotes: (): Promise<void> => {
+    return invoke("lock_confidential_notes");
+  },
+};
@@ -0,0 +1,74 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Automatic cleanup rules for recorded audio */
+export interface RetentionPolicy {
+  /** Delete audio of notes that ended more than this many days ago, keeping transcripts (null = keep forever) */
+  deleteAudioAfterDays: number | null;
+}
+
+/** Disk usage of one note's recordings */
+export interface NoteStorage {
+  noteId: string;
+  title: string;
+  bytes: number;
+  files: number;
+}
+
+/** Why a recordings file is considered orphaned */
+export type OrphanReason = "deleted_note" | "unknown_segment" | "unrecognized";
+
+/** A recordings file that no note accounts for */
+export interface OrphanFile {
+  path: string;
+  bytes: number;
+  reason: OrphanReason;
+}
+
+/** Disk usage of the recordings directory */
+export interface StorageReport {
+  recordingsDir: string;
+  totalBytes: number;
+  /** Notes with files on disk, largest first */
+  notes: NoteStorage[];
+  orphans: OrphanFile[];
+  /** Segment files referenced by the database that no longer exist */
+  missingFiles: string[];
+}
+
+/** Outcome of deleting audio files */
+export interface CleanupResult {
+  notes: number;
+  files: number;
+  bytesFreed: number;
+}
+
+export const storageApi = {
+  /** Get disk usage per note and in total, plus orphaned and missing files */
+  getStorageReport: (): Promise<StorageReport> => {
+    return invoke("get_storage_report");
+  },
+
+  /** Delete orphaned recordings files (all of them, or only the given paths) */
+  deleteOrphanFiles: (paths?: string[] | null): Promise<CleanupResult> => {
+    return invoke("delete_orphan_files", { paths });
+  },
+
+  /** Delete a note's recorded audio, keeping its transcripts and summaries */
+  deleteNoteAudio: (noteId: string): Promise<CleanupResult> => {
+    return invoke("delete_note_audio", { noteId });
+  },
+
+  getRetentionPolicy: (): Promise<RetentionPolicy> => {
+    return invoke("get_retention_policy");
+  },
+
+  /** Save the retention policy (also applied on every app start) */
+  setRetentionPolicy: (policy: RetentionPolicy): Promise<void> => {
+    return invoke("set_retention_policy", { policy });
+  },
+
+  /** Apply the retention policy now */
+  applyRetentionPolicy: (): Promise<CleanupResult> => {
+    return invoke("apply_retention_policy");
+  },
+};
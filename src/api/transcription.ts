@@ -2,6 +2,7 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   ModelInfo,
   ModelSize,
+  Transcript,
   TranscriptSegment,
   TranscriptionResult,
 } from "../types";
@@ -16,6 +17,62 @@ export interface DualTranscriptionResult {
   totalSegments: number;
 }
 
+/** What a re-transcription job does with the existing transcript */
+export type JobMode = "replace" | "keep_alternate" | "refine";
+
+export type JobStatus = "pending" | "running" | "done" | "failed" | "cancelled";
+
+/** A queued background re-transcription of one note */
+export interface TranscriptionJob {
+  id: number;
+  note_id: string;
+  model_id: ModelSize;
+  language: string | null;
+  mode: JobMode;
+  status: JobStatus;
+  error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+/** Tauri event emitted when a job starts, advances or finishes */
+export const JOB_PROGRESS_EVENT = "transcription-job-progress";
+
+/** Payload of the transcription-job-progress event */
+export interface JobProgressEvent {
+  jobId: number;
+  noteId: string;
+  status: JobStatus;
+  /** Audio files transcribed so far */
+  completedFiles: number;
+  totalFiles: number;
+  error: string | null;
+}
+
+/** Tauri event emitted when post-meeting refinement replaced a live transcript */
+export const TRANSCRIPT_REFINED_EVENT = "transcript-refined";
+
+/** Payload of the transcript-refined event */
+export interface TranscriptRefinedEvent {
+  noteId: string;
+  /** The new active transcript (the live one is kept as an inactive version) */
+  transcriptId: number;
+  modelId: ModelSize;
+}
+
+/** Automatic re-transcription of live transcripts after a recording stops */
+export interface RefinementOptions {
+  enabled: boolean;
+  /** Model for the refinement pass (null = the loaded model) */
+  model: ModelSize | null;
+}
+
+export interface JobQueueStatus {
+  paused: boolean;
+  pending: number;
+  running: TranscriptionJob | null;
+}
+
 export const transcriptionApi = {
   // Model management
   listModels: (): Promise<ModelInfo[]> => {
@@ -46,6 +103,21 @@ export const transcriptionApi = {
     return invoke("get_loaded_model");
   },
 
+  /** Register a local ggml .bin file as a model */
+  addCustomModel: (path: string, name?: string): Promise<ModelInfo> => {
+    return invoke("add_custom_model", { path, name });
+  },
+
+  /** Get the mirror URL used for model downloads (null = catalog URLs) */
+  getModelMirror: (): Promise<string | null> => {
+    return invoke("get_model_mirror");
+  },
+
+  /** Download models from `<url>/<filename>` instead (e.g. a local server); empty resets */
+  setModelMirror: (url: string | null): Promise<void> => {
+    return invoke("set_model_mirror", { url });
+  },
+
   // Transcription
   transcribeAudio: (
     audioPath: string,
@@ -90,6 +162,26 @@ export const transcriptionApi = {
     });
   },
 
+  // Transcript versions
+  /** List the transcript versions of a note, newest first */
+  listTranscripts: (noteId: string): Promise<Transcript[]> => {
+    return invoke("list_transcripts", { noteId });
+  },
+
+  /** Get the segments of one transcript version */
+  getTranscriptVersion: (transcriptId: number): Promise<TranscriptSegment[]> => {
+    return invoke("get_transcript_version", { transcriptId });
+  },
+
+  /** Make a version the one used by summaries, search and export */
+  setActiveTranscript: (noteId: string, transcriptId: number): Promise<void> => {
+    return invoke("set_active_transcript", { noteId, transcriptId });
+  },
+
+  deleteTranscript: (transcriptId: number): Promise<void> => {
+    return invoke("delete_transcript", { transcriptId });
+  },
+
   // Live transcription
   startLiveTranscription: (noteId: string, language?: string): Promise<void> => {
     return invoke("start_live_transcription", { noteId, language });
@@ -102,4 +194,49 @@ export const transcriptionApi = {
   isLiveTranscribing: (): Promise<boolean> => {
     return invoke("is_live_transcribing");
   },
+
+  // Background re-transcription queue
+  /** Queue re-transcription of notes (all notes with audio when noteIds is omitted) */
+  enqueueRetranscription: (
+    model: ModelSize,
+    mode: JobMode,
+    noteIds?: string[],
+    language?: string
+  ): Promise<TranscriptionJob[]> => {
+    return invoke("enqueue_retranscription", { noteIds, model, language, mode });
+  },
+
+  listTranscriptionJobs: (): Promise<TranscriptionJob[]> => {
+    return invoke("list_transcription_jobs");
+  },
+
+  getTranscriptionQueueStatus: (): Promise<JobQueueStatus> => {
+    return invoke("get_transcription_queue_status");
+  },
+
+  pauseTranscriptionQueue: (): Promise<void> => {
+    return invoke("pause_transcription_queue");
+  },
+
+  resumeTranscriptionQueue: (): Promise<void> => {
+    return invoke("resume_transcription_queue");
+  },
+
+  /** Cancel a job; returns false if it had already finished */
+  cancelTranscriptionJob: (jobId: number): Promise<boolean> => {
+    return invoke("cancel_transcription_job", { jobId });
+  },
+
+  clearFinishedTranscriptionJobs: (): Promise<number> => {
+    return invoke("clear_finished_transcription_jobs");
+  },
+
+  // Post-meeting refinement
+  getRefinementOptions: (): Promise<RefinementOptions> => {
+    return invoke("get_refinement_options");
+  },
+
+  setRefinementOptions: (options: RefinementOptions): Promise<void> => {
+    return invoke("set_refinement_options", { options });
+  },
 };
@@ -18,10 +18,10 @@ export function AudioPlayer({ audioPath }: AudioPlayerProps) {
 
   const playbackRates = [0.5, 0.75, 1, 1.25, 1.5, 2];
 
-  // Convert file path to asset URL for the webview
+  // Serve the file through the app's audio protocol, which decrypts encrypted recordings
   const audioSrc = useMemo(() => {
     try {
-      return convertFileSrc(audioPath);
+      return convertFileSrc(audioPath, "netnote-audio");
     } catch {
       return null;
     }
@@ -0,0 +1,77 @@
+import { useState } from "react";
+import { privacyApi } from "../api";
+import { LogoImage } from "./LogoImage";
+
+interface LockScreenProps {
+  onUnlocked: () => void;
+}
+
+/** Covers the whole window while the app lock is engaged */
+export function LockScreen({ onUnlocked }: LockScreenProps) {
+  const [passphrase, setPassphrase] = useState("");
+  const [unlocking, setUnlocking] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!passphrase) return;
+
+    try {
+      setUnlocking(true);
+      setError(null);
+      await privacyApi.unlockApp(passphrase);
+      setPassphrase("");
+      onUnlocked();
+    } catch {
+      setError("Passphrase errata");
+    } finally {
+      setUnlocking(false);
+    }
+  };
+
+  return (
+    <div
+      className="fixed inset-0 z-[200] flex items-center justify-center p-4"
+      style={{ backgroundColor: "var(--color-bg)" }}
+    >
+      <form onSubmit={handleSubmit} className="w-full max-w-xs flex flex-col items-center gap-4">
+        <LogoImage className="mb-2" />
+        <h2 className="text-lg font-semibold" style={{ color: "var(--color-text)" }}>
+          NetNote è bloccato
+        </h2>
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          className="w-full px-3 py-2.5 text-sm rounded-lg outline-none transition-colors"
+          style={{
+            backgroundColor: "var(--color-bg-subtle)",
+            color: "var(--color-text)",
+            border: "1px solid var(--color-border)",
+          }}
+          placeholder="Passphrase"
+        />
+        {error && (
+          <div
+            className="w-full px-3 py-2 rounded-lg text-sm"
+            style={{ backgroundColor: "rgba(239, 68, 68, 0.08)", color: "#dc2626" }}
+          >
+            {error}
+          </div>
+        )}
+        <button
+          type="submit"
+          disabled={unlocking || !passphrase}
+          className="w-full px-4 py-2 text-sm font-medium rounded-lg transition-colors disabled:opacity-50"
+          style={{
+            backgroundColor: "var(--color-text)",
+            color: "var(--color-bg)",
+          }}
+        >
+          {unlocking ? "Sblocco..." : "Sblocca"}
+        </button>
+      </form>
+    </div>
+  );
+}
@@ -1,31 +1,53 @@
-import { useEffect } from "react";
+import { useEffect, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
-import { useMeetingStore } from "../stores/meetingStore";
-
-interface MeetingDetectedPayload {
-  app_name: string;
-  bundle_id: string | null;
-  is_browser: boolean;
-}
+import {
+  MeetingDetected,
+  MeetingEnded,
+  useMeetingStore,
+} from "../stores/meetingStore";
 
 interface MeetingDetectedPopupProps {
-  onStartListening: () => void;
+  onStartListening: (meeting: MeetingDetected) => void;
+  /** A detected meeting's window is gone */
+  onMeetingEnded: (meeting: MeetingEnded) => void;
 }
 
 export function MeetingDetectedPopup({
   onStartListening,
+  onMeetingEnded,
 }: MeetingDetectedPopupProps) {
   const { detectedMeeting, dismissed, setDetectedMeeting, dismissMeeting } =
     useMeetingStore();
 
+  // The listeners below outlive renders; always call the latest handlers
+  const handlersRef = useRef({ onStartListening, onMeetingEnded });
+  handlersRef.current = { onStartListening, onMeetingEnded };
+
   // Listen for meeting-detected events from Rust
   useEffect(() => {
-    const unlisten = listen<MeetingDetectedPayload>(
-      "meeting-detected",
-      (event) => {
+    const unlisten = listen<MeetingDetected>("meeting-detected", (event) => {
+      if (event.payload.action === "record") {
+        // Rule says record right away, no need to ask
+        handlersRef.current.onStartListening(event.payload);
+      } else {
         setDetectedMeeting(event.payload);
       }
-    );
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [setDetectedMeeting]);
+
+  useEffect(() => {
+    const unlisten = listen<MeetingEnded>("meeting-ended", (event) => {
+      // Stop offering to record a meeting that is over
+      const { detectedMeeting } = useMeetingStore.getState();
+      if (detectedMeeting?.meeting_id === event.payload.meeting_id) {
+        setDetectedMeeting(null);
+      }
+      handlersRef.current.onMeetingEnded(event.payload);
+    });
 
     return () => {
       unlisten.then((fn) => fn());
@@ -36,7 +58,7 @@ export function MeetingDetectedPopup({
 
   const handleStartListening = () => {
     dismissMeeting();
-    onStartListening();
+    onStartListening(detectedMeeting);
   };
 
   return (
@@ -1,5 +1,5 @@
 import { useModels } from "../hooks";
-import type { ModelInfo, ModelSize } from "../types";
+import type { ModelInfo } from "../types";
 
 interface ModelManagerProps {
   onClose: () => void;
@@ -121,7 +121,7 @@ function ModelCard({
   onLoad,
   isAnyDownloading,
 }: ModelCardProps) {
-  const sizeLabels: Record<ModelSize, string> = {
+  const sizeLabels: Record<string, string> = {
     tiny: "Fastest, basic accuracy",
     base: "Fast, good accuracy",
     small: "Balanced performance",
@@ -153,7 +153,7 @@ function ModelCard({
             )}
           </div>
           <p className="text-xs mt-0.5" style={{ color: "var(--color-text-tertiary)" }}>
-            {sizeLabels[model.size]} · {model.size_mb} MB
+            {sizeLabels[model.size] ?? model.description ?? (model.english_only ? "English only" : "")} · {model.size_mb} MB
           </p>
         </div>
         <div className="flex items-center gap-2 shrink-0">
@@ -0,0 +1,107 @@
+import { useState } from "react";
+import { privacyApi } from "../api";
+import type { Note } from "../types";
+
+interface UnlockNoteDialogProps {
+  note: Note;
+  onUnlocked: () => void;
+  onCancel: () => void;
+}
+
+/** Asks for the app lock passphrase to open a confidential note for this session */
+export function UnlockNoteDialog({ note, onUnlocked, onCancel }: UnlockNoteDialogProps) {
+  const [passphrase, setPassphrase] = useState("");
+  const [unlocking, setUnlocking] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!passphrase) return;
+
+    try {
+      setUnlocking(true);
+      setError(null);
+      await privacyApi.unlockConfidentialNote(note.id, passphrase);
+      onUnlocked();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setUnlocking(false);
+    }
+  };
+
+  return (
+    <div
+      className="fixed inset-0 z-50 flex items-center justify-center p-4"
+      style={{ backgroundColor: "rgba(0, 0, 0, 0.4)" }}
+      onClick={(e) => e.target === e.currentTarget && onCancel()}
+    >
+      <form
+        onSubmit={handleSubmit}
+        className="w-full max-w-sm rounded-xl p-5"
+        style={{
+          backgroundColor: "var(--color-bg-elevated)",
+          boxShadow: "var(--shadow-lg)",
+        }}
+      >
+        <h3
+          className="text-lg font-semibold mb-2"
+          style={{ color: "var(--color-text)" }}
+        >
+          Nota riservata
+        </h3>
+        <p
+          className="text-sm mb-4"
+          style={{ color: "var(--color-text-secondary)" }}
+        >
+          Inserisci la passphrase di blocco per aprire "{note.title}".
+        </p>
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          className="w-full px-3 py-2.5 text-sm rounded-lg outline-none transition-colors"
+          style={{
+            backgroundColor: "var(--color-bg-subtle)",
+            color: "var(--color-text)",
+            border: "1px solid var(--color-border)",
+          }}
+          placeholder="Passphrase"
+        />
+        {error && (
+          <div
+            className="mt-3 px-3 py-2 rounded-lg text-sm"
+            style={{ backgroundColor: "rgba(239, 68, 68, 0.08)", color: "#dc2626" }}
+          >
+            {error}
+          </div>
+        )}
+        <div className="flex justify-end gap-2 mt-5">
+          <button
+            type="button"
+            onClick={onCancel}
+            className="px-4 py-2 text-sm rounded-lg transition-colors"
+            style={{
+              backgroundColor: "var(--color-sidebar)",
+              color: "var(--color-text)",
+            }}
+          >
+            Annulla
+          </button>
+          <button
+            type="submit"
+            disabled={unlocking || !passphrase}
+            className="px-4 py-2 text-sm rounded-lg transition-colors disabled:opacity-50"
+            style={{
+              backgroundColor: "var(--color-accent)",
+              color: "var(--color-accent-text)",
+            }}
+          >
+            {unlocking ? "Sblocco..." : "Sblocca"}
+          </button>
+        </div>
+      </form>
+    </div>
+  );
+}
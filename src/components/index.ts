@@ -11,3 +11,5 @@ export { TranscriptSearch } from "./TranscriptSearch";
 export { AudioPlayer } from "./AudioPlayer";
 export { UpdateNotification } from "./UpdateNotification";
 export { MeetingDetectedPopup } from "./MeetingDetectedPopup";
+export { LockScreen } from "./LockScreen";
+export { UnlockNoteDialog } from "./UnlockNoteDialog";
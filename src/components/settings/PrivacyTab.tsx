@@ -1,3 +1,148 @@
+import { useState, useEffect } from "react";
+import { privacyApi } from "../../api";
+
+const inputStyle = {
+  backgroundColor: "var(--color-bg-subtle)",
+  color: "var(--color-text)",
+  border: "1px solid var(--color-border)",
+};
+
+/** Set, change or remove the passphrase that locks the app and confidential notes */
+function AppLockSection() {
+  const [enabled, setEnabled] = useState(false);
+  const [current, setCurrent] = useState("");
+  const [passphrase, setPassphrase] = useState("");
+  const [confirm, setConfirm] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [message, setMessage] = useState<string | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    privacyApi.getAppLockStatus().then((status) => setEnabled(status.enabled));
+  }, []);
+
+  const save = async (next: string | null) => {
+    try {
+      setSaving(true);
+      setError(null);
+      setMessage(null);
+      await privacyApi.setAppLock(next, enabled ? current : null);
+      setEnabled(next !== null);
+      setCurrent("");
+      setPassphrase("");
+      setConfirm("");
+      setMessage(next === null ? "Blocco rimosso" : "Passphrase salvata");
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleLockNotes = async () => {
+    await privacyApi.lockConfidentialNotes();
+    setMessage("Note riservate bloccate");
+  };
+
+  const canSave =
+    passphrase.length > 0 && passphrase === confirm && (!enabled || current.length > 0);
+
+  return (
+    <div
+      className="p-4 rounded-xl space-y-3"
+      style={{ backgroundColor: "var(--color-bg-subtle)" }}
+    >
+      <div>
+        <h4
+          className="text-sm font-medium mb-1"
+          style={{ color: "var(--color-text)" }}
+        >
+          Blocco dell'app
+        </h4>
+        <p
+          className="text-xs"
+          style={{ color: "var(--color-text-secondary)" }}
+        >
+          {enabled
+            ? "NetNote si blocca quando la finestra viene nascosta. La stessa passphrase apre le note riservate."
+            : "Imposta una passphrase per bloccare NetNote quando la finestra viene nascosta e per proteggere le note riservate."}
+        </p>
+      </div>
+
+      {enabled && (
+        <input
+          type="password"
+          value={current}
+          onChange={(e) => setCurrent(e.target.value)}
+          placeholder="Passphrase attuale"
+          className="w-full px-3 py-2 rounded-lg text-sm"
+          style={inputStyle}
+        />
+      )}
+      <input
+        type="password"
+        value={passphrase}
+        onChange={(e) => setPassphrase(e.target.value)}
+        placeholder={enabled ? "Nuova passphrase" : "Passphrase"}
+        className="w-full px-3 py-2 rounded-lg text-sm"
+        style={inputStyle}
+      />
+      <input
+        type="password"
+        value={confirm}
+        onChange={(e) => setConfirm(e.target.value)}
+        placeholder="Conferma passphrase"
+        className="w-full px-3 py-2 rounded-lg text-sm"
+        style={inputStyle}
+      />
+
+      {error && (
+        <p className="text-xs" style={{ color: "#dc2626" }}>
+          {error}
+        </p>
+      )}
+      {message && (
+        <p className="text-xs" style={{ color: "#22c55e" }}>
+          {message}
+        </p>
+      )}
+
+      <div className="flex flex-wrap justify-end gap-2">
+        {enabled && (
+          <>
+            <button
+              onClick={handleLockNotes}
+              className="px-4 py-2 text-sm rounded-lg transition-colors"
+              style={{ color: "var(--color-text)", border: "1px solid var(--color-border)" }}
+            >
+              Blocca note riservate
+            </button>
+            <button
+              onClick={() => save(null)}
+              disabled={saving || current.length === 0}
+              className="px-4 py-2 text-sm rounded-lg transition-colors disabled:opacity-40"
+              style={{ color: "#dc2626", border: "1px solid var(--color-border)" }}
+            >
+              Rimuovi blocco
+            </button>
+          </>
+        )}
+        <button
+          onClick={() => save(passphrase)}
+          disabled={saving || !canSave}
+          className="px-4 py-2 text-sm font-medium rounded-lg transition-colors disabled:opacity-40"
+          style={{
+            backgroundColor: "var(--color-accent)",
+            color: "var(--color-accent-text)",
+          }}
+        >
+          {enabled ? "Cambia passphrase" : "Attiva blocco"}
+        </button>
+      </div>
+    </div>
+  );
+}
+
 export function PrivacyTab() {
   return (
     <div className="space-y-6">
@@ -15,6 +160,8 @@ export function PrivacyTab() {
         </p>
       </div>
 
+      <AppLockSection />
+
       <div
         className="p-4 rounded-xl"
         style={{ backgroundColor: "var(--color-bg-subtle)" }}
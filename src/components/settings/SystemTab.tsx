@@ -5,7 +5,7 @@ import { useSettingsStore } from "../../stores/settingsStore";
 interface AudioDeviceInfo {
   id: string;
   name: string;
-  is_default: boolean;
+  isDefault: boolean;
 }
 
 interface SystemTabProps {
@@ -86,7 +86,7 @@ export function SystemTab({ onPermissionChange }: SystemTabProps) {
         setLoadingDevices(false);
         // If no mic selected yet, try to find default
         if (!selectedMicId) {
-          const defaultDevice = devices.find(d => d.is_default);
+          const defaultDevice = devices.find(d => d.isDefault);
           if (defaultDevice) {
             // Don't auto-set store to avoid overriding "default" semantic if user prefers explicit
             // But for now, let's leave selectedMicId as null = default
@@ -468,7 +468,7 @@ export function SystemTab({ onPermissionChange }: SystemTabProps) {
               <option value="">Predefinito di Sistema</option>
               {inputDevices.map((device) => (
                 <option key={device.id} value={device.id}>
-                  {device.name} {device.is_default ? "(Predefinito)" : ""}
+                  {device.name} {device.isDefault ? "(Predefinito)" : ""}
                 </option>
               ))}
             </select>
@@ -4,7 +4,7 @@ import {
   WHISPER_LANGUAGES,
   type WhisperLanguage,
 } from "../../stores/whisperStore";
-import type { ModelInfo, ModelSize } from "../../types";
+import type { ModelInfo } from "../../types";
 
 export function WhisperTab() {
   const {
@@ -22,7 +22,7 @@ export function WhisperTab() {
   const language = useWhisperStore((state) => state.language);
   const setLanguage = useWhisperStore((state) => state.setLanguage);
 
-  const sizeLabels: Record<ModelSize, string> = {
+  const sizeLabels: Record<string, string> = {
     tiny: "Molto veloce, accuratezza base",
     base: "Veloce, buona accuratezza",
     small: "Prestazioni bilanciate",
@@ -126,7 +126,7 @@ interface WhisperModelCardProps {
   isLoaded: boolean;
   isDownloading: boolean;
   downloadProgress: number;
-  sizeLabel: string;
+  sizeLabel?: string;
   isRecommended: boolean;
   onDownload: () => void;
   onDelete: () => void;
@@ -191,7 +191,7 @@ function WhisperModelCard({
             className="text-xs mt-0.5"
             style={{ color: "var(--color-text-tertiary)" }}
           >
-            {sizeLabel} · {model.size_mb} MB
+            {sizeLabel ?? model.description ?? (model.english_only ? "English" : "")} · {model.size_mb} MB
           </p>
         </div>
         <div className="flex items-center gap-2 shrink-0">
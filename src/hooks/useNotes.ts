@@ -1,14 +1,21 @@
 import { useCallback, useEffect, useState } from "react";
 import { notesApi } from "../api";
-import type { Note, UpdateNote } from "../types";
+import type { Note, NoteQuery, UpdateNote } from "../types";
+
+/** Notes loaded per page in the sidebar */
+const PAGE_SIZE = 100;
 
 interface UseNotesReturn {
   notes: Note[];
+  /** Notes matching the current listing or search across all pages */
+  total: number;
+  hasMore: boolean;
   loading: boolean;
   error: string | null;
   searchQuery: string;
   isSearching: boolean;
   refresh: () => Promise<void>;
+  loadMore: () => Promise<void>;
   createNote: (title: string, description?: string, participants?: string) => Promise<Note>;
   updateNote: (id: string, update: UpdateNote) => Promise<Note>;
   searchNotes: (query: string) => Promise<void>;
@@ -23,25 +30,44 @@ export function useNotes(): UseNotesReturn {
   const [error, setError] = useState<string | null>(null);
   const [searchQuery, setSearchQuery] = useState("");
   const [isSearching, setIsSearching] = useState(false);
+  const [total, setTotal] = useState(0);
+  const [cursor, setCursor] = useState<string | null>(null);
+
+  const loadPage = useCallback(async (query: NoteQuery, append: boolean) => {
+    const page = await notesApi.query({ ...query, limit: PAGE_SIZE });
+    setNotes((prev) => (append ? [...prev, ...page.items] : page.items));
+    setTotal(page.total);
+    setCursor(page.next_cursor);
+  }, []);
 
   const refresh = useCallback(async () => {
     try {
       setLoading(true);
       setError(null);
       setSearchQuery("");
-      const data = await notesApi.list();
-      setNotes(data);
+      await loadPage({}, false);
     } catch (e) {
       setError(e instanceof Error ? e.message : String(e));
     } finally {
       setLoading(false);
     }
-  }, []);
+  }, [loadPage]);
+
+  const loadMore = useCallback(async (): Promise<void> => {
+    if (!cursor) return;
+    try {
+      setError(null);
+      await loadPage({ search: searchQuery || null, cursor }, true);
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    }
+  }, [cursor, searchQuery, loadPage]);
 
   const createNote = useCallback(
     async (title: string, description?: string, participants?: string): Promise<Note> => {
       const note = await notesApi.create({ title, description, participants });
       setNotes((prev) => [note, ...prev]);
+      setTotal((prev) => prev + 1);
       return note;
     },
     []
@@ -65,14 +91,13 @@ export function useNotes(): UseNotesReturn {
       setIsSearching(true);
       setSearchQuery(query);
       setError(null);
-      const results = await notesApi.search(query);
-      setNotes(results);
+      await loadPage({ search: query }, false);
     } catch (e) {
       setError(e instanceof Error ? e.message : String(e));
     } finally {
       setIsSearching(false);
     }
-  }, [refresh]);
+  }, [refresh, loadPage]);
 
   const clearSearch = useCallback(() => {
     setSearchQuery("");
@@ -91,6 +116,7 @@ export function useNotes(): UseNotesReturn {
   const deleteNote = useCallback(async (id: string): Promise<void> => {
     await notesApi.delete(id);
     setNotes((prev) => prev.filter((n) => n.id !== id));
+    setTotal((prev) => Math.max(0, prev - 1));
   }, []);
 
   useEffect(() => {
@@ -99,11 +125,14 @@ export function useNotes(): UseNotesReturn {
 
   return {
     notes,
+    total,
+    hasMore: cursor !== null,
     loading,
     error,
     searchQuery,
     isSearching,
     refresh,
+    loadMore,
     createNote,
     updateNote,
     searchNotes,
@@ -13,7 +13,7 @@ interface UseRecordingReturn {
   error: string | null;
   isDualRecording: boolean;
   startRecording: (noteId: string) => Promise<void>;
-  stopRecording: (noteId?: string) => Promise<string | null>;
+  stopRecording: (noteId?: string, meetingApp?: string | null) => Promise<string | null>;
   pauseRecording: () => Promise<void>;
   resumeRecording: (noteId: string) => Promise<void>;
   continueRecording: (noteId: string) => Promise<void>;
@@ -80,7 +80,7 @@ export function useRecording(): UseRecordingReturn {
   }, [audioSource, selectedMicId]); // Add audioSource and selectedMicId dependency
 
   const stopRecording = useCallback(
-    async (noteId?: string): Promise<string | null> => {
+    async (noteId?: string, meetingApp?: string | null): Promise<string | null> => {
       try {
         setError(null);
         const id = noteId || currentNoteIdRef.current;
@@ -90,7 +90,7 @@ export function useRecording(): UseRecordingReturn {
         if (isDualRecording && id) {
           // Stop dual recording
           console.log("Stopping dual recording");
-          const result = await audioApi.stopDualRecording(id);
+          const result = await audioApi.stopDualRecording(id, meetingApp);
           // Use the merged playback path, or fall back to system path, then mic path
           path = result.playbackPath || result.systemPath || result.micPath;
         } else {
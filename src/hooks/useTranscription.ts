@@ -19,6 +19,8 @@ interface TranscriptionUpdateEvent {
   }>;
   is_final: boolean;
   audio_source?: "mic" | "system";
+  /** Transcript version the segments were saved to */
+  transcript_id?: number | null;
 }
 
 interface UseModelsReturn {
@@ -96,16 +98,8 @@ export function useTranscription(): UseTranscriptionReturn {
         const langParam = language === "auto" ? undefined : language;
 
         const result = await transcriptionApi.transcribeAudio(audioPath, noteId, langParam);
-        // Convert result segments to TranscriptSegment format
-        const segments: TranscriptSegment[] = result.segments.map((s, idx) => ({
-          id: idx,
-          note_id: noteId,
-          start_time: s.start_time,
-          end_time: s.end_time,
-          text: s.text,
-          speaker: null,
-          created_at: new Date().toISOString(),
-        }));
+        // Load the saved transcript (the run is now the note's active version)
+        const segments = await transcriptionApi.getTranscript(noteId);
         setTranscript(segments);
         return result;
       } catch (e) {
@@ -236,7 +230,7 @@ export function useLiveTranscription(): UseLiveTranscriptionReturn {
           // Ignore events if effect was cleaned up (StrictMode double-mount)
           if (cancelled) return;
 
-          const { note_id, segments, is_final, audio_source } = event.payload;
+          const { note_id, segments, is_final, audio_source, transcript_id } = event.payload;
 
           // Only process events for the current note
           if (note_id !== currentNoteIdRef.current) return;
@@ -249,6 +243,7 @@ export function useLiveTranscription(): UseLiveTranscriptionReturn {
             const newSegments: TranscriptSegment[] = segments.map((s, idx) => ({
               id: Date.now() + idx,
               note_id,
+              transcript_id: transcript_id ?? 0,
               start_time: s.start_time,
               end_time: s.end_time,
               text: s.text,
@@ -1,10 +1,31 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 
-interface MeetingDetected {
+export type MeetingAction = "notify" | "record" | "ignore";
+export type MeetingEndAction = "nothing" | "pause" | "stop";
+
+export interface MeetingDetected {
+  meeting_id: string;
   app_name: string;
   bundle_id: string | null;
   is_browser: boolean;
+  action: MeetingAction;
+}
+
+export interface MeetingEnded {
+  meeting_id: string;
+  app_name: string;
+  on_end: MeetingEndAction;
+}
+
+/** Rules are tried in order; the first matching a window title decides */
+export interface MeetingRule {
+  app_name: string;
+  /** Text the title contains, or a regular expression when `regex` is set */
+  pattern: string;
+  regex: boolean;
+  action: MeetingAction;
+  on_end: MeetingEndAction;
 }
 
 interface MeetingStore {
@@ -12,18 +33,23 @@ interface MeetingStore {
   detectedMeeting: MeetingDetected | null;
   isEnabled: boolean;
   dismissed: boolean;
+  rules: MeetingRule[];
 
   // Actions
   setDetectedMeeting: (meeting: MeetingDetected | null) => void;
   dismissMeeting: () => void;
   setEnabled: (enabled: boolean) => Promise<void>;
   loadEnabled: () => Promise<void>;
+  loadRules: () => Promise<void>;
+  saveRules: (rules: MeetingRule[]) => Promise<void>;
+  resetRules: () => Promise<void>;
 }
 
 export const useMeetingStore = create<MeetingStore>((set) => ({
   detectedMeeting: null,
   isEnabled: true,
   dismissed: false,
+  rules: [],
 
   setDetectedMeeting: (meeting) => set({ detectedMeeting: meeting, dismissed: false }),
 
@@ -50,4 +76,28 @@ export const useMeetingStore = create<MeetingStore>((set) => ({
       console.error("Failed to load meeting detection enabled:", error);
     }
   },
+
+  loadRules: async () => {
+    try {
+      const rules = await invoke<MeetingRule[]>("get_meeting_rules");
+      set({ rules });
+    } catch (error) {
+      console.error("Failed to load meeting rules:", error);
+    }
+  },
+
+  // Throws when a rule is invalid (e.g. a bad regular expression)
+  saveRules: async (rules) => {
+    await invoke("set_meeting_rules", { rules });
+    set({ rules });
+  },
+
+  resetRules: async () => {
+    try {
+      const rules = await invoke<MeetingRule[]>("reset_meeting_rules");
+      set({ rules });
+    } catch (error) {
+      console.error("Failed to reset meeting rules:", error);
+    }
+  },
 }));
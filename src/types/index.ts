@@ -2,7 +2,22 @@ export type {
   Note,
   NewNote,
   UpdateNote,
+  Tag,
+  FolderKind,
+  Folder,
+  NoteFilter,
+  NoteSort,
+  NoteQuery,
+  NoteListItem,
+  NotePage,
+  CalendarEvent,
+  CalendarImport,
+  Person,
+  SpeakerLink,
+  ActionItem,
   TranscriptSegment,
+  Transcript,
+  TranscriptSource,
   Summary,
   SummaryType,
   ModelSize,
@@ -12,6 +27,13 @@ export type {
   OllamaModel,
   OllamaStatus,
   AudioSegment,
+  PipelineStep,
+  PipelineStepStatus,
+  PipelineStepState,
+  PipelineRun,
+  PipelineTemplate,
+  PipelineSettings,
+  PipelineProgress,
 } from "./note";
 
 export { RecordingPhase } from "./note";
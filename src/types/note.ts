@@ -2,18 +2,150 @@ export interface Note {
   id: string;
   title: string;
   description: string | null;
-  participants: string | null; // Comma-separated list
+  /** Display string of the participants; peopleApi has the linked people */
+  participants: string | null;
   started_at: string; // ISO 8601 datetime
   ended_at: string | null;
   audio_path: string | null;
   created_at: string;
   updated_at: string;
+  /** Excluded from search, AI summarization and export until unlocked */
+  confidential: boolean;
+  folder_id: number | null;
 }
 
 export interface NewNote {
   title: string;
   description?: string;
   participants?: string;
+  folder_id?: number | null;
+  /** Calendar event the note is recorded for; fills in what the note leaves empty */
+  event_id?: string | null;
+}
+
+export interface Tag {
+  id: number;
+  name: string;
+  color: string | null;
+  /** Number of notes carrying the tag */
+  note_count: number;
+  created_at: string;
+}
+
+export type FolderKind = "folder" | "project";
+
+export interface Folder {
+  id: number;
+  name: string;
+  /** null for top-level folders */
+  parent_id: number | null;
+  kind: FolderKind;
+  /** Notes directly inside the folder (not counting subfolders) */
+  note_count: number;
+  created_at: string;
+  updated_at: string;
+}
+
+/** Filters for listing and querying notes; every field that is set must match */
+export interface NoteFilter {
+  tag_id?: number | null;
+  folder_id?: number | null;
+  /** Also match notes in subfolders of folder_id */
+  include_subfolders?: boolean;
+  /** ISO 8601; only notes started at or after this time */
+  started_after?: string | null;
+  /** ISO 8601; only notes started before this time */
+  started_before?: string | null;
+  /** Case-insensitive substring of the participants list */
+  participant?: string | null;
+  /** Notes the person took part in, spoke in or owns a summary of */
+  person_id?: number | null;
+}
+
+export type NoteSort = "started_at" | "updated_at" | "duration" | "title";
+
+/** A page request for notesApi.query; filter fields apply as in NoteFilter */
+export interface NoteQuery extends NoteFilter {
+  /** Full-text search over titles, descriptions, participants, tags, folders and transcripts */
+  search?: string | null;
+  /** Has audio (a recording file or segments) */
+  has_recording?: boolean | null;
+  has_summary?: boolean | null;
+  /** true for ended notes, false for ongoing ones */
+  ended?: boolean | null;
+  /** Defaults to started_at */
+  sort?: NoteSort;
+  /** Oldest/smallest first instead of newest/largest first */
+  ascending?: boolean;
+  /** next_cursor of the previous page */
+  cursor?: string | null;
+  /** Page size, 50 when unset */
+  limit?: number;
+}
+
+/** A note in a listing, with aggregates over its segments and summaries */
+export interface NoteListItem extends Note {
+  duration_ms: number;
+  segment_count: number;
+  summary_count: number;
+}
+
+export interface NotePage {
+  items: NoteListItem[];
+  /** Pass back as cursor for the next page; null on the last page */
+  next_cursor: string | null;
+  /** Notes matching the filters across all pages */
+  total: number;
+}
+
+/** An event imported from a calendar file */
+export interface CalendarEvent {
+  id: string;
+  title: string;
+  description: string | null;
+  location: string | null;
+  /** Organizer and attendees ("Name <email>, ...") */
+  participants: string | null;
+  starts_at: string;
+  ends_at: string;
+  /** The .ics file the event came from */
+  source: string;
+  /** Note recorded for the event */
+  note_id: string | null;
+}
+
+/** Outcome of a calendar import */
+export interface CalendarImport {
+  files: number;
+  events: number;
+  /** Files that could not be read, with the reason */
+  errors: string[];
+}
+
+export interface Person {
+  id: number;
+  name: string;
+  email: string | null;
+  /** Other names the person goes by (nicknames, diarization labels, ...) */
+  aliases: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+/** A diarized speaker label of a note linked to a person */
+export interface SpeakerLink {
+  note_id: string;
+  speaker: string;
+  person_id: number;
+}
+
+/** An open item from a note's latest action items summary */
+export interface ActionItem {
+  note_id: string;
+  note_title: string;
+  note_started_at: string;
+  summary_id: number;
+  text: string;
 }
 
 export interface UpdateNote {
@@ -25,6 +157,7 @@ export interface UpdateNote {
 export interface TranscriptSegment {
   id: number;
   note_id: string;
+  transcript_id: number;
   start_time: number; // seconds from note start
   end_time: number;
   text: string;
@@ -32,12 +165,29 @@ export interface TranscriptSegment {
   created_at: string;
 }
 
+/** What produced a transcript version */
+export type TranscriptSource = "live" | "batch" | "import" | "legacy";
+
+/** One transcript version of a note; exactly one per note is active */
+export interface Transcript {
+  id: number;
+  note_id: string;
+  model_id: string | null;
+  language: string | null;
+  source: TranscriptSource;
+  is_active: boolean;
+  segment_count: number;
+  created_at: string;
+}
+
 export interface Summary {
   id: number;
   note_id: string;
   summary_type: SummaryType;
   content: string;
   created_at: string;
+  /** Person responsible for the summary's action items */
+  owner_id: number | null;
 }
 
 export type SummaryType =
@@ -47,14 +197,19 @@ export type SummaryType =
   | "custom";
 
 // Model types for transcription
-export type ModelSize = "tiny" | "base" | "small" | "medium" | "large";
+/** Model catalog id ("tiny" … "large", "large-v3-turbo", "base.en", "custom:<name>", …) */
+export type ModelSize = string;
 
 export interface ModelInfo {
   size: ModelSize;
   name: string;
+  description: string | null;
   downloaded: boolean;
   path: string | null;
   size_mb: number;
+  english_only: boolean;
+  /** User-supplied local .bin file */
+  custom: boolean;
 }
 
 // Segment from whisper transcription (before saving to DB)
@@ -93,9 +248,60 @@ export interface AudioSegment {
   system_path: string | null;
   start_offset_ms: number;
   duration_ms: number | null;
+  /** Re-transcribed by post-meeting refinement */
+  refined: boolean;
   created_at: string;
 }
 
+// Post-recording pipeline (matches Rust PipelineStep / PipelineRun)
+export type PipelineStep =
+  | { kind: "mix" }
+  | { kind: "transcribe"; model: string | null }
+  | { kind: "summary"; summary_type: SummaryType; custom_prompt: string | null }
+  | { kind: "title" };
+
+export type PipelineStepStatus = "pending" | "running" | "done" | "failed" | "skipped";
+
+export interface PipelineStepState {
+  position: number;
+  step: PipelineStep;
+  status: PipelineStepStatus;
+  error: string | null;
+  updated_at: string;
+}
+
+export interface PipelineRun {
+  id: number;
+  note_id: string;
+  template: string;
+  steps: PipelineStepState[];
+  created_at: string;
+}
+
+export interface PipelineTemplate {
+  name: string;
+  /** Detected meeting apps (as named by the meeting rules) using this template */
+  apps: string[];
+  steps: PipelineStep[];
+}
+
+export interface PipelineSettings {
+  /** Run automatically when a dual recording stops */
+  enabled: boolean;
+  defaultTemplate: string;
+  templates: PipelineTemplate[];
+}
+
+// Payload of the "pipeline-progress" event
+export interface PipelineProgress {
+  runId: number;
+  noteId: string;
+  position: number;
+  step: PipelineStep;
+  status: PipelineStepStatus;
+  error: string | null;
+}
+
 // Recording phase enum (matches Rust RecordingPhase)
 export enum RecordingPhase {
   Idle = 0,